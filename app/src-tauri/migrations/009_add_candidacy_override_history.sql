@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS candidacy_override_history (
+  id                       INTEGER PRIMARY KEY AUTOINCREMENT,
+  warehouse_item_id        TEXT NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+  schema_name              TEXT NOT NULL,
+  procedure_name           TEXT NOT NULL,
+  action                   TEXT NOT NULL
+    CHECK(action IN ('override','revert')),
+  previous_tier            TEXT NOT NULL
+    CHECK(previous_tier IN ('migrate','review','reject')),
+  new_tier                 TEXT NOT NULL
+    CHECK(new_tier IN ('migrate','review','reject')),
+  previous_overridden      INTEGER NOT NULL,
+  previous_override_reason TEXT,
+  reason                   TEXT NOT NULL,
+  actor                    TEXT,
+  reverts_override_id      INTEGER,
+  created_at               TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS ix_candidacy_override_history_procedure
+  ON candidacy_override_history(warehouse_item_id, schema_name, procedure_name);
+
+-- History rows are append-only; deletes are still allowed so FK cascades from items keep working.
+CREATE TRIGGER IF NOT EXISTS trg_candidacy_override_history_append_only
+BEFORE UPDATE ON candidacy_override_history
+BEGIN
+  SELECT RAISE(ABORT, 'candidacy_override_history is append-only');
+END;
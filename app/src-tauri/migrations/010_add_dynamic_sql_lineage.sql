@@ -0,0 +1,22 @@
+ALTER TABLE sqlserver_procedure_lineage
+  ADD COLUMN evidence_source TEXT NOT NULL DEFAULT 'static_sql';
+
+CREATE INDEX IF NOT EXISTS ix_sqlserver_procedure_lineage_procedure
+  ON sqlserver_procedure_lineage(procedure_data_object_id);
+CREATE INDEX IF NOT EXISTS ix_sqlserver_procedure_lineage_table
+  ON sqlserver_procedure_lineage(table_data_object_id);
+
+CREATE TABLE IF NOT EXISTS sqlserver_dynamic_sql (
+  id                        TEXT PRIMARY KEY,
+  procedure_data_object_id  TEXT NOT NULL REFERENCES data_objects(id) ON DELETE CASCADE,
+  line_number               INTEGER NOT NULL,
+  exec_kind                 TEXT NOT NULL
+    CHECK(exec_kind IN ('exec', 'sp_executesql')),
+  resolution                TEXT NOT NULL
+    CHECK(resolution IN ('resolved', 'partial', 'opaque')),
+  statements_json           TEXT NOT NULL DEFAULT '[]',
+  reason                    TEXT
+);
+
+CREATE INDEX IF NOT EXISTS ix_sqlserver_dynamic_sql_procedure
+  ON sqlserver_dynamic_sql(procedure_data_object_id);
@@ -0,0 +1,17 @@
+-- Full-text index over procedure/view/function bodies. Identifiers keep `_`, `@`, `#` and `$`
+-- so `sp_load_orders` or `@batch_id` match as single tokens.
+CREATE VIRTUAL TABLE IF NOT EXISTS data_object_search USING fts5(
+  data_object_id UNINDEXED,
+  workspace_id UNINDEXED,
+  object_name,
+  sql_body,
+  tokenize = "unicode61 tokenchars '_@#$'"
+);
+
+INSERT INTO data_object_search(data_object_id, workspace_id, object_name, sql_body)
+SELECT o.id, s.workspace_id, o.object_name, o.sql_body
+FROM data_objects o
+JOIN namespaces n ON n.id = o.namespace_id
+JOIN containers c ON c.id = n.container_id
+JOIN sources s ON s.id = c.source_id
+WHERE o.sql_body IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Activities nested in ForEach / IfCondition / Until / Switch containers record the
+-- container activity's name; top-level activities leave it NULL.
+ALTER TABLE pipeline_activities ADD COLUMN parent_activity_name TEXT;
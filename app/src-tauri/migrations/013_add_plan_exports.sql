@@ -0,0 +1,6 @@
+-- Last plan.json written per workspace; the base for reconciling edits made in the repo.
+CREATE TABLE IF NOT EXISTS plan_exports (
+  workspace_id TEXT PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+  content      TEXT NOT NULL,
+  exported_at  TEXT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Masking decision per PII column as a JSON object, e.g. {"email": "hash", "country": "keep"}.
+ALTER TABLE table_config ADD COLUMN pii_masking_json TEXT;
@@ -0,0 +1,8 @@
+-- Pull request opened for each workspace's plan branch on migration_repo_name.
+CREATE TABLE IF NOT EXISTS plan_pull_requests (
+  workspace_id TEXT PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+  number       INTEGER NOT NULL,
+  url          TEXT NOT NULL,
+  branch       TEXT NOT NULL,
+  updated_at   TEXT NOT NULL
+);
@@ -0,0 +1,18 @@
+-- Phase state moves from global settings keys onto each workspace.
+ALTER TABLE workspaces ADD COLUMN app_phase TEXT;
+ALTER TABLE workspaces ADD COLUMN scope_finalized INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE workspaces ADD COLUMN plan_finalized INTEGER NOT NULL DEFAULT 0;
+
+-- Earlier versions kept a single workspace: the most recently created one.
+UPDATE workspaces SET
+  app_phase = (SELECT value FROM settings WHERE key = 'app_phase'),
+  scope_finalized = COALESCE(
+    (SELECT value IN ('1', 'true', 'TRUE') FROM settings WHERE key = 'scope_finalized'), 0),
+  plan_finalized = COALESCE(
+    (SELECT value IN ('1', 'true', 'TRUE') FROM settings WHERE key = 'plan_finalized'), 0)
+WHERE id = (SELECT id FROM workspaces ORDER BY created_at DESC LIMIT 1);
+
+INSERT OR REPLACE INTO settings (key, value)
+SELECT 'active_workspace_id', id FROM workspaces ORDER BY created_at DESC LIMIT 1;
+
+DELETE FROM settings WHERE key IN ('app_phase', 'scope_finalized', 'plan_finalized');
@@ -0,0 +1,7 @@
+-- Fabric notebook content kept from the last sync so notebook lineage can be rebuilt whenever
+-- the workspace's canonical source model is re-applied.
+CREATE TABLE IF NOT EXISTS notebook_definitions (
+  notebook_item_id TEXT PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+  workspace_id     TEXT NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+  content          TEXT NOT NULL
+);
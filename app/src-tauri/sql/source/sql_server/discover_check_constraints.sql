@@ -0,0 +1,13 @@
+SELECT
+  s.name AS schema_name,
+  t.name AS table_name,
+  cc.name AS constraint_name,
+  col.name AS column_name,
+  cc.definition AS definition
+FROM sys.check_constraints AS cc
+INNER JOIN sys.tables AS t ON t.object_id = cc.parent_object_id
+INNER JOIN sys.schemas AS s ON s.schema_id = t.schema_id
+LEFT JOIN sys.columns AS col
+  ON col.object_id = cc.parent_object_id AND col.column_id = cc.parent_column_id
+WHERE s.name NOT IN ('sys', 'INFORMATION_SCHEMA') AND cc.is_disabled = 0
+ORDER BY s.name, t.name, cc.name;
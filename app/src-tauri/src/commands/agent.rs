@@ -513,15 +513,17 @@ fn resolve_working_directory(
     conn: &rusqlite::Connection,
     app: &AppHandle,
 ) -> Result<String, String> {
-    let latest_workspace_dir: Option<String> = conn
-        .query_row(
-            "SELECT migration_repo_path FROM workspaces ORDER BY created_at DESC LIMIT 1",
-            [],
-            |row| row.get(0),
-        )
-        .ok();
+    let active_workspace_dir: Option<String> =
+        crate::db::read_active_workspace_id(conn)?.and_then(|id| {
+            conn.query_row(
+                "SELECT migration_repo_path FROM workspaces WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .ok()
+        });
 
-    if let Some(path) = latest_workspace_dir {
+    if let Some(path) = active_workspace_dir {
         let trimmed = path.trim();
         if !trimmed.is_empty() {
             return Ok(trimmed.to_string());
@@ -1,9 +1,19 @@
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension};
 use tauri::State;
 
+use crate::commands::lineage::{
+    record_notebook_lineage, store_notebook_definition, workspace_source_id,
+};
 use crate::db::DbState;
+use crate::fabric::client::{
+    parse_fabric_url, FabricClient, FabricEndpoints, ServicePrincipal, SQL_ENDPOINT_SCOPE,
+};
+use crate::fabric::discovery::{fetch_workspace_snapshot, FabricSnapshot};
+use crate::fabric::pipeline_definition::parse_pipeline_definition;
+use crate::fabric::warehouse::{fetch_warehouse_catalog, WarehouseCatalog};
 use crate::types::{
-    CommandError, Item, PipelineActivity, WarehouseProcedure, WarehouseSchema, WarehouseTable,
+    CommandError, FabricSyncSummary, Item, NotebookLineageSummary, PipelineActivity,
+    PipelineDefinitionImport, WarehouseProcedure, WarehouseSchema, WarehouseTable,
 };
 
 #[tauri::command]
@@ -192,8 +202,8 @@ pub fn fabric_upsert_pipeline_activities(
             activity.activity_name
         );
         tx.execute(
-            "INSERT OR REPLACE INTO pipeline_activities(pipeline_item_id, activity_name, activity_type, target_warehouse_item_id, target_schema_name, target_procedure_name, parameters_json, depends_on_json)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT OR REPLACE INTO pipeline_activities(pipeline_item_id, activity_name, activity_type, target_warehouse_item_id, target_schema_name, target_procedure_name, parameters_json, depends_on_json, parent_activity_name)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![
                 activity.pipeline_item_id,
                 activity.activity_name,
@@ -203,6 +213,7 @@ pub fn fabric_upsert_pipeline_activities(
                 activity.target_procedure_name,
                 activity.parameters_json,
                 activity.depends_on_json,
+                activity.parent_activity_name,
             ],
         )
         .map_err(|e| {
@@ -220,6 +231,384 @@ pub fn fabric_upsert_pipeline_activities(
     Ok(())
 }
 
+#[tauri::command]
+pub fn fabric_import_pipeline_definition(
+    pipeline_item_id: String,
+    definition_path: String,
+    state: State<DbState>,
+) -> Result<PipelineDefinitionImport, CommandError> {
+    log::info!(
+        "fabric_import_pipeline_definition: pipeline_item_id={} path={}",
+        pipeline_item_id,
+        definition_path
+    );
+    let definition = std::fs::read_to_string(&definition_path)
+        .map_err(|e| CommandError::Io(format!("failed to read {definition_path}: {e}")))
+        .inspect_err(|e| log::error!("fabric_import_pipeline_definition: {e}"))?;
+    let mut import = parse_pipeline_definition(&pipeline_item_id, &definition)
+        .map_err(|e| CommandError::Io(format!("invalid pipeline definition: {e}")))
+        .inspect_err(|e| log::error!("fabric_import_pipeline_definition: {e}"))?;
+    let conn = state.0.lock().unwrap();
+    import_pipeline_activities(&conn, &mut import)
+        .inspect_err(|e| log::error!("fabric_import_pipeline_definition: failed: {e}"))?;
+    if !import.unresolved.is_empty() {
+        log::warn!(
+            "fabric_import_pipeline_definition: unresolved targets: {}",
+            import.unresolved.join(", ")
+        );
+    }
+    Ok(import)
+}
+
+/// Read a notebook definition exported from Fabric (`notebook-content.py` or `.ipynb`) and
+/// record the tables its cells read and write as notebook activity links.
+#[tauri::command]
+pub fn fabric_import_notebook_definition(
+    notebook_item_id: String,
+    definition_path: String,
+    state: State<DbState>,
+) -> Result<NotebookLineageSummary, CommandError> {
+    log::info!(
+        "fabric_import_notebook_definition: notebook_item_id={} path={}",
+        notebook_item_id,
+        definition_path
+    );
+    let content = std::fs::read_to_string(&definition_path)
+        .map_err(|e| CommandError::Io(format!("failed to read {definition_path}: {e}")))
+        .inspect_err(|e| log::error!("fabric_import_notebook_definition: {e}"))?;
+    let conn = state.0.lock().unwrap();
+    let (workspace_id, display_name): (String, String) = conn
+        .query_row(
+            "SELECT workspace_id, display_name FROM items WHERE id = ?1 AND item_type = 'Notebook'",
+            params![notebook_item_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .ok_or_else(|| CommandError::NotFound(format!("notebook {notebook_item_id}")))
+        .inspect_err(|e| log::error!("fabric_import_notebook_definition: {e}"))?;
+    let tx = conn.unchecked_transaction()?;
+    store_notebook_definition(&tx, &workspace_id, &notebook_item_id, &content)?;
+    let summary = record_notebook_lineage(
+        &tx,
+        &workspace_id,
+        &notebook_item_id,
+        &display_name,
+        &content,
+    )
+    .inspect_err(|e| log::error!("fabric_import_notebook_definition: failed: {e}"))?;
+    tx.commit()?;
+    Ok(summary)
+}
+
+/// Replace a pipeline's activities with an imported definition.
+///
+/// Existing rows are updated in place so activity ids (and the table artifacts pointing at them)
+/// stay stable. Activities missing from the definition are removed; artifacts that pointed at a
+/// removed (or renamed) activity are kept and detached from it. Warehouse ids that are not known
+/// items are dropped rather than failing the import.
+pub(crate) fn import_pipeline_activities(
+    conn: &Connection,
+    import: &mut PipelineDefinitionImport,
+) -> Result<(), CommandError> {
+    let tx = conn.unchecked_transaction()?;
+    replace_pipeline_activities(&tx, import)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// [`import_pipeline_activities`] without its own transaction.
+fn replace_pipeline_activities(
+    tx: &Connection,
+    import: &mut PipelineDefinitionImport,
+) -> Result<(), CommandError> {
+    let item_type: Option<String> = tx
+        .query_row(
+            "SELECT item_type FROM items WHERE id = ?1",
+            params![import.pipeline_item_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if item_type.as_deref() != Some("DataPipeline") {
+        return Err(CommandError::NotFound(format!(
+            "pipeline {}",
+            import.pipeline_item_id
+        )));
+    }
+
+    for activity in &mut import.activities {
+        if let Some(warehouse_id) = &activity.target_warehouse_item_id {
+            let known: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM items WHERE id = ?1)",
+                params![warehouse_id],
+                |row| row.get(0),
+            )?;
+            if !known {
+                log::debug!(
+                    "import_pipeline_activities: unknown warehouse {} for activity {}",
+                    warehouse_id,
+                    activity.activity_name
+                );
+                activity.target_warehouse_item_id = None;
+            }
+        }
+        activity.id = Some(tx.query_row(
+            "INSERT INTO pipeline_activities(pipeline_item_id, activity_name, activity_type, target_warehouse_item_id, target_schema_name, target_procedure_name, parameters_json, depends_on_json, parent_activity_name)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(pipeline_item_id, activity_name) DO UPDATE SET
+               activity_type = excluded.activity_type,
+               target_warehouse_item_id = excluded.target_warehouse_item_id,
+               target_schema_name = excluded.target_schema_name,
+               target_procedure_name = excluded.target_procedure_name,
+               parameters_json = excluded.parameters_json,
+               depends_on_json = excluded.depends_on_json,
+               parent_activity_name = excluded.parent_activity_name
+             RETURNING id",
+            params![
+                activity.pipeline_item_id,
+                activity.activity_name,
+                activity.activity_type,
+                activity.target_warehouse_item_id,
+                activity.target_schema_name,
+                activity.target_procedure_name,
+                activity.parameters_json,
+                activity.depends_on_json,
+                activity.parent_activity_name,
+            ],
+            |row| row.get(0),
+        )?);
+    }
+    let keep: Vec<String> = import
+        .activities
+        .iter()
+        .map(|a| a.activity_name.clone())
+        .collect();
+    let keep_json = serde_json::to_string(&keep).unwrap_or_else(|_| "[]".to_string());
+    // Detach first: the artifact FK cascades on delete and would take the artifacts with it.
+    tx.execute(
+        "UPDATE table_artifacts SET pipeline_activity_id = NULL
+         WHERE pipeline_activity_id IN (
+           SELECT id FROM pipeline_activities
+           WHERE pipeline_item_id = ?1
+             AND activity_name NOT IN (SELECT value FROM json_each(?2)))",
+        params![import.pipeline_item_id, keep_json],
+    )?;
+    tx.execute(
+        "DELETE FROM pipeline_activities
+         WHERE pipeline_item_id = ?1
+           AND activity_name NOT IN (SELECT value FROM json_each(?2))",
+        params![import.pipeline_item_id, keep_json],
+    )?;
+    Ok(())
+}
+
+/// Discover the workspace's Fabric items over the REST API and store them locally: items,
+/// warehouse schemas/tables/procedures (read from each warehouse's SQL endpoint), pipeline
+/// activities parsed from the pipeline definitions and notebook table links.
+#[tauri::command]
+pub async fn fabric_sync_workspace(
+    workspace_id: String,
+    state: State<'_, DbState>,
+) -> Result<FabricSyncSummary, CommandError> {
+    log::info!("fabric_sync_workspace: workspace_id={}", workspace_id);
+    let (fabric_workspace_id, principal) = {
+        let conn = state.0.lock().unwrap();
+        fabric_credentials(&conn, &workspace_id)
+            .inspect_err(|e| log::error!("fabric_sync_workspace: {e}"))?
+    };
+    let mut client = FabricClient::new(FabricEndpoints::from_env(), principal);
+    let mut snapshot = fetch_workspace_snapshot(&mut client, &fabric_workspace_id)
+        .await
+        .inspect_err(|e| log::error!("fabric_sync_workspace: failed: {e}"))?;
+
+    let mut catalogs = Vec::new();
+    for (warehouse_id, properties) in &snapshot.warehouses {
+        let Some(connection_string) = properties.connection_string.as_deref() else {
+            continue;
+        };
+        let Some(item) = snapshot.items.iter().find(|i| i.id == *warehouse_id) else {
+            continue;
+        };
+        let catalog = match client.access_token(SQL_ENDPOINT_SCOPE).await {
+            Ok(token) => {
+                fetch_warehouse_catalog(warehouse_id, connection_string, &item.display_name, &token)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        match catalog {
+            Ok(catalog) => catalogs.push((warehouse_id.clone(), catalog)),
+            Err(e) => snapshot
+                .warnings
+                .push(format!("warehouse {}: {e}", item.display_name)),
+        }
+    }
+
+    let conn = state.0.lock().unwrap();
+    let summary = persist_fabric_snapshot(&conn, &workspace_id, &snapshot, &catalogs)
+        .inspect_err(|e| log::error!("fabric_sync_workspace: failed to persist: {e}"))?;
+    for warning in &summary.warnings {
+        log::warn!("fabric_sync_workspace: {warning}");
+    }
+    Ok(summary)
+}
+
+/// Fabric workspace id and service principal from the workspace's Fabric settings, entered
+/// on the workspace settings page. The tenant comes from the `ctid` parameter of the URL.
+fn fabric_credentials(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<(String, ServicePrincipal), CommandError> {
+    let (fabric_url, client_id, client_secret): (Option<String>, Option<String>, Option<String>) =
+        conn.query_row(
+            "SELECT fabric_url, fabric_service_principal_id, fabric_service_principal_secret
+             FROM workspaces WHERE id = ?1",
+            params![workspace_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?
+        .ok_or_else(|| CommandError::NotFound(format!("workspace {workspace_id}")))?;
+    let client_secret =
+        crate::secrets::decrypt_opt(client_secret.as_deref()).map_err(CommandError::Io)?;
+    let required = |value: Option<String>, what: &str| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| CommandError::Io(format!("{what} is required")))
+    };
+    let workspace_ref = parse_fabric_url(&required(fabric_url, "Fabric URL")?)?;
+    let tenant_id = workspace_ref
+        .tenant_id
+        .ok_or_else(|| CommandError::Io("Fabric URL has no tenant (ctid) parameter".to_string()))?;
+    Ok((
+        workspace_ref.workspace_id,
+        ServicePrincipal {
+            tenant_id,
+            client_id: required(client_id, "Service principal id")?,
+            client_secret: required(client_secret, "Service principal secret")?,
+        },
+    ))
+}
+
+/// Store a fetched workspace in one transaction. Items are updated in place so selections and
+/// artifacts that reference them survive; each fetched warehouse catalog replaces the stored one.
+pub(crate) fn persist_fabric_snapshot(
+    conn: &Connection,
+    workspace_id: &str,
+    snapshot: &FabricSnapshot,
+    catalogs: &[(String, WarehouseCatalog)],
+) -> Result<FabricSyncSummary, CommandError> {
+    let mut summary = FabricSyncSummary {
+        warnings: snapshot.warnings.clone(),
+        ..FabricSyncSummary::default()
+    };
+    let tx = conn.unchecked_transaction()?;
+    for item in &snapshot.items {
+        let warehouse = snapshot
+            .warehouses
+            .iter()
+            .find(|(id, _)| *id == item.id)
+            .map(|(_, properties)| properties);
+        tx.execute(
+            "INSERT INTO items(id, workspace_id, display_name, description, folder_id, item_type, connection_string, collation_type)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(id) DO UPDATE SET
+               workspace_id = excluded.workspace_id,
+               display_name = excluded.display_name,
+               description = excluded.description,
+               folder_id = excluded.folder_id,
+               item_type = excluded.item_type,
+               connection_string = COALESCE(excluded.connection_string, items.connection_string),
+               collation_type = COALESCE(excluded.collation_type, items.collation_type)",
+            params![
+                item.id,
+                workspace_id,
+                item.display_name,
+                item.description,
+                item.folder_id,
+                item.item_type,
+                warehouse.and_then(|w| w.connection_string.as_deref()),
+                warehouse.and_then(|w| w.collation_type.as_deref()),
+            ],
+        )?;
+        summary.items += 1;
+    }
+
+    for (warehouse_id, catalog) in catalogs {
+        tx.execute(
+            "DELETE FROM warehouse_schemas WHERE warehouse_item_id = ?1",
+            params![warehouse_id],
+        )?;
+        for schema in &catalog.schemas {
+            tx.execute(
+                "INSERT INTO warehouse_schemas(warehouse_item_id, schema_name, schema_id_local)
+                 VALUES (?1, ?2, ?3)",
+                params![warehouse_id, schema.schema_name, schema.schema_id_local],
+            )?;
+        }
+        for table in &catalog.tables {
+            tx.execute(
+                "INSERT INTO warehouse_tables(warehouse_item_id, schema_name, table_name, object_id_local)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![warehouse_id, table.schema_name, table.table_name, table.object_id_local],
+            )?;
+        }
+        for procedure in &catalog.procedures {
+            tx.execute(
+                "INSERT INTO warehouse_procedures(warehouse_item_id, schema_name, procedure_name, object_id_local, sql_body)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    warehouse_id,
+                    procedure.schema_name,
+                    procedure.procedure_name,
+                    procedure.object_id_local,
+                    procedure.sql_body,
+                ],
+            )?;
+        }
+        summary.warehouses += 1;
+        summary.schemas += catalog.schemas.len();
+        summary.tables += catalog.tables.len();
+        summary.procedures += catalog.procedures.len();
+    }
+
+    for (pipeline_id, content) in &snapshot.pipeline_definitions {
+        let mut import = match parse_pipeline_definition(pipeline_id, content) {
+            Ok(import) => import,
+            Err(e) => {
+                summary
+                    .warnings
+                    .push(format!("pipeline {pipeline_id}: {e}"));
+                continue;
+            }
+        };
+        replace_pipeline_activities(&tx, &mut import)?;
+        summary.pipeline_activities += import.activities.len();
+    }
+
+    // Notebook lineage links into the canonical source model; before the workspace source is
+    // applied the definitions are only stored, and applying it builds their lineage.
+    let has_source_model = workspace_source_id(&tx, workspace_id)?.is_some();
+    for (notebook_id, content) in &snapshot.notebook_definitions {
+        let name = snapshot
+            .items
+            .iter()
+            .find(|i| i.id == *notebook_id)
+            .map_or(notebook_id.as_str(), |i| i.display_name.as_str());
+        store_notebook_definition(&tx, workspace_id, notebook_id, content)?;
+        summary.notebooks += 1;
+        if !has_source_model {
+            continue;
+        }
+        match record_notebook_lineage(&tx, workspace_id, notebook_id, name, content) {
+            Ok(lineage) => summary.notebook_links += lineage.links,
+            Err(e @ CommandError::Database(_)) => return Err(e),
+            Err(e) => summary.warnings.push(format!("notebook {name}: {e}")),
+        }
+    }
+    tx.commit()?;
+    Ok(summary)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -405,6 +794,7 @@ mod tests {
             target_procedure_name: None,
             parameters_json: None,
             depends_on_json: None,
+            parent_activity_name: None,
         };
 
         for _ in 0..2 {
@@ -424,4 +814,268 @@ mod tests {
             .unwrap();
         assert_eq!(count, 1);
     }
+
+    #[test]
+    fn import_pipeline_activities_keeps_ids_and_prunes_removed() {
+        let conn = db::open_in_memory().unwrap();
+        insert_workspace(&conn, "ws-6");
+        insert_item(&conn, "pipe-1", "ws-6", "DataPipeline");
+        insert_item(&conn, "wh-1", "ws-6", "Warehouse");
+        conn.execute(
+            "INSERT INTO pipeline_activities(pipeline_item_id, activity_name, activity_type) VALUES ('pipe-1', 'Old', 'Wait')",
+            [],
+        )
+        .unwrap();
+
+        let definition = r#"{"properties": {"activities": [
+            {"name": "Load", "type": "SqlServerStoredProcedure",
+             "linkedService": {"properties": {"typeProperties": {"artifactId": "wh-1"}}},
+             "typeProperties": {"storedProcedureName": "dbo.sp_load"}},
+            {"name": "Elsewhere", "type": "SqlServerStoredProcedure",
+             "linkedService": {"properties": {"typeProperties": {"artifactId": "wh-other"}}},
+             "typeProperties": {"storedProcedureName": "sp_other"}}
+        ]}}"#;
+        let mut first = parse_pipeline_definition("pipe-1", definition).unwrap();
+        import_pipeline_activities(&conn, &mut first).unwrap();
+        assert_eq!(first.activities[1].target_warehouse_item_id, None);
+
+        let mut second = parse_pipeline_definition("pipe-1", definition).unwrap();
+        import_pipeline_activities(&conn, &mut second).unwrap();
+        assert_eq!(first.activities[0].id, second.activities[0].id);
+
+        let names: Vec<String> = conn
+            .prepare("SELECT activity_name FROM pipeline_activities ORDER BY activity_name")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(names, vec!["Elsewhere", "Load"]);
+        let (warehouse, schema): (Option<String>, Option<String>) = conn
+            .query_row(
+                "SELECT target_warehouse_item_id, target_schema_name FROM pipeline_activities WHERE activity_name = 'Load'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(warehouse.as_deref(), Some("wh-1"));
+        assert_eq!(schema.as_deref(), Some("dbo"));
+
+        let mut wrong = parse_pipeline_definition("wh-1", definition).unwrap();
+        assert!(matches!(
+            import_pipeline_activities(&conn, &mut wrong),
+            Err(CommandError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn import_pipeline_activities_keeps_artifacts_of_renamed_activities() {
+        let conn = db::open_in_memory().unwrap();
+        insert_workspace(&conn, "ws-7");
+        insert_item(&conn, "pipe-1", "ws-7", "DataPipeline");
+        insert_item(&conn, "wh-1", "ws-7", "Warehouse");
+        let definition = |name: &str| {
+            format!(
+                r#"{{"properties": {{"activities": [
+                    {{"name": "{name}", "type": "SqlServerStoredProcedure",
+                      "linkedService": {{"properties": {{"typeProperties": {{"artifactId": "wh-1"}}}}}},
+                      "typeProperties": {{"storedProcedureName": "dbo.sp_load"}}}}
+                ]}}}}"#
+            )
+        };
+        let mut first = parse_pipeline_definition("pipe-1", &definition("Load")).unwrap();
+        import_pipeline_activities(&conn, &mut first).unwrap();
+        conn.execute_batch(&format!(
+            "INSERT INTO selected_tables(id, workspace_id, warehouse_item_id, schema_name, table_name)
+               VALUES ('st-1', 'ws-7', 'wh-1', 'dbo', 'orders');
+             INSERT INTO table_artifacts(selected_table_id, warehouse_item_id, schema_name, procedure_name, pipeline_activity_id, discovery_status)
+               VALUES ('st-1', 'wh-1', 'dbo', 'sp_load', {}, 'resolved');",
+            first.activities[0].id.unwrap()
+        ))
+        .unwrap();
+
+        let mut renamed = parse_pipeline_definition("pipe-1", &definition("Load orders")).unwrap();
+        import_pipeline_activities(&conn, &mut renamed).unwrap();
+
+        let (procedure, activity_id): (String, Option<i64>) = conn
+            .query_row(
+                "SELECT procedure_name, pipeline_activity_id FROM table_artifacts WHERE selected_table_id = 'st-1'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(procedure, "sp_load");
+        assert_eq!(activity_id, None);
+    }
+
+    #[test]
+    fn fabric_credentials_require_tenant_and_principal() {
+        let conn = db::open_in_memory().unwrap();
+        insert_workspace(&conn, "ws-7");
+        assert!(matches!(
+            fabric_credentials(&conn, "missing"),
+            Err(CommandError::NotFound(_))
+        ));
+        assert!(fabric_credentials(&conn, "ws-7")
+            .unwrap_err()
+            .to_string()
+            .contains("Fabric URL is required"));
+
+        conn.execute(
+            "UPDATE workspaces SET fabric_url = 'https://app.fabric.microsoft.com/groups/fws/list',
+               fabric_service_principal_id = 'client', fabric_service_principal_secret = 'secret'
+             WHERE id = 'ws-7'",
+            [],
+        )
+        .unwrap();
+        assert!(fabric_credentials(&conn, "ws-7")
+            .unwrap_err()
+            .to_string()
+            .contains("ctid"));
+
+        conn.execute(
+            "UPDATE workspaces SET fabric_url = fabric_url || '?ctid=tenant' WHERE id = 'ws-7'",
+            [],
+        )
+        .unwrap();
+        let (fabric_workspace_id, principal) = fabric_credentials(&conn, "ws-7").unwrap();
+        assert_eq!(fabric_workspace_id, "fws");
+        assert_eq!(principal.tenant_id, "tenant");
+        assert_eq!(principal.client_id, "client");
+    }
+
+    #[test]
+    fn persist_fabric_snapshot_updates_items_in_place() {
+        use crate::fabric::client::{FabricItem, WarehouseProperties};
+
+        let conn = db::open_in_memory().unwrap();
+        insert_workspace(&conn, "ws-8");
+        insert_item(&conn, "wh", "ws-8", "Warehouse");
+        insert_schema(&conn, "wh", "old");
+        conn.execute(
+            "INSERT INTO selected_tables(id, workspace_id, warehouse_item_id, schema_name, table_name)
+             VALUES ('sel', 'ws-8', 'wh', 'dbo', 'orders')",
+            [],
+        )
+        .unwrap();
+
+        let item = |id: &str, name: &str, item_type: &str| FabricItem {
+            id: id.to_string(),
+            display_name: name.to_string(),
+            item_type: item_type.to_string(),
+            description: None,
+            folder_id: None,
+        };
+        let snapshot = FabricSnapshot {
+            items: vec![
+                item("wh", "Sales", "Warehouse"),
+                item("pl", "Nightly", "DataPipeline"),
+                item("pl-bad", "Broken", "DataPipeline"),
+                item("nb", "Enrich", "Notebook"),
+            ],
+            warehouses: vec![(
+                "wh".to_string(),
+                WarehouseProperties {
+                    connection_string: Some("x.datawarehouse.fabric.microsoft.com".to_string()),
+                    collation_type: None,
+                },
+            )],
+            pipeline_definitions: vec![
+                (
+                    "pl".to_string(),
+                    r#"{"properties":{"activities":[{"name":"Load","type":"SqlServerStoredProcedure",
+                        "linkedService":{"properties":{"typeProperties":{"artifactId":"wh"}}},
+                        "typeProperties":{"storedProcedureName":"dbo.sp_load"}}]}}"#
+                        .to_string(),
+                ),
+                ("pl-bad".to_string(), "{}".to_string()),
+            ],
+            notebook_definitions: vec![(
+                "nb".to_string(),
+                "# Fabric notebook source\n\n# CELL ********************\n\nspark.table('dbo.orders')\n"
+                    .to_string(),
+            )],
+            warnings: vec!["warehouse Other: denied".to_string()],
+        };
+        let catalog = WarehouseCatalog {
+            schemas: vec![WarehouseSchema {
+                warehouse_item_id: "wh".to_string(),
+                schema_name: "dbo".to_string(),
+                schema_id_local: Some(1),
+            }],
+            tables: vec![WarehouseTable {
+                warehouse_item_id: "wh".to_string(),
+                schema_name: "dbo".to_string(),
+                table_name: "orders".to_string(),
+                object_id_local: Some(10),
+            }],
+            procedures: vec![WarehouseProcedure {
+                warehouse_item_id: "wh".to_string(),
+                schema_name: "dbo".to_string(),
+                procedure_name: "sp_load".to_string(),
+                object_id_local: Some(11),
+                sql_body: Some("CREATE PROCEDURE dbo.sp_load AS SELECT 1".to_string()),
+            }],
+        };
+
+        let summary =
+            persist_fabric_snapshot(&conn, "ws-8", &snapshot, &[("wh".to_string(), catalog)])
+                .unwrap();
+        assert_eq!(
+            (
+                summary.items,
+                summary.warehouses,
+                summary.schemas,
+                summary.tables
+            ),
+            (4, 1, 1, 1)
+        );
+        assert_eq!(summary.procedures, 1);
+        assert_eq!(summary.pipeline_activities, 1);
+        // No canonical source model has been applied yet: the notebook is stored for later.
+        assert_eq!((summary.notebooks, summary.notebook_links), (1, 0));
+        assert_eq!(summary.warnings.len(), 2);
+        assert!(summary.warnings[1].starts_with("pipeline pl-bad:"));
+        let stored: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM notebook_definitions WHERE notebook_item_id = 'nb'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(stored, 1);
+
+        let (name, connection): (String, Option<String>) = conn
+            .query_row(
+                "SELECT display_name, connection_string FROM items WHERE id = 'wh'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(name, "Sales");
+        assert_eq!(
+            connection.as_deref(),
+            Some("x.datawarehouse.fabric.microsoft.com")
+        );
+        let selections: i64 = conn
+            .query_row("SELECT COUNT(*) FROM selected_tables", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(selections, 1);
+        let schemas: Vec<String> = conn
+            .prepare("SELECT schema_name FROM warehouse_schemas WHERE warehouse_item_id = 'wh'")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(schemas, vec!["dbo"]);
+        let target: Option<String> = conn
+            .query_row(
+                "SELECT target_warehouse_item_id FROM pipeline_activities WHERE pipeline_item_id = 'pl'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(target.as_deref(), Some("wh"));
+    }
 }
@@ -1,20 +1,31 @@
 use tauri::State;
 
 use crate::db::DbState;
-use crate::types::{DeviceFlowResponse, GitHubAuthResult, GitHubRepo, GitHubUser};
+use crate::github::client::GitHubClient;
+use crate::github::config::GitHubInstance;
+use crate::types::{DeviceFlowResponse, GitHubAuthResult, GitHubConfig, GitHubRepo, GitHubUser};
 
-const GITHUB_CLIENT_ID: &str = "Ov23lioPbQz4gAFxEfhM";
+fn load_instance(state: &State<'_, DbState>) -> Result<GitHubInstance, String> {
+    let conn = state.0.lock().unwrap();
+    GitHubInstance::load(&conn)
+}
 
 /// Start the GitHub Device Flow by requesting a device code.
 #[tauri::command]
-pub async fn github_start_device_flow() -> Result<DeviceFlowResponse, String> {
+pub async fn github_start_device_flow(
+    state: State<'_, DbState>,
+) -> Result<DeviceFlowResponse, String> {
     log::info!("[github_start_device_flow] starting device flow");
+    let instance = load_instance(&state)?;
     let client = reqwest::Client::new();
 
     let response = client
-        .post("https://github.com/login/device/code")
+        .post(format!("{}/login/device/code", instance.web_base_url))
         .header("Accept", "application/json")
-        .form(&[("client_id", GITHUB_CLIENT_ID), ("scope", "repo,read:user")])
+        .form(&[
+            ("client_id", instance.oauth_client_id.as_str()),
+            ("scope", "repo,read:user,read:org"),
+        ])
         .send()
         .await
         .map_err(|e| {
@@ -75,13 +86,17 @@ pub async fn github_poll_for_token(
     device_code: String,
 ) -> Result<GitHubAuthResult, String> {
     log::info!("[github_poll_for_token] polling for token");
+    let instance = load_instance(&state)?;
     let client = reqwest::Client::new();
 
     let response = client
-        .post("https://github.com/login/oauth/access_token")
+        .post(format!(
+            "{}/login/oauth/access_token",
+            instance.web_base_url
+        ))
         .header("Accept", "application/json")
         .form(&[
-            ("client_id", GITHUB_CLIENT_ID),
+            ("client_id", instance.oauth_client_id.as_str()),
             ("device_code", device_code.as_str()),
             ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
         ])
@@ -119,7 +134,7 @@ pub async fn github_poll_for_token(
         .ok_or("Missing access_token in response")?
         .to_string();
 
-    let user = fetch_github_user(&client, &access_token)
+    let user = fetch_github_user(&client, &instance.api_base_url, &access_token)
         .await
         .map_err(|e| {
             log::error!("[github_poll_for_token] failed to fetch user profile: {e}");
@@ -181,29 +196,170 @@ pub fn github_logout(state: State<'_, DbState>) -> Result<(), String> {
     Ok(())
 }
 
+/// API client for the configured installation, signed in with the stored token.
+fn github_client(state: &State<'_, DbState>) -> Result<GitHubClient, String> {
+    let conn = state.0.lock().unwrap();
+    let settings = crate::db::read_settings(&conn)?;
+    let token = settings
+        .github_oauth_token
+        .ok_or_else(|| "GitHub is not connected".to_string())?;
+    Ok(GitHubClient::new(
+        GitHubInstance::load(&conn)?.endpoints(),
+        token,
+    ))
+}
+
+/// Largest `limit` `github_list_repos` accepts.
+const MAX_REPO_RESULTS: usize = 1000;
+
+/// List repos the user can access whose name contains `query`, optionally only those of
+/// the organization `org`. Pages through the whole listing until `limit` repos match.
 #[tauri::command]
 pub async fn github_list_repos(
     state: State<'_, DbState>,
     query: String,
     limit: Option<usize>,
+    org: Option<String>,
 ) -> Result<Vec<GitHubRepo>, String> {
-    log::info!("[github_list_repos] query={}", query);
-    let token = {
+    log::info!("[github_list_repos] query={} org={:?}", query, org);
+    let client = github_client(&state)?;
+    let org = org.map(|o| o.trim().to_string()).filter(|o| !o.is_empty());
+    let max = limit.unwrap_or(10).min(MAX_REPO_RESULTS);
+    client
+        .list_repos(org.as_deref(), &query, max)
+        .await
+        .map_err(|e| {
+            let msg = format!("Failed to list GitHub repos: {e}");
+            log::error!("[github_list_repos] {msg}");
+            msg
+        })
+}
+
+/// List the organizations the user belongs to, for org-scoped repo listing.
+#[tauri::command]
+pub async fn github_list_orgs(state: State<'_, DbState>) -> Result<Vec<String>, String> {
+    log::info!("[github_list_orgs]");
+    github_client(&state)?.list_orgs().await.map_err(|e| {
+        let msg = format!("Failed to list GitHub organizations: {e}");
+        log::error!("[github_list_orgs] {msg}");
+        msg
+    })
+}
+
+/// Create a private migration repo `owner/name`, generated from `template` (`owner/repo`)
+/// when one is given. `owner` defaults to the signed-in user.
+#[tauri::command]
+pub async fn github_create_repo(
+    state: State<'_, DbState>,
+    name: String,
+    owner: Option<String>,
+    description: Option<String>,
+    template: Option<String>,
+) -> Result<GitHubRepo, String> {
+    log::info!(
+        "[github_create_repo] owner={:?} name={} template={:?}",
+        owner,
+        name,
+        template
+    );
+    let client = github_client(&state)?;
+    let login = {
         let conn = state.0.lock().unwrap();
         let settings = crate::db::read_settings(&conn)?;
-        settings
-            .github_oauth_token
-            .ok_or_else(|| "GitHub is not connected".to_string())?
+        settings.github_user_login.unwrap_or_default()
     };
+    let name = name.trim();
+    if !is_valid_repo_name(name) {
+        let err = format!("'{name}' is not a valid repository name");
+        log::error!("[github_create_repo] {err}");
+        return Err(err);
+    }
+    let owner = owner
+        .map(|o| o.trim().to_string())
+        .filter(|o| !o.is_empty())
+        .unwrap_or_else(|| login.clone());
+    let template = template
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    let repo = client
+        .create_repo(
+            &owner,
+            name,
+            description.as_deref().unwrap_or_default(),
+            template.as_deref(),
+            &login,
+        )
+        .await
+        .map_err(|e| {
+            let msg = format!("Failed to create GitHub repo {owner}/{name}: {e}");
+            log::error!("[github_create_repo] {msg}");
+            msg
+        })?;
+    log::info!("[github_create_repo] created {}", repo.full_name);
+    Ok(repo)
+}
 
-    let client = reqwest::Client::new();
+/// GitHub repo names are ASCII letters, digits, `.`, `-` and `_`, and not `.` or `..`.
+fn is_valid_repo_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 100
+        && name != "."
+        && name != ".."
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
+/// The saved GitHub installation settings (blank fields mean github.com defaults).
+#[tauri::command]
+pub fn github_get_config(state: State<'_, DbState>) -> Result<GitHubConfig, String> {
+    log::info!("[github_get_config]");
+    let conn = state.0.lock().unwrap();
+    crate::db::read_github_config(&conn)
+}
+
+/// Save the GitHub host, API base and OAuth client ID for this installation. Switching to
+/// a different installation signs out, since the stored token belongs to the old one.
+#[tauri::command]
+pub fn github_save_config(state: State<'_, DbState>, config: GitHubConfig) -> Result<(), String> {
+    log::info!(
+        "[github_save_config] host={:?} api_base_url={:?}",
+        config.host,
+        config.api_base_url
+    );
+    let conn = state.0.lock().unwrap();
+    save_config(&conn, &config).map_err(|e| {
+        log::error!("[github_save_config] {e}");
+        e
+    })
+}
+
+fn save_config(conn: &rusqlite::Connection, config: &GitHubConfig) -> Result<(), String> {
+    let next = GitHubInstance::resolve(config)?;
+    let current = GitHubInstance::load(conn)?;
+    crate::db::write_github_config(conn, config)?;
+    if next.web_base_url != current.web_base_url || next.api_base_url != current.api_base_url {
+        let mut settings = crate::db::read_settings(conn)?;
+        if settings.github_oauth_token.is_some() {
+            log::info!("[github_save_config] GitHub installation changed; signing out");
+            settings.github_oauth_token = None;
+            settings.github_user_login = None;
+            settings.github_user_avatar = None;
+            settings.github_user_email = None;
+            crate::db::write_settings(conn, &settings)?;
+            let _ = crate::db::reconcile_and_persist_app_phase(conn)?;
+        }
+    }
+    Ok(())
+}
+
+async fn fetch_github_user(
+    client: &reqwest::Client,
+    api_base_url: &str,
+    token: &str,
+) -> Result<GitHubUser, String> {
     let response = client
-        .get("https://api.github.com/user/repos")
-        .query(&[
-            ("per_page", "100"),
-            ("sort", "updated"),
-            ("affiliation", "owner,collaborator,organization_member"),
-        ])
+        .get(format!("{api_base_url}/user"))
         .header("Authorization", format!("Bearer {}", token))
         .header("Accept", "application/vnd.github+json")
         .header("User-Agent", "MigrationUtility")
@@ -211,54 +367,46 @@ pub async fn github_list_repos(
         .send()
         .await
         .map_err(|e| {
-            let msg = format!("Failed to list GitHub repos: {e}");
-            log::error!("[github_list_repos] {msg}");
+            let msg = format!("Failed to fetch GitHub user: {e}");
+            log::error!("[fetch_github_user] {msg}");
             msg
         })?;
 
     let status = response.status();
     let body: serde_json::Value = response.json().await.map_err(|e| {
-        let msg = format!("Failed to parse repo list response: {e}");
-        log::error!("[github_list_repos] {msg}");
+        let msg = format!("Failed to parse GitHub user response: {e}");
+        log::error!("[fetch_github_user] {msg}");
         msg
     })?;
 
     if !status.is_success() {
         let message = body["message"].as_str().unwrap_or("Unknown error");
-        let err = format!("GitHub API error listing repos ({}): {}", status, message);
-        log::error!("[github_list_repos] {err}");
+        let err = format!("GitHub API error fetching user ({}): {}", status, message);
+        log::error!("[fetch_github_user] {err}");
         return Err(err);
     }
 
-    let query_lc = query.to_lowercase();
-    let max = limit.unwrap_or(10).min(100);
-    let repos = body
-        .as_array()
-        .ok_or_else(|| "Unexpected response format from GitHub".to_string())?
-        .iter()
-        .filter_map(|repo| {
-            let id = repo["id"].as_i64()?;
-            let full_name = repo["full_name"].as_str()?.to_string();
-            let private = repo["private"].as_bool().unwrap_or(false);
-            if !query_lc.is_empty() && !full_name.to_lowercase().contains(&query_lc) {
-                return None;
-            }
-            Some(GitHubRepo {
-                id,
-                full_name,
-                private,
-            })
-        })
-        .take(max)
-        .collect::<Vec<_>>();
+    let login = body["login"]
+        .as_str()
+        .ok_or("Missing login in user response")?
+        .to_string();
+    let avatar_url = body["avatar_url"]
+        .as_str()
+        .ok_or("Missing avatar_url in user response")?
+        .to_string();
+    let email = body["email"].as_str().map(|s| s.to_string());
 
-    Ok(repos)
+    Ok(GitHubUser {
+        login,
+        avatar_url,
+        email,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use crate::db;
-    use crate::types::AppSettings;
+    use crate::types::{AppSettings, GitHubConfig};
 
     #[test]
     fn settings_roundtrip_persists_github_fields() {
@@ -284,6 +432,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn repo_names_are_validated_before_creating() {
+        assert!(super::is_valid_repo_name("migration-sales_2.0"));
+        assert!(!super::is_valid_repo_name(""));
+        assert!(!super::is_valid_repo_name(".."));
+        assert!(!super::is_valid_repo_name("sales/plan"));
+        assert!(!super::is_valid_repo_name("sales plan"));
+    }
+
+    #[test]
+    fn switching_installation_signs_out() {
+        let conn = db::open_in_memory().unwrap();
+        let settings = AppSettings {
+            anthropic_api_key: None,
+            github_oauth_token: Some("tok_abc".to_string()),
+            github_user_login: Some("octocat".to_string()),
+            github_user_avatar: None,
+            github_user_email: None,
+        };
+        db::write_settings(&conn, &settings).unwrap();
+
+        let client_only = GitHubConfig {
+            oauth_client_id: Some("Iv1.other".to_string()),
+            ..GitHubConfig::default()
+        };
+        super::save_config(&conn, &client_only).unwrap();
+        assert!(db::read_settings(&conn)
+            .unwrap()
+            .github_oauth_token
+            .is_some());
+
+        let ghes = GitHubConfig {
+            host: Some("ghe.example.com".to_string()),
+            ..client_only
+        };
+        super::save_config(&conn, &ghes).unwrap();
+        assert_eq!(db::read_github_config(&conn).unwrap(), ghes);
+        let after = db::read_settings(&conn).unwrap();
+        assert!(after.github_oauth_token.is_none());
+        assert!(after.github_user_login.is_none());
+
+        let invalid = GitHubConfig {
+            host: Some("ftp://ghe.example.com".to_string()),
+            ..GitHubConfig::default()
+        };
+        assert!(super::save_config(&conn, &invalid).is_err());
+        assert_eq!(db::read_github_config(&conn).unwrap(), ghes);
+    }
+
     #[test]
     fn read_settings_returns_default_when_empty() {
         let conn = db::open_in_memory().unwrap();
@@ -360,49 +557,3 @@ mod tests {
         assert!(user.email.is_none());
     }
 }
-
-async fn fetch_github_user(client: &reqwest::Client, token: &str) -> Result<GitHubUser, String> {
-    let response = client
-        .get("https://api.github.com/user")
-        .header("Authorization", format!("Bearer {}", token))
-        .header("Accept", "application/vnd.github+json")
-        .header("User-Agent", "MigrationUtility")
-        .header("X-GitHub-Api-Version", "2022-11-28")
-        .send()
-        .await
-        .map_err(|e| {
-            let msg = format!("Failed to fetch GitHub user: {e}");
-            log::error!("[fetch_github_user] {msg}");
-            msg
-        })?;
-
-    let status = response.status();
-    let body: serde_json::Value = response.json().await.map_err(|e| {
-        let msg = format!("Failed to parse GitHub user response: {e}");
-        log::error!("[fetch_github_user] {msg}");
-        msg
-    })?;
-
-    if !status.is_success() {
-        let message = body["message"].as_str().unwrap_or("Unknown error");
-        let err = format!("GitHub API error fetching user ({}): {}", status, message);
-        log::error!("[fetch_github_user] {err}");
-        return Err(err);
-    }
-
-    let login = body["login"]
-        .as_str()
-        .ok_or("Missing login in user response")?
-        .to_string();
-    let avatar_url = body["avatar_url"]
-        .as_str()
-        .ok_or("Missing avatar_url in user response")?
-        .to_string();
-    let email = body["email"].as_str().map(|s| s.to_string());
-
-    Ok(GitHubUser {
-        login,
-        avatar_url,
-        email,
-    })
-}
@@ -0,0 +1,493 @@
+use std::collections::{BTreeSet, HashMap};
+
+use rusqlite::{params, Connection};
+use tauri::State;
+
+use crate::db::DbState;
+use crate::graph::dependency::{DependencyGraph, Direction};
+use crate::types::{
+    CommandError, GraphEdgeRelation, GraphNeighbor, GraphNode, GraphNodeKind, ImpactAnalysis,
+    ScopeWarning,
+};
+
+#[tauri::command]
+pub fn graph_upstream(
+    workspace_id: String,
+    node_id: String,
+    max_depth: Option<usize>,
+    state: State<DbState>,
+) -> Result<Vec<GraphNeighbor>, CommandError> {
+    log::info!(
+        "graph_upstream: workspace_id={} node_id={} max_depth={:?}",
+        workspace_id,
+        node_id,
+        max_depth
+    );
+    let conn = state.0.lock().unwrap();
+    traverse(
+        &conn,
+        &workspace_id,
+        &node_id,
+        Direction::Upstream,
+        max_depth,
+    )
+    .inspect_err(|e| log::error!("graph_upstream: failed: {e}"))
+}
+
+#[tauri::command]
+pub fn graph_downstream(
+    workspace_id: String,
+    node_id: String,
+    max_depth: Option<usize>,
+    state: State<DbState>,
+) -> Result<Vec<GraphNeighbor>, CommandError> {
+    log::info!(
+        "graph_downstream: workspace_id={} node_id={} max_depth={:?}",
+        workspace_id,
+        node_id,
+        max_depth
+    );
+    let conn = state.0.lock().unwrap();
+    traverse(
+        &conn,
+        &workspace_id,
+        &node_id,
+        Direction::Downstream,
+        max_depth,
+    )
+    .inspect_err(|e| log::error!("graph_downstream: failed: {e}"))
+}
+
+#[tauri::command]
+pub fn graph_strongly_connected_components(
+    workspace_id: String,
+    state: State<DbState>,
+) -> Result<Vec<Vec<GraphNode>>, CommandError> {
+    log::info!(
+        "graph_strongly_connected_components: workspace_id={}",
+        workspace_id
+    );
+    let conn = state.0.lock().unwrap();
+    let graph = DependencyGraph::load(&conn, &workspace_id).map_err(|e| {
+        log::error!("graph_strongly_connected_components: failed: {e}");
+        CommandError::from(e)
+    })?;
+    let components = graph.strongly_connected_components();
+    log::info!(
+        "graph_strongly_connected_components: cycles={}",
+        components.len()
+    );
+    Ok(components)
+}
+
+#[tauri::command]
+pub fn graph_impact(
+    workspace_id: String,
+    node_id: String,
+    state: State<DbState>,
+) -> Result<ImpactAnalysis, CommandError> {
+    log::info!(
+        "graph_impact: workspace_id={} node_id={}",
+        workspace_id,
+        node_id
+    );
+    let conn = state.0.lock().unwrap();
+    impact(&conn, &workspace_id, &node_id).inspect_err(|e| log::error!("graph_impact: failed: {e}"))
+}
+
+/// Selected tables whose upstream producers are out of scope, for the scope step to show.
+#[tauri::command]
+pub fn graph_scope_warnings(
+    workspace_id: String,
+    state: State<DbState>,
+) -> Result<Vec<ScopeWarning>, CommandError> {
+    log::info!("graph_scope_warnings: workspace_id={}", workspace_id);
+    let conn = state.0.lock().unwrap();
+    scope_warnings(&conn, &workspace_id)
+        .inspect_err(|e| log::error!("graph_scope_warnings: failed: {e}"))
+}
+
+fn traverse(
+    conn: &Connection,
+    workspace_id: &str,
+    node_id: &str,
+    direction: Direction,
+    max_depth: Option<usize>,
+) -> Result<Vec<GraphNeighbor>, CommandError> {
+    let graph = DependencyGraph::load(conn, workspace_id)?;
+    if graph.node(node_id).is_none() {
+        return Err(CommandError::NotFound(format!("graph node {node_id}")));
+    }
+    Ok(graph.traverse(node_id, direction, max_depth))
+}
+
+fn impact(
+    conn: &Connection,
+    workspace_id: &str,
+    node_id: &str,
+) -> Result<ImpactAnalysis, CommandError> {
+    DependencyGraph::load(conn, workspace_id)?
+        .impact(node_id)
+        .ok_or_else(|| CommandError::NotFound(format!("graph node {node_id}")))
+}
+
+/// Selected tables whose upstream tables or views are not selected themselves.
+///
+/// Selections are matched to the canonical model by schema and table name; tables that are
+/// not in the canonical model have no known producers and never warn.
+pub(crate) fn scope_warnings(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<Vec<ScopeWarning>, CommandError> {
+    let graph = DependencyGraph::load(conn, workspace_id)?;
+    let tables: HashMap<(String, String), &GraphNode> = graph
+        .nodes()
+        .iter()
+        .filter(|n| n.kind == GraphNodeKind::Table)
+        .filter_map(|n| {
+            let schema = n.schema_name.as_ref()?.to_lowercase();
+            Some(((schema, n.name.to_lowercase()), n))
+        })
+        .collect();
+
+    let mut stmt = conn.prepare(
+        "SELECT id, schema_name, table_name FROM selected_tables
+         WHERE workspace_id = ?1 ORDER BY schema_name, table_name",
+    )?;
+    let selected = stmt
+        .query_map(params![workspace_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let in_scope: BTreeSet<&str> = selected
+        .iter()
+        .filter_map(|(_, schema, table)| {
+            tables
+                .get(&(schema.to_lowercase(), table.to_lowercase()))
+                .map(|n| n.id.as_str())
+        })
+        .collect();
+
+    let mut warnings = Vec::new();
+    for (selected_table_id, schema_name, table_name) in &selected {
+        let Some(node) = tables.get(&(schema_name.to_lowercase(), table_name.to_lowercase()))
+        else {
+            continue;
+        };
+        let missing: BTreeSet<String> = graph
+            .traverse(&node.id, Direction::Upstream, None)
+            .into_iter()
+            .filter(|n| matches!(n.node.kind, GraphNodeKind::Table | GraphNodeKind::View))
+            .filter(|n| !in_scope.contains(n.node.id.as_str()))
+            .map(|n| match n.node.schema_name {
+                Some(schema) => format!("{schema}.{}", n.node.name),
+                None => n.node.name,
+            })
+            .collect();
+        if !missing.is_empty() {
+            warnings.push(ScopeWarning {
+                selected_table_id: selected_table_id.clone(),
+                schema_name: schema_name.clone(),
+                table_name: table_name.clone(),
+                missing_upstream: missing.into_iter().collect(),
+            });
+        }
+    }
+    Ok(warnings)
+}
+
+struct SelectionRow {
+    schema_name: String,
+    table_name: String,
+    procedure: Option<(String, String)>,
+    pipeline_activity_id: Option<i64>,
+}
+
+struct PipelineActivityRow {
+    id: i64,
+    pipeline_item_id: String,
+    activity_name: String,
+    /// Keyed by the pipeline item, since pipelines can share a display name.
+    node_id: String,
+    label: String,
+    target: Option<(String, String)>,
+    depends_on: Vec<String>,
+}
+
+fn export_node_id(kind: &str, label: &str) -> String {
+    format!("{kind}:{}", label.to_lowercase())
+}
+
+/// Lineage of the selected tables for the migration repo: each table, its writer procedure,
+/// the tables that procedure reads in the canonical model, and the pipeline activities that
+/// run it together with their ordering inside each pipeline.
+pub(crate) fn selection_lineage(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<DependencyGraph, CommandError> {
+    let mut stmt = conn.prepare(
+        "SELECT st.schema_name, st.table_name, ta.schema_name, ta.procedure_name,
+                ta.pipeline_activity_id
+         FROM selected_tables st
+         LEFT JOIN table_artifacts ta ON ta.selected_table_id = st.id
+         WHERE st.workspace_id = ?1
+         ORDER BY st.schema_name, st.table_name",
+    )?;
+    let selections = stmt
+        .query_map(params![workspace_id], |row| {
+            let schema: Option<String> = row.get(2)?;
+            let procedure: Option<String> = row.get(3)?;
+            Ok(SelectionRow {
+                schema_name: row.get(0)?,
+                table_name: row.get(1)?,
+                procedure: schema.zip(procedure),
+                pipeline_activity_id: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT pa.id, pa.pipeline_item_id, i.display_name, pa.activity_name,
+                pa.target_schema_name, pa.target_procedure_name, pa.depends_on_json
+         FROM pipeline_activities pa
+         JOIN items i ON i.id = pa.pipeline_item_id
+         WHERE i.workspace_id = ?1
+         ORDER BY i.display_name, pa.activity_name",
+    )?;
+    let activities = stmt
+        .query_map(params![workspace_id], |row| {
+            let schema: Option<String> = row.get(4)?;
+            let procedure: Option<String> = row.get(5)?;
+            let depends_on: Option<String> = row.get(6)?;
+            let pipeline_item_id: String = row.get(1)?;
+            let pipeline_name: String = row.get(2)?;
+            let activity_name: String = row.get(3)?;
+            Ok(PipelineActivityRow {
+                id: row.get(0)?,
+                node_id: export_node_id("activity", &format!("{pipeline_item_id}/{activity_name}")),
+                pipeline_item_id,
+                label: format!("{pipeline_name}/{activity_name}"),
+                activity_name,
+                target: procedure.map(|p| (schema.unwrap_or_else(|| "dbo".to_string()), p)),
+                depends_on: crate::graph::dependency::parse_depends_on(depends_on.as_deref()),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let canonical = DependencyGraph::load(conn, workspace_id)?;
+    let canonical_procedures: HashMap<(String, String), &str> = canonical
+        .nodes()
+        .iter()
+        .filter(|n| n.kind == GraphNodeKind::Procedure)
+        .filter_map(|n| {
+            let schema = n.schema_name.as_ref()?.to_lowercase();
+            Some(((schema, n.name.to_lowercase()), n.id.as_str()))
+        })
+        .collect();
+
+    let mut graph = DependencyGraph::new();
+    let mut included_activities = BTreeSet::new();
+    for selection in &selections {
+        let table_label = format!("{}.{}", selection.schema_name, selection.table_name);
+        let table_id = export_node_id("table", &table_label);
+        graph.add_node(GraphNode {
+            id: table_id.clone(),
+            kind: GraphNodeKind::Table,
+            schema_name: Some(selection.schema_name.clone()),
+            name: selection.table_name.clone(),
+        });
+        let Some((proc_schema, proc_name)) = &selection.procedure else {
+            continue;
+        };
+        let proc_id = export_node_id("procedure", &format!("{proc_schema}.{proc_name}"));
+        graph.add_node(GraphNode {
+            id: proc_id.clone(),
+            kind: GraphNodeKind::Procedure,
+            schema_name: Some(proc_schema.clone()),
+            name: proc_name.clone(),
+        });
+        graph.add_edge(&proc_id, &table_id, GraphEdgeRelation::Writes);
+
+        let proc_key = (proc_schema.to_lowercase(), proc_name.to_lowercase());
+        if let Some(canonical_id) = canonical_procedures.get(&proc_key) {
+            for edge in canonical
+                .edges()
+                .iter()
+                .filter(|e| e.to == *canonical_id && e.relation == GraphEdgeRelation::Reads)
+            {
+                let Some(source) = canonical
+                    .node(&edge.from)
+                    .filter(|n| matches!(n.kind, GraphNodeKind::Table | GraphNodeKind::View))
+                else {
+                    continue;
+                };
+                let kind = if source.kind == GraphNodeKind::View {
+                    "view"
+                } else {
+                    "table"
+                };
+                let source_label = format!(
+                    "{}.{}",
+                    source.schema_name.as_deref().unwrap_or_default(),
+                    source.name
+                );
+                let source_id = export_node_id(kind, &source_label);
+                graph.add_node(GraphNode {
+                    id: source_id.clone(),
+                    ..source.clone()
+                });
+                graph.add_edge(&source_id, &proc_id, GraphEdgeRelation::Reads);
+            }
+        }
+
+        for activity in &activities {
+            let runs_writer = activity.target.as_ref().is_some_and(|(schema, name)| {
+                (schema.to_lowercase(), name.to_lowercase()) == proc_key
+            });
+            if runs_writer || selection.pipeline_activity_id == Some(activity.id) {
+                graph.add_node(GraphNode {
+                    id: activity.node_id.clone(),
+                    kind: GraphNodeKind::PipelineActivity,
+                    schema_name: None,
+                    name: activity.label.clone(),
+                });
+                graph.add_edge(&activity.node_id, &proc_id, GraphEdgeRelation::Executes);
+                included_activities.insert(activity.id);
+            }
+        }
+    }
+
+    for activity in activities
+        .iter()
+        .filter(|a| included_activities.contains(&a.id))
+    {
+        for upstream in &activity.depends_on {
+            let predecessor = activities.iter().find(|a| {
+                a.pipeline_item_id == activity.pipeline_item_id
+                    && included_activities.contains(&a.id)
+                    && a.activity_name.eq_ignore_ascii_case(upstream)
+            });
+            if let Some(predecessor) = predecessor {
+                graph.add_edge(
+                    &predecessor.node_id,
+                    &activity.node_id,
+                    GraphEdgeRelation::Precedes,
+                );
+            }
+        }
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    const WS: &str = "ws-graph";
+
+    /// Canonical model with stg.orders -> dbo.load_fact -> dbo.fact_orders, a Fabric warehouse
+    /// item for selections and a pipeline whose Load activity runs after Stage.
+    fn setup(conn: &Connection) {
+        conn.execute(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![WS, "Test Workspace", "/tmp/repo", "2026-01-01T00:00:00Z"],
+        )
+        .unwrap();
+        conn.execute_batch(
+            "INSERT INTO sources(id, workspace_id, source_type, external_source_id)
+               VALUES ('source-ws-graph', 'ws-graph', 'sql_server', 'ext');
+             INSERT INTO containers(id, source_id, container_type, external_container_id, container_name)
+               VALUES ('container-ws-graph', 'source-ws-graph', 'database', '5', 'db');
+             INSERT INTO namespaces(id, container_id, namespace_name) VALUES
+               ('ns-dbo', 'container-ws-graph', 'dbo'),
+               ('ns-stg', 'container-ws-graph', 'stg');
+             INSERT INTO data_objects(id, namespace_id, object_name, object_type) VALUES
+               ('t-orders', 'ns-stg', 'orders', 'table'),
+               ('t-fact', 'ns-dbo', 'fact_orders', 'table'),
+               ('p-load', 'ns-dbo', 'load_fact', 'procedure');
+             INSERT INTO sqlserver_procedure_lineage(id, procedure_data_object_id, table_data_object_id, lineage_type)
+               VALUES ('l1', 'p-load', 't-orders', 'read'), ('l2', 'p-load', 't-fact', 'write');
+             INSERT INTO items(id, workspace_id, display_name, item_type) VALUES
+               ('wh', 'ws-graph', 'Warehouse', 'Warehouse'),
+               ('pl', 'ws-graph', 'Nightly', 'DataPipeline');
+             INSERT INTO pipeline_activities(id, pipeline_item_id, activity_name, activity_type, target_schema_name, target_procedure_name, depends_on_json)
+               VALUES (1, 'pl', 'Stage', 'Copy', NULL, NULL, NULL),
+                      (2, 'pl', 'Load', 'SqlServerStoredProcedure', 'dbo', 'LOAD_FACT',
+                       '[{\"activity\":\"Stage\",\"dependencyConditions\":[\"Succeeded\"]}]');",
+        )
+        .unwrap();
+    }
+
+    fn select(conn: &Connection, id: &str, schema: &str, table: &str) {
+        conn.execute(
+            "INSERT INTO selected_tables(id, workspace_id, warehouse_item_id, schema_name, table_name)
+             VALUES (?1, ?2, 'wh', ?3, ?4)",
+            params![id, WS, schema, table],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn load_links_lineage_and_pipeline_activities() {
+        let conn = db::open_in_memory().unwrap();
+        setup(&conn);
+        let graph = DependencyGraph::load(&conn, WS).unwrap();
+        assert_eq!(graph.nodes().len(), 5);
+        let edges: Vec<(&str, &str, GraphEdgeRelation)> = graph
+            .edges()
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.relation))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("t-orders", "p-load", GraphEdgeRelation::Reads),
+                ("p-load", "t-fact", GraphEdgeRelation::Writes),
+                ("pipeline-activity-2", "p-load", GraphEdgeRelation::Executes),
+                (
+                    "pipeline-activity-1",
+                    "pipeline-activity-2",
+                    GraphEdgeRelation::Precedes
+                ),
+            ]
+        );
+
+        let upstream = traverse(&conn, WS, "t-fact", Direction::Upstream, None).unwrap();
+        let names: Vec<&str> = upstream.iter().map(|n| n.node.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["load_fact", "Nightly/Load", "orders", "Nightly/Stage"]
+        );
+        assert!(matches!(
+            impact(&conn, WS, "missing"),
+            Err(CommandError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn scope_warnings_flag_unselected_upstream_tables() {
+        let conn = db::open_in_memory().unwrap();
+        setup(&conn);
+        select(&conn, "sel-fact", "dbo", "Fact_Orders");
+        select(&conn, "sel-other", "dbo", "not_in_model");
+
+        let warnings = scope_warnings(&conn, WS).unwrap();
+        assert_eq!(
+            warnings,
+            vec![ScopeWarning {
+                selected_table_id: "sel-fact".to_string(),
+                schema_name: "dbo".to_string(),
+                table_name: "Fact_Orders".to_string(),
+                missing_upstream: vec!["stg.orders".to_string()],
+            }]
+        );
+
+        select(&conn, "sel-orders", "stg", "orders");
+        assert!(scope_warnings(&conn, WS).unwrap().is_empty());
+    }
+}
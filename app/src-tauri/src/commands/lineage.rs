@@ -0,0 +1,702 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::State;
+
+use crate::db::DbState;
+use crate::fabric::notebook::{notebook_table_accesses, parse_notebook, CellLanguage};
+use crate::tsql::dynamic_sql::find_dynamic_sql;
+use crate::tsql::references::{extract_table_references, TableReference};
+use crate::types::{CommandError, DynamicSqlSite, NotebookLineageSummary, ProcedureLineageSummary};
+
+const WORKSPACE_PROCEDURES_SQL: &str = "SELECT o.id FROM data_objects o
+     JOIN namespaces n ON n.id = o.namespace_id
+     JOIN containers c ON c.id = n.container_id
+     JOIN sources s ON s.id = c.source_id
+     WHERE s.workspace_id = ?1 AND o.object_type = 'procedure'";
+
+#[tauri::command]
+pub fn lineage_rebuild_procedures(
+    workspace_id: String,
+    state: State<DbState>,
+) -> Result<ProcedureLineageSummary, CommandError> {
+    log::info!("lineage_rebuild_procedures: workspace_id={}", workspace_id);
+    let conn = state.0.lock().unwrap();
+    let tx = conn.unchecked_transaction().map_err(|e| {
+        log::error!("lineage_rebuild_procedures: failed to begin transaction: {e}");
+        CommandError::from(e)
+    })?;
+    let summary = rebuild_procedure_lineage(&tx, &workspace_id)
+        .inspect_err(|e| log::error!("lineage_rebuild_procedures: failed: {e}"))?;
+    tx.commit().map_err(|e| {
+        log::error!("lineage_rebuild_procedures: failed to commit: {e}");
+        CommandError::from(e)
+    })?;
+    Ok(summary)
+}
+
+#[tauri::command]
+pub fn lineage_list_dynamic_sql(
+    workspace_id: String,
+    resolution: Option<String>,
+    state: State<DbState>,
+) -> Result<Vec<DynamicSqlSite>, CommandError> {
+    log::info!(
+        "lineage_list_dynamic_sql: workspace_id={} resolution={:?}",
+        workspace_id,
+        resolution
+    );
+    let conn = state.0.lock().unwrap();
+    list_dynamic_sql(&conn, &workspace_id, resolution.as_deref())
+        .inspect_err(|e| log::error!("lineage_list_dynamic_sql: failed: {e}"))
+}
+
+/// Recompute `sqlserver_procedure_lineage` and `sqlserver_dynamic_sql` for every procedure
+/// in the workspace's canonical model.
+///
+/// Static references come from the procedure body itself (`static_sql` evidence). Dynamic SQL
+/// executions are reconstructed and their candidate statements scanned the same way
+/// (`dynamic_sql` evidence); sites that cannot be reconstructed are recorded as opaque so the
+/// procedure is not mistaken for one that touches nothing.
+pub(crate) fn rebuild_procedure_lineage(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<ProcedureLineageSummary, CommandError> {
+    let tables = load_table_objects(conn, workspace_id)?;
+    let procedures = load_procedures(conn, workspace_id)?;
+
+    conn.execute(
+        &format!(
+            "DELETE FROM sqlserver_procedure_lineage
+             WHERE procedure_data_object_id IN ({WORKSPACE_PROCEDURES_SQL})"
+        ),
+        params![workspace_id],
+    )?;
+    conn.execute(
+        &format!(
+            "DELETE FROM sqlserver_dynamic_sql
+             WHERE procedure_data_object_id IN ({WORKSPACE_PROCEDURES_SQL})"
+        ),
+        params![workspace_id],
+    )?;
+
+    let mut summary = ProcedureLineageSummary::default();
+    for procedure in &procedures {
+        let Some(body) = procedure.sql_body.as_deref() else {
+            continue;
+        };
+        summary.procedures_analyzed += 1;
+
+        for reference in extract_table_references(body).references {
+            summary.lineage_edges +=
+                insert_edge(conn, procedure, &tables, &reference, "static_sql")?;
+        }
+
+        for (index, call) in find_dynamic_sql(body).into_iter().enumerate() {
+            summary.dynamic_sql_sites += 1;
+            if call.resolution == "opaque" {
+                summary.opaque_sites += 1;
+            }
+            let statements_json = serde_json::to_string(&call.statements)
+                .map_err(|e| CommandError::Io(e.to_string()))?;
+            conn.execute(
+                "INSERT INTO sqlserver_dynamic_sql(
+                    id, procedure_data_object_id, line_number, exec_kind, resolution,
+                    statements_json, reason
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    format!("dynamic-sql-{}-{}", procedure.id, index + 1),
+                    procedure.id,
+                    call.line as i64,
+                    call.exec_kind,
+                    call.resolution,
+                    statements_json,
+                    call.reason,
+                ],
+            )?;
+            for statement in &call.statements {
+                for reference in extract_table_references(statement).references {
+                    summary.lineage_edges +=
+                        insert_edge(conn, procedure, &tables, &reference, "dynamic_sql")?;
+                }
+            }
+        }
+    }
+
+    log::info!(
+        "rebuild_procedure_lineage: workspace_id={} procedures={} edges={} dynamic_sql_sites={} opaque={}",
+        workspace_id,
+        summary.procedures_analyzed,
+        summary.lineage_edges,
+        summary.dynamic_sql_sites,
+        summary.opaque_sites
+    );
+    Ok(summary)
+}
+
+/// Keep a synced notebook's content so its lineage can be rebuilt after the canonical source
+/// model is replaced (see [`rebuild_notebook_lineage`]).
+pub(crate) fn store_notebook_definition(
+    conn: &Connection,
+    workspace_id: &str,
+    notebook_item_id: &str,
+    content: &str,
+) -> Result<(), CommandError> {
+    conn.execute(
+        "INSERT INTO notebook_definitions(notebook_item_id, workspace_id, content)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(notebook_item_id) DO UPDATE SET
+           workspace_id = excluded.workspace_id,
+           content = excluded.content",
+        params![notebook_item_id, workspace_id, content],
+    )?;
+    Ok(())
+}
+
+/// The workspace's canonical source, if one has been applied.
+pub(crate) fn workspace_source_id(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<Option<String>, CommandError> {
+    Ok(conn
+        .query_row(
+            "SELECT id FROM sources WHERE workspace_id = ?1 ORDER BY id LIMIT 1",
+            params![workspace_id],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Re-record lineage for every stored notebook in the workspace.
+///
+/// Notebook orchestration items hang off the canonical source, so they are lost whenever the
+/// source model is re-applied; this runs right after [`rebuild_procedure_lineage`] to restore
+/// them. Notebooks that no longer parse are skipped with a warning. Returns the links written.
+pub(crate) fn rebuild_notebook_lineage(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<usize, CommandError> {
+    let notebooks: Vec<(String, String, String)> = conn
+        .prepare(
+            "SELECT d.notebook_item_id, i.display_name, d.content
+             FROM notebook_definitions d
+             JOIN items i ON i.id = d.notebook_item_id
+             WHERE d.workspace_id = ?1
+             ORDER BY d.notebook_item_id",
+        )?
+        .query_map(params![workspace_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut links = 0;
+    for (notebook_item_id, name, content) in &notebooks {
+        match record_notebook_lineage(conn, workspace_id, notebook_item_id, name, content) {
+            Ok(summary) => links += summary.links,
+            Err(e @ CommandError::Database(_)) => return Err(e),
+            Err(e) => log::warn!("rebuild_notebook_lineage: skipping notebook {name}: {e}"),
+        }
+    }
+    log::info!(
+        "rebuild_notebook_lineage: workspace_id={} notebooks={} links={}",
+        workspace_id,
+        notebooks.len(),
+        links
+    );
+    Ok(links)
+}
+
+/// Replace the orchestration item recorded for a Fabric notebook with one activity per cell
+/// and `notebook` evidence links to the tables its SQL and PySpark cells read and write.
+///
+/// Links attach to the workspace's canonical source, so the source model must be applied
+/// first. Unqualified names resolve to `dbo`, then to the only table with that name.
+pub(crate) fn record_notebook_lineage(
+    conn: &Connection,
+    workspace_id: &str,
+    notebook_item_id: &str,
+    notebook_name: &str,
+    content: &str,
+) -> Result<NotebookLineageSummary, CommandError> {
+    let cells = parse_notebook(content).map_err(CommandError::Io)?;
+    let source_id = workspace_source_id(conn, workspace_id)?.ok_or_else(|| {
+        CommandError::NotFound(format!(
+            "workspace {workspace_id} has no source model; apply the workspace source first"
+        ))
+    })?;
+    let tables = load_table_objects(conn, workspace_id)?;
+
+    conn.execute(
+        "DELETE FROM orchestration_items
+         WHERE source_id = ?1 AND orchestration_type = 'fabric_notebook'
+           AND external_orchestration_id = ?2",
+        params![source_id, notebook_item_id],
+    )?;
+    let item_id = format!("orchestration-{workspace_id}-notebook-{notebook_item_id}");
+    conn.execute(
+        "INSERT INTO orchestration_items(
+            id, source_id, orchestration_type, external_orchestration_id, orchestration_name
+         ) VALUES (?1, ?2, 'fabric_notebook', ?3, ?4)",
+        params![item_id, source_id, notebook_item_id, notebook_name],
+    )?;
+    for cell in &cells {
+        conn.execute(
+            "INSERT INTO orchestration_activities(
+                id, orchestration_item_id, activity_name, external_activity_id, activity_type
+             ) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                format!("{item_id}-cell-{}", cell.index),
+                item_id,
+                format!("cell {}", cell.index),
+                cell.index.to_string(),
+                cell.language.as_str(),
+            ],
+        )?;
+    }
+
+    let scan = notebook_table_accesses(&cells);
+    let mut summary = NotebookLineageSummary {
+        notebook_item_id: notebook_item_id.to_string(),
+        cells: cells.len(),
+        analyzed_cells: cells
+            .iter()
+            .filter(|c| matches!(c.language, CellLanguage::Sql | CellLanguage::Python))
+            .count(),
+        dynamic_cells: scan.dynamic_cells,
+        ..Default::default()
+    };
+    for access in &scan.accesses {
+        let reference = &access.reference;
+        let Some(table_id) = resolve_notebook_table(&tables, reference) else {
+            let name = match &reference.schema {
+                Some(schema) => format!("{schema}.{}", reference.name),
+                None => reference.name.clone(),
+            };
+            if !summary.unresolved_tables.contains(&name) {
+                summary.unresolved_tables.push(name);
+            }
+            continue;
+        };
+        let activity_id = format!("{item_id}-cell-{}", access.cell_index);
+        let access_type = reference.access.as_str();
+        summary.links += conn.execute(
+            "INSERT OR IGNORE INTO activity_object_links(
+                id, orchestration_activity_id, data_object_id, access_type, evidence_source
+             ) VALUES (?1, ?2, ?3, ?4, 'notebook')",
+            params![
+                format!("link-{activity_id}-{table_id}-{access_type}"),
+                activity_id,
+                table_id,
+                access_type,
+            ],
+        )?;
+    }
+
+    log::info!(
+        "record_notebook_lineage: notebook_item_id={} cells={} links={} unresolved={} dynamic_cells={}",
+        notebook_item_id,
+        summary.cells,
+        summary.links,
+        summary.unresolved_tables.len(),
+        summary.dynamic_cells.len()
+    );
+    Ok(summary)
+}
+
+fn resolve_notebook_table<'a>(
+    tables: &'a TableIndex,
+    reference: &TableReference,
+) -> Option<&'a String> {
+    let name = reference.name.to_lowercase();
+    if let Some(schema) = &reference.schema {
+        return tables.get(&(schema.to_lowercase(), name));
+    }
+    if let Some(id) = tables.get(&("dbo".to_string(), name.clone())) {
+        return Some(id);
+    }
+    let mut matches = tables
+        .iter()
+        .filter(|((_, table), _)| *table == name)
+        .map(|(_, id)| id);
+    match (matches.next(), matches.next()) {
+        (Some(id), None) => Some(id),
+        _ => None,
+    }
+}
+
+struct ProcedureObject {
+    id: String,
+    schema_name: String,
+    sql_body: Option<String>,
+}
+
+/// Table and view object ids keyed by lowercase `(schema, name)`.
+type TableIndex = HashMap<(String, String), String>;
+
+fn load_table_objects(conn: &Connection, workspace_id: &str) -> Result<TableIndex, CommandError> {
+    let mut stmt = conn.prepare(
+        "SELECT n.namespace_name, o.object_name, o.id FROM data_objects o
+         JOIN namespaces n ON n.id = o.namespace_id
+         JOIN containers c ON c.id = n.container_id
+         JOIN sources s ON s.id = c.source_id
+         WHERE s.workspace_id = ?1 AND o.object_type IN ('table', 'view')",
+    )?;
+    let rows = stmt.query_map(params![workspace_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    let mut index = HashMap::new();
+    for row in rows {
+        let (schema, name, id) = row?;
+        index.insert((schema.to_lowercase(), name.to_lowercase()), id);
+    }
+    Ok(index)
+}
+
+fn load_procedures(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<Vec<ProcedureObject>, CommandError> {
+    let mut stmt = conn.prepare(
+        "SELECT o.id, n.namespace_name, o.sql_body FROM data_objects o
+         JOIN namespaces n ON n.id = o.namespace_id
+         JOIN containers c ON c.id = n.container_id
+         JOIN sources s ON s.id = c.source_id
+         WHERE s.workspace_id = ?1 AND o.object_type = 'procedure'
+         ORDER BY n.namespace_name, o.object_name",
+    )?;
+    let rows = stmt.query_map(params![workspace_id], |row| {
+        Ok(ProcedureObject {
+            id: row.get(0)?,
+            schema_name: row.get(1)?,
+            sql_body: row.get(2)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(CommandError::from)
+}
+
+/// Resolve `reference` to a table object and insert one lineage edge. Unqualified names are
+/// looked up in the procedure's own schema first, then `dbo`, as SQL Server does for modules.
+fn insert_edge(
+    conn: &Connection,
+    procedure: &ProcedureObject,
+    tables: &TableIndex,
+    reference: &TableReference,
+    evidence_source: &str,
+) -> Result<usize, CommandError> {
+    let name = reference.name.to_lowercase();
+    let table_id = match &reference.schema {
+        Some(schema) => tables.get(&(schema.to_lowercase(), name)),
+        None => tables
+            .get(&(procedure.schema_name.to_lowercase(), name.clone()))
+            .or_else(|| tables.get(&("dbo".to_string(), name))),
+    };
+    let Some(table_id) = table_id else {
+        return Ok(0);
+    };
+    let lineage_type = reference.access.as_str();
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO sqlserver_procedure_lineage(
+            id, procedure_data_object_id, table_data_object_id, lineage_type, evidence_source
+         ) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            format!(
+                "lineage-{}-{}-{}-{}",
+                procedure.id, table_id, lineage_type, evidence_source
+            ),
+            procedure.id,
+            table_id,
+            lineage_type,
+            evidence_source,
+        ],
+    )?;
+    Ok(inserted)
+}
+
+fn list_dynamic_sql(
+    conn: &Connection,
+    workspace_id: &str,
+    resolution: Option<&str>,
+) -> Result<Vec<DynamicSqlSite>, CommandError> {
+    let mut stmt = conn.prepare(
+        "SELECT d.procedure_data_object_id, n.namespace_name, o.object_name, d.line_number,
+                d.exec_kind, d.resolution, d.statements_json, d.reason
+         FROM sqlserver_dynamic_sql d
+         JOIN data_objects o ON o.id = d.procedure_data_object_id
+         JOIN namespaces n ON n.id = o.namespace_id
+         JOIN containers c ON c.id = n.container_id
+         JOIN sources s ON s.id = c.source_id
+         WHERE s.workspace_id = ?1 AND (?2 IS NULL OR d.resolution = ?2)
+         ORDER BY n.namespace_name, o.object_name, d.line_number",
+    )?;
+    let rows = stmt.query_map(params![workspace_id, resolution], |row| {
+        let statements_json: String = row.get(6)?;
+        Ok(DynamicSqlSite {
+            procedure_data_object_id: row.get(0)?,
+            schema_name: row.get(1)?,
+            procedure_name: row.get(2)?,
+            line_number: row.get(3)?,
+            exec_kind: row.get(4)?,
+            resolution: row.get(5)?,
+            statements: serde_json::from_str(&statements_json).unwrap_or_default(),
+            reason: row.get(7)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(CommandError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    /// Workspace with a canonical source model holding the given tables and procedures.
+    fn setup_canonical_model(
+        conn: &Connection,
+        tables: &[(&str, &str)],
+        procedures: &[(&str, &str, &str)],
+    ) -> String {
+        let ws_id = "ws-lineage".to_string();
+        conn.execute(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![ws_id, "Test Workspace", "/tmp/repo", "2026-01-01T00:00:00Z"],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO sources(id, workspace_id, source_type, external_source_id) VALUES (?1, ?2, 'sql_server', 'ext')",
+            params![format!("source-{ws_id}"), ws_id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO containers(id, source_id, container_type, external_container_id, container_name)
+             VALUES (?1, ?2, 'database', '5', 'db')",
+            params![format!("container-{ws_id}"), format!("source-{ws_id}")],
+        )
+        .unwrap();
+        let namespace = |schema: &str| format!("namespace-{ws_id}-{}", schema.to_lowercase());
+        let mut schemas: Vec<&str> = tables
+            .iter()
+            .map(|(s, _)| *s)
+            .chain(procedures.iter().map(|(s, _, _)| *s))
+            .collect();
+        schemas.sort();
+        schemas.dedup();
+        for schema in schemas {
+            conn.execute(
+                "INSERT INTO namespaces(id, container_id, namespace_name) VALUES (?1, ?2, ?3)",
+                params![namespace(schema), format!("container-{ws_id}"), schema],
+            )
+            .unwrap();
+        }
+        for (schema, table) in tables {
+            conn.execute(
+                "INSERT INTO data_objects(id, namespace_id, object_name, object_type) VALUES (?1, ?2, ?3, 'table')",
+                params![format!("table-{schema}-{table}"), namespace(schema), table],
+            )
+            .unwrap();
+        }
+        for (schema, procedure, body) in procedures {
+            conn.execute(
+                "INSERT INTO data_objects(id, namespace_id, object_name, object_type, sql_body) VALUES (?1, ?2, ?3, 'procedure', ?4)",
+                params![format!("proc-{schema}-{procedure}"), namespace(schema), procedure, body],
+            )
+            .unwrap();
+        }
+        ws_id
+    }
+
+    fn edges(conn: &Connection) -> Vec<(String, String, String, String)> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT procedure_data_object_id, table_data_object_id, lineage_type, evidence_source
+                 FROM sqlserver_procedure_lineage ORDER BY 1, 2, 3, 4",
+            )
+            .unwrap();
+        stmt.query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+    }
+
+    #[test]
+    fn rebuild_links_static_and_dynamic_references() {
+        let conn = db::open_in_memory().unwrap();
+        let ws_id = setup_canonical_model(
+            &conn,
+            &[
+                ("dbo", "fact_sales"),
+                ("stg", "sales"),
+                ("dbo", "dim_region"),
+            ],
+            &[
+                (
+                    "dbo",
+                    "load_sales",
+                    "CREATE PROCEDURE dbo.load_sales AS
+                     INSERT INTO fact_sales SELECT * FROM stg.sales;",
+                ),
+                (
+                    "dbo",
+                    "load_dynamic",
+                    "CREATE PROCEDURE dbo.load_dynamic @region sysname AS
+                     DECLARE @sql NVARCHAR(MAX) = N'TRUNCATE TABLE dbo.dim_region';
+                     EXEC sp_executesql @sql;
+                     SET @sql = N'SELECT * FROM stg.' + @region;
+                     EXEC(@sql);
+                     SELECT @sql = body FROM dbo.templates;
+                     EXEC(@sql);",
+                ),
+            ],
+        );
+
+        let summary = rebuild_procedure_lineage(&conn, &ws_id).unwrap();
+        assert_eq!(
+            summary,
+            ProcedureLineageSummary {
+                procedures_analyzed: 2,
+                lineage_edges: 3,
+                dynamic_sql_sites: 3,
+                opaque_sites: 1,
+            }
+        );
+        assert_eq!(
+            edges(&conn),
+            vec![
+                (
+                    "proc-dbo-load_dynamic".to_string(),
+                    "table-dbo-dim_region".to_string(),
+                    "write".to_string(),
+                    "dynamic_sql".to_string()
+                ),
+                (
+                    "proc-dbo-load_sales".to_string(),
+                    "table-dbo-fact_sales".to_string(),
+                    "write".to_string(),
+                    "static_sql".to_string()
+                ),
+                (
+                    "proc-dbo-load_sales".to_string(),
+                    "table-stg-sales".to_string(),
+                    "read".to_string(),
+                    "static_sql".to_string()
+                ),
+            ]
+        );
+
+        let sites = list_dynamic_sql(&conn, &ws_id, None).unwrap();
+        let resolutions: Vec<&str> = sites.iter().map(|s| s.resolution.as_str()).collect();
+        assert_eq!(resolutions, vec!["resolved", "partial", "opaque"]);
+        assert_eq!(sites[1].statements, vec!["SELECT * FROM stg.{@region}"]);
+
+        let opaque = list_dynamic_sql(&conn, &ws_id, Some("opaque")).unwrap();
+        assert_eq!(opaque.len(), 1);
+        assert_eq!(opaque[0].procedure_name, "load_dynamic");
+    }
+
+    #[test]
+    fn rebuild_replaces_previous_results() {
+        let conn = db::open_in_memory().unwrap();
+        let ws_id = setup_canonical_model(
+            &conn,
+            &[("dbo", "target")],
+            &[("dbo", "p", "EXEC('DELETE FROM dbo.target')")],
+        );
+        rebuild_procedure_lineage(&conn, &ws_id).unwrap();
+        let summary = rebuild_procedure_lineage(&conn, &ws_id).unwrap();
+        assert_eq!(summary.lineage_edges, 1);
+        assert_eq!(edges(&conn).len(), 1);
+        assert_eq!(list_dynamic_sql(&conn, &ws_id, None).unwrap().len(), 1);
+    }
+
+    fn notebook_links(conn: &Connection) -> Vec<(String, String, String)> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT a.activity_name, l.data_object_id, l.access_type
+                 FROM activity_object_links l
+                 JOIN orchestration_activities a ON a.id = l.orchestration_activity_id
+                 WHERE l.evidence_source = 'notebook' ORDER BY 1, 2, 3",
+            )
+            .unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn notebook_lineage_links_cells_to_tables() {
+        let conn = db::open_in_memory().unwrap();
+        let ws_id = setup_canonical_model(
+            &conn,
+            &[
+                ("dbo", "orders"),
+                ("silver", "orders_clean"),
+                ("gold", "daily"),
+            ],
+            &[],
+        );
+        let notebook = r#"{"cells": [
+            {"cell_type": "code", "source": "df = spark.table('orders')\ndf.write.saveAsTable('silver.orders_clean')\nspark.sql(q)"},
+            {"cell_type": "code", "source": "%%sql\nINSERT INTO gold.daily SELECT * FROM silver.orders_clean JOIN lake.missing m ON 1 = 1"}
+        ]}"#;
+
+        let summary = record_notebook_lineage(&conn, &ws_id, "nb-1", "Enrich", notebook).unwrap();
+        assert_eq!(
+            summary,
+            NotebookLineageSummary {
+                notebook_item_id: "nb-1".to_string(),
+                cells: 2,
+                analyzed_cells: 2,
+                links: 4,
+                unresolved_tables: vec!["lake.missing".to_string()],
+                dynamic_cells: vec![1],
+            }
+        );
+        let expected = vec![
+            (
+                "cell 1".to_string(),
+                "table-dbo-orders".to_string(),
+                "read".to_string(),
+            ),
+            (
+                "cell 1".to_string(),
+                "table-silver-orders_clean".to_string(),
+                "write".to_string(),
+            ),
+            (
+                "cell 2".to_string(),
+                "table-gold-daily".to_string(),
+                "write".to_string(),
+            ),
+            (
+                "cell 2".to_string(),
+                "table-silver-orders_clean".to_string(),
+                "read".to_string(),
+            ),
+        ];
+        assert_eq!(notebook_links(&conn), expected);
+
+        // Re-importing replaces the notebook's activities instead of adding to them.
+        record_notebook_lineage(&conn, &ws_id, "nb-1", "Enrich", notebook).unwrap();
+        assert_eq!(notebook_links(&conn), expected);
+        let items: i64 = conn
+            .query_row("SELECT COUNT(*) FROM orchestration_items", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(items, 1);
+    }
+
+    #[test]
+    fn notebook_lineage_requires_source_model() {
+        let conn = db::open_in_memory().unwrap();
+        let err = record_notebook_lineage(&conn, "ws-none", "nb", "Enrich", r#"{"cells": []}"#)
+            .unwrap_err();
+        assert!(matches!(err, CommandError::NotFound(_)));
+    }
+}
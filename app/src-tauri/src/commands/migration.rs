@@ -1,8 +1,10 @@
-use rusqlite::{params, OptionalExtension};
+use rusqlite::{params, Connection, OptionalExtension};
 use tauri::State;
 
 use crate::db::DbState;
-use crate::types::{Candidacy, CommandError, SelectedTable, TableArtifact, TableConfig};
+use crate::types::{
+    Candidacy, CandidacyOverride, CommandError, SelectedTable, TableArtifact, TableConfig,
+};
 
 #[tauri::command]
 pub fn migration_save_selected_tables(
@@ -44,10 +46,22 @@ pub fn migration_save_selected_tables(
             CommandError::from(e)
         })?;
     }
+    // Checked inside the transaction so a failed check does not report an error for a
+    // selection that was already saved.
+    let warnings = crate::commands::graph::scope_warnings(&tx, &workspace_id)
+        .inspect_err(|e| log::error!("migration_save_selected_tables: scope check failed: {e}"))?;
     tx.commit().map_err(|e| {
         log::error!("migration_save_selected_tables: failed to commit: {e}");
         CommandError::from(e)
     })?;
+    for warning in &warnings {
+        log::warn!(
+            "migration_save_selected_tables: {}.{} has unselected upstream tables: {}",
+            warning.schema_name,
+            warning.table_name,
+            warning.missing_upstream.join(", ")
+        );
+    }
     Ok(())
 }
 
@@ -129,27 +143,267 @@ pub fn migration_override_candidacy(
         new_tier
     );
     let conn = state.0.lock().unwrap();
-    let rows_affected = conn
-        .execute(
-            "UPDATE candidacy SET tier=?1, overridden=1, override_reason=?2 WHERE warehouse_item_id=?3 AND schema_name=?4 AND procedure_name=?5",
-            params![new_tier, reason, warehouse_item_id, schema_name, procedure_name],
-        )
-        .map_err(|e| {
-            log::error!("migration_override_candidacy: failed: {e}");
-            CommandError::from(e)
-        })?;
-    if rows_affected == 0 {
-        log::error!(
-            "migration_override_candidacy: not found {}.{}",
+    let actor = crate::db::read_stored_settings(&conn)
+        .map_err(CommandError::Io)?
+        .github_user_login;
+    override_candidacy(
+        &conn,
+        &warehouse_item_id,
+        &schema_name,
+        &procedure_name,
+        &new_tier,
+        &reason,
+        actor.as_deref(),
+    )
+    .inspect_err(|e| log::error!("migration_override_candidacy: failed: {e}"))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn migration_list_candidacy_overrides(
+    warehouse_item_id: String,
+    schema_name: String,
+    procedure_name: String,
+    state: State<DbState>,
+) -> Result<Vec<CandidacyOverride>, CommandError> {
+    log::info!(
+        "migration_list_candidacy_overrides: warehouse_item_id={} schema={} procedure={}",
+        warehouse_item_id,
+        schema_name,
+        procedure_name
+    );
+    let conn = state.0.lock().unwrap();
+    list_candidacy_overrides(&conn, &warehouse_item_id, &schema_name, &procedure_name)
+        .inspect_err(|e| log::error!("migration_list_candidacy_overrides: failed: {e}"))
+}
+
+#[tauri::command]
+pub fn migration_revert_candidacy_override(
+    override_id: i64,
+    reason: String,
+    state: State<DbState>,
+) -> Result<(), CommandError> {
+    log::info!(
+        "migration_revert_candidacy_override: override_id={}",
+        override_id
+    );
+    let conn = state.0.lock().unwrap();
+    let actor = crate::db::read_stored_settings(&conn)
+        .map_err(CommandError::Io)?
+        .github_user_login;
+    revert_candidacy_override(&conn, override_id, &reason, actor.as_deref())
+        .inspect_err(|e| log::error!("migration_revert_candidacy_override: failed: {e}"))?;
+    Ok(())
+}
+
+/// Current tier, overridden flag and override reason for a candidacy row.
+pub(crate) type CandidacyDecision = (String, bool, Option<String>);
+
+pub(crate) fn read_candidacy_decision(
+    conn: &Connection,
+    warehouse_item_id: &str,
+    schema_name: &str,
+    procedure_name: &str,
+) -> Result<Option<CandidacyDecision>, CommandError> {
+    conn.query_row(
+        "SELECT tier, overridden, override_reason FROM candidacy
+         WHERE warehouse_item_id=?1 AND schema_name=?2 AND procedure_name=?3",
+        params![warehouse_item_id, schema_name, procedure_name],
+        |row| Ok((row.get(0)?, row.get::<_, i64>(1)? != 0, row.get(2)?)),
+    )
+    .optional()
+    .map_err(CommandError::from)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn append_override_history(
+    conn: &Connection,
+    warehouse_item_id: &str,
+    schema_name: &str,
+    procedure_name: &str,
+    action: &str,
+    previous: &CandidacyDecision,
+    new_tier: &str,
+    reason: &str,
+    actor: Option<&str>,
+    reverts_override_id: Option<i64>,
+) -> Result<i64, CommandError> {
+    let (previous_tier, previous_overridden, previous_override_reason) = previous;
+    conn.execute(
+        "INSERT INTO candidacy_override_history(
+            warehouse_item_id, schema_name, procedure_name, action, previous_tier, new_tier,
+            previous_overridden, previous_override_reason, reason, actor, reverts_override_id,
+            created_at
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            warehouse_item_id,
             schema_name,
-            procedure_name
-        );
-        return Err(CommandError::NotFound(format!(
-            "{}.{}",
-            schema_name, procedure_name
+            procedure_name,
+            action,
+            previous_tier,
+            new_tier,
+            *previous_overridden as i64,
+            previous_override_reason,
+            reason,
+            actor,
+            reverts_override_id,
+            chrono::Utc::now().to_rfc3339(),
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Override the tier of a candidacy row and append the change to the override history.
+fn override_candidacy(
+    conn: &Connection,
+    warehouse_item_id: &str,
+    schema_name: &str,
+    procedure_name: &str,
+    new_tier: &str,
+    reason: &str,
+    actor: Option<&str>,
+) -> Result<i64, CommandError> {
+    let tx = conn.unchecked_transaction()?;
+    let previous = read_candidacy_decision(&tx, warehouse_item_id, schema_name, procedure_name)?
+        .ok_or_else(|| CommandError::NotFound(format!("{}.{}", schema_name, procedure_name)))?;
+
+    tx.execute(
+        "UPDATE candidacy SET tier=?1, overridden=1, override_reason=?2 WHERE warehouse_item_id=?3 AND schema_name=?4 AND procedure_name=?5",
+        params![new_tier, reason, warehouse_item_id, schema_name, procedure_name],
+    )?;
+    let override_id = append_override_history(
+        &tx,
+        warehouse_item_id,
+        schema_name,
+        procedure_name,
+        "override",
+        &previous,
+        new_tier,
+        reason,
+        actor,
+        None,
+    )?;
+    tx.commit()?;
+    Ok(override_id)
+}
+
+fn list_candidacy_overrides(
+    conn: &Connection,
+    warehouse_item_id: &str,
+    schema_name: &str,
+    procedure_name: &str,
+) -> Result<Vec<CandidacyOverride>, CommandError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, warehouse_item_id, schema_name, procedure_name, action, previous_tier, new_tier,
+                reason, actor, reverts_override_id, created_at
+         FROM candidacy_override_history
+         WHERE warehouse_item_id=?1 AND schema_name=?2 AND procedure_name=?3
+         ORDER BY id",
+    )?;
+    let rows = stmt
+        .query_map(
+            params![warehouse_item_id, schema_name, procedure_name],
+            |row| {
+                Ok(CandidacyOverride {
+                    id: row.get(0)?,
+                    warehouse_item_id: row.get(1)?,
+                    schema_name: row.get(2)?,
+                    procedure_name: row.get(3)?,
+                    action: row.get(4)?,
+                    previous_tier: row.get(5)?,
+                    new_tier: row.get(6)?,
+                    reason: row.get(7)?,
+                    actor: row.get(8)?,
+                    reverts_override_id: row.get(9)?,
+                    created_at: row.get(10)?,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Restore the candidacy decision that was in place before `override_id` was applied.
+/// The revert is itself appended to the history; the original entry is never modified.
+fn revert_candidacy_override(
+    conn: &Connection,
+    override_id: i64,
+    reason: &str,
+    actor: Option<&str>,
+) -> Result<i64, CommandError> {
+    let tx = conn.unchecked_transaction()?;
+    let entry: Option<(String, String, String, String, String, CandidacyDecision)> = tx
+        .query_row(
+            "SELECT warehouse_item_id, schema_name, procedure_name, action, new_tier,
+                    previous_tier, previous_overridden, previous_override_reason
+             FROM candidacy_override_history WHERE id=?1",
+            params![override_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    (row.get(5)?, row.get::<_, i64>(6)? != 0, row.get(7)?),
+                ))
+            },
+        )
+        .optional()?;
+    let (warehouse_item_id, schema_name, procedure_name, action, overridden_tier, restored) =
+        entry.ok_or_else(|| CommandError::NotFound(format!("candidacy override {override_id}")))?;
+
+    if action != "override" {
+        return Err(CommandError::Io(format!(
+            "History entry {override_id} is a revert and cannot itself be reverted"
         )));
     }
-    Ok(())
+    let already_reverted: bool = tx.query_row(
+        "SELECT EXISTS(SELECT 1 FROM candidacy_override_history WHERE reverts_override_id=?1)",
+        params![override_id],
+        |row| row.get(0),
+    )?;
+    if already_reverted {
+        return Err(CommandError::Io(format!(
+            "Override {override_id} has already been reverted"
+        )));
+    }
+
+    let current = read_candidacy_decision(&tx, &warehouse_item_id, &schema_name, &procedure_name)?
+        .ok_or_else(|| CommandError::NotFound(format!("{}.{}", schema_name, procedure_name)))?;
+    if current.0 != overridden_tier {
+        return Err(CommandError::Io(format!(
+            "Cannot revert override {override_id}: {}.{} has moved from '{}' to '{}' since",
+            schema_name, procedure_name, overridden_tier, current.0
+        )));
+    }
+
+    let (restored_tier, restored_overridden, restored_reason) = &restored;
+    tx.execute(
+        "UPDATE candidacy SET tier=?1, overridden=?2, override_reason=?3 WHERE warehouse_item_id=?4 AND schema_name=?5 AND procedure_name=?6",
+        params![
+            restored_tier,
+            *restored_overridden as i64,
+            restored_reason,
+            warehouse_item_id,
+            schema_name,
+            procedure_name
+        ],
+    )?;
+    let revert_id = append_override_history(
+        &tx,
+        &warehouse_item_id,
+        &schema_name,
+        &procedure_name,
+        "revert",
+        &current,
+        restored_tier,
+        reason,
+        actor,
+        Some(override_id),
+    )?;
+    tx.commit()?;
+    Ok(revert_id)
 }
 
 #[tauri::command]
@@ -214,9 +468,29 @@ pub fn migration_save_table_config(
         config.selected_table_id
     );
     let conn = state.0.lock().unwrap();
+    save_table_config(&conn, &config)
+}
+
+/// Upsert a table config. A save that omits `pii_masking_json` keeps the
+/// stored masking decisions instead of clearing them.
+fn save_table_config(
+    conn: &rusqlite::Connection,
+    config: &TableConfig,
+) -> Result<(), CommandError> {
     conn.execute(
-        "INSERT OR REPLACE INTO table_config(selected_table_id, table_type, load_strategy, grain_columns, relationships_json, incremental_column, date_column, snapshot_strategy, pii_columns, confirmed_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        "INSERT INTO table_config(selected_table_id, table_type, load_strategy, grain_columns, relationships_json, incremental_column, date_column, snapshot_strategy, pii_columns, pii_masking_json, confirmed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+         ON CONFLICT(selected_table_id) DO UPDATE SET
+           table_type = excluded.table_type,
+           load_strategy = excluded.load_strategy,
+           grain_columns = excluded.grain_columns,
+           relationships_json = excluded.relationships_json,
+           incremental_column = excluded.incremental_column,
+           date_column = excluded.date_column,
+           snapshot_strategy = excluded.snapshot_strategy,
+           pii_columns = excluded.pii_columns,
+           pii_masking_json = COALESCE(excluded.pii_masking_json, table_config.pii_masking_json),
+           confirmed_at = excluded.confirmed_at",
         params![
             config.selected_table_id,
             config.table_type,
@@ -227,6 +501,7 @@ pub fn migration_save_table_config(
             config.date_column,
             config.snapshot_strategy,
             config.pii_columns,
+            config.pii_masking_json,
             config.confirmed_at,
         ],
     )
@@ -249,7 +524,7 @@ pub fn migration_get_table_config(
     let conn = state.0.lock().unwrap();
     let result = conn
         .query_row(
-            "SELECT selected_table_id, table_type, load_strategy, grain_columns, relationships_json, incremental_column, date_column, snapshot_strategy, pii_columns, confirmed_at
+            "SELECT selected_table_id, table_type, load_strategy, grain_columns, relationships_json, incremental_column, date_column, snapshot_strategy, pii_columns, confirmed_at, pii_masking_json
              FROM table_config WHERE selected_table_id=?1",
             params![selected_table_id],
             |row| {
@@ -263,6 +538,7 @@ pub fn migration_get_table_config(
                     date_column: row.get(6)?,
                     snapshot_strategy: row.get(7)?,
                     pii_columns: row.get(8)?,
+                    pii_masking_json: row.get(10)?,
                     confirmed_at: row.get(9)?,
                 })
             },
@@ -353,6 +629,145 @@ mod tests {
         assert_eq!(reason.as_deref(), Some("Not suitable"));
     }
 
+    fn insert_candidacy(conn: &rusqlite::Connection, item_id: &str, tier: &str) {
+        conn.execute(
+            "INSERT INTO candidacy(warehouse_item_id, schema_name, procedure_name, tier, reasoning, overridden) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![item_id, "dbo", "sp_load", tier, "agent reasoning", 0],
+        )
+        .unwrap();
+    }
+
+    fn read_decision(conn: &rusqlite::Connection, item_id: &str) -> CandidacyDecision {
+        read_candidacy_decision(conn, item_id, "dbo", "sp_load")
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn override_candidacy_appends_history_with_actor() {
+        let conn = db::open_in_memory().unwrap();
+        let (_ws_id, item_id) = setup_workspace_and_item(&conn);
+        insert_candidacy(&conn, &item_id, "reject");
+
+        override_candidacy(
+            &conn,
+            &item_id,
+            "dbo",
+            "sp_load",
+            "review",
+            "Business owner confirmed still in use",
+            Some("octocat"),
+        )
+        .unwrap();
+        override_candidacy(
+            &conn,
+            &item_id,
+            "dbo",
+            "sp_load",
+            "migrate",
+            "Reviewed with data team",
+            Some("hubot"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_decision(&conn, &item_id),
+            (
+                "migrate".to_string(),
+                true,
+                Some("Reviewed with data team".to_string())
+            )
+        );
+
+        let history = list_candidacy_overrides(&conn, &item_id, "dbo", "sp_load").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].action, "override");
+        assert_eq!(history[0].previous_tier, "reject");
+        assert_eq!(history[0].new_tier, "review");
+        assert_eq!(history[0].actor.as_deref(), Some("octocat"));
+        assert_eq!(history[1].previous_tier, "review");
+        assert_eq!(history[1].new_tier, "migrate");
+        assert_eq!(history[1].actor.as_deref(), Some("hubot"));
+
+        let update = conn.execute(
+            "UPDATE candidacy_override_history SET reason='rewritten' WHERE id=?1",
+            rusqlite::params![history[0].id],
+        );
+        assert!(update.is_err(), "history rows must be append-only");
+    }
+
+    #[test]
+    fn override_candidacy_returns_not_found_for_missing_row() {
+        let conn = db::open_in_memory().unwrap();
+        let (_ws_id, item_id) = setup_workspace_and_item(&conn);
+
+        let result =
+            override_candidacy(&conn, &item_id, "dbo", "sp_load", "migrate", "reason", None);
+        assert!(matches!(result, Err(CommandError::NotFound(_))));
+        assert!(list_candidacy_overrides(&conn, &item_id, "dbo", "sp_load")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn revert_candidacy_override_restores_previous_decision() {
+        let conn = db::open_in_memory().unwrap();
+        let (_ws_id, item_id) = setup_workspace_and_item(&conn);
+        insert_candidacy(&conn, &item_id, "reject");
+
+        let override_id = override_candidacy(
+            &conn,
+            &item_id,
+            "dbo",
+            "sp_load",
+            "migrate",
+            "Needed for finance close",
+            Some("octocat"),
+        )
+        .unwrap();
+        let revert_id =
+            revert_candidacy_override(&conn, override_id, "Finance moved off it", Some("hubot"))
+                .unwrap();
+
+        assert_eq!(
+            read_decision(&conn, &item_id),
+            ("reject".to_string(), false, None)
+        );
+
+        let history = list_candidacy_overrides(&conn, &item_id, "dbo", "sp_load").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].id, revert_id);
+        assert_eq!(history[1].action, "revert");
+        assert_eq!(history[1].previous_tier, "migrate");
+        assert_eq!(history[1].new_tier, "reject");
+        assert_eq!(history[1].reverts_override_id, Some(override_id));
+        assert_eq!(history[1].actor.as_deref(), Some("hubot"));
+
+        assert!(
+            revert_candidacy_override(&conn, override_id, "again", None).is_err(),
+            "an override can only be reverted once"
+        );
+        assert!(
+            revert_candidacy_override(&conn, revert_id, "undo the undo", None).is_err(),
+            "revert entries cannot be reverted"
+        );
+    }
+
+    #[test]
+    fn revert_candidacy_override_rejects_when_tier_moved_since() {
+        let conn = db::open_in_memory().unwrap();
+        let (_ws_id, item_id) = setup_workspace_and_item(&conn);
+        insert_candidacy(&conn, &item_id, "reject");
+
+        let first =
+            override_candidacy(&conn, &item_id, "dbo", "sp_load", "review", "first", None).unwrap();
+        override_candidacy(&conn, &item_id, "dbo", "sp_load", "migrate", "second", None).unwrap();
+
+        let result = revert_candidacy_override(&conn, first, "stale revert", None);
+        assert!(matches!(result, Err(CommandError::Io(_))));
+        assert_eq!(read_decision(&conn, &item_id).0, "migrate");
+    }
+
     #[test]
     fn list_candidacy_returns_items_for_workspace() {
         let conn = db::open_in_memory().unwrap();
@@ -452,4 +867,45 @@ mod tests {
         assert_eq!(results[0].procedure_name, "sp_load");
         assert_eq!(results[0].tier, "migrate");
     }
+
+    #[test]
+    fn save_table_config_keeps_masking_when_field_is_omitted() {
+        let conn = db::open_in_memory().unwrap();
+        let (ws_id, item_id) = setup_workspace_and_item(&conn);
+        let st_id = uuid::Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO selected_tables(id, workspace_id, warehouse_item_id, schema_name, table_name) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![st_id, ws_id, item_id, "dbo", "customers"],
+        )
+        .unwrap();
+
+        let with_masking: TableConfig = serde_json::from_value(serde_json::json!({
+            "selectedTableId": st_id,
+            "snapshotStrategy": "sample_1day",
+            "piiColumns": "[\"email\"]",
+            "piiMaskingJson": "{\"email\":\"hash\"}",
+        }))
+        .unwrap();
+        save_table_config(&conn, &with_masking).unwrap();
+
+        // The frontend payload has no masking field.
+        let without_masking: TableConfig = serde_json::from_value(serde_json::json!({
+            "selectedTableId": st_id,
+            "loadStrategy": "incremental",
+            "snapshotStrategy": "sample_1day",
+            "piiColumns": "[\"email\"]",
+        }))
+        .unwrap();
+        save_table_config(&conn, &without_masking).unwrap();
+
+        let (load_strategy, masking): (Option<String>, Option<String>) = conn
+            .query_row(
+                "SELECT load_strategy, pii_masking_json FROM table_config WHERE selected_table_id=?1",
+                rusqlite::params![st_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(load_strategy.as_deref(), Some("incremental"));
+        assert_eq!(masking.as_deref(), Some("{\"email\":\"hash\"}"));
+    }
 }
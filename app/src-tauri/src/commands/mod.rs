@@ -2,8 +2,14 @@ pub mod agent;
 pub mod app_info;
 pub mod fabric;
 pub mod github_auth;
+pub mod graph;
+pub mod lineage;
 pub mod migration;
+pub mod pipeline;
 pub mod plan;
+pub mod repo_health;
+pub mod search;
 pub mod settings;
+pub mod transpile;
 pub mod usage;
 pub mod workspace;
@@ -0,0 +1,121 @@
+use rusqlite::{params, Connection};
+use tauri::State;
+
+use crate::db::DbState;
+use crate::graph::pipeline::{plan_pipeline, PipelineActivityInput};
+use crate::types::{CommandError, PipelineExecutionPlan};
+
+#[tauri::command]
+pub fn pipeline_execution_plan(
+    workspace_id: String,
+    pipeline_item_id: Option<String>,
+    state: State<DbState>,
+) -> Result<Vec<PipelineExecutionPlan>, CommandError> {
+    log::info!(
+        "pipeline_execution_plan: workspace_id={} pipeline_item_id={:?}",
+        workspace_id,
+        pipeline_item_id
+    );
+    let conn = state.0.lock().unwrap();
+    let plans = execution_plans(&conn, &workspace_id, pipeline_item_id.as_deref())
+        .inspect_err(|e| log::error!("pipeline_execution_plan: failed: {e}"))?;
+    for plan in &plans {
+        if !plan.cycles.is_empty() || !plan.dangling.is_empty() || !plan.parse_errors.is_empty() {
+            log::warn!(
+                "pipeline_execution_plan: pipeline={} cycles={} dangling={} parse_errors={}",
+                plan.pipeline_name,
+                plan.cycles.len(),
+                plan.dangling.len(),
+                plan.parse_errors.len()
+            );
+        }
+    }
+    Ok(plans)
+}
+
+/// Execution plans for the workspace's pipelines, ordered by pipeline name.
+fn execution_plans(
+    conn: &Connection,
+    workspace_id: &str,
+    pipeline_item_id: Option<&str>,
+) -> Result<Vec<PipelineExecutionPlan>, CommandError> {
+    let mut stmt = conn.prepare(
+        "SELECT i.id, i.display_name, pa.activity_name, pa.depends_on_json,
+                pa.parent_activity_name
+         FROM items i
+         LEFT JOIN pipeline_activities pa ON pa.pipeline_item_id = i.id
+         WHERE i.workspace_id = ?1 AND i.item_type = 'DataPipeline'
+           AND (?2 IS NULL OR i.id = ?2)
+         ORDER BY i.display_name, i.id, pa.activity_name",
+    )?;
+    let rows = stmt
+        .query_map(params![workspace_id, pipeline_item_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut pipelines: Vec<(String, String, Vec<PipelineActivityInput>)> = Vec::new();
+    for (item_id, display_name, activity_name, depends_on_json, parent) in rows {
+        if pipelines.last().is_none_or(|(id, _, _)| *id != item_id) {
+            pipelines.push((item_id, display_name, Vec::new()));
+        }
+        if let (Some(name), Some((_, _, activities))) = (activity_name, pipelines.last_mut()) {
+            activities.push(PipelineActivityInput {
+                name,
+                depends_on_json,
+                parent,
+            });
+        }
+    }
+    if let Some(id) = pipeline_item_id {
+        if pipelines.is_empty() {
+            return Err(CommandError::NotFound(format!("pipeline {id}")));
+        }
+    }
+    Ok(pipelines
+        .iter()
+        .map(|(id, name, activities)| plan_pipeline(id, name, activities))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    #[test]
+    fn execution_plans_group_activities_by_pipeline() {
+        let conn = db::open_in_memory().unwrap();
+        conn.execute_batch(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at)
+               VALUES ('ws', 'Test', '/tmp/repo', '2026-01-01T00:00:00Z');
+             INSERT INTO items(id, workspace_id, display_name, item_type) VALUES
+               ('wh', 'ws', 'WH', 'Warehouse'),
+               ('p1', 'ws', 'Nightly', 'DataPipeline'),
+               ('p2', 'ws', 'Empty', 'DataPipeline');
+             INSERT INTO pipeline_activities(pipeline_item_id, activity_name, activity_type, depends_on_json) VALUES
+               ('p1', 'Load', 'SqlServerStoredProcedure', '[{\"activity\":\"Stage\",\"dependencyConditions\":[\"Succeeded\"]}]'),
+               ('p1', 'Stage', 'Copy', NULL);",
+        )
+        .unwrap();
+
+        let plans = execution_plans(&conn, "ws", None).unwrap();
+        let names: Vec<&str> = plans.iter().map(|p| p.pipeline_name.as_str()).collect();
+        assert_eq!(names, vec!["Empty", "Nightly"]);
+        assert!(plans[0].order.is_empty());
+        assert_eq!(plans[1].order, vec!["Stage", "Load"]);
+
+        let single = execution_plans(&conn, "ws", Some("p1")).unwrap();
+        assert_eq!(single.len(), 1);
+        assert!(matches!(
+            execution_plans(&conn, "ws", Some("wh")),
+            Err(CommandError::NotFound(_))
+        ));
+    }
+}
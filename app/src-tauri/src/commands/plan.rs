@@ -1,120 +1,687 @@
-use std::fmt::Write as FmtWrite;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension};
 use tauri::State;
 
+use crate::commands::repo_health::ensure_plan_repo_writable;
 use crate::db::DbState;
-use crate::types::CommandError;
+use crate::dbt::project::{
+    identifier, split_columns, write_scaffold, DbtProject, ModelSpec, SourceSchema, DBT_DIR,
+};
+use crate::dbt::schema_tests::{parse_relationships, ColumnSpec};
+use crate::fabric::client::parse_fabric_url;
+use crate::git::{self, GitAuthor};
+use crate::github::client::GitHubClient;
+use crate::github::config::GitHubInstance;
+use crate::graph::export;
+use crate::plan::diff::diff_plans;
+use crate::plan::file::{load_plan, parse_plan, render_plan, PlanFile, PLAN_FILE};
+use crate::plan::markdown::{render_plan_markdown, render_pull_request_body};
+use crate::plan::reconcile::{exported_plan, import_plan, record_export};
+use crate::plan::validate::validate_plan;
+use crate::types::{
+    AppSettings, CommandError, DbtScaffoldSummary, PlanDiff, PlanImportSummary, PlanPublishResult,
+    PlanPullRequest, PlanValidation,
+};
+
+/// Directory inside the migration repo that holds the generated lineage artifacts.
+const LINEAGE_DIR: &str = "lineage";
+
+/// Repo paths the plan commands write; anything else in the repo belongs to the user.
+pub(crate) const GENERATED_PATHS: &[&str] = &["plan.md", PLAN_FILE, LINEAGE_DIR, DBT_DIR];
+
+/// Branch `plan_publish` commits to when none is given.
+pub(crate) const DEFAULT_PLAN_BRANCH: &str = "migration-utility/plan";
 
 #[tauri::command]
-pub fn plan_serialize(workspace_id: String, state: State<DbState>) -> Result<(), CommandError> {
+pub fn plan_serialize(
+    workspace_id: String,
+    state: State<DbState>,
+) -> Result<PlanDiff, CommandError> {
     log::info!("plan_serialize: workspace_id={}", workspace_id);
     let conn = state.0.lock().unwrap();
+    ensure_plan_repo_writable(&conn, &workspace_id, DEFAULT_PLAN_BRANCH, "plan_serialize")?;
+    serialize_plan(&conn, &workspace_id, &chrono::Utc::now().to_rfc3339())
+        .inspect_err(|e| log::error!("plan_serialize: failed: {e}"))
+}
+
+/// Write `plan.md`, `plan.json` and the lineage artifacts into the migration repo and
+/// return what changed since the previously committed plan.
+pub(crate) fn serialize_plan(
+    conn: &Connection,
+    workspace_id: &str,
+    generated_at: &str,
+) -> Result<PlanDiff, CommandError> {
+    let migration_repo_path: String = conn.query_row(
+        "SELECT migration_repo_path FROM workspaces WHERE id=?1",
+        params![workspace_id],
+        |row| row.get(0),
+    )?;
+    let repo = Path::new(&migration_repo_path);
+
+    log::debug!("plan_serialize: loading plan data");
+    let current = load_plan(conn, workspace_id, generated_at)?;
+    let previous = match committed_plan(repo) {
+        Some(plan) => Some(plan),
+        None => exported_plan(conn, workspace_id)?,
+    };
+    let diff = diff_plans(previous.as_ref(), &current);
+
+    log::debug!(
+        "plan_serialize: building plan.md with {} tables",
+        current.tables.len()
+    );
+    let plan_path = repo.join("plan.md");
+    fs::write(
+        &plan_path,
+        render_plan_markdown(&current, &diff, LINEAGE_DIR),
+    )?;
+    log::info!("plan_serialize: wrote plan.md to {}", plan_path.display());
+
+    write_plan_file(conn, &current, repo)?;
+    write_lineage_artifacts(conn, workspace_id, repo)?;
+    log::info!(
+        "plan_serialize: added={} removed={} tier_changes={} load_strategy_changes={}",
+        diff.tables_added.len(),
+        diff.tables_removed.len(),
+        diff.tier_changes.len(),
+        diff.load_strategy_changes.len()
+    );
+    Ok(diff)
+}
+
+/// `plan.json` as of the repo's HEAD commit; `None` when the repo has no commit of it.
+fn committed_plan(repo: &Path) -> Option<PlanFile> {
+    let Some(content) = git::read_head_file(repo, PLAN_FILE) else {
+        log::debug!("plan_serialize: no committed {PLAN_FILE}, using the last export");
+        return None;
+    };
+    parse_plan(&content)
+        .inspect_err(|e| log::warn!("plan_serialize: ignoring committed {PLAN_FILE}: {e}"))
+        .ok()
+}
+
+/// Write `<repo>/plan.json` and keep it as the base for the next `plan_import`.
+fn write_plan_file(conn: &Connection, plan: &PlanFile, repo: &Path) -> Result<(), CommandError> {
+    let content = render_plan(plan);
+    fs::write(repo.join(PLAN_FILE), &content)?;
+    record_export(conn, &plan.workspace_id, &content, &plan.generated_at)?;
+    Ok(())
+}
+
+/// Reconcile `<repo>/plan.json`, as edited in pull requests, back into the app.
+#[tauri::command]
+pub fn plan_import(
+    workspace_id: String,
+    state: State<DbState>,
+) -> Result<PlanImportSummary, CommandError> {
+    log::info!("plan_import: workspace_id={}", workspace_id);
+    let conn = state.0.lock().unwrap();
+    let actor = crate::db::read_stored_settings(&conn)
+        .map_err(CommandError::Io)?
+        .github_user_login;
+    import_plan_file(&conn, &workspace_id, actor.as_deref())
+        .inspect_err(|e| log::error!("plan_import: failed: {e}"))
+}
+
+pub(crate) fn import_plan_file(
+    conn: &Connection,
+    workspace_id: &str,
+    actor: Option<&str>,
+) -> Result<PlanImportSummary, CommandError> {
+    let repo_path: String = conn.query_row(
+        "SELECT migration_repo_path FROM workspaces WHERE id=?1",
+        params![workspace_id],
+        |row| row.get(0),
+    )?;
+    let path = Path::new(&repo_path).join(PLAN_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(CommandError::NotFound(path.display().to_string()))
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let plan = parse_plan(&content).map_err(CommandError::Io)?;
+    let base = exported_plan(conn, workspace_id)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let summary = import_plan(&tx, workspace_id, &plan, base.as_ref(), actor)?;
+    tx.commit()?;
+    log::info!(
+        "plan_import: added={} updated={} fields={} conflicts={}",
+        summary.tables_added,
+        summary.tables_updated,
+        summary.fields_applied,
+        summary.conflicts.len()
+    );
+    Ok(summary)
+}
+
+/// Issues to fix before the plan can be finalized; errors block finalization.
+#[tauri::command]
+pub fn plan_validate(
+    workspace_id: String,
+    state: State<DbState>,
+) -> Result<PlanValidation, CommandError> {
+    log::info!("plan_validate: workspace_id={}", workspace_id);
+    let conn = state.0.lock().unwrap();
+    let validation = validate_plan(&conn, &workspace_id)
+        .map_err(CommandError::from)
+        .inspect_err(|e| log::error!("plan_validate: failed: {e}"))?;
+    log::info!(
+        "plan_validate: issues={} can_finalize={}",
+        validation.issues.len(),
+        validation.can_finalize
+    );
+    Ok(validation)
+}
+
+/// Regenerate the plan artifacts on a working branch of the migration repo, commit them as
+/// the signed-in GitHub user and push the branch to origin. When the workspace has a GitHub
+/// repo, open (or update) the branch's pull request and link it to the workspace.
+#[tauri::command]
+pub async fn plan_publish(
+    workspace_id: String,
+    branch: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<PlanPublishResult, CommandError> {
+    log::info!(
+        "plan_publish: workspace_id={} branch={:?}",
+        workspace_id,
+        branch
+    );
+    let branch = branch
+        .map(|b| b.trim().to_string())
+        .filter(|b| !b.is_empty())
+        .unwrap_or_else(|| DEFAULT_PLAN_BRANCH.to_string());
+    let (repo_path, token) = {
+        let conn = state.0.lock().unwrap();
+        let settings = crate::db::read_settings(&conn).map_err(CommandError::Io)?;
+        let repo_path: Option<String> = conn
+            .query_row(
+                "SELECT migration_repo_path FROM workspaces WHERE id = ?1",
+                params![workspace_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        (repo_path, settings.github_oauth_token)
+    };
+    // Fetch first so the health check sees commits pushed from elsewhere.
+    if let Some(repo_path) = repo_path {
+        let fetched = tokio::task::spawn_blocking(move || {
+            git::fetch(Path::new(&repo_path), token.as_deref())
+        })
+        .await
+        .map_err(|e| CommandError::Git(e.to_string()))?;
+        if let Err(e) = fetched {
+            log::warn!("plan_publish: fetch failed, checking against the last fetch: {e}");
+        }
+    }
+    let generated_at = chrono::Utc::now().to_rfc3339();
+    let (repo, token, instance, repo_name, display_name, validation, mut result) = {
+        let conn = state.0.lock().unwrap();
+        let settings = crate::db::read_settings(&conn).map_err(CommandError::Io)?;
+        let instance = GitHubInstance::load(&conn).map_err(CommandError::Io)?;
+        let author = plan_author(&settings, &instance)?;
+        let (repo, result) = commit_plan(&conn, &workspace_id, &branch, &author, &generated_at)
+            .inspect_err(|e| log::error!("plan_publish: failed: {e}"))?;
+        let (display_name, repo_name): (String, Option<String>) = conn.query_row(
+            "SELECT display_name, migration_repo_name FROM workspaces WHERE id = ?1",
+            params![workspace_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let validation = validate_plan(&conn, &workspace_id)?;
+        (
+            repo,
+            settings.github_oauth_token,
+            instance,
+            repo_name,
+            display_name,
+            validation,
+            result,
+        )
+    };
+
+    let (push_repo, push_branch, push_token) = (repo.clone(), branch.clone(), token.clone());
+    tokio::task::spawn_blocking(move || git::push(&push_repo, &push_branch, push_token.as_deref()))
+        .await
+        .map_err(|e| CommandError::Git(e.to_string()))?
+        .inspect_err(|e| log::error!("plan_publish: push failed: {e}"))?;
+    log::info!(
+        "plan_publish: pushed {} at {:?}",
+        result.branch,
+        result.commit_sha
+    );
+
+    let Some(repo_name) = repo_name.filter(|name| !name.is_empty()) else {
+        return Ok(result);
+    };
+    let token = token.ok_or_else(|| CommandError::Io("GitHub is not connected".to_string()))?;
+    let plan = parse_plan(&fs::read_to_string(repo.join(PLAN_FILE))?).map_err(CommandError::Io)?;
+    let client = GitHubClient::new(instance.endpoints(), token);
+    let pull_request = open_plan_pull_request(
+        &client,
+        &repo_name,
+        &branch,
+        &display_name,
+        &plan,
+        &validation,
+        &generated_at,
+    )
+    .await
+    .inspect_err(|e| log::error!("plan_publish: pull request failed: {e}"))?;
+    {
+        let conn = state.0.lock().unwrap();
+        save_pull_request(&conn, &workspace_id, &pull_request)?;
+    }
+    log::info!(
+        "plan_publish: pull request #{} at {}",
+        pull_request.number,
+        pull_request.url
+    );
+    result.pull_request = Some(pull_request);
+    Ok(result)
+}
+
+/// The pull request last opened for the workspace's plan branch.
+#[tauri::command]
+pub fn plan_get_pull_request(
+    workspace_id: String,
+    state: State<DbState>,
+) -> Result<Option<PlanPullRequest>, CommandError> {
+    log::info!("plan_get_pull_request: workspace_id={}", workspace_id);
+    let conn = state.0.lock().unwrap();
+    linked_pull_request(&conn, &workspace_id)
+        .map_err(CommandError::from)
+        .inspect_err(|e| log::error!("plan_get_pull_request: failed: {e}"))
+}
 
-    // Fetch workspace to get migration_repo_path
-    log::debug!("plan_serialize: fetching workspace record");
-    let (migration_repo_path, generated_ws_id): (String, String) = conn
+/// Create the pull request for `branch`, or refresh the title and body of the open one. The
+/// body's changelog compares against `plan.json` on the repo's default branch.
+async fn open_plan_pull_request(
+    client: &GitHubClient,
+    repo_name: &str,
+    branch: &str,
+    display_name: &str,
+    plan: &PlanFile,
+    validation: &PlanValidation,
+    updated_at: &str,
+) -> Result<PlanPullRequest, CommandError> {
+    let base = client.default_branch(repo_name).await?;
+    let base_plan = client
+        .file_content(repo_name, PLAN_FILE, &base)
+        .await?
+        .and_then(|content| {
+            parse_plan(&content)
+                .inspect_err(|e| log::warn!("plan_publish: ignoring {PLAN_FILE} on {base}: {e}"))
+                .ok()
+        });
+    let diff = diff_plans(base_plan.as_ref(), plan);
+    let title = plan_title(display_name);
+    let body = render_pull_request_body(display_name, plan, &diff, validation);
+    let pull = match client.find_open_pull_request(repo_name, branch).await? {
+        Some(open) => {
+            client
+                .update_pull_request(repo_name, open.number, &title, &body)
+                .await?
+        }
+        None => {
+            client
+                .create_pull_request(repo_name, branch, &base, &title, &body)
+                .await?
+        }
+    };
+    Ok(PlanPullRequest {
+        number: pull.number,
+        url: pull.html_url,
+        branch: branch.to_string(),
+        updated_at: updated_at.to_string(),
+    })
+}
+
+fn save_pull_request(
+    conn: &Connection,
+    workspace_id: &str,
+    pull_request: &PlanPullRequest,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO plan_pull_requests(workspace_id, number, url, branch, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(workspace_id) DO UPDATE SET number=excluded.number, url=excluded.url,
+           branch=excluded.branch, updated_at=excluded.updated_at",
+        params![
+            workspace_id,
+            pull_request.number,
+            pull_request.url,
+            pull_request.branch,
+            pull_request.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+fn linked_pull_request(
+    conn: &Connection,
+    workspace_id: &str,
+) -> rusqlite::Result<Option<PlanPullRequest>> {
+    conn.query_row(
+        "SELECT number, url, branch, updated_at FROM plan_pull_requests WHERE workspace_id=?1",
+        params![workspace_id],
+        |row| {
+            Ok(PlanPullRequest {
+                number: row.get(0)?,
+                url: row.get(1)?,
+                branch: row.get(2)?,
+                updated_at: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Commit author for generated artifacts: the signed-in GitHub user, with the no-reply
+/// address when GitHub did not share an email.
+fn plan_author(
+    settings: &AppSettings,
+    instance: &GitHubInstance,
+) -> Result<GitAuthor, CommandError> {
+    let login = settings
+        .github_user_login
+        .as_deref()
+        .filter(|login| !login.is_empty())
+        .ok_or_else(|| CommandError::Io("Sign in to GitHub before publishing the plan".into()))?;
+    let email = settings
+        .github_user_email
+        .clone()
+        .filter(|email| !email.is_empty())
+        .unwrap_or_else(|| instance.noreply_email(login));
+    Ok(GitAuthor {
+        name: login.to_string(),
+        email,
+    })
+}
+
+/// Check out `branch`, regenerate the plan artifacts and commit them. Returns the repo path
+/// to push from.
+pub(crate) fn commit_plan(
+    conn: &Connection,
+    workspace_id: &str,
+    branch: &str,
+    author: &GitAuthor,
+    generated_at: &str,
+) -> Result<(PathBuf, PlanPublishResult), CommandError> {
+    let (display_name, repo_path): (String, String) = conn
         .query_row(
-            "SELECT migration_repo_path, id FROM workspaces WHERE id=?1",
+            "SELECT display_name, migration_repo_path FROM workspaces WHERE id = ?1",
             params![workspace_id],
             |row| Ok((row.get(0)?, row.get(1)?)),
         )
-        .map_err(|e| {
-            log::error!("plan_serialize: workspace not found: {e}");
-            CommandError::from(e)
-        })?;
-
-    // Fetch all rows joining selected_tables, table_artifacts, candidacy, and table_config
-    log::debug!("plan_serialize: querying plan data");
-    let mut stmt = conn
-        .prepare(
-            "SELECT
-               st.schema_name || '.' || st.table_name AS table_ref,
-               ta.schema_name || '.' || ta.procedure_name AS proc_ref,
-               COALESCE(c.tier, 'unknown') AS candidacy_tier,
-               COALESCE(tc.load_strategy, 'unknown') AS load_strategy
-             FROM selected_tables st
-             LEFT JOIN table_artifacts ta ON ta.selected_table_id = st.id
-             LEFT JOIN candidacy c
-               ON c.warehouse_item_id = ta.warehouse_item_id
-               AND c.schema_name = ta.schema_name
-               AND c.procedure_name = ta.procedure_name
-             LEFT JOIN table_config tc ON tc.selected_table_id = st.id
-             WHERE st.workspace_id = ?1
-             ORDER BY table_ref",
+        .optional()?
+        .ok_or_else(|| CommandError::NotFound(format!("workspace {workspace_id}")))?;
+    let repo = PathBuf::from(repo_path);
+
+    // Check before switching branches so a blocked publish leaves the checkout alone.
+    ensure_plan_repo_writable(conn, workspace_id, branch, "plan_publish")?;
+    git::checkout_branch(&repo, branch)?;
+    let diff = serialize_plan(conn, workspace_id, generated_at)?;
+    git::stage(&repo, GENERATED_PATHS)?;
+    let commit_sha = git::commit(
+        &repo,
+        &commit_message(&display_name, generated_at, &diff),
+        author,
+    )?;
+    Ok((
+        repo,
+        PlanPublishResult {
+            branch: branch.to_string(),
+            commit_sha,
+            diff,
+            pull_request: None,
+        },
+    ))
+}
+
+fn plan_title(display_name: &str) -> String {
+    format!("Update migration plan for {display_name}")
+}
+
+fn commit_message(display_name: &str, generated_at: &str, diff: &PlanDiff) -> String {
+    format!(
+        "{}\n\n\
+         Generated at {generated_at}.\n\n\
+         Tables added: {}\n\
+         Tables removed: {}\n\
+         Tier changes: {}\n\
+         Load strategy changes: {}\n\
+         Config confirmations: {}\n",
+        plan_title(display_name),
+        diff.tables_added.len(),
+        diff.tables_removed.len(),
+        diff.tier_changes.len(),
+        diff.load_strategy_changes.len(),
+        diff.confirmation_changes.len()
+    )
+}
+
+/// Scaffold the dbt project for the finalized plan into `<repo>/dbt/`.
+#[tauri::command]
+pub fn plan_generate_dbt(
+    workspace_id: String,
+    state: State<DbState>,
+) -> Result<DbtScaffoldSummary, CommandError> {
+    log::info!("plan_generate_dbt: workspace_id={}", workspace_id);
+    let conn = state.0.lock().unwrap();
+    let finalized =
+        crate::db::read_plan_finalized(&conn, &workspace_id).map_err(CommandError::Io)?;
+    if !finalized {
+        log::error!("plan_generate_dbt: plan is not finalized");
+        return Err(CommandError::Io(
+            "Finalize the plan before generating the dbt project".to_string(),
+        ));
+    }
+    ensure_plan_repo_writable(
+        &conn,
+        &workspace_id,
+        DEFAULT_PLAN_BRANCH,
+        "plan_generate_dbt",
+    )?;
+    generate_dbt_project(&conn, &workspace_id)
+        .inspect_err(|e| log::error!("plan_generate_dbt: failed: {e}"))
+}
+
+pub(crate) fn generate_dbt_project(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<DbtScaffoldSummary, CommandError> {
+    let (repo_path, project) = load_dbt_project(conn, workspace_id)?;
+    let summary = write_scaffold(Path::new(&repo_path), &project)?;
+    log::info!(
+        "plan_generate_dbt: wrote {} models ({} kept) and {} source tables to {}",
+        summary.models_written,
+        summary.models_kept,
+        summary.source_tables,
+        summary.project_path
+    );
+    Ok(summary)
+}
+
+/// Migration repo path and dbt project for a workspace: sources from the canonical source
+/// model, one model per selected table shaped by its `table_config` and carrying the source
+/// table's columns and CHECK constraints for the generated tests.
+fn load_dbt_project(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<(String, DbtProject), CommandError> {
+    let (display_name, repo_name, repo_path, fabric_url): (
+        String,
+        Option<String>,
+        String,
+        Option<String>,
+    ) = conn
+        .query_row(
+            "SELECT display_name, migration_repo_name, migration_repo_path, fabric_url
+             FROM workspaces WHERE id = ?1",
+            params![workspace_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
         )
-        .map_err(|e| {
-            log::error!("plan_serialize: failed to prepare query: {e}");
-            CommandError::from(e)
-        })?;
-
-    #[derive(Debug)]
-    struct PlanRow {
-        table_ref: String,
-        proc_ref: String,
-        candidacy_tier: String,
-        load_strategy: String,
+        .optional()?
+        .ok_or_else(|| CommandError::NotFound(format!("workspace {workspace_id}")))?;
+    let fabric_ref = fabric_url
+        .as_deref()
+        .and_then(|url| parse_fabric_url(url).ok());
+
+    let mut stmt = conn.prepare(
+        "SELECT c.container_name, n.namespace_name, o.object_name, o.id FROM data_objects o
+         JOIN namespaces n ON n.id = o.namespace_id
+         JOIN containers c ON c.id = n.container_id
+         JOIN sources s ON s.id = c.source_id
+         WHERE s.workspace_id = ?1 AND o.object_type IN ('table', 'view')
+         ORDER BY c.container_name, n.namespace_name, o.object_name",
+    )?;
+    let objects = stmt
+        .query_map(params![workspace_id], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let databases: BTreeSet<&Option<String>> = objects.iter().map(|(db, ..)| db).collect();
+    let mut sources: Vec<SourceSchema> = Vec::new();
+    // Lowercase (schema, table) -> (source name, table name, data object id).
+    let mut source_tables: HashMap<(String, String), (String, String, String)> = HashMap::new();
+    for (database, schema, table, object_id) in &objects {
+        let name = match database {
+            Some(database) if databases.len() > 1 => format!("{}_{}", identifier(database), schema),
+            _ => schema.clone(),
+        };
+        source_tables
+            .entry((schema.to_lowercase(), table.to_lowercase()))
+            .or_insert_with(|| (name.clone(), table.clone(), object_id.clone()));
+        match sources.last_mut() {
+            Some(source) if source.name == name => source.tables.push(table.clone()),
+            _ => sources.push(SourceSchema {
+                name,
+                database: database.clone(),
+                schema: schema.clone(),
+                tables: vec![table.clone()],
+            }),
+        }
     }
 
-    let rows: Vec<PlanRow> = stmt
+    let mut stmt = conn.prepare(
+        "SELECT st.schema_name, st.table_name, tc.load_strategy, tc.grain_columns,
+                tc.incremental_column, ta.schema_name || '.' || ta.procedure_name,
+                tc.relationships_json, tc.date_column, tc.snapshot_strategy
+         FROM selected_tables st
+         LEFT JOIN table_artifacts ta ON ta.selected_table_id = st.id
+         LEFT JOIN table_config tc ON tc.selected_table_id = st.id
+         WHERE st.workspace_id = ?1
+         ORDER BY st.schema_name, st.table_name",
+    )?;
+    let mut models = stmt
         .query_map(params![workspace_id], |row| {
-            Ok(PlanRow {
-                table_ref: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
-                proc_ref: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
-                candidacy_tier: row
-                    .get::<_, Option<String>>(2)?
-                    .unwrap_or_else(|| "unknown".to_string()),
-                load_strategy: row
-                    .get::<_, Option<String>>(3)?
-                    .unwrap_or_else(|| "unknown".to_string()),
+            let grain_columns: Option<String> = row.get(3)?;
+            let relationships: Option<String> = row.get(6)?;
+            Ok(ModelSpec {
+                schema_name: row.get(0)?,
+                table_name: row.get(1)?,
+                load_strategy: row.get(2)?,
+                grain_columns: split_columns(grain_columns.as_deref()),
+                incremental_column: row.get(4)?,
+                date_column: row.get(7)?,
+                snapshot_strategy: row
+                    .get::<_, Option<String>>(8)?
+                    .unwrap_or_else(|| "sample_1day".to_string()),
+                procedure: row.get(5)?,
+                source: None,
+                columns: Vec::new(),
+                check_definitions: Vec::new(),
+                relationships: parse_relationships(relationships.as_deref()),
             })
-        })
-        .map_err(|e| {
-            log::error!("plan_serialize: query failed: {e}");
-            CommandError::from(e)
         })?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| {
-            log::error!("plan_serialize: row error: {e}");
-            CommandError::from(e)
-        })?;
-
-    log::debug!("plan_serialize: building plan.md with {} rows", rows.len());
+        .collect::<Result<Vec<_>, _>>()?;
 
-    // Build markdown content
-    let generated_at = chrono::Utc::now().to_rfc3339();
-    let mut content = String::new();
-    writeln!(content, "---").ok();
-    writeln!(content, "workspace_id: {}", generated_ws_id).ok();
-    writeln!(content, "generated_at: {}", generated_at).ok();
-    writeln!(content, "---").ok();
-    writeln!(content).ok();
-    writeln!(content, "# Migration Plan").ok();
-    writeln!(content).ok();
-    writeln!(content, "## Selected Tables").ok();
-    writeln!(content).ok();
-    writeln!(content, "| table | procedure | candidacy | load_strategy |").ok();
-    writeln!(content, "|---|---|---|---|").ok();
-    for row in &rows {
-        writeln!(
-            content,
-            "| {} | {} | {} | {} |",
-            row.table_ref, row.proc_ref, row.candidacy_tier, row.load_strategy
-        )
-        .ok();
+    let mut columns_stmt = conn.prepare(
+        "SELECT column_name, is_nullable FROM sqlserver_object_columns
+         WHERE data_object_id = ?1 ORDER BY column_id, column_name",
+    )?;
+    let mut checks_stmt = conn.prepare(
+        "SELECT definition_json FROM sqlserver_constraints_indexes
+         WHERE data_object_id = ?1 AND constraint_type = 'CHECK' ORDER BY constraint_name",
+    )?;
+    for model in &mut models {
+        let key = (
+            model.schema_name.to_lowercase(),
+            model.table_name.to_lowercase(),
+        );
+        let Some((source, table, object_id)) = source_tables.get(&key) else {
+            continue;
+        };
+        model.source = Some((source.clone(), table.clone()));
+        model.columns = columns_stmt
+            .query_map(params![object_id], |row| {
+                Ok(ColumnSpec {
+                    name: row.get(0)?,
+                    nullable: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        model.check_definitions = checks_stmt
+            .query_map(params![object_id], |row| row.get::<_, Option<String>>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .filter_map(|json| {
+                serde_json::from_str::<serde_json::Value>(&json)
+                    .ok()?
+                    .get("definition")?
+                    .as_str()
+                    .map(str::to_string)
+            })
+            .collect();
     }
 
-    // Write to migration_repo_path/plan.md
-    let plan_path = PathBuf::from(&migration_repo_path).join("plan.md");
-    log::debug!("plan_serialize: writing to {}", plan_path.display());
-    fs::write(&plan_path, content).map_err(|e| {
-        log::error!("plan_serialize: failed to write plan.md: {e}");
-        CommandError::Io(e.to_string())
-    })?;
+    let project = DbtProject {
+        name: identifier(repo_name.as_deref().unwrap_or(&display_name)),
+        fabric_workspace_id: fabric_ref.as_ref().map(|r| r.workspace_id.clone()),
+        fabric_tenant_id: fabric_ref.and_then(|r| r.tenant_id),
+        sources,
+        models,
+    };
+    Ok((repo_path, project))
+}
 
-    log::info!("plan_serialize: wrote plan.md to {}", plan_path.display());
+/// Write `lineage.dot`, `lineage.md` (Mermaid) and `lineage.json` for the selected tables
+/// into `<repo>/lineage/`.
+fn write_lineage_artifacts(
+    conn: &Connection,
+    workspace_id: &str,
+    repo_path: &Path,
+) -> Result<(), CommandError> {
+    let graph = crate::commands::graph::selection_lineage(conn, workspace_id)?;
+    let json =
+        export::to_json(&graph, workspace_id).map_err(|e| CommandError::Io(e.to_string()))?;
+    let dir = repo_path.join(LINEAGE_DIR);
+    fs::create_dir_all(&dir).map_err(|e| CommandError::Io(e.to_string()))?;
+    for (file_name, content) in [
+        ("lineage.dot", export::to_dot(&graph)),
+        ("lineage.md", export::to_mermaid_markdown(&graph)),
+        ("lineage.json", json),
+    ] {
+        fs::write(dir.join(file_name), content).map_err(|e| CommandError::Io(e.to_string()))?;
+    }
+    log::info!(
+        "plan_serialize: wrote lineage with {} nodes and {} edges to {}",
+        graph.nodes().len(),
+        graph.edges().len(),
+        dir.display()
+    );
     Ok(())
 }
 
@@ -122,6 +689,7 @@ pub fn plan_serialize(workspace_id: String, state: State<DbState>) -> Result<(),
 mod tests {
     use super::*;
     use crate::db;
+    use base64::Engine;
 
     #[test]
     fn plan_serialize_creates_plan_md() {
@@ -186,75 +754,11 @@ mod tests {
         )
         .unwrap();
 
-        // Call the core plan-building logic directly (without Tauri State)
-        // We replicate the logic here to test it against the in-memory DB
-        let (migration_repo_path, generated_ws_id): (String, String) = conn
-            .query_row(
-                "SELECT migration_repo_path, id FROM workspaces WHERE id=?1",
-                rusqlite::params![ws_id],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
-            .unwrap();
-
-        let mut stmt = conn
-            .prepare(
-                "SELECT
-                   st.schema_name || '.' || st.table_name AS table_ref,
-                   ta.schema_name || '.' || ta.procedure_name AS proc_ref,
-                   COALESCE(c.tier, 'unknown') AS candidacy_tier,
-                   COALESCE(tc.load_strategy, 'unknown') AS load_strategy
-                 FROM selected_tables st
-                 LEFT JOIN table_artifacts ta ON ta.selected_table_id = st.id
-                 LEFT JOIN candidacy c
-                   ON c.warehouse_item_id = ta.warehouse_item_id
-                   AND c.schema_name = ta.schema_name
-                   AND c.procedure_name = ta.procedure_name
-                 LEFT JOIN table_config tc ON tc.selected_table_id = st.id
-                 WHERE st.workspace_id = ?1
-                 ORDER BY table_ref",
-            )
-            .unwrap();
-
-        let rows: Vec<(String, String, String, String)> = stmt
-            .query_map(rusqlite::params![ws_id], |row| {
-                Ok((
-                    row.get::<_, Option<String>>(0)?.unwrap_or_default(),
-                    row.get::<_, Option<String>>(1)?.unwrap_or_default(),
-                    row.get::<_, Option<String>>(2)?
-                        .unwrap_or_else(|| "unknown".to_string()),
-                    row.get::<_, Option<String>>(3)?
-                        .unwrap_or_else(|| "unknown".to_string()),
-                ))
-            })
-            .unwrap()
-            .map(|r| r.unwrap())
-            .collect();
-
-        let mut content = String::new();
-        writeln!(content, "---").ok();
-        writeln!(content, "workspace_id: {}", generated_ws_id).ok();
-        writeln!(content, "generated_at: 2026-01-01T00:00:00Z").ok();
-        writeln!(content, "---").ok();
-        writeln!(content).ok();
-        writeln!(content, "# Migration Plan").ok();
-        writeln!(content).ok();
-        writeln!(content, "## Selected Tables").ok();
-        writeln!(content).ok();
-        writeln!(content, "| table | procedure | candidacy | load_strategy |").ok();
-        writeln!(content, "|---|---|---|---|").ok();
-        for (table_ref, proc_ref, candidacy_tier, load_strategy) in &rows {
-            writeln!(
-                content,
-                "| {} | {} | {} | {} |",
-                table_ref, proc_ref, candidacy_tier, load_strategy
-            )
-            .ok();
-        }
-
-        let plan_path = PathBuf::from(&migration_repo_path).join("plan.md");
-        fs::write(&plan_path, &content).unwrap();
+        let first = serialize_plan(&conn, &ws_id, "2026-01-01T00:00:00Z").unwrap();
+        assert_eq!(first.tables_added, vec!["dbo.orders"]);
 
         // Assert file exists and has expected content
+        let plan_path = PathBuf::from(&repo_path).join("plan.md");
         assert!(plan_path.exists(), "plan.md should be created");
         let written = fs::read_to_string(&plan_path).unwrap();
         assert!(
@@ -279,5 +783,398 @@ mod tests {
             written.contains("incremental"),
             "should contain the load strategy"
         );
+        assert!(PathBuf::from(&repo_path).join(PLAN_FILE).exists());
+
+        // The next generation is diffed against the previous export.
+        conn.execute(
+            "UPDATE table_config SET load_strategy='full_refresh' WHERE selected_table_id=?1",
+            rusqlite::params![st_id],
+        )
+        .unwrap();
+        let second = serialize_plan(&conn, &ws_id, "2026-01-08T00:00:00Z").unwrap();
+        assert!(second.tables_added.is_empty());
+        assert_eq!(second.load_strategy_changes.len(), 1);
+        let written = fs::read_to_string(&plan_path).unwrap();
+        assert!(written.contains("Changes since the plan generated at 2026-01-01T00:00:00Z."));
+        assert!(written.contains("| dbo.orders | incremental | full_refresh |"));
+    }
+
+    #[test]
+    fn write_lineage_artifacts_renders_selection_flow() {
+        let conn = db::open_in_memory().unwrap();
+        let tmp_dir = tempfile::tempdir().unwrap();
+        conn.execute_batch(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at)
+               VALUES ('ws', 'Test', '/tmp/repo', '2026-01-01T00:00:00Z');
+             INSERT INTO items(id, workspace_id, display_name, item_type) VALUES
+               ('wh', 'ws', 'WH', 'Warehouse'), ('pl', 'ws', 'Nightly', 'DataPipeline'),
+               ('pl2', 'ws', 'Nightly', 'DataPipeline');
+             INSERT INTO pipeline_activities(id, pipeline_item_id, activity_name, activity_type, target_schema_name, target_procedure_name, depends_on_json)
+               VALUES (1, 'pl', 'LoadCustomers', 'SqlServerStoredProcedure', 'dbo', 'sp_load_customers', NULL),
+                      (2, 'pl', 'LoadOrders', 'SqlServerStoredProcedure', 'dbo', 'sp_load_orders',
+                       '[{\"activity\":\"LoadCustomers\",\"dependencyConditions\":[\"Succeeded\"]}]'),
+                      (3, 'pl2', 'LoadCustomers', 'SqlServerStoredProcedure', 'dbo', 'sp_load_customers', NULL);
+             INSERT INTO selected_tables(id, workspace_id, warehouse_item_id, schema_name, table_name) VALUES
+               ('st1', 'ws', 'wh', 'dbo', 'orders'), ('st2', 'ws', 'wh', 'dbo', 'customers');
+             INSERT INTO table_artifacts(selected_table_id, warehouse_item_id, schema_name, procedure_name, pipeline_activity_id, discovery_status) VALUES
+               ('st1', 'wh', 'dbo', 'sp_load_orders', 2, 'resolved'),
+               ('st2', 'wh', 'dbo', 'sp_load_customers', NULL, 'resolved');",
+        )
+        .unwrap();
+
+        write_lineage_artifacts(&conn, "ws", tmp_dir.path()).unwrap();
+
+        let dir = tmp_dir.path().join(LINEAGE_DIR);
+        let dot = fs::read_to_string(dir.join("lineage.dot")).unwrap();
+        assert!(dot.contains(
+            "\"procedure:dbo.sp_load_orders\" -> \"table:dbo.orders\" [label=\"writes\"];"
+        ));
+        assert!(dot.contains(
+            "\"activity:pl/loadcustomers\" -> \"activity:pl/loadorders\" [label=\"precedes\", style=dashed];"
+        ));
+        // Same-named pipelines keep their activities apart.
+        assert!(dot.contains(
+            "\"activity:pl2/loadcustomers\" -> \"procedure:dbo.sp_load_customers\" [label=\"executes\"];"
+        ));
+        let markdown = fs::read_to_string(dir.join("lineage.md")).unwrap();
+        assert_eq!(markdown.matches("```mermaid").count(), 1);
+        assert!(markdown.contains("## dbo.customers, dbo.orders"));
+        let json: crate::types::LineageGraph =
+            serde_json::from_str(&fs::read_to_string(dir.join("lineage.json")).unwrap()).unwrap();
+        assert_eq!(json.nodes.len(), 7);
+        assert_eq!(json.edges.len(), 6);
+    }
+
+    #[test]
+    fn generate_dbt_project_scaffolds_models_and_keeps_edits() {
+        let conn = db::open_in_memory().unwrap();
+        let tmp_dir = tempfile::tempdir().unwrap();
+        conn.execute(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, migration_repo_name, fabric_url, created_at)
+             VALUES ('ws', 'Sales DW', ?1, 'sales-dw-migration',
+                     'https://app.fabric.microsoft.com/groups/fws-1/list?ctid=tenant-1', '2026-01-01T00:00:00Z')",
+            params![tmp_dir.path().to_str().unwrap()],
+        )
+        .unwrap();
+        conn.execute_batch(
+            "INSERT INTO sources(id, workspace_id, source_type, external_source_id) VALUES ('src', 'ws', 'sql_server', 'ext');
+             INSERT INTO containers(id, source_id, container_type, external_container_id, container_name)
+               VALUES ('db', 'src', 'database', '5', 'SalesDW');
+             INSERT INTO namespaces(id, container_id, namespace_name) VALUES ('ns-dbo', 'db', 'dbo');
+             INSERT INTO data_objects(id, namespace_id, object_name, object_type) VALUES
+               ('t-orders', 'ns-dbo', 'Orders', 'table'), ('t-regions', 'ns-dbo', 'regions', 'table');
+             INSERT INTO sqlserver_object_columns(id, data_object_id, column_name, column_id, is_nullable) VALUES
+               ('c1', 't-orders', 'order_id', 1, 0), ('c2', 't-orders', 'status', 2, 1);
+             INSERT INTO sqlserver_constraints_indexes(id, data_object_id, constraint_name, constraint_type, definition_json)
+               VALUES ('k1', 't-orders', 'CK_status', 'CHECK',
+                       '{\"column\":\"status\",\"definition\":\"([status]=''open'' OR [status]=''closed'')\"}');
+             INSERT INTO items(id, workspace_id, display_name, item_type) VALUES ('wh', 'ws', 'WH', 'Warehouse');
+             INSERT INTO selected_tables(id, workspace_id, warehouse_item_id, schema_name, table_name) VALUES
+               ('st1', 'ws', 'wh', 'dbo', 'orders'), ('st2', 'ws', 'wh', 'stg', 'missing'),
+               ('st3', 'ws', 'wh', 'dbo', 'customers');
+             INSERT INTO table_artifacts(selected_table_id, warehouse_item_id, schema_name, procedure_name, discovery_status)
+               VALUES ('st1', 'wh', 'dbo', 'sp_load_orders', 'resolved');
+             INSERT INTO table_config(selected_table_id, load_strategy, grain_columns, incremental_column, relationships_json)
+               VALUES ('st1', 'incremental', '[\"order_id\"]', 'modified_at',
+                       '[{\"column\":\"region\",\"to\":\"dbo.regions\"}]');
+             INSERT INTO table_config(selected_table_id, load_strategy, grain_columns, date_column, snapshot_strategy)
+               VALUES ('st2', 'snapshot', NULL, NULL, 'full'),
+                      ('st3', 'snapshot', 'customer_id', 'updated_at', 'sample_1day');",
+        )
+        .unwrap();
+
+        let summary = generate_dbt_project(&conn, "ws").unwrap();
+        let dir = tmp_dir.path().join(crate::dbt::project::DBT_DIR);
+        assert_eq!(
+            summary,
+            DbtScaffoldSummary {
+                project_path: dir.display().to_string(),
+                source_tables: 2,
+                models_written: 3,
+                models_kept: 0,
+                schema_tests: 6,
+                skipped_relationships: Vec::new(),
+                snapshots: 1,
+                skipped_snapshots: vec![
+                    "stg.missing: grain_columns are required for a snapshot unique_key".to_string()
+                ],
+            }
+        );
+        let schema = fs::read_to_string(dir.join("models/dbo/schema.yml")).unwrap();
+        assert!(schema.contains("      - name: order_id\n        data_tests:\n          - not_null\n          - unique\n"));
+        assert!(schema.contains("values: ['open', 'closed']"));
+        assert!(schema.contains("to: \"source('dbo', 'regions')\""));
+        let project = fs::read_to_string(dir.join("dbt_project.yml")).unwrap();
+        assert!(project.contains("name: sales_dw_migration\n"));
+        let profiles = fs::read_to_string(dir.join("profiles.yml")).unwrap();
+        assert!(profiles.contains("workspaceid: 'fws-1'"));
+        assert!(profiles.contains("tenant_id: 'tenant-1'"));
+        let sources = fs::read_to_string(dir.join("models/sources.yml")).unwrap();
+        assert!(sources.contains("  - name: dbo\n    database: SalesDW\n"));
+        let orders = fs::read_to_string(dir.join("models/dbo/orders.sql")).unwrap();
+        assert!(orders.contains("unique_key='order_id',"));
+        assert!(orders.contains("from {{ source('dbo', 'Orders') }}"));
+        assert!(orders.contains("loaded today by dbo.sp_load_orders."));
+        let missing = fs::read_to_string(dir.join("models/stg/missing.sql")).unwrap();
+        assert!(missing.contains("materialized='table',"));
+        assert!(missing.contains("select 1 as placeholder"));
+        let snapshot = fs::read_to_string(dir.join("snapshots/customers_snapshot.sql")).unwrap();
+        assert!(snapshot.contains("updated_at='updated_at',"));
+        assert!(snapshot.contains("from {{ ref('customers') }}"));
+
+        fs::write(dir.join("models/dbo/orders.sql"), "select 42").unwrap();
+        let again = generate_dbt_project(&conn, "ws").unwrap();
+        assert_eq!((again.models_written, again.models_kept), (0, 3));
+        assert_eq!(
+            fs::read_to_string(dir.join("models/dbo/orders.sql")).unwrap(),
+            "select 42"
+        );
+
+        conn.execute(
+            "UPDATE table_config SET load_strategy = 'full_refresh' WHERE selected_table_id = 'st3'",
+            [],
+        )
+        .unwrap();
+        fs::write(dir.join("snapshots/manual.sql"), "{% snapshot manual %}").unwrap();
+        let again = generate_dbt_project(&conn, "ws").unwrap();
+        assert_eq!(again.snapshots, 0);
+        assert!(!dir.join("snapshots/customers_snapshot.sql").exists());
+        assert!(dir.join("snapshots/manual.sql").exists());
+    }
+
+    #[test]
+    fn plan_file_round_trips_edits_from_the_repo() {
+        let conn = db::open_in_memory().unwrap();
+        let tmp_dir = tempfile::tempdir().unwrap();
+        conn.execute(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at)
+             VALUES ('ws', 'WS', ?1, '2026-01-01T00:00:00Z')",
+            params![tmp_dir.path().to_str().unwrap()],
+        )
+        .unwrap();
+        conn.execute_batch(
+            "INSERT INTO items(id, workspace_id, display_name, item_type) VALUES ('wh', 'ws', 'WH', 'Warehouse');
+             INSERT INTO selected_tables(id, workspace_id, warehouse_item_id, schema_name, table_name)
+               VALUES ('st1', 'ws', 'wh', 'dbo', 'orders');
+             INSERT INTO table_config(selected_table_id, load_strategy) VALUES ('st1', 'full_refresh');",
+        )
+        .unwrap();
+        assert!(matches!(
+            import_plan_file(&conn, "ws", None),
+            Err(CommandError::NotFound(_))
+        ));
+
+        let plan = load_plan(&conn, "ws", "2026-01-02T00:00:00Z").unwrap();
+        write_plan_file(&conn, &plan, tmp_dir.path()).unwrap();
+        let path = tmp_dir.path().join(PLAN_FILE);
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.contains("\"schema_version\": 2"));
+        fs::write(&path, written.replace("\"full_refresh\"", "\"snapshot\"")).unwrap();
+
+        let summary = import_plan_file(&conn, "ws", None).unwrap();
+        assert_eq!((summary.fields_applied, summary.conflicts.len()), (1, 0));
+        let strategy: String = conn
+            .query_row(
+                "SELECT load_strategy FROM table_config WHERE selected_table_id='st1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(strategy, "snapshot");
+
+        fs::write(
+            &path,
+            written.replace("\"schema_version\": 2", "\"schema_version\": 9"),
+        )
+        .unwrap();
+        assert!(matches!(
+            import_plan_file(&conn, "ws", None),
+            Err(CommandError::Io(message)) if message.contains("schema_version 9")
+        ));
+    }
+
+    #[test]
+    fn committed_plan_reads_the_head_version() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let repo = tmp_dir.path();
+        assert!(committed_plan(repo).is_none());
+
+        let committed = PlanFile {
+            schema_version: crate::plan::file::PLAN_SCHEMA_VERSION,
+            workspace_id: "ws".to_string(),
+            generated_at: "2026-01-01T00:00:00Z".to_string(),
+            tables: Vec::new(),
+        };
+        git2::Repository::init(repo).unwrap();
+        fs::write(repo.join(PLAN_FILE), render_plan(&committed)).unwrap();
+        crate::git::tests::commit_all(repo, "plan");
+        fs::write(repo.join(PLAN_FILE), "uncommitted edits").unwrap();
+
+        assert_eq!(committed_plan(repo), Some(committed));
+    }
+
+    #[test]
+    fn commit_plan_commits_generated_artifacts_on_the_branch() {
+        let conn = db::open_in_memory().unwrap();
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let repo = tmp_dir.path();
+        git2::Repository::init(repo).unwrap();
+        conn.execute(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at)
+             VALUES ('ws', 'Sales', ?1, '2026-01-01T00:00:00Z')",
+            params![repo.to_str().unwrap()],
+        )
+        .unwrap();
+
+        let settings = AppSettings {
+            github_user_login: Some("octocat".to_string()),
+            ..AppSettings::default()
+        };
+        let github = GitHubInstance::default();
+        let author = plan_author(&settings, &github).unwrap();
+        assert_eq!(author.email, "octocat@users.noreply.github.com");
+        assert!(plan_author(&AppSettings::default(), &github).is_err());
+
+        let (path, result) = commit_plan(
+            &conn,
+            "ws",
+            DEFAULT_PLAN_BRANCH,
+            &author,
+            "2026-02-01T00:00:00Z",
+        )
+        .unwrap();
+        assert_eq!(path, repo);
+        let sha = result.commit_sha.expect("a commit");
+        let repository = git2::Repository::open(repo).unwrap();
+        let head = repository.head().unwrap();
+        assert_eq!(head.shorthand(), Some(DEFAULT_PLAN_BRANCH));
+        let commit = head.peel_to_commit().unwrap();
+        assert_eq!(commit.author().name(), Some("octocat"));
+        let log = commit.message().unwrap();
+        assert!(log.starts_with("Update migration plan for Sales\n"));
+        assert!(log.contains("Tables added: 0"));
+
+        // The second generation rewrites the changelog against the committed plan; a third
+        // with the same timestamp produces identical artifacts and nothing to commit.
+        let (_, second) = commit_plan(
+            &conn,
+            "ws",
+            DEFAULT_PLAN_BRANCH,
+            &author,
+            "2026-02-01T00:00:00Z",
+        )
+        .unwrap();
+        assert!(second.commit_sha.is_some_and(|second| second != sha));
+        let (_, again) = commit_plan(
+            &conn,
+            "ws",
+            DEFAULT_PLAN_BRANCH,
+            &author,
+            "2026-02-01T00:00:00Z",
+        )
+        .unwrap();
+        assert_eq!(again.commit_sha, None);
+        assert_eq!(sha.len(), 40);
+
+        // Unimported plan edits block the publish before the checkout moves.
+        crate::git::checkout_branch(repo, "scratch").unwrap();
+        fs::write(repo.join(PLAN_FILE), "hand edits").unwrap();
+        assert!(matches!(
+            commit_plan(
+                &conn,
+                "ws",
+                DEFAULT_PLAN_BRANCH,
+                &author,
+                "2026-03-01T00:00:00Z"
+            ),
+            Err(CommandError::Git(_))
+        ));
+        let repository = git2::Repository::open(repo).unwrap();
+        assert_eq!(repository.head().unwrap().shorthand(), Some("scratch"));
+    }
+
+    #[tokio::test]
+    async fn open_plan_pull_request_updates_the_open_pull_request() {
+        let mut server = mockito::Server::new_async().await;
+        let base_plan = PlanFile {
+            schema_version: crate::plan::file::PLAN_SCHEMA_VERSION,
+            workspace_id: "ws".to_string(),
+            generated_at: "2026-01-01T00:00:00Z".to_string(),
+            tables: Vec::new(),
+        };
+        let content = base64::engine::general_purpose::STANDARD.encode(render_plan(&base_plan));
+        let mocks = [
+            server
+                .mock("GET", "/repos/acme/plans")
+                .with_body(r#"{"default_branch":"main"}"#)
+                .create_async()
+                .await,
+            server
+                .mock("GET", "/repos/acme/plans/contents/plan.json")
+                .match_query(mockito::Matcher::UrlEncoded("ref".into(), "main".into()))
+                .with_body(serde_json::json!({ "content": content }).to_string())
+                .create_async()
+                .await,
+            server
+                .mock("GET", "/repos/acme/plans/pulls")
+                .match_query(mockito::Matcher::Any)
+                .with_body(r#"[{"number":3,"html_url":"https://github.com/acme/plans/pull/3"}]"#)
+                .create_async()
+                .await,
+            server
+                .mock("PATCH", "/repos/acme/plans/pulls/3")
+                .match_body(mockito::Matcher::Regex(
+                    "Changes since the plan generated at 2026-01-01T00:00:00Z".to_string(),
+                ))
+                .with_body(r#"{"number":3,"html_url":"https://github.com/acme/plans/pull/3"}"#)
+                .create_async()
+                .await,
+        ];
+        let client = GitHubClient::new(
+            crate::github::client::GitHubEndpoints {
+                api_base_url: server.url(),
+            },
+            "gho_token".to_string(),
+        );
+        let plan = PlanFile {
+            generated_at: "2026-02-01T00:00:00Z".to_string(),
+            ..base_plan
+        };
+        let validation = PlanValidation {
+            issues: Vec::new(),
+            can_finalize: true,
+        };
+
+        let pull_request = open_plan_pull_request(
+            &client,
+            "acme/plans",
+            DEFAULT_PLAN_BRANCH,
+            "Sales",
+            &plan,
+            &validation,
+            "2026-02-01T00:00:00Z",
+        )
+        .await
+        .unwrap();
+        assert_eq!(pull_request.number, 3);
+        assert_eq!(pull_request.branch, DEFAULT_PLAN_BRANCH);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+
+        let conn = db::open_in_memory().unwrap();
+        conn.execute(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at)
+             VALUES ('ws', 'Sales', '/tmp/repo', '2026-01-01T00:00:00Z')",
+            [],
+        )
+        .unwrap();
+        assert_eq!(linked_pull_request(&conn, "ws").unwrap(), None);
+        save_pull_request(&conn, "ws", &pull_request).unwrap();
+        save_pull_request(&conn, "ws", &pull_request).unwrap();
+        assert_eq!(
+            linked_pull_request(&conn, "ws").unwrap(),
+            Some(pull_request)
+        );
     }
 }
@@ -0,0 +1,469 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::State;
+
+use crate::commands::plan::{DEFAULT_PLAN_BRANCH, GENERATED_PATHS};
+use crate::db::DbState;
+use crate::plan::file::PLAN_FILE;
+use crate::plan::reconcile::exported_content;
+use crate::types::{CommandError, RepoHealth, RepoIssue};
+
+/// Paths or branches named in an issue before the rest are counted.
+const MAX_LISTED_PATHS: usize = 5;
+
+/// What the caller is about to do to the migration repo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RepoWrite {
+    /// Regenerate `plan.md`, `plan.json`, lineage and dbt artifacts.
+    PlanArtifacts,
+    /// Delete everything in the repo directory.
+    Clear,
+}
+
+fn is_generated(path: &str) -> bool {
+    GENERATED_PATHS
+        .iter()
+        .any(|generated| path == *generated || path.starts_with(&format!("{generated}/")))
+}
+
+fn short_list(paths: &[&String]) -> String {
+    let mut list = paths
+        .iter()
+        .take(MAX_LISTED_PATHS)
+        .map(|path| path.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if paths.len() > MAX_LISTED_PATHS {
+        list.push_str(&format!(" (and {} more)", paths.len() - MAX_LISTED_PATHS));
+    }
+    list
+}
+
+/// Issues with writing into the repo at `repo`. `exported_plan` is the `plan.json` the app
+/// last wrote; a working copy that differs from it holds edits that were never imported.
+/// A directory that is not a git repository has nothing to check.
+pub(crate) fn repo_health(
+    repo: &Path,
+    expected_branch: &str,
+    exported_plan: Option<&str>,
+    write: RepoWrite,
+) -> Result<RepoHealth, CommandError> {
+    let mut health = RepoHealth {
+        is_repo: false,
+        branch: None,
+        expected_branch: expected_branch.to_string(),
+        dirty_paths: Vec::new(),
+        ahead: 0,
+        behind: 0,
+        issues: Vec::new(),
+    };
+    let Some(status) = crate::git::status(repo)? else {
+        return Ok(health);
+    };
+    let issue = |code: &str, blocking: bool, message: String| RepoIssue {
+        code: code.to_string(),
+        blocking,
+        message,
+    };
+    let mut issues = Vec::new();
+    let branch = status.branch.as_deref().unwrap_or("a detached HEAD");
+    let (mut ahead, mut behind) = (status.ahead, status.behind);
+    match write {
+        RepoWrite::PlanArtifacts => {
+            if status.branch.as_deref() != Some(expected_branch) {
+                issues.push(issue(
+                    "unexpected_branch",
+                    false,
+                    format!("The repo is on {branch}, not the working branch {expected_branch}"),
+                ));
+            }
+            // The plan is written on the expected branch, which the publish checkout first
+            // fast-forwards to origin, so that branch's state matters rather than HEAD's.
+            let divergence = crate::git::branch_divergence(repo, expected_branch)?;
+            (ahead, behind) = divergence.unwrap_or_default();
+            if ahead > 0 && behind > 0 {
+                issues.push(issue(
+                    "diverged_from_remote",
+                    true,
+                    format!(
+                        "{expected_branch} has {ahead} local and {behind} remote commit(s) \
+                         that the other lacks; reconcile it with origin first"
+                    ),
+                ));
+            } else if behind > 0 {
+                issues.push(issue(
+                    "behind_remote",
+                    false,
+                    format!(
+                        "{expected_branch} is {behind} commit(s) behind origin; publishing \
+                         fast-forwards it first"
+                    ),
+                ));
+            }
+            // Remote commits that change plan.json are pull request edits the fast-forward
+            // would bring in and the regenerated plan would then overwrite.
+            let read = |refname: &str| crate::git::read_ref_file(repo, refname, PLAN_FILE);
+            let remote_plan = read(&format!("refs/remotes/origin/{expected_branch}"));
+            let remote_plan_edited =
+                behind > 0 && remote_plan != read(&format!("refs/heads/{expected_branch}"));
+            let differs = |content: Option<&str>| {
+                exported_plan.is_some_and(|exported| content.is_some_and(|c| c != exported))
+            };
+            let working_copy = fs::read_to_string(repo.join(PLAN_FILE)).ok();
+            if status.dirty_paths.iter().any(|path| path == PLAN_FILE)
+                && differs(working_copy.as_deref())
+            {
+                issues.push(issue(
+                    "unimported_plan_edits",
+                    true,
+                    format!(
+                        "{PLAN_FILE} has uncommitted edits that were not imported; import or \
+                         discard them before regenerating the plan"
+                    ),
+                ));
+            } else if remote_plan_edited && differs(remote_plan.as_deref()) {
+                issues.push(issue(
+                    "unimported_plan_edits",
+                    true,
+                    format!(
+                        "origin/{expected_branch} has {PLAN_FILE} edits that were not imported; \
+                         pull and import them before regenerating the plan"
+                    ),
+                ));
+            }
+            let user_changes: Vec<_> = status
+                .dirty_paths
+                .iter()
+                .filter(|path| !is_generated(path))
+                .collect();
+            if !user_changes.is_empty() {
+                issues.push(issue(
+                    "uncommitted_changes",
+                    false,
+                    format!(
+                        "Uncommitted changes outside the generated files: {}",
+                        short_list(&user_changes)
+                    ),
+                ));
+            }
+        }
+        RepoWrite::Clear => {
+            let dirty: Vec<_> = status.dirty_paths.iter().collect();
+            if !dirty.is_empty() {
+                issues.push(issue(
+                    "uncommitted_changes",
+                    true,
+                    format!(
+                        "Uncommitted changes would be deleted: {}",
+                        short_list(&dirty)
+                    ),
+                ));
+            }
+            // Every branch goes with the directory, not just the checked-out one.
+            let branches = crate::git::local_branches(repo)?;
+            let unpushed: Vec<_> = branches
+                .iter()
+                .filter(|b| b.unpushed > 0)
+                .map(|b| format!("{} ({})", b.name, b.unpushed))
+                .collect();
+            if !unpushed.is_empty() {
+                issues.push(issue(
+                    "unpushed_commits",
+                    true,
+                    format!(
+                        "Commits that are on no remote would be deleted: {}",
+                        short_list(&unpushed.iter().collect::<Vec<_>>())
+                    ),
+                ));
+            }
+            let untracked: Vec<_> = branches
+                .iter()
+                .filter(|b| !b.has_upstream && b.unpushed == 0)
+                .map(|b| &b.name)
+                .collect();
+            if !untracked.is_empty() {
+                // Without unpushed commits these hold nothing origin lacks.
+                issues.push(issue(
+                    "branch_without_upstream",
+                    false,
+                    format!(
+                        "Branches that were never pushed to origin would be deleted: {}",
+                        short_list(&untracked)
+                    ),
+                ));
+            }
+        }
+    }
+    health.is_repo = true;
+    health.branch = status.branch;
+    health.dirty_paths = status.dirty_paths;
+    health.ahead = ahead;
+    health.behind = behind;
+    health.issues = issues;
+    Ok(health)
+}
+
+/// Fail with the blocking issues and log the others.
+pub(crate) fn ensure_repo_writable(
+    repo: &Path,
+    expected_branch: &str,
+    exported_plan: Option<&str>,
+    write: RepoWrite,
+    context: &str,
+) -> Result<RepoHealth, CommandError> {
+    let health = repo_health(repo, expected_branch, exported_plan, write)?;
+    let mut blocking = Vec::new();
+    for issue in &health.issues {
+        if issue.blocking {
+            blocking.push(issue.message.as_str());
+        } else {
+            log::warn!("{context}: {}", issue.message);
+        }
+    }
+    if !blocking.is_empty() {
+        let message = blocking.join("; ");
+        log::error!(
+            "{context}: refusing to write to {}: {message}",
+            repo.display()
+        );
+        return Err(CommandError::Git(message));
+    }
+    Ok(health)
+}
+
+fn workspace_repo_path(conn: &Connection, workspace_id: &str) -> Result<PathBuf, CommandError> {
+    let repo_path: String = conn
+        .query_row(
+            "SELECT migration_repo_path FROM workspaces WHERE id = ?1",
+            params![workspace_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| CommandError::NotFound(format!("workspace {workspace_id}")))?;
+    Ok(PathBuf::from(repo_path))
+}
+
+/// Check a workspace's migration repo before regenerating the plan artifacts in it.
+pub(crate) fn ensure_plan_repo_writable(
+    conn: &Connection,
+    workspace_id: &str,
+    expected_branch: &str,
+    context: &str,
+) -> Result<RepoHealth, CommandError> {
+    let repo = workspace_repo_path(conn, workspace_id)?;
+    let exported = exported_content(conn, workspace_id)?;
+    ensure_repo_writable(
+        &repo,
+        expected_branch,
+        exported.as_deref(),
+        RepoWrite::PlanArtifacts,
+        context,
+    )
+}
+
+/// Git state of the workspace's migration repo and what would block writing plan artifacts.
+#[tauri::command]
+pub fn workspace_repo_health(
+    workspace_id: String,
+    state: State<DbState>,
+) -> Result<RepoHealth, CommandError> {
+    log::info!("workspace_repo_health: workspace_id={}", workspace_id);
+    let conn = state.0.lock().unwrap();
+    let repo = workspace_repo_path(&conn, &workspace_id)?;
+    let exported = exported_content(&conn, &workspace_id)?;
+    repo_health(
+        &repo,
+        DEFAULT_PLAN_BRANCH,
+        exported.as_deref(),
+        RepoWrite::PlanArtifacts,
+    )
+    .inspect_err(|e| log::error!("workspace_repo_health: failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::tests::commit_all;
+    use git2::Repository;
+
+    fn codes(health: &RepoHealth) -> Vec<(&str, bool)> {
+        health
+            .issues
+            .iter()
+            .map(|issue| (issue.code.as_str(), issue.blocking))
+            .collect()
+    }
+
+    #[test]
+    fn plan_writes_block_on_unimported_edits_and_warn_on_other_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let plain = repo_health(tmp.path(), "plan", None, RepoWrite::PlanArtifacts).unwrap();
+        assert!(!plain.is_repo);
+        assert!(plain.issues.is_empty());
+
+        Repository::init(tmp.path()).unwrap();
+        fs::write(tmp.path().join(PLAN_FILE), "exported").unwrap();
+        commit_all(tmp.path(), "init");
+        crate::git::checkout_branch(tmp.path(), "plan").unwrap();
+        let clean = repo_health(tmp.path(), "plan", None, RepoWrite::PlanArtifacts).unwrap();
+        assert!(clean.is_repo);
+        assert_eq!(clean.branch.as_deref(), Some("plan"));
+        assert!(clean.issues.is_empty());
+
+        fs::write(tmp.path().join(PLAN_FILE), "edited by hand").unwrap();
+        fs::create_dir_all(tmp.path().join("lineage")).unwrap();
+        fs::write(tmp.path().join("lineage/lineage.md"), "regenerated").unwrap();
+        fs::write(tmp.path().join("notes.md"), "mine").unwrap();
+        let health = repo_health(
+            tmp.path(),
+            "other",
+            Some("exported"),
+            RepoWrite::PlanArtifacts,
+        )
+        .unwrap();
+        assert_eq!(
+            codes(&health),
+            vec![
+                ("unexpected_branch", false),
+                ("unimported_plan_edits", true),
+                ("uncommitted_changes", false),
+            ]
+        );
+        assert_eq!(
+            health.issues[2].message,
+            "Uncommitted changes outside the generated files: notes.md"
+        );
+        let err = ensure_repo_writable(
+            tmp.path(),
+            "plan",
+            Some("exported"),
+            RepoWrite::PlanArtifacts,
+            "test",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("were not imported"));
+
+        // The app's own previous output is not a user edit.
+        fs::write(tmp.path().join(PLAN_FILE), "regenerated").unwrap();
+        ensure_repo_writable(
+            tmp.path(),
+            "plan",
+            Some("regenerated"),
+            RepoWrite::PlanArtifacts,
+            "test",
+        )
+        .unwrap();
+
+        let clear = repo_health(tmp.path(), "plan", None, RepoWrite::Clear).unwrap();
+        assert_eq!(
+            codes(&clear),
+            vec![("uncommitted_changes", true), ("unpushed_commits", true)]
+        );
+    }
+
+    #[test]
+    fn behind_and_unpushed_commits_are_reported_against_origin() {
+        let tmp = tempfile::tempdir().unwrap();
+        let origin = tmp.path().join("origin.git");
+        let (ours, theirs) = (tmp.path().join("ours"), tmp.path().join("theirs"));
+        let bare = Repository::init_bare(&origin).unwrap();
+        let url = origin.to_str().unwrap();
+        crate::git::clone(url, &theirs, None, &mut |_| {}).unwrap();
+        crate::git::checkout_branch(&theirs, "plan").unwrap();
+        fs::write(theirs.join("README.md"), "one").unwrap();
+        commit_all(&theirs, "one");
+        crate::git::push(&theirs, "plan", None).unwrap();
+
+        // The local transport cannot make shallow clones, so clone the branch in full.
+        bare.set_head("refs/heads/plan").unwrap();
+        Repository::clone(url, &ours).unwrap();
+        fs::write(theirs.join("README.md"), "two").unwrap();
+        commit_all(&theirs, "two");
+        crate::git::push(&theirs, "plan", None).unwrap();
+        crate::git::fetch(&ours, None).unwrap();
+
+        let health = repo_health(&ours, "plan", None, RepoWrite::PlanArtifacts).unwrap();
+        assert_eq!(health.behind, 1);
+        assert_eq!(codes(&health), vec![("behind_remote", false)]);
+
+        // The plan branch is checked whichever branch HEAD is on.
+        crate::git::checkout_branch(&ours, "scratch").unwrap();
+        fs::write(theirs.join(PLAN_FILE), "edited in review").unwrap();
+        commit_all(&theirs, "review");
+        crate::git::push(&theirs, "plan", None).unwrap();
+        crate::git::fetch(&ours, None).unwrap();
+        let health =
+            repo_health(&ours, "plan", Some("exported"), RepoWrite::PlanArtifacts).unwrap();
+        assert_eq!(health.branch.as_deref(), Some("scratch"));
+        assert_eq!(health.behind, 2);
+        assert_eq!(
+            codes(&health),
+            vec![
+                ("unexpected_branch", false),
+                ("behind_remote", false),
+                ("unimported_plan_edits", true),
+            ]
+        );
+        assert!(health.issues[2]
+            .message
+            .starts_with("origin/plan has plan.json"));
+
+        crate::git::checkout_branch(&ours, "plan").unwrap();
+        fs::write(ours.join("README.md"), "mine").unwrap();
+        commit_all(&ours, "mine");
+        fs::write(theirs.join("README.md"), "three").unwrap();
+        commit_all(&theirs, "three");
+        crate::git::push(&theirs, "plan", None).unwrap();
+        crate::git::fetch(&ours, None).unwrap();
+        let health = repo_health(&ours, "plan", None, RepoWrite::PlanArtifacts).unwrap();
+        assert_eq!((health.ahead, health.behind), (1, 1));
+        assert_eq!(codes(&health), vec![("diverged_from_remote", true)]);
+
+        fs::write(theirs.join("README.md"), "four").unwrap();
+        commit_all(&theirs, "four");
+        let clear = repo_health(&theirs, "plan", None, RepoWrite::Clear).unwrap();
+        assert_eq!(clear.ahead, 1);
+        assert_eq!(codes(&clear), vec![("unpushed_commits", true)]);
+    }
+
+    #[test]
+    fn clearing_checks_every_local_branch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let origin = tmp.path().join("origin.git");
+        let ours = tmp.path().join("ours");
+        Repository::init_bare(&origin).unwrap();
+        crate::git::clone(origin.to_str().unwrap(), &ours, None, &mut |_| {}).unwrap();
+        crate::git::checkout_branch(&ours, "main").unwrap();
+        fs::write(ours.join("README.md"), "one").unwrap();
+        commit_all(&ours, "one");
+        crate::git::push(&ours, "main", None).unwrap();
+        crate::git::fetch(&ours, None).unwrap();
+        let pushed = repo_health(&ours, "main", None, RepoWrite::Clear).unwrap();
+        assert!(pushed.issues.is_empty(), "{:?}", pushed.issues);
+
+        // A plan branch whose push failed, and one that was never pushed at all.
+        crate::git::checkout_branch(&ours, "migration-utility/plan").unwrap();
+        fs::write(ours.join("plan.md"), "plan").unwrap();
+        commit_all(&ours, "plan");
+        crate::git::checkout_branch(&ours, "main").unwrap();
+        crate::git::checkout_branch(&ours, "scratch").unwrap();
+        crate::git::checkout_branch(&ours, "main").unwrap();
+
+        let clear = repo_health(&ours, "main", None, RepoWrite::Clear).unwrap();
+        assert_eq!(clear.ahead, 0);
+        assert_eq!(
+            codes(&clear),
+            vec![
+                ("unpushed_commits", true),
+                ("branch_without_upstream", false)
+            ]
+        );
+        assert_eq!(
+            clear.issues[0].message,
+            "Commits that are on no remote would be deleted: migration-utility/plan (1)"
+        );
+        assert!(clear.issues[1].message.ends_with(": scratch"));
+    }
+}
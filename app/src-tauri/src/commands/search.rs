@@ -0,0 +1,316 @@
+use rusqlite::{params, Connection};
+use tauri::State;
+
+use crate::db::DbState;
+use crate::types::{CommandError, ObjectSearchHit, SnippetSegment};
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 500;
+/// Private-use characters that bracket matched terms in FTS5 snippets; they cannot occur in
+/// SQL text pulled from the source, so splitting on them is unambiguous.
+const MATCH_START: char = '\u{E000}';
+const MATCH_END: char = '\u{E001}';
+
+#[tauri::command]
+pub fn search_object_bodies(
+    workspace_id: String,
+    query: String,
+    namespace: Option<String>,
+    object_type: Option<String>,
+    limit: Option<u32>,
+    state: State<DbState>,
+) -> Result<Vec<ObjectSearchHit>, CommandError> {
+    log::info!(
+        "search_object_bodies: workspace_id={} query={:?} namespace={:?} object_type={:?}",
+        workspace_id,
+        query,
+        namespace,
+        object_type
+    );
+    let conn = state.0.lock().unwrap();
+    let hits = search_objects(
+        &conn,
+        &workspace_id,
+        &query,
+        namespace.as_deref(),
+        object_type.as_deref(),
+        limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT),
+    )
+    .inspect_err(|e| log::error!("search_object_bodies: failed: {e}"))?;
+    log::info!("search_object_bodies: hits={}", hits.len());
+    Ok(hits)
+}
+
+/// Replace the workspace's rows in `data_object_search` with the current object bodies.
+pub(crate) fn refresh_object_search_index(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<usize, CommandError> {
+    conn.execute(
+        "DELETE FROM data_object_search WHERE workspace_id = ?1",
+        params![workspace_id],
+    )?;
+    let indexed = conn.execute(
+        "INSERT INTO data_object_search(data_object_id, workspace_id, object_name, sql_body)
+         SELECT o.id, s.workspace_id, o.object_name, o.sql_body
+         FROM data_objects o
+         JOIN namespaces n ON n.id = o.namespace_id
+         JOIN containers c ON c.id = n.container_id
+         JOIN sources s ON s.id = c.source_id
+         WHERE s.workspace_id = ?1 AND o.sql_body IS NOT NULL",
+        params![workspace_id],
+    )?;
+    log::info!(
+        "refresh_object_search_index: workspace_id={} indexed={}",
+        workspace_id,
+        indexed
+    );
+    Ok(indexed)
+}
+
+fn search_objects(
+    conn: &Connection,
+    workspace_id: &str,
+    query: &str,
+    namespace: Option<&str>,
+    object_type: Option<&str>,
+    limit: u32,
+) -> Result<Vec<ObjectSearchHit>, CommandError> {
+    let Some(match_expr) = match_expression(query) else {
+        return Ok(Vec::new());
+    };
+    let mut stmt = conn.prepare(
+        "SELECT f.data_object_id, n.namespace_name, o.object_name, o.object_type,
+                snippet(data_object_search, -1, ?5, ?6, '…', 24),
+                bm25(data_object_search, 0.0, 0.0, 5.0, 1.0) AS rank
+         FROM data_object_search f
+         JOIN data_objects o ON o.id = f.data_object_id
+         JOIN namespaces n ON n.id = o.namespace_id
+         WHERE data_object_search MATCH ?1
+           AND f.workspace_id = ?2
+           AND (?3 IS NULL OR n.namespace_name = ?3 COLLATE NOCASE)
+           AND (?4 IS NULL OR o.object_type = ?4)
+         ORDER BY rank, n.namespace_name, o.object_name
+         LIMIT ?7",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            match_expr,
+            workspace_id,
+            namespace,
+            object_type,
+            MATCH_START.to_string(),
+            MATCH_END.to_string(),
+            limit
+        ],
+        |row| {
+            let snippet: String = row.get(4)?;
+            Ok(ObjectSearchHit {
+                data_object_id: row.get(0)?,
+                schema_name: row.get(1)?,
+                object_name: row.get(2)?,
+                object_type: row.get(3)?,
+                snippet: split_snippet(&snippet),
+                rank: row.get(5)?,
+            })
+        },
+    )?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(CommandError::from)
+}
+
+/// Turn free text into an FTS5 expression: every whitespace-separated term must match, terms
+/// are quoted so SQL punctuation is not read as query syntax, and a trailing `*` keeps its
+/// prefix meaning (`sp_load*`).
+fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter_map(|raw| {
+            let prefix = raw.ends_with('*');
+            let term = raw.trim_end_matches('*');
+            if term.is_empty() {
+                return None;
+            }
+            let quoted = format!("\"{}\"", term.replace('"', "\"\""));
+            Some(if prefix { quoted + "*" } else { quoted })
+        })
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+fn split_snippet(snippet: &str) -> Vec<SnippetSegment> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut highlighted = false;
+    for ch in snippet.chars() {
+        let marker = match ch {
+            MATCH_START => Some(true),
+            MATCH_END => Some(false),
+            _ => None,
+        };
+        let Some(next_highlighted) = marker else {
+            current.push(ch);
+            continue;
+        };
+        if !current.is_empty() {
+            segments.push(SnippetSegment {
+                text: std::mem::take(&mut current),
+                highlighted,
+            });
+        }
+        highlighted = next_highlighted;
+    }
+    if !current.is_empty() {
+        segments.push(SnippetSegment {
+            text: current,
+            highlighted,
+        });
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn setup_objects(conn: &Connection, ws_id: &str, objects: &[(&str, &str, &str, &str)]) {
+        conn.execute(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![ws_id, "Test Workspace", "/tmp/repo", "2026-01-01T00:00:00Z"],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO sources(id, workspace_id, source_type, external_source_id) VALUES (?1, ?2, 'sql_server', ?1)",
+            params![format!("source-{ws_id}"), ws_id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO containers(id, source_id, container_type, external_container_id, container_name)
+             VALUES (?1, ?2, 'database', '5', 'db')",
+            params![format!("container-{ws_id}"), format!("source-{ws_id}")],
+        )
+        .unwrap();
+        for (schema, name, object_type, body) in objects {
+            let namespace_id = format!("namespace-{ws_id}-{schema}");
+            conn.execute(
+                "INSERT OR IGNORE INTO namespaces(id, container_id, namespace_name) VALUES (?1, ?2, ?3)",
+                params![namespace_id, format!("container-{ws_id}"), schema],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO data_objects(id, namespace_id, object_name, object_type, sql_body) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![format!("object-{ws_id}-{schema}-{name}"), namespace_id, name, object_type, body],
+            )
+            .unwrap();
+        }
+    }
+
+    fn hit_names(hits: &[ObjectSearchHit]) -> Vec<String> {
+        hits.iter()
+            .map(|h| format!("{}.{}", h.schema_name, h.object_name))
+            .collect()
+    }
+
+    #[test]
+    fn search_finds_callers_and_column_references() {
+        let conn = db::open_in_memory().unwrap();
+        setup_objects(
+            &conn,
+            "ws-a",
+            &[
+                (
+                    "dbo",
+                    "load_orders",
+                    "procedure",
+                    "EXEC dbo.sp_foo @batch_id = 1; SELECT customer_id FROM dbo.orders",
+                ),
+                (
+                    "rpt",
+                    "v_orders",
+                    "view",
+                    "SELECT order_id, customer_id FROM dbo.orders",
+                ),
+                (
+                    "dbo",
+                    "cleanup",
+                    "procedure",
+                    "DELETE FROM dbo.audit WHERE created < GETDATE()",
+                ),
+            ],
+        );
+        refresh_object_search_index(&conn, "ws-a").unwrap();
+
+        let callers = search_objects(&conn, "ws-a", "sp_foo", None, None, 50).unwrap();
+        assert_eq!(hit_names(&callers), vec!["dbo.load_orders"]);
+        assert!(callers[0]
+            .snippet
+            .iter()
+            .any(|s| s.highlighted && s.text == "sp_foo"));
+
+        let mut column =
+            hit_names(&search_objects(&conn, "ws-a", "customer_id", None, None, 50).unwrap());
+        column.sort();
+        assert_eq!(column, vec!["dbo.load_orders", "rpt.v_orders"]);
+
+        let views = search_objects(&conn, "ws-a", "customer_id", None, Some("view"), 50).unwrap();
+        assert_eq!(hit_names(&views), vec!["rpt.v_orders"]);
+        let in_dbo = search_objects(&conn, "ws-a", "customer_id", Some("DBO"), None, 50).unwrap();
+        assert_eq!(hit_names(&in_dbo), vec!["dbo.load_orders"]);
+
+        let prefix = search_objects(&conn, "ws-a", "sp_f*", None, None, 50).unwrap();
+        assert_eq!(prefix.len(), 1);
+        assert!(search_objects(&conn, "ws-a", "  \"* ", None, None, 50)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn refresh_replaces_rows_and_scopes_to_workspace() {
+        let conn = db::open_in_memory().unwrap();
+        setup_objects(
+            &conn,
+            "ws-a",
+            &[("dbo", "p1", "procedure", "SELECT 1 FROM dbo.shared")],
+        );
+        setup_objects(
+            &conn,
+            "ws-b",
+            &[("dbo", "p2", "procedure", "SELECT 2 FROM dbo.shared")],
+        );
+        assert_eq!(refresh_object_search_index(&conn, "ws-a").unwrap(), 1);
+        assert_eq!(refresh_object_search_index(&conn, "ws-a").unwrap(), 1);
+        refresh_object_search_index(&conn, "ws-b").unwrap();
+
+        let hits = search_objects(&conn, "ws-a", "shared", None, None, 50).unwrap();
+        assert_eq!(hit_names(&hits), vec!["dbo.p1"]);
+        let total: i64 = conn
+            .query_row("SELECT COUNT(*) FROM data_object_search", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(total, 2);
+    }
+
+    #[test]
+    fn split_snippet_marks_highlighted_segments() {
+        let snippet = format!("EXEC {MATCH_START}sp_foo{MATCH_END} @a = 1");
+        assert_eq!(
+            split_snippet(&snippet),
+            vec![
+                SnippetSegment {
+                    text: "EXEC ".to_string(),
+                    highlighted: false
+                },
+                SnippetSegment {
+                    text: "sp_foo".to_string(),
+                    highlighted: true
+                },
+                SnippetSegment {
+                    text: " @a = 1".to_string(),
+                    highlighted: false
+                },
+            ]
+        );
+    }
+}
@@ -1,16 +1,69 @@
 use tauri::State;
 
 use crate::db::DbState;
-use crate::types::{AppPhase, AppPhaseState, AppSettings};
+use crate::types::{AppPhase, AppPhaseState, AppSettings, SecretsStatus, SettingsView};
+
+/// Characters of a saved API key shown so users can tell keys apart.
+const API_KEY_HINT_LEN: usize = 4;
+
+fn settings_view(settings: AppSettings) -> SettingsView {
+    let api_key_hint = settings
+        .anthropic_api_key
+        .as_deref()
+        .and_then(|stored| crate::secrets::decrypt(stored).ok())
+        .map(|key| {
+            let chars: Vec<char> = key.chars().collect();
+            chars[chars.len().saturating_sub(API_KEY_HINT_LEN)..]
+                .iter()
+                .collect()
+        });
+    SettingsView {
+        has_anthropic_api_key: settings.anthropic_api_key.is_some(),
+        anthropic_api_key_hint: api_key_hint,
+        has_github_oauth_token: settings.github_oauth_token.is_some(),
+        github_user_login: settings.github_user_login,
+        github_user_avatar: settings.github_user_avatar,
+        github_user_email: settings.github_user_email,
+    }
+}
 
 #[tauri::command]
-pub fn get_settings(state: State<'_, DbState>) -> Result<AppSettings, String> {
+pub fn get_settings(state: State<'_, DbState>) -> Result<SettingsView, String> {
     log::info!("[get_settings]");
     let conn = state.0.lock().map_err(|e| {
         log::error!("[get_settings] Failed to acquire DB lock: {}", e);
         e.to_string()
     })?;
-    crate::db::read_settings(&conn)
+    Ok(settings_view(crate::db::read_stored_settings(&conn)?))
+}
+
+#[tauri::command]
+pub fn secrets_status(state: State<'_, DbState>) -> Result<SecretsStatus, String> {
+    log::info!("[secrets_status]");
+    let conn = state.0.lock().map_err(|e| {
+        log::error!("[secrets_status] Failed to acquire DB lock: {}", e);
+        e.to_string()
+    })?;
+    crate::secrets::status(&conn)
+}
+
+/// Unlock passphrase-protected credentials. The first call sets the passphrase.
+#[tauri::command]
+pub fn secrets_unlock(
+    state: State<'_, DbState>,
+    passphrase: String,
+) -> Result<SecretsStatus, String> {
+    log::info!("[secrets_unlock]");
+    let conn = state.0.lock().map_err(|e| {
+        log::error!("[secrets_unlock] Failed to acquire DB lock: {}", e);
+        e.to_string()
+    })?;
+    let status = crate::secrets::unlock(&conn, &passphrase).map_err(|e| {
+        log::error!("[secrets_unlock] {}", e);
+        e
+    })?;
+    let _ = crate::db::reconcile_and_persist_app_phase(&conn)?;
+    Ok(status)
 }
 
 #[tauri::command]
@@ -52,7 +105,8 @@ pub fn app_set_phase(
     })?;
     let phase = AppPhase::from_str(app_phase.as_str())
         .ok_or_else(|| format!("Unsupported app phase: {}", app_phase))?;
-    crate::db::write_app_phase(&conn, phase)?;
+    let workspace_id = active_workspace_id(&conn)?;
+    crate::db::write_app_phase(&conn, &workspace_id, phase)?;
     crate::db::read_current_app_phase_state(&conn)
 }
 
@@ -71,18 +125,68 @@ pub fn app_set_phase_flags(
         log::error!("[app_set_phase_flags] Failed to acquire DB lock: {}", e);
         e.to_string()
     })?;
+    let workspace_id = active_workspace_id(&conn)?;
     if let Some(value) = scope_finalized {
-        crate::db::write_scope_finalized(&conn, value)?;
+        crate::db::write_scope_finalized(&conn, &workspace_id, value)?;
     }
     if let Some(value) = plan_finalized {
-        crate::db::write_plan_finalized(&conn, value)?;
+        if value {
+            ensure_plan_can_finalize(&conn, &workspace_id)?;
+        }
+        crate::db::write_plan_finalized(&conn, &workspace_id, value)?;
     }
     crate::db::reconcile_and_persist_app_phase(&conn)
 }
 
+fn active_workspace_id(conn: &rusqlite::Connection) -> Result<String, String> {
+    crate::db::read_active_workspace_id(conn)?.ok_or_else(|| "No workspace is selected".to_string())
+}
+
+/// Refuse to finalize while `plan_validate` reports errors; warnings do not block.
+fn ensure_plan_can_finalize(conn: &rusqlite::Connection, workspace_id: &str) -> Result<(), String> {
+    let blockers = crate::plan::validate::finalization_blockers(conn, workspace_id)
+        .map_err(|e| e.to_string())?;
+    if blockers.is_empty() {
+        return Ok(());
+    }
+    log::error!(
+        "[app_set_phase_flags] plan has {} blocking issue(s)",
+        blockers.len()
+    );
+    let listed: Vec<_> = blockers
+        .iter()
+        .take(5)
+        .map(|issue| format!("{}: {}", issue.table, issue.message))
+        .collect();
+    let more = match blockers.len().saturating_sub(listed.len()) {
+        0 => String::new(),
+        n => format!(" (and {n} more)"),
+    };
+    Err(format!(
+        "Plan cannot be finalized: {}{more}",
+        listed.join("; ")
+    ))
+}
+
+/// Validate `api_key`, or the saved key when none is given.
 #[tauri::command]
-pub async fn test_api_key(api_key: String) -> Result<bool, String> {
+pub async fn test_api_key(
+    state: State<'_, DbState>,
+    api_key: Option<String>,
+) -> Result<bool, String> {
     log::info!("[test_api_key]");
+    let api_key = match api_key.filter(|key| !key.trim().is_empty()) {
+        Some(key) => key,
+        None => {
+            let conn = state.0.lock().map_err(|e| {
+                log::error!("[test_api_key] Failed to acquire DB lock: {}", e);
+                e.to_string()
+            })?;
+            crate::db::read_settings(&conn)?
+                .anthropic_api_key
+                .ok_or_else(|| "No API key is saved".to_string())?
+        }
+    };
     let client = reqwest::Client::new();
     let resp = client
         .post("https://api.anthropic.com/v1/messages")
@@ -118,6 +222,25 @@ mod tests {
     use crate::db;
     use crate::types::AppSettings;
 
+    #[test]
+    fn plan_finalization_is_blocked_by_validation_errors() {
+        let conn = db::open_in_memory().unwrap();
+        assert!(super::ensure_plan_can_finalize(&conn, "ws").is_ok());
+        conn.execute_batch(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at)
+               VALUES ('ws', 'WS', '/tmp/repo', '2026-01-01T00:00:00Z');
+             INSERT INTO items(id, workspace_id, display_name, item_type) VALUES ('wh', 'ws', 'WH', 'Warehouse');
+             INSERT INTO selected_tables(id, workspace_id, warehouse_item_id, schema_name, table_name)
+               VALUES ('st1', 'ws', 'wh', 'dbo', 'orders');",
+        )
+        .unwrap();
+        let err = super::ensure_plan_can_finalize(&conn, "ws").unwrap_err();
+        assert_eq!(
+            err,
+            "Plan cannot be finalized: dbo.orders: No procedure writing this table has been recorded"
+        );
+    }
+
     #[test]
     fn settings_roundtrip_persists_anthropic_key() {
         let conn = db::open_in_memory().unwrap();
@@ -129,4 +252,22 @@ mod tests {
         let read = db::read_settings(&conn).unwrap();
         assert_eq!(read.anthropic_api_key.as_deref(), Some("sk-ant-test"));
     }
+
+    #[test]
+    fn settings_view_reports_secrets_without_their_values() {
+        let conn = db::open_in_memory().unwrap();
+        let settings = AppSettings {
+            anthropic_api_key: Some("sk-ant-test-1234".to_string()),
+            github_user_login: Some("octocat".to_string()),
+            ..AppSettings::default()
+        };
+        db::write_settings(&conn, &settings).unwrap();
+        let view = super::settings_view(db::read_stored_settings(&conn).unwrap());
+        assert!(view.has_anthropic_api_key);
+        assert_eq!(view.anthropic_api_key_hint.as_deref(), Some("1234"));
+        assert!(!view.has_github_oauth_token);
+        assert_eq!(view.github_user_login.as_deref(), Some("octocat"));
+        let json = serde_json::to_string(&view).unwrap();
+        assert!(!json.contains("sk-ant-test"));
+    }
 }
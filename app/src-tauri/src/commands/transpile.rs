@@ -0,0 +1,106 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::State;
+
+use crate::db::DbState;
+use crate::tsql::transpile::transpile;
+use crate::types::{CommandError, TranspileResult, TranspileTarget};
+
+#[tauri::command]
+pub fn transpile_procedure(
+    warehouse_item_id: String,
+    schema_name: String,
+    procedure_name: String,
+    target: TranspileTarget,
+    state: State<DbState>,
+) -> Result<TranspileResult, CommandError> {
+    log::info!(
+        "transpile_procedure: warehouse_item_id={} schema={} procedure={} target={:?}",
+        warehouse_item_id,
+        schema_name,
+        procedure_name,
+        target
+    );
+    let conn = state.0.lock().unwrap();
+    let body = read_procedure_body(&conn, &warehouse_item_id, &schema_name, &procedure_name)
+        .inspect_err(|e| log::error!("transpile_procedure: failed: {e}"))?;
+    let result = transpile(&body, target);
+    log::info!(
+        "transpile_procedure: issues={} fully_translated={}",
+        result.issues.len(),
+        result.fully_translated
+    );
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn transpile_sql(sql: String, target: TranspileTarget) -> TranspileResult {
+    log::info!("transpile_sql: target={:?} length={}", target, sql.len());
+    transpile(&sql, target)
+}
+
+fn read_procedure_body(
+    conn: &Connection,
+    warehouse_item_id: &str,
+    schema_name: &str,
+    procedure_name: &str,
+) -> Result<String, CommandError> {
+    conn.query_row(
+        "SELECT sql_body FROM warehouse_procedures
+         WHERE warehouse_item_id=?1 AND schema_name=?2 AND procedure_name=?3",
+        params![warehouse_item_id, schema_name, procedure_name],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .optional()?
+    .flatten()
+    .ok_or_else(|| CommandError::NotFound(format!("{schema_name}.{procedure_name}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn insert_procedure(conn: &Connection, sql_body: Option<&str>) -> String {
+        let ws_id = uuid::Uuid::new_v4().to_string();
+        let item_id = uuid::Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![ws_id, "Test Workspace", "/tmp/repo", "2026-01-01T00:00:00Z"],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO items(id, workspace_id, display_name, item_type) VALUES (?1, ?2, ?3, ?4)",
+            params![item_id, ws_id, "Warehouse", "Warehouse"],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO warehouse_schemas(warehouse_item_id, schema_name) VALUES (?1, ?2)",
+            params![item_id, "dbo"],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO warehouse_procedures(warehouse_item_id, schema_name, procedure_name, sql_body) VALUES (?1, ?2, ?3, ?4)",
+            params![item_id, "dbo", "sp_load", sql_body],
+        )
+        .unwrap();
+        item_id
+    }
+
+    #[test]
+    fn read_procedure_body_returns_stored_sql() {
+        let conn = db::open_in_memory().unwrap();
+        let item_id = insert_procedure(&conn, Some("SELECT 1"));
+        let body = read_procedure_body(&conn, &item_id, "dbo", "sp_load").unwrap();
+        assert_eq!(body, "SELECT 1");
+    }
+
+    #[test]
+    fn read_procedure_body_returns_not_found_without_body() {
+        let conn = db::open_in_memory().unwrap();
+        let item_id = insert_procedure(&conn, None);
+        let missing_body = read_procedure_body(&conn, &item_id, "dbo", "sp_load");
+        assert!(matches!(missing_body, Err(CommandError::NotFound(_))));
+        let missing_row = read_procedure_body(&conn, &item_id, "dbo", "sp_other");
+        assert!(matches!(missing_row, Err(CommandError::NotFound(_))));
+    }
+}
@@ -134,15 +134,17 @@ pub fn usage_get_run_detail(
 }
 
 fn resolve_logs_dir(conn: &rusqlite::Connection, app: &AppHandle) -> Result<PathBuf, String> {
-    let latest_workspace_dir: Option<String> = conn
-        .query_row(
-            "SELECT migration_repo_path FROM workspaces ORDER BY created_at DESC LIMIT 1",
-            [],
-            |row| row.get(0),
-        )
-        .ok();
-
-    let base = if let Some(path) = latest_workspace_dir {
+    let active_workspace_dir: Option<String> =
+        crate::db::read_active_workspace_id(conn)?.and_then(|id| {
+            conn.query_row(
+                "SELECT migration_repo_path FROM workspaces WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .ok()
+        });
+
+    let base = if let Some(path) = active_workspace_dir {
         let trimmed = path.trim();
         if !trimmed.is_empty() {
             PathBuf::from(trimmed)
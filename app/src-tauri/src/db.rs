@@ -1,9 +1,10 @@
 use std::{path::Path, sync::Mutex};
 
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension, ToSql};
 use thiserror::Error;
 
-use crate::types::{AppPhase, AppPhaseState, AppSettings};
+use crate::secrets;
+use crate::types::{AppPhase, AppPhaseState, AppSettings, GitHubConfig};
 
 #[derive(Debug, Error)]
 pub enum DbError {
@@ -15,9 +16,8 @@ pub enum DbError {
 
 pub struct DbState(pub Mutex<Connection>);
 
-const APP_PHASE_KEY: &str = "app_phase";
-const SCOPE_FINALIZED_KEY: &str = "scope_finalized";
-const PLAN_FINALIZED_KEY: &str = "plan_finalized";
+const ACTIVE_WORKSPACE_KEY: &str = "active_workspace_id";
+const GITHUB_CONFIG_KEY: &str = "github_config";
 
 const MIGRATIONS: &[(i64, &str)] = &[
     (1, include_str!("../migrations/001_initial_schema.sql")),
@@ -43,6 +43,33 @@ const MIGRATIONS: &[(i64, &str)] = &[
         8,
         include_str!("../migrations/008_add_canonical_source_model.sql"),
     ),
+    (
+        9,
+        include_str!("../migrations/009_add_candidacy_override_history.sql"),
+    ),
+    (
+        10,
+        include_str!("../migrations/010_add_dynamic_sql_lineage.sql"),
+    ),
+    (11, include_str!("../migrations/011_add_object_search.sql")),
+    (
+        12,
+        include_str!("../migrations/012_add_pipeline_activity_parent.sql"),
+    ),
+    (13, include_str!("../migrations/013_add_plan_exports.sql")),
+    (14, include_str!("../migrations/014_add_pii_masking.sql")),
+    (
+        15,
+        include_str!("../migrations/015_add_plan_pull_requests.sql"),
+    ),
+    (
+        16,
+        include_str!("../migrations/016_add_workspace_phase_state.sql"),
+    ),
+    (
+        17,
+        include_str!("../migrations/017_add_notebook_definitions.sql"),
+    ),
 ];
 
 pub fn open(path: &Path) -> Result<Connection, DbError> {
@@ -58,6 +85,7 @@ pub fn open(path: &Path) -> Result<Connection, DbError> {
 
 #[cfg(test)]
 pub(crate) fn open_in_memory() -> Result<Connection, DbError> {
+    crate::secrets::install_test_key();
     let conn = Connection::open_in_memory()?;
     conn.execute_batch("PRAGMA foreign_keys = ON;")?;
     run_migrations(&conn)?;
@@ -93,9 +121,18 @@ fn run_migrations(conn: &Connection) -> Result<(), DbError> {
     Ok(())
 }
 
-/// Read the persisted app settings from the settings table.
+/// Read the persisted app settings from the settings table, decrypting the secret fields.
 /// Returns defaults if no row exists yet.
 pub fn read_settings(conn: &Connection) -> Result<AppSettings, String> {
+    let mut settings = read_stored_settings(conn)?;
+    settings.anthropic_api_key = secrets::decrypt_opt(settings.anthropic_api_key.as_deref())?;
+    settings.github_oauth_token = secrets::decrypt_opt(settings.github_oauth_token.as_deref())?;
+    Ok(settings)
+}
+
+/// App settings as stored, with the secret fields still encrypted. Enough to read the other
+/// fields, or tell whether a secret is set, without unlocking the secrets store.
+pub fn read_stored_settings(conn: &Connection) -> Result<AppSettings, String> {
     let mut stmt = conn
         .prepare("SELECT value FROM settings WHERE key = ?1")
         .map_err(|e| e.to_string())?;
@@ -109,9 +146,16 @@ pub fn read_settings(conn: &Connection) -> Result<AppSettings, String> {
     }
 }
 
-/// Write app settings to the settings table.
+/// Write app settings to the settings table, encrypting the secret fields.
 pub fn write_settings(conn: &Connection, settings: &AppSettings) -> Result<(), String> {
-    let json = serde_json::to_string(settings).map_err(|e| e.to_string())?;
+    let stored = AppSettings {
+        anthropic_api_key: secrets::encrypt_opt(settings.anthropic_api_key.as_deref())?,
+        github_oauth_token: secrets::encrypt_opt(settings.github_oauth_token.as_deref())?,
+        github_user_login: settings.github_user_login.clone(),
+        github_user_avatar: settings.github_user_avatar.clone(),
+        github_user_email: settings.github_user_email.clone(),
+    };
+    let json = serde_json::to_string(&stored).map_err(|e| e.to_string())?;
     conn.execute(
         "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
         ["app_settings", &json],
@@ -120,7 +164,20 @@ pub fn write_settings(conn: &Connection, settings: &AppSettings) -> Result<(), S
     Ok(())
 }
 
-fn read_settings_value(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+/// Read the GitHub installation settings; defaults (github.com) when none are saved.
+pub fn read_github_config(conn: &Connection) -> Result<GitHubConfig, String> {
+    match read_settings_value(conn, GITHUB_CONFIG_KEY)? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string()),
+        None => Ok(GitHubConfig::default()),
+    }
+}
+
+pub fn write_github_config(conn: &Connection, config: &GitHubConfig) -> Result<(), String> {
+    let json = serde_json::to_string(config).map_err(|e| e.to_string())?;
+    write_settings_value(conn, GITHUB_CONFIG_KEY, &json)
+}
+
+pub(crate) fn read_settings_value(conn: &Connection, key: &str) -> Result<Option<String>, String> {
     conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
         row.get(0)
     })
@@ -132,7 +189,11 @@ fn read_settings_value(conn: &Connection, key: &str) -> Result<Option<String>, S
     .map_err(|e| e.to_string())
 }
 
-fn write_settings_value(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+pub(crate) fn write_settings_value(
+    conn: &Connection,
+    key: &str,
+    value: &str,
+) -> Result<(), String> {
     conn.execute(
         "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
         [key, value],
@@ -141,46 +202,110 @@ fn write_settings_value(conn: &Connection, key: &str, value: &str) -> Result<(),
     Ok(())
 }
 
-fn read_bool_flag(conn: &Connection, key: &str) -> Result<bool, String> {
-    let raw = read_settings_value(conn, key)?;
-    Ok(matches!(
-        raw.as_deref(),
-        Some("1") | Some("true") | Some("TRUE")
-    ))
+/// The workspace the app is working in. Without a saved choice this is the most recently
+/// created workspace; a saved choice that no longer exists means none is selected.
+pub fn read_active_workspace_id(conn: &Connection) -> Result<Option<String>, String> {
+    let query = match read_settings_value(conn, ACTIVE_WORKSPACE_KEY)? {
+        None => conn
+            .query_row(
+                "SELECT id FROM workspaces ORDER BY created_at DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional(),
+        Some(id) => conn
+            .query_row("SELECT id FROM workspaces WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional(),
+    };
+    query.map_err(|e| e.to_string())
+}
+
+/// Select the active workspace; `None` leaves no workspace selected.
+pub fn write_active_workspace_id(
+    conn: &Connection,
+    workspace_id: Option<&str>,
+) -> Result<(), String> {
+    write_settings_value(conn, ACTIVE_WORKSPACE_KEY, workspace_id.unwrap_or(""))
 }
 
-fn write_bool_flag(conn: &Connection, key: &str, value: bool) -> Result<(), String> {
-    write_settings_value(conn, key, if value { "1" } else { "0" })
+fn read_workspace_column<T: rusqlite::types::FromSql>(
+    conn: &Connection,
+    workspace_id: &str,
+    column: &str,
+) -> Result<T, String> {
+    conn.query_row(
+        &format!("SELECT {column} FROM workspaces WHERE id = ?1"),
+        [workspace_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => format!("Workspace not found: {workspace_id}"),
+        e => e.to_string(),
+    })
+}
+
+fn write_workspace_column(
+    conn: &Connection,
+    workspace_id: &str,
+    column: &str,
+    value: &dyn ToSql,
+) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            &format!("UPDATE workspaces SET {column} = ?1 WHERE id = ?2"),
+            rusqlite::params![value, workspace_id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Workspace not found: {workspace_id}"));
+    }
+    Ok(())
 }
 
-pub fn read_scope_finalized(conn: &Connection) -> Result<bool, String> {
-    read_bool_flag(conn, SCOPE_FINALIZED_KEY)
+pub fn read_scope_finalized(conn: &Connection, workspace_id: &str) -> Result<bool, String> {
+    read_workspace_column(conn, workspace_id, "scope_finalized")
 }
 
-pub fn write_scope_finalized(conn: &Connection, finalized: bool) -> Result<(), String> {
-    write_bool_flag(conn, SCOPE_FINALIZED_KEY, finalized)
+pub fn write_scope_finalized(
+    conn: &Connection,
+    workspace_id: &str,
+    finalized: bool,
+) -> Result<(), String> {
+    write_workspace_column(conn, workspace_id, "scope_finalized", &finalized)
 }
 
-pub fn read_plan_finalized(conn: &Connection) -> Result<bool, String> {
-    read_bool_flag(conn, PLAN_FINALIZED_KEY)
+pub fn read_plan_finalized(conn: &Connection, workspace_id: &str) -> Result<bool, String> {
+    read_workspace_column(conn, workspace_id, "plan_finalized")
 }
 
-pub fn write_plan_finalized(conn: &Connection, finalized: bool) -> Result<(), String> {
-    write_bool_flag(conn, PLAN_FINALIZED_KEY, finalized)
+pub fn write_plan_finalized(
+    conn: &Connection,
+    workspace_id: &str,
+    finalized: bool,
+) -> Result<(), String> {
+    write_workspace_column(conn, workspace_id, "plan_finalized", &finalized)
 }
 
-pub fn read_app_phase(conn: &Connection) -> Result<Option<AppPhase>, String> {
-    let raw = read_settings_value(conn, APP_PHASE_KEY)?;
-    let phase = raw.and_then(|v| AppPhase::from_str(v.as_str()));
-    Ok(phase)
+pub fn read_app_phase(conn: &Connection, workspace_id: &str) -> Result<Option<AppPhase>, String> {
+    let raw: Option<String> = read_workspace_column(conn, workspace_id, "app_phase")?;
+    Ok(raw.and_then(|v| AppPhase::from_str(v.as_str())))
 }
 
-pub fn write_app_phase(conn: &Connection, phase: AppPhase) -> Result<(), String> {
-    write_settings_value(conn, APP_PHASE_KEY, phase.as_str())
+pub fn write_app_phase(
+    conn: &Connection,
+    workspace_id: &str,
+    phase: AppPhase,
+) -> Result<(), String> {
+    write_workspace_column(conn, workspace_id, "app_phase", &phase.as_str())
 }
 
-fn read_phase_facts(conn: &Connection) -> Result<AppPhaseState, String> {
-    let settings = read_settings(conn)?;
+fn read_phase_facts(
+    conn: &Connection,
+    workspace_id: Option<&str>,
+) -> Result<AppPhaseState, String> {
+    let settings = read_stored_settings(conn)?;
     let has_github_auth = settings
         .github_oauth_token
         .as_deref()
@@ -189,35 +314,43 @@ fn read_phase_facts(conn: &Connection) -> Result<AppPhaseState, String> {
         .anthropic_api_key
         .as_deref()
         .is_some_and(|v| !v.trim().is_empty());
-    let is_source_applied: bool = conn
-        .query_row(
-            "SELECT EXISTS(SELECT 1 FROM workspaces LIMIT 1)",
-            [],
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-    let scope_finalized = read_scope_finalized(conn)?;
-    let plan_finalized = read_plan_finalized(conn)?;
+    let (scope_finalized, plan_finalized) = match workspace_id {
+        Some(id) => (
+            read_scope_finalized(conn, id)?,
+            read_plan_finalized(conn, id)?,
+        ),
+        None => (false, false),
+    };
 
     Ok(AppPhaseState {
         app_phase: AppPhase::SetupRequired,
         has_github_auth,
         has_anthropic_key,
-        is_source_applied,
+        is_source_applied: workspace_id.is_some(),
         scope_finalized,
         plan_finalized,
     })
 }
 
+/// Phase state of the active workspace as stored.
 pub fn read_current_app_phase_state(conn: &Connection) -> Result<AppPhaseState, String> {
-    let mut state = read_phase_facts(conn)?;
-    state.app_phase = read_app_phase(conn)?.unwrap_or(AppPhase::SetupRequired);
+    let workspace_id = read_active_workspace_id(conn)?;
+    let mut state = read_phase_facts(conn, workspace_id.as_deref())?;
+    if let Some(id) = workspace_id.as_deref() {
+        state.app_phase = read_app_phase(conn, id)?.unwrap_or(AppPhase::SetupRequired);
+    }
     Ok(state)
 }
 
+/// Recompute the active workspace's phase from its flags and the app prerequisites, and
+/// store it. Without an active workspace the phase is always `SetupRequired`.
 pub fn reconcile_and_persist_app_phase(conn: &Connection) -> Result<AppPhaseState, String> {
-    let persisted_phase = read_app_phase(conn)?;
-    let mut state = read_phase_facts(conn)?;
+    let workspace_id = read_active_workspace_id(conn)?;
+    let persisted_phase = match workspace_id.as_deref() {
+        Some(id) => read_app_phase(conn, id)?,
+        None => None,
+    };
+    let mut state = read_phase_facts(conn, workspace_id.as_deref())?;
 
     let reconciled =
         if !state.has_github_auth || !state.has_anthropic_key || !state.is_source_applied {
@@ -232,8 +365,10 @@ pub fn reconcile_and_persist_app_phase(conn: &Connection) -> Result<AppPhaseStat
             AppPhase::ReadyToRun
         };
 
-    if persisted_phase != Some(reconciled) {
-        write_app_phase(conn, reconciled)?;
+    if let Some(id) = workspace_id.as_deref() {
+        if persisted_phase != Some(reconciled) {
+            write_app_phase(conn, id, reconciled)?;
+        }
     }
     state.app_phase = reconciled;
     Ok(state)
@@ -245,6 +380,7 @@ mod tests {
     use rusqlite::params;
 
     fn open_memory() -> Connection {
+        crate::secrets::install_test_key();
         let conn = Connection::open_in_memory().expect("in-memory db");
         conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
         run_migrations(&conn).expect("migrations failed");
@@ -331,6 +467,11 @@ mod tests {
             "sqlserver_procedure_runtime_stats",
             "sqlserver_procedure_lineage",
             "sqlserver_table_ddl_snapshots",
+            "candidacy_override_history",
+            "sqlserver_dynamic_sql",
+            "data_object_search",
+            "plan_exports",
+            "plan_pull_requests",
         ];
         for table in expected {
             let count: i64 = conn
@@ -354,7 +495,7 @@ mod tests {
         let count: i64 = conn
             .query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))
             .unwrap();
-        assert_eq!(count, 8, "schema_version should have exactly 8 rows");
+        assert_eq!(count, 17, "schema_version should have exactly 17 rows");
     }
 
     #[test]
@@ -651,12 +792,24 @@ mod tests {
             "data_objects",
             "id",
         );
+        assert_fk_delete_cascade(
+            &conn,
+            "sqlserver_dynamic_sql",
+            "procedure_data_object_id",
+            "data_objects",
+            "id",
+        );
     }
 
     #[test]
     fn reconcile_phase_prefers_setup_when_prereqs_missing() {
         let conn = open_memory();
-        write_app_phase(&conn, AppPhase::RunningLocked).unwrap();
+        conn.execute(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params!["ws-1", "ws", "/tmp/repo", "2026-01-01T00:00:00Z"],
+        )
+        .unwrap();
+        write_app_phase(&conn, "ws-1", AppPhase::RunningLocked).unwrap();
 
         let state = reconcile_and_persist_app_phase(&conn).unwrap();
         assert_eq!(state.app_phase, AppPhase::SetupRequired);
@@ -699,8 +852,8 @@ mod tests {
             params!["ws-1", "ws", "/tmp/repo", "2026-01-01T00:00:00Z"],
         )
         .unwrap();
-        write_scope_finalized(&conn, true).unwrap();
-        write_plan_finalized(&conn, true).unwrap();
+        write_scope_finalized(&conn, "ws-1", true).unwrap();
+        write_plan_finalized(&conn, "ws-1", true).unwrap();
 
         let state = reconcile_and_persist_app_phase(&conn).unwrap();
         assert_eq!(state.app_phase, AppPhase::ReadyToRun);
@@ -722,9 +875,88 @@ mod tests {
             params!["ws-1", "ws", "/tmp/repo", "2026-01-01T00:00:00Z"],
         )
         .unwrap();
-        write_app_phase(&conn, AppPhase::RunningLocked).unwrap();
+        write_app_phase(&conn, "ws-1", AppPhase::RunningLocked).unwrap();
 
         let state = reconcile_and_persist_app_phase(&conn).unwrap();
         assert_eq!(state.app_phase, AppPhase::RunningLocked);
     }
+
+    #[test]
+    fn phase_state_is_kept_per_workspace() {
+        let conn = open_memory();
+        let settings = AppSettings {
+            anthropic_api_key: Some("sk-ant-test".to_string()),
+            github_oauth_token: Some("gho_test".to_string()),
+            ..AppSettings::default()
+        };
+        write_settings(&conn, &settings).unwrap();
+        conn.execute_batch(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at)
+               VALUES ('ws-1', 'One', '/tmp/one', '2026-01-01T00:00:00Z'),
+                      ('ws-2', 'Two', '/tmp/two', '2026-01-02T00:00:00Z');",
+        )
+        .unwrap();
+        assert_eq!(
+            read_active_workspace_id(&conn).unwrap().as_deref(),
+            Some("ws-2")
+        );
+
+        write_active_workspace_id(&conn, Some("ws-1")).unwrap();
+        write_scope_finalized(&conn, "ws-1", true).unwrap();
+        let one = reconcile_and_persist_app_phase(&conn).unwrap();
+        assert_eq!(one.app_phase, AppPhase::PlanEditable);
+
+        write_active_workspace_id(&conn, Some("ws-2")).unwrap();
+        let two = reconcile_and_persist_app_phase(&conn).unwrap();
+        assert_eq!(two.app_phase, AppPhase::ScopeEditable);
+        assert_eq!(
+            read_app_phase(&conn, "ws-1").unwrap(),
+            Some(AppPhase::PlanEditable)
+        );
+
+        write_active_workspace_id(&conn, None).unwrap();
+        assert_eq!(read_active_workspace_id(&conn).unwrap(), None);
+        let none = reconcile_and_persist_app_phase(&conn).unwrap();
+        assert_eq!(none.app_phase, AppPhase::SetupRequired);
+        assert!(!none.is_source_applied);
+        assert!(write_scope_finalized(&conn, "missing", true).is_err());
+    }
+
+    #[test]
+    fn migration_16_moves_global_phase_state_to_latest_workspace() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, applied_at TEXT NOT NULL);",
+        )
+        .unwrap();
+        for (version, sql) in MIGRATIONS.iter().filter(|(v, _)| *v < 16) {
+            conn.execute_batch(sql).unwrap();
+            conn.execute(
+                "INSERT INTO schema_version(version, applied_at) VALUES (?1, datetime('now'))",
+                [version],
+            )
+            .unwrap();
+        }
+        conn.execute_batch(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at)
+               VALUES ('old', 'Old', '/tmp/old', '2026-01-01T00:00:00Z'),
+                      ('new', 'New', '/tmp/new', '2026-01-02T00:00:00Z');
+             INSERT INTO settings(key, value) VALUES ('app_phase', 'plan_editable'),
+               ('scope_finalized', '1'), ('plan_finalized', '0');",
+        )
+        .unwrap();
+
+        run_migrations(&conn).unwrap();
+        assert_eq!(
+            read_active_workspace_id(&conn).unwrap().as_deref(),
+            Some("new")
+        );
+        assert_eq!(
+            read_app_phase(&conn, "new").unwrap(),
+            Some(AppPhase::PlanEditable)
+        );
+        assert!(read_scope_finalized(&conn, "new").unwrap());
+        assert!(!read_scope_finalized(&conn, "old").unwrap());
+        assert_eq!(read_settings_value(&conn, "app_phase").unwrap(), None);
+    }
 }
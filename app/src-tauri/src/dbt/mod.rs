@@ -0,0 +1,3 @@
+pub mod project;
+pub mod schema_tests;
+pub mod snapshots;
@@ -0,0 +1,580 @@
+use std::collections::HashMap;
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::path::Path;
+
+use super::schema_tests::{render_schema_files, ColumnSpec, Relationship};
+use super::snapshots::{render_snapshot, snapshot_name, SNAPSHOT_HEADER};
+use crate::types::{CommandError, DbtScaffoldSummary};
+
+/// Directory inside the migration repo that holds the generated dbt project.
+pub const DBT_DIR: &str = "dbt";
+
+/// Everything needed to scaffold the dbt project for one workspace.
+pub struct DbtProject {
+    /// dbt project and profile name, already passed through [`identifier`].
+    pub name: String,
+    pub fabric_workspace_id: Option<String>,
+    pub fabric_tenant_id: Option<String>,
+    pub sources: Vec<SourceSchema>,
+    pub models: Vec<ModelSpec>,
+}
+
+/// One dbt source: a schema of the legacy database from the canonical source model.
+pub struct SourceSchema {
+    pub name: String,
+    pub database: Option<String>,
+    pub schema: String,
+    pub tables: Vec<String>,
+}
+
+/// One selected table and the plan settings that shape its model stub.
+pub struct ModelSpec {
+    pub schema_name: String,
+    pub table_name: String,
+    pub load_strategy: Option<String>,
+    pub grain_columns: Vec<String>,
+    pub incremental_column: Option<String>,
+    pub date_column: Option<String>,
+    /// Initial backfill for snapshots: `sample_1day`, `full` or `full_flagged`.
+    pub snapshot_strategy: String,
+    /// `schema.procedure` that writes the table today.
+    pub procedure: Option<String>,
+    /// `(source name, table name)` the stub selects from; `None` when the canonical model
+    /// has no matching table.
+    pub source: Option<(String, String)>,
+    /// Columns of the source table, in column order.
+    pub columns: Vec<ColumnSpec>,
+    /// T-SQL definitions of the source table's CHECK constraints.
+    pub check_definitions: Vec<String>,
+    pub relationships: Vec<Relationship>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Materialization {
+    Incremental,
+    Table,
+    /// Built as a table; history is kept by a dbt snapshot over the model.
+    Snapshot,
+}
+
+impl Materialization {
+    pub fn from_load_strategy(load_strategy: Option<&str>) -> Self {
+        match load_strategy {
+            Some("incremental") => Materialization::Incremental,
+            Some("snapshot") => Materialization::Snapshot,
+            _ => Materialization::Table,
+        }
+    }
+}
+
+/// Lowercase identifier safe for dbt project, profile and model names.
+pub fn identifier(value: &str) -> String {
+    let mut out = String::new();
+    for c in value.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    let out = out.trim_end_matches('_').to_string();
+    match out.chars().next() {
+        None => "migration".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("m_{out}"),
+        Some(_) => out,
+    }
+}
+
+/// Column list stored as a JSON array or a comma-separated string.
+pub fn split_columns(value: Option<&str>) -> Vec<String> {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Vec::new();
+    };
+    if let Ok(columns) = serde_json::from_str::<Vec<String>>(value) {
+        return columns
+            .into_iter()
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect();
+    }
+    value
+        .split(',')
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+/// YAML scalar, single-quoted unless it is a plain identifier YAML would not reinterpret.
+pub fn yaml_scalar(value: &str) -> String {
+    let plain = value
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !matches!(
+            value.to_ascii_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "y" | "n"
+        );
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+pub fn sql_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Model name per selected table: the table name, or `schema__table` when the same table
+/// name is selected in more than one schema (dbt model names are project-wide).
+pub fn model_names(models: &[ModelSpec]) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for model in models {
+        *counts.entry(identifier(&model.table_name)).or_default() += 1;
+    }
+    models
+        .iter()
+        .map(|model| {
+            let table = identifier(&model.table_name);
+            if counts[&table] > 1 {
+                format!("{}__{table}", identifier(&model.schema_name))
+            } else {
+                table
+            }
+        })
+        .collect()
+}
+
+pub fn render_dbt_project(project: &DbtProject) -> String {
+    let name = &project.name;
+    let mut out = String::new();
+    writeln!(
+        out,
+        "# Generated from the migration plan; regenerating the plan rewrites this file."
+    )
+    .ok();
+    writeln!(out, "name: {name}").ok();
+    writeln!(out, "version: '1.0.0'").ok();
+    writeln!(out, "config-version: 2").ok();
+    writeln!(out, "profile: {name}").ok();
+    writeln!(out).ok();
+    writeln!(out, "model-paths: [\"models\"]").ok();
+    writeln!(out, "snapshot-paths: [\"snapshots\"]").ok();
+    writeln!(out, "test-paths: [\"tests\"]").ok();
+    writeln!(out, "macro-paths: [\"macros\"]").ok();
+    writeln!(out, "target-path: \"target\"").ok();
+    writeln!(out, "clean-targets: [\"target\", \"dbt_packages\"]").ok();
+    writeln!(out).ok();
+    writeln!(out, "models:").ok();
+    writeln!(out, "  {name}:").ok();
+    writeln!(out, "    +file_format: delta").ok();
+    out
+}
+
+/// Packages the generated tests rely on (`dbt_utils.unique_combination_of_columns`).
+pub fn render_packages() -> String {
+    let mut out = String::new();
+    writeln!(out, "packages:").ok();
+    writeln!(out, "  - package: dbt-labs/dbt_utils").ok();
+    writeln!(out, "    version: [\">=1.1.0\", \"<2.0.0\"]").ok();
+    out
+}
+
+/// dbt-fabricspark profile. Secrets and lakehouse ids come from environment variables so the
+/// file can be committed; the workspace and tenant are filled in when the Fabric URL is known.
+pub fn render_profiles(project: &DbtProject) -> String {
+    let known_or_env = |value: &Option<String>, var: &str| match value {
+        Some(value) => yaml_scalar(value),
+        None => format!("\"{{{{ env_var('{var}') }}}}\""),
+    };
+    let env = |var: &str| format!("\"{{{{ env_var('{var}') }}}}\"");
+    let mut out = String::new();
+    writeln!(
+        out,
+        "# Profile template for dbt-fabricspark. Set the FABRIC_* environment variables"
+    )
+    .ok();
+    writeln!(
+        out,
+        "# before running dbt; never commit secret values to this file."
+    )
+    .ok();
+    writeln!(out, "{}:", project.name).ok();
+    writeln!(out, "  target: dev").ok();
+    writeln!(out, "  outputs:").ok();
+    writeln!(out, "    dev:").ok();
+    writeln!(out, "      type: fabricspark").ok();
+    writeln!(out, "      method: livy").ok();
+    writeln!(out, "      authentication: SPN").ok();
+    writeln!(out, "      endpoint: https://api.fabric.microsoft.com/v1").ok();
+    writeln!(
+        out,
+        "      workspaceid: {}",
+        known_or_env(&project.fabric_workspace_id, "FABRIC_WORKSPACE_ID")
+    )
+    .ok();
+    writeln!(out, "      lakehouseid: {}", env("FABRIC_LAKEHOUSE_ID")).ok();
+    writeln!(out, "      lakehouse: {}", env("FABRIC_LAKEHOUSE")).ok();
+    writeln!(out, "      schema: {}", env("FABRIC_LAKEHOUSE")).ok();
+    writeln!(
+        out,
+        "      tenant_id: {}",
+        known_or_env(&project.fabric_tenant_id, "FABRIC_TENANT_ID")
+    )
+    .ok();
+    writeln!(out, "      client_id: {}", env("FABRIC_CLIENT_ID")).ok();
+    writeln!(out, "      client_secret: {}", env("FABRIC_CLIENT_SECRET")).ok();
+    writeln!(out, "      threads: 4").ok();
+    writeln!(out, "      connect_retries: 3").ok();
+    writeln!(out, "      connect_timeout: 60").ok();
+    out
+}
+
+pub fn render_sources(sources: &[SourceSchema]) -> String {
+    let mut out = String::new();
+    writeln!(out, "version: 2").ok();
+    writeln!(out).ok();
+    if sources.is_empty() {
+        // `sources:` with no entries is null, which dbt rejects.
+        writeln!(out, "sources: []").ok();
+    } else {
+        writeln!(out, "sources:").ok();
+    }
+    for source in sources {
+        writeln!(out, "  - name: {}", yaml_scalar(&source.name)).ok();
+        if let Some(database) = &source.database {
+            writeln!(out, "    database: {}", yaml_scalar(database)).ok();
+        }
+        writeln!(out, "    schema: {}", yaml_scalar(&source.schema)).ok();
+        writeln!(out, "    tables:").ok();
+        for table in &source.tables {
+            writeln!(out, "      - name: {}", yaml_scalar(table)).ok();
+        }
+    }
+    out
+}
+
+/// Model stub: config block derived from the load strategy and a `select *` from the legacy
+/// table for engineers to replace with the translated procedure logic.
+pub fn render_model(model: &ModelSpec, model_name: &str) -> String {
+    let materialization = Materialization::from_load_strategy(model.load_strategy.as_deref());
+    let mut config = Vec::new();
+    match materialization {
+        Materialization::Incremental => {
+            config.push("materialized='incremental'".to_string());
+            if model.grain_columns.is_empty() {
+                config.push("incremental_strategy='append'".to_string());
+            } else {
+                config.push("incremental_strategy='merge'".to_string());
+            }
+        }
+        Materialization::Table | Materialization::Snapshot => {
+            config.push("materialized='table'".to_string());
+        }
+    }
+    match model.grain_columns.as_slice() {
+        [] => {}
+        [column] => config.push(format!("unique_key={}", sql_literal(column))),
+        columns => config.push(format!(
+            "unique_key=[{}]",
+            columns
+                .iter()
+                .map(|c| sql_literal(c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+    if model.table_name != model_name {
+        config.push(format!("alias={}", sql_literal(&model.table_name)));
+    }
+
+    let mut out = String::new();
+    writeln!(out, "{{{{").ok();
+    writeln!(out, "  config(").ok();
+    for entry in &config {
+        writeln!(out, "    {entry},").ok();
+    }
+    writeln!(out, "  )").ok();
+    writeln!(out, "}}}}").ok();
+    writeln!(out).ok();
+    match &model.procedure {
+        Some(procedure) => writeln!(
+            out,
+            "-- Scaffolded for {}.{}, loaded today by {procedure}.",
+            model.schema_name, model.table_name
+        ),
+        None => writeln!(
+            out,
+            "-- Scaffolded for {}.{}.",
+            model.schema_name, model.table_name
+        ),
+    }
+    .ok();
+    writeln!(
+        out,
+        "-- Replace the select below with the translated load logic."
+    )
+    .ok();
+    if materialization == Materialization::Snapshot {
+        writeln!(
+            out,
+            "-- History for this table is kept by a dbt snapshot over this model."
+        )
+        .ok();
+    }
+    writeln!(out).ok();
+    match &model.source {
+        Some((source, table)) => {
+            writeln!(out, "select *").ok();
+            writeln!(
+                out,
+                "from {{{{ source({}, {}) }}}}",
+                sql_literal(source),
+                sql_literal(table)
+            )
+            .ok();
+        }
+        None => {
+            writeln!(
+                out,
+                "-- No source table found in the source model; add one to sources.yml."
+            )
+            .ok();
+            writeln!(out, "select 1 as placeholder").ok();
+        }
+    }
+    if let (Materialization::Incremental, Some(column)) =
+        (materialization, model.incremental_column.as_deref())
+    {
+        if model.source.is_some() {
+            let column = format!("{{{{ adapter.quote({}) }}}}", sql_literal(column));
+            writeln!(out, "{{% if is_incremental() %}}").ok();
+            writeln!(
+                out,
+                "where {column} > (select max({column}) from {{{{ this }}}})"
+            )
+            .ok();
+            writeln!(out, "{{% endif %}}").ok();
+        }
+    }
+    out
+}
+
+/// Write the project into `<repo>/dbt/`. Project, profile, source, `schema.yml` test and
+/// snapshot files are regenerated; model stubs are only written when missing so engineers'
+/// edits survive regeneration.
+pub fn write_scaffold(
+    repo_path: &Path,
+    project: &DbtProject,
+) -> Result<DbtScaffoldSummary, CommandError> {
+    let dir = repo_path.join(DBT_DIR);
+    let models_dir = dir.join("models");
+    create_dir(&models_dir)?;
+    write_file(&dir.join("dbt_project.yml"), &render_dbt_project(project))?;
+    write_file(&dir.join("profiles.yml"), &render_profiles(project))?;
+    write_file(&dir.join("packages.yml"), &render_packages())?;
+    write_file(
+        &models_dir.join("sources.yml"),
+        &render_sources(&project.sources),
+    )?;
+
+    let mut summary = DbtScaffoldSummary {
+        project_path: dir.display().to_string(),
+        source_tables: project.sources.iter().map(|s| s.tables.len()).sum(),
+        ..DbtScaffoldSummary::default()
+    };
+    for (model, name) in project.models.iter().zip(model_names(&project.models)) {
+        let schema_dir = models_dir.join(identifier(&model.schema_name));
+        let path = schema_dir.join(format!("{name}.sql"));
+        if path.exists() {
+            summary.models_kept += 1;
+            continue;
+        }
+        create_dir(&schema_dir)?;
+        write_file(&path, &render_model(model, &name))?;
+        summary.models_written += 1;
+    }
+
+    let schema = render_schema_files(project);
+    for (directory, content) in &schema.files {
+        write_file(&models_dir.join(directory).join("schema.yml"), content)?;
+    }
+    summary.schema_tests = schema.tests;
+    summary.skipped_relationships = schema.skipped_relationships;
+
+    write_snapshots(&dir.join("snapshots"), project, &mut summary)?;
+    Ok(summary)
+}
+
+/// Write one snapshot per snapshot-strategy table and remove generated snapshots for tables
+/// that no longer use that strategy. Hand-written snapshot files are left alone.
+fn write_snapshots(
+    snapshots_dir: &Path,
+    project: &DbtProject,
+    summary: &mut DbtScaffoldSummary,
+) -> Result<(), CommandError> {
+    let mut written = Vec::new();
+    for (model, name) in project.models.iter().zip(model_names(&project.models)) {
+        if Materialization::from_load_strategy(model.load_strategy.as_deref())
+            != Materialization::Snapshot
+        {
+            continue;
+        }
+        match render_snapshot(model, &name) {
+            Ok(content) => {
+                create_dir(snapshots_dir)?;
+                let file_name = format!("{}.sql", snapshot_name(&name));
+                write_file(&snapshots_dir.join(&file_name), &content)?;
+                written.push(file_name);
+            }
+            Err(reason) => summary.skipped_snapshots.push(format!(
+                "{}.{}: {reason}",
+                model.schema_name, model.table_name
+            )),
+        }
+    }
+    summary.snapshots = written.len();
+
+    let Ok(entries) = fs::read_dir(snapshots_dir) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if written.contains(&file_name) || !file_name.ends_with(".sql") {
+            continue;
+        }
+        let generated = fs::read_to_string(entry.path())
+            .is_ok_and(|content| content.starts_with(SNAPSHOT_HEADER));
+        if generated {
+            fs::remove_file(entry.path()).map_err(|e| {
+                CommandError::Io(format!("failed to remove {}: {e}", entry.path().display()))
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn create_dir(path: &Path) -> Result<(), CommandError> {
+    fs::create_dir_all(path)
+        .map_err(|e| CommandError::Io(format!("failed to create {}: {e}", path.display())))
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), CommandError> {
+    fs::write(path, content)
+        .map_err(|e| CommandError::Io(format!("failed to write {}: {e}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(schema: &str, table: &str, load_strategy: Option<&str>) -> ModelSpec {
+        ModelSpec {
+            schema_name: schema.to_string(),
+            table_name: table.to_string(),
+            load_strategy: load_strategy.map(str::to_string),
+            grain_columns: Vec::new(),
+            incremental_column: None,
+            date_column: None,
+            snapshot_strategy: "sample_1day".to_string(),
+            procedure: None,
+            source: Some((schema.to_string(), table.to_string())),
+            columns: Vec::new(),
+            check_definitions: Vec::new(),
+            relationships: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn identifiers_columns_and_scalars() {
+        assert_eq!(identifier("Sales Migration (2026)"), "sales_migration_2026");
+        assert_eq!(identifier("2026-dw"), "m_2026_dw");
+        assert_eq!(identifier("--"), "migration");
+        assert_eq!(
+            split_columns(Some(r#"["order_id", " line "]"#)),
+            vec!["order_id", "line"]
+        );
+        assert_eq!(split_columns(Some("a, b,,")), vec!["a", "b"]);
+        assert!(split_columns(Some("  ")).is_empty());
+        assert_eq!(yaml_scalar("orders"), "orders");
+        assert_eq!(yaml_scalar("Order Lines"), "'Order Lines'");
+        assert_eq!(yaml_scalar("on"), "'on'");
+        assert_eq!(yaml_scalar("it's"), "'it''s'");
+    }
+
+    #[test]
+    fn models_follow_load_strategy() {
+        let mut incremental = model("dbo", "Orders", Some("incremental"));
+        incremental.grain_columns = vec!["order_id".to_string(), "line_no".to_string()];
+        incremental.incremental_column = Some("modified_at".to_string());
+        incremental.procedure = Some("dbo.sp_load_orders".to_string());
+        let sql = render_model(&incremental, "orders");
+        assert!(sql.contains("materialized='incremental',"));
+        assert!(sql.contains("incremental_strategy='merge',"));
+        assert!(sql.contains("unique_key=['order_id', 'line_no'],"));
+        assert!(sql.contains("alias='Orders',"));
+        assert!(sql.contains("loaded today by dbo.sp_load_orders."));
+        assert!(sql.contains("from {{ source('dbo', 'Orders') }}\n{% if is_incremental() %}"));
+        assert!(sql.contains(
+            "where {{ adapter.quote('modified_at') }} > \
+             (select max({{ adapter.quote('modified_at') }}) from {{ this }})"
+        ));
+
+        let full = render_model(&model("dbo", "regions", Some("full_refresh")), "regions");
+        assert!(full.contains("materialized='table',"));
+        assert!(!full.contains("unique_key"));
+        assert!(!full.contains("alias"));
+
+        let mut snapshot = model("dbo", "customers", Some("snapshot"));
+        snapshot.grain_columns = vec!["customer_id".to_string()];
+        snapshot.source = None;
+        let sql = render_model(&snapshot, "customers");
+        assert!(sql.contains("materialized='table',"));
+        assert!(sql.contains("unique_key='customer_id',"));
+        assert!(sql.contains("select 1 as placeholder"));
+    }
+
+    #[test]
+    fn duplicate_table_names_are_prefixed_with_schema() {
+        let models = vec![
+            model("dbo", "orders", None),
+            model("stg", "orders", None),
+            model("dbo", "customers", None),
+        ];
+        assert_eq!(
+            model_names(&models),
+            vec!["dbo__orders", "stg__orders", "customers"]
+        );
+    }
+
+    #[test]
+    fn sources_and_profiles_render_yaml() {
+        let sources = vec![SourceSchema {
+            name: "dbo".to_string(),
+            database: Some("Sales DW".to_string()),
+            schema: "dbo".to_string(),
+            tables: vec!["orders".to_string(), "Order Lines".to_string()],
+        }];
+        assert_eq!(
+            render_sources(&sources),
+            "version: 2\n\nsources:\n  - name: dbo\n    database: 'Sales DW'\n    schema: dbo\n    tables:\n      - name: orders\n      - name: 'Order Lines'\n"
+        );
+        assert!(render_sources(&[]).ends_with("sources: []\n"));
+
+        let project = DbtProject {
+            name: "sales".to_string(),
+            fabric_workspace_id: Some("4b1c7d5e-0000-0000-0000-000000000001".to_string()),
+            fabric_tenant_id: None,
+            sources,
+            models: Vec::new(),
+        };
+        let profiles = render_profiles(&project);
+        assert!(profiles.starts_with("# Profile template"));
+        assert!(profiles.contains("sales:\n  target: dev\n"));
+        assert!(profiles.contains("workspaceid: '4b1c7d5e-0000-0000-0000-000000000001'"));
+        assert!(profiles.contains("tenant_id: \"{{ env_var('FABRIC_TENANT_ID') }}\""));
+        assert!(render_dbt_project(&project).contains("name: sales\n"));
+    }
+}
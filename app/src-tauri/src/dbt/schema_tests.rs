@@ -0,0 +1,472 @@
+use std::fmt::Write as FmtWrite;
+
+use serde_json::Value;
+
+use super::project::{identifier, model_names, yaml_scalar, DbtProject, ModelSpec};
+use crate::tsql::lexer::{tokenize, TokenKind};
+
+/// Column of the legacy table, from the canonical source model.
+pub struct ColumnSpec {
+    pub name: String,
+    pub nullable: Option<bool>,
+}
+
+/// Foreign-key style link from `table_config.relationships_json`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Relationship {
+    pub column: String,
+    /// `schema.table` or `table`.
+    pub to: String,
+    /// Referenced column; the same name as `column` when not given.
+    pub field: String,
+}
+
+/// Parse `relationships_json`: an array of objects with `column`, `to` (or `references`) and
+/// optionally `field` (or `to_column`). Entries missing a column or target are skipped.
+pub fn parse_relationships(value: Option<&str>) -> Vec<Relationship> {
+    let Some(Value::Array(entries)) = value.and_then(|v| serde_json::from_str(v).ok()) else {
+        return Vec::new();
+    };
+    let text = |entry: &Value, keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| entry.get(*key).and_then(Value::as_str))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let column = text(entry, &["column", "from"])?;
+            let to = text(entry, &["to", "references"])?;
+            let field = text(entry, &["field", "to_column"]).unwrap_or_else(|| column.clone());
+            Some(Relationship { column, to, field })
+        })
+        .collect()
+}
+
+/// `(column, values, numeric)` for a CHECK constraint that only lists allowed values, as SQL
+/// Server stores them: `([status]='closed' OR [status]='open')` or `([kind] IN (1, 2))`.
+pub fn accepted_values(definition: &str) -> Option<(String, Vec<String>, bool)> {
+    let tokens: Vec<_> = tokenize(definition)
+        .into_iter()
+        .filter(|t| !t.is_trivia() && !t.is_symbol("(") && !t.is_symbol(")"))
+        .collect();
+    let mut column: Option<String> = None;
+    let mut values = Vec::new();
+    let mut numeric = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let name = tokens[i].identifier()?;
+        if !column
+            .get_or_insert_with(|| name.clone())
+            .eq_ignore_ascii_case(&name)
+        {
+            return None;
+        }
+        let in_list = match tokens.get(i + 1) {
+            Some(t) if t.is_symbol("=") => false,
+            Some(t) if t.is_word("IN") => true,
+            _ => return None,
+        };
+        i += 2;
+        loop {
+            let negative = tokens.get(i).is_some_and(|t| t.is_symbol("-"));
+            if negative {
+                i += 1;
+            }
+            let token = tokens.get(i)?;
+            let (value, is_number) = match token.kind {
+                TokenKind::StringLiteral if !negative => (token.string_value()?, false),
+                TokenKind::Number if negative => (format!("-{}", token.text), true),
+                TokenKind::Number => (token.text.clone(), true),
+                _ => return None,
+            };
+            if *numeric.get_or_insert(is_number) != is_number {
+                return None;
+            }
+            values.push(value);
+            i += 1;
+            if in_list && tokens.get(i).is_some_and(|t| t.is_symbol(",")) {
+                i += 1;
+                continue;
+            }
+            break;
+        }
+        match tokens.get(i) {
+            None => break,
+            Some(t) if t.is_word("OR") => i += 1,
+            Some(_) => return None,
+        }
+    }
+    Some((column?, values, numeric?))
+}
+
+#[derive(Default)]
+struct ColumnTests {
+    name: String,
+    tests: Vec<String>,
+}
+
+/// Rendered `schema.yml` per model directory plus counts for the scaffold summary.
+pub struct SchemaFiles {
+    /// `(schema directory, file content)`.
+    pub files: Vec<(String, String)>,
+    pub tests: usize,
+    /// `model.column -> target` relationships whose target is neither a model nor a source.
+    pub skipped_relationships: Vec<String>,
+}
+
+/// Tests for every model, grouped into one `schema.yml` per model directory: `not_null` on
+/// grain columns and non-nullable source columns, `unique` on the grain, `accepted_values`
+/// from CHECK constraints and `relationships` from the table config.
+pub fn render_schema_files(project: &DbtProject) -> SchemaFiles {
+    let names = model_names(&project.models);
+    let mut result = SchemaFiles {
+        files: Vec::new(),
+        tests: 0,
+        skipped_relationships: Vec::new(),
+    };
+    let mut directories: Vec<String> = project
+        .models
+        .iter()
+        .map(|m| identifier(&m.schema_name))
+        .collect();
+    directories.sort();
+    directories.dedup();
+
+    for directory in directories {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "# Generated from the source metadata and table config; regenerating the plan rewrites"
+        )
+        .ok();
+        writeln!(
+            out,
+            "# this file. Keep hand-written tests in a separate file."
+        )
+        .ok();
+        writeln!(out, "version: 2").ok();
+        writeln!(out).ok();
+        writeln!(out, "models:").ok();
+        for (model, name) in project.models.iter().zip(&names) {
+            if identifier(&model.schema_name) != directory {
+                continue;
+            }
+            let columns = column_tests(project, model, name, &mut result.skipped_relationships);
+            writeln!(out, "  - name: {name}").ok();
+            if model.grain_columns.len() > 1 {
+                writeln!(out, "    data_tests:").ok();
+                writeln!(out, "      - dbt_utils.unique_combination_of_columns:").ok();
+                writeln!(out, "          combination_of_columns:").ok();
+                for column in &model.grain_columns {
+                    writeln!(out, "            - {}", yaml_scalar(column)).ok();
+                }
+                result.tests += 1;
+            }
+            let tested: Vec<&ColumnTests> =
+                columns.iter().filter(|c| !c.tests.is_empty()).collect();
+            if tested.is_empty() {
+                continue;
+            }
+            writeln!(out, "    columns:").ok();
+            for column in tested {
+                writeln!(out, "      - name: {}", yaml_scalar(&column.name)).ok();
+                writeln!(out, "        data_tests:").ok();
+                for test in &column.tests {
+                    writeln!(out, "          - {test}").ok();
+                    result.tests += 1;
+                }
+            }
+        }
+        result.files.push((directory, out));
+    }
+    result
+}
+
+fn column_tests(
+    project: &DbtProject,
+    model: &ModelSpec,
+    model_name: &str,
+    skipped: &mut Vec<String>,
+) -> Vec<ColumnTests> {
+    let mut columns: Vec<ColumnTests> = model
+        .columns
+        .iter()
+        .map(|c| ColumnTests {
+            name: c.name.clone(),
+            ..ColumnTests::default()
+        })
+        .collect();
+    let add = |columns: &mut Vec<ColumnTests>, name: &str, test: String| {
+        let index = match columns
+            .iter()
+            .position(|c| c.name.eq_ignore_ascii_case(name))
+        {
+            Some(index) => index,
+            None => {
+                columns.push(ColumnTests {
+                    name: name.to_string(),
+                    ..ColumnTests::default()
+                });
+                columns.len() - 1
+            }
+        };
+        if !columns[index].tests.contains(&test) {
+            columns[index].tests.push(test);
+        }
+    };
+
+    for column in &model.grain_columns {
+        add(&mut columns, column, "not_null".to_string());
+    }
+    for column in &model.columns {
+        if column.nullable == Some(false) {
+            add(&mut columns, &column.name, "not_null".to_string());
+        }
+    }
+    if let [column] = model.grain_columns.as_slice() {
+        add(&mut columns, column, "unique".to_string());
+    }
+    for definition in &model.check_definitions {
+        let Some((column, values, numeric)) = accepted_values(definition) else {
+            continue;
+        };
+        let values = values
+            .iter()
+            .map(|v| {
+                if numeric {
+                    v.clone()
+                } else {
+                    format!("'{}'", v.replace('\'', "''"))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut test = format!("accepted_values:\n              values: [{values}]");
+        if numeric {
+            test.push_str("\n              quote: false");
+        }
+        add(&mut columns, &column, test);
+    }
+    for relationship in &model.relationships {
+        let Some(target) = relationship_target(project, &relationship.to) else {
+            skipped.push(format!(
+                "{model_name}.{} -> {}",
+                relationship.column, relationship.to
+            ));
+            continue;
+        };
+        add(
+            &mut columns,
+            &relationship.column,
+            format!(
+                "relationships:\n              to: \"{}\"\n              field: {}",
+                target.replace('"', "\\\""),
+                yaml_scalar(&relationship.field)
+            ),
+        );
+    }
+    columns
+}
+
+/// `ref('model')` when the target is a selected table, else `source('name', 'table')`.
+fn relationship_target(project: &DbtProject, to: &str) -> Option<String> {
+    let (schema, table) = match to.rsplit_once('.') {
+        Some((schema, table)) => (Some(schema.trim()), table.trim()),
+        None => (None, to.trim()),
+    };
+    let schema_matches = |candidate: &str| schema.is_none_or(|s| s.eq_ignore_ascii_case(candidate));
+    let names = model_names(&project.models);
+    if let Some((_, name)) =
+        project.models.iter().zip(&names).find(|(m, _)| {
+            m.table_name.eq_ignore_ascii_case(table) && schema_matches(&m.schema_name)
+        })
+    {
+        return Some(format!("ref('{name}')"));
+    }
+    project.sources.iter().find_map(|source| {
+        if !schema_matches(&source.schema) {
+            return None;
+        }
+        source
+            .tables
+            .iter()
+            .find(|t| t.eq_ignore_ascii_case(table))
+            .map(|t| format!("source('{}', '{}')", source.name, t))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dbt::project::SourceSchema;
+
+    fn model(schema: &str, table: &str) -> ModelSpec {
+        ModelSpec {
+            schema_name: schema.to_string(),
+            table_name: table.to_string(),
+            load_strategy: None,
+            grain_columns: Vec::new(),
+            incremental_column: None,
+            date_column: None,
+            snapshot_strategy: "sample_1day".to_string(),
+            procedure: None,
+            source: None,
+            columns: Vec::new(),
+            check_definitions: Vec::new(),
+            relationships: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn accepted_values_from_check_definitions() {
+        assert_eq!(
+            accepted_values("([status]='closed' OR [status]=N'open')"),
+            Some((
+                "status".to_string(),
+                vec!["closed".to_string(), "open".to_string()],
+                false
+            ))
+        );
+        assert_eq!(
+            accepted_values("([kind] IN ((1), (-2)))"),
+            Some((
+                "kind".to_string(),
+                vec!["1".to_string(), "-2".to_string()],
+                true
+            ))
+        );
+        assert_eq!(accepted_values("([qty]>(0))"), None);
+        assert_eq!(accepted_values("([a]='x' OR [b]='y')"), None);
+        assert_eq!(accepted_values("([a]='x' OR [a]=(1))"), None);
+        assert_eq!(accepted_values("([a]='x' AND [a]='y')"), None);
+    }
+
+    #[test]
+    fn relationships_accept_aliases_and_default_field() {
+        assert_eq!(
+            parse_relationships(Some(
+                r#"[{"column":"customer_id","to":"dbo.customers"},
+                    {"from":"region","references":"regions","to_column":"code"},
+                    {"column":"orphan"}]"#
+            )),
+            vec![
+                Relationship {
+                    column: "customer_id".to_string(),
+                    to: "dbo.customers".to_string(),
+                    field: "customer_id".to_string(),
+                },
+                Relationship {
+                    column: "region".to_string(),
+                    to: "regions".to_string(),
+                    field: "code".to_string(),
+                },
+            ]
+        );
+        assert!(parse_relationships(Some("not json")).is_empty());
+        assert!(parse_relationships(None).is_empty());
+    }
+
+    #[test]
+    fn schema_file_combines_metadata_and_config() {
+        let mut orders = model("dbo", "orders");
+        orders.grain_columns = vec!["order_id".to_string()];
+        orders.columns = vec![
+            ColumnSpec {
+                name: "Order_Id".to_string(),
+                nullable: Some(false),
+            },
+            ColumnSpec {
+                name: "status".to_string(),
+                nullable: Some(true),
+            },
+            ColumnSpec {
+                name: "note".to_string(),
+                nullable: Some(true),
+            },
+        ];
+        orders.check_definitions = vec!["([status]='closed' OR [status]='open')".to_string()];
+        orders.relationships = vec![
+            Relationship {
+                column: "customer_id".to_string(),
+                to: "dbo.customers".to_string(),
+                field: "id".to_string(),
+            },
+            Relationship {
+                column: "region".to_string(),
+                to: "ref.regions".to_string(),
+                field: "region".to_string(),
+            },
+            Relationship {
+                column: "store_id".to_string(),
+                to: "nowhere.stores".to_string(),
+                field: "store_id".to_string(),
+            },
+        ];
+        let mut lines = model("dbo", "order_lines");
+        lines.grain_columns = vec!["order_id".to_string(), "line_no".to_string()];
+        let project = DbtProject {
+            name: "sales".to_string(),
+            fabric_workspace_id: None,
+            fabric_tenant_id: None,
+            sources: vec![SourceSchema {
+                name: "ref".to_string(),
+                database: None,
+                schema: "ref".to_string(),
+                tables: vec!["Regions".to_string()],
+            }],
+            models: vec![orders, model("dbo", "customers"), lines],
+        };
+
+        let schema = render_schema_files(&project);
+        assert_eq!(schema.files.len(), 1);
+        assert_eq!(schema.tests, 8);
+        assert_eq!(
+            schema.skipped_relationships,
+            vec!["orders.store_id -> nowhere.stores"]
+        );
+        let (directory, content) = &schema.files[0];
+        assert_eq!(directory, "dbo");
+        let body = content.split_once("models:\n").unwrap().1;
+        assert_eq!(
+            body,
+            "  - name: orders
+    columns:
+      - name: Order_Id
+        data_tests:
+          - not_null
+          - unique
+      - name: status
+        data_tests:
+          - accepted_values:
+              values: ['closed', 'open']
+      - name: customer_id
+        data_tests:
+          - relationships:
+              to: \"ref('customers')\"
+              field: id
+      - name: region
+        data_tests:
+          - relationships:
+              to: \"source('ref', 'Regions')\"
+              field: region
+  - name: customers
+  - name: order_lines
+    data_tests:
+      - dbt_utils.unique_combination_of_columns:
+          combination_of_columns:
+            - order_id
+            - line_no
+    columns:
+      - name: order_id
+        data_tests:
+          - not_null
+      - name: line_no
+        data_tests:
+          - not_null
+"
+        );
+    }
+}
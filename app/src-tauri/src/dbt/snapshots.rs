@@ -0,0 +1,210 @@
+use std::fmt::Write as FmtWrite;
+
+use super::project::{sql_literal, Materialization, ModelSpec};
+
+/// First line of every generated snapshot; files starting with it are owned by the generator.
+pub const SNAPSHOT_HEADER: &str =
+    "-- Generated from the table config; regenerating the plan rewrites this file.";
+
+/// Column set on the rows loaded by a `full_flagged` initial backfill.
+const BACKFILL_FLAG_COLUMN: &str = "_is_backfill";
+
+pub fn snapshot_name(model_name: &str) -> String {
+    format!("{model_name}_snapshot")
+}
+
+/// dbt snapshot over a model whose load strategy is `snapshot`.
+///
+/// The timestamp strategy is used when the table has a `date_column`, the check strategy
+/// otherwise. `snapshot_strategy` shapes the first run only: `sample_1day` loads the last day,
+/// `full` everything and `full_flagged` everything with the backfilled rows flagged.
+/// Returns why no snapshot can be generated when the table has no grain.
+pub fn render_snapshot(model: &ModelSpec, model_name: &str) -> Result<String, String> {
+    if Materialization::from_load_strategy(model.load_strategy.as_deref())
+        != Materialization::Snapshot
+    {
+        return Err("load strategy is not snapshot".to_string());
+    }
+    let unique_key = match model.grain_columns.as_slice() {
+        [] => return Err("grain_columns are required for a snapshot unique_key".to_string()),
+        [column] => sql_literal(column),
+        columns => format!(
+            "[{}]",
+            columns
+                .iter()
+                .map(|c| sql_literal(c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let backfill = model.snapshot_strategy.as_str();
+    let flagged = backfill == "full_flagged";
+
+    let mut config = vec![
+        "target_schema='snapshots'".to_string(),
+        format!("unique_key={unique_key}"),
+    ];
+    match &model.date_column {
+        Some(date_column) => {
+            config.push("strategy='timestamp'".to_string());
+            config.push(format!("updated_at={}", sql_literal(date_column)));
+        }
+        None if model.columns.is_empty() => {
+            config.push("strategy='check'".to_string());
+            config.push("check_cols='all'".to_string());
+        }
+        // Listing the source columns keeps the backfill flag out of the comparison.
+        None => {
+            config.push("strategy='check'".to_string());
+            config.push(format!(
+                "check_cols=[{}]",
+                model
+                    .columns
+                    .iter()
+                    .map(|c| sql_literal(&c.name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
+    // The flag flips after the first run, so `check_cols='all'` would version every row.
+    let flag_backfill = flagged && (model.date_column.is_some() || !model.columns.is_empty());
+
+    let name = snapshot_name(model_name);
+    let mut out = String::new();
+    writeln!(out, "{SNAPSHOT_HEADER}").ok();
+    writeln!(
+        out,
+        "-- {}.{}: initial backfill {backfill}.",
+        model.schema_name, model.table_name
+    )
+    .ok();
+    writeln!(out, "{{% snapshot {name} %}}").ok();
+    writeln!(out).ok();
+    writeln!(out, "{{{{").ok();
+    writeln!(out, "  config(").ok();
+    for entry in &config {
+        writeln!(out, "    {entry},").ok();
+    }
+    writeln!(out, "  )").ok();
+    writeln!(out, "}}}}").ok();
+    writeln!(out).ok();
+    if flag_backfill {
+        writeln!(out, "select").ok();
+        writeln!(out, "  source_rows.*,").ok();
+        writeln!(
+            out,
+            "  {{% if load_relation(this) is none %}}true{{% else %}}false{{% endif %}} as {BACKFILL_FLAG_COLUMN}"
+        )
+        .ok();
+        writeln!(out, "from {{{{ ref('{model_name}') }}}} as source_rows").ok();
+    } else {
+        writeln!(out, "select *").ok();
+        writeln!(out, "from {{{{ ref('{model_name}') }}}}").ok();
+    }
+    match (backfill, &model.date_column) {
+        ("sample_1day", Some(date_column)) => {
+            writeln!(out, "{{% if load_relation(this) is none %}}").ok();
+            let column = format!("{{{{ adapter.quote({}) }}}}", sql_literal(date_column));
+            writeln!(out, "where {column} >= date_sub(current_date(), 1)").ok();
+            writeln!(out, "{{% endif %}}").ok();
+        }
+        ("sample_1day", None) => {
+            writeln!(
+                out,
+                "-- sample_1day needs a date_column; the initial backfill loads every row."
+            )
+            .ok();
+        }
+        ("full_flagged", _) if !flag_backfill => {
+            writeln!(
+                out,
+                "-- full_flagged needs a date_column or source columns; backfilled rows are not flagged."
+            )
+            .ok();
+        }
+        _ => {}
+    }
+    writeln!(out).ok();
+    writeln!(out, "{{% endsnapshot %}}").ok();
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dbt::schema_tests::ColumnSpec;
+
+    fn snapshot_model(snapshot_strategy: &str) -> ModelSpec {
+        ModelSpec {
+            schema_name: "dbo".to_string(),
+            table_name: "customers".to_string(),
+            load_strategy: Some("snapshot".to_string()),
+            grain_columns: vec!["customer_id".to_string()],
+            incremental_column: None,
+            date_column: None,
+            snapshot_strategy: snapshot_strategy.to_string(),
+            procedure: None,
+            source: None,
+            columns: Vec::new(),
+            check_definitions: Vec::new(),
+            relationships: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn timestamp_strategy_with_one_day_sample() {
+        let mut model = snapshot_model("sample_1day");
+        model.date_column = Some("modified_at".to_string());
+        let sql = render_snapshot(&model, "customers").unwrap();
+        assert!(sql.starts_with(SNAPSHOT_HEADER));
+        assert!(sql.contains("{% snapshot customers_snapshot %}"));
+        assert!(sql.contains("unique_key='customer_id',"));
+        assert!(sql.contains("strategy='timestamp',\n    updated_at='modified_at',"));
+        assert!(sql.contains(
+            "from {{ ref('customers') }}\n{% if load_relation(this) is none %}\nwhere {{ adapter.quote('modified_at') }} >= date_sub(current_date(), 1)\n{% endif %}"
+        ));
+        assert!(sql.trim_end().ends_with("{% endsnapshot %}"));
+    }
+
+    #[test]
+    fn check_strategy_and_flagged_backfill() {
+        let mut model = snapshot_model("full_flagged");
+        model.grain_columns = vec!["customer_id".to_string(), "region".to_string()];
+        model.columns = vec![
+            ColumnSpec {
+                name: "customer_id".to_string(),
+                nullable: Some(false),
+            },
+            ColumnSpec {
+                name: "name".to_string(),
+                nullable: Some(true),
+            },
+        ];
+        let sql = render_snapshot(&model, "customers").unwrap();
+        assert!(sql.contains("unique_key=['customer_id', 'region'],"));
+        assert!(sql.contains("strategy='check',\n    check_cols=['customer_id', 'name'],"));
+        assert!(sql.contains("true{% else %}false{% endif %} as _is_backfill"));
+        assert!(!sql.contains("where"));
+
+        model.columns.clear();
+        let sql = render_snapshot(&model, "customers").unwrap();
+        assert!(sql.contains("check_cols='all',"));
+        assert!(!sql.contains("_is_backfill"));
+        assert!(sql.contains("backfilled rows are not flagged"));
+
+        let sql = render_snapshot(&snapshot_model("full"), "customers").unwrap();
+        assert!(sql.contains("select *\nfrom {{ ref('customers') }}\n\n{% endsnapshot %}"));
+    }
+
+    #[test]
+    fn snapshot_requires_grain_and_snapshot_strategy() {
+        let mut model = snapshot_model("full");
+        model.grain_columns.clear();
+        assert!(render_snapshot(&model, "customers")
+            .unwrap_err()
+            .contains("grain_columns"));
+        model.load_strategy = Some("incremental".to_string());
+        assert!(render_snapshot(&model, "customers").is_err());
+    }
+}
@@ -0,0 +1,615 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use reqwest::{Method, RequestBuilder, Response, StatusCode, Url};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::types::CommandError;
+
+pub const DEFAULT_API_BASE_URL: &str = "https://api.fabric.microsoft.com/v1";
+pub const DEFAULT_AUTHORITY_URL: &str = "https://login.microsoftonline.com";
+pub const FABRIC_API_SCOPE: &str = "https://api.fabric.microsoft.com/.default";
+pub const SQL_ENDPOINT_SCOPE: &str = "https://database.windows.net/.default";
+
+/// Attempts per request when the service throttles (429) or is unavailable (503).
+const MAX_ATTEMPTS: u32 = 5;
+/// Upper bound on a single `Retry-After` wait so a bad header cannot stall a sync.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+/// How long a long-running operation may stay `NotStarted`/`Running` before it is abandoned.
+const MAX_OPERATION_WAIT: Duration = Duration::from_secs(600);
+/// Tokens are refreshed this long before they expire.
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(120);
+
+/// Where the client sends requests. Both URLs can be overridden with `FABRIC_API_BASE_URL`
+/// and `FABRIC_AUTHORITY_URL`, e.g. to point at a local mock server.
+#[derive(Clone, Debug)]
+pub struct FabricEndpoints {
+    pub api_base_url: String,
+    pub authority_url: String,
+}
+
+impl Default for FabricEndpoints {
+    fn default() -> Self {
+        Self {
+            api_base_url: DEFAULT_API_BASE_URL.to_string(),
+            authority_url: DEFAULT_AUTHORITY_URL.to_string(),
+        }
+    }
+}
+
+impl FabricEndpoints {
+    pub fn from_env() -> Self {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().trim_end_matches('/').to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+        Self {
+            api_base_url: var("FABRIC_API_BASE_URL").unwrap_or(defaults.api_base_url),
+            authority_url: var("FABRIC_AUTHORITY_URL").unwrap_or(defaults.authority_url),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ServicePrincipal {
+    pub tenant_id: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl std::fmt::Debug for ServicePrincipal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServicePrincipal")
+            .field("tenant_id", &self.tenant_id)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Fabric workspace and tenant ids taken from a workspace URL such as
+/// `https://app.fabric.microsoft.com/groups/<workspace>/list?ctid=<tenant>`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FabricWorkspaceRef {
+    pub workspace_id: String,
+    pub tenant_id: Option<String>,
+}
+
+pub fn parse_fabric_url(fabric_url: &str) -> Result<FabricWorkspaceRef, CommandError> {
+    let url = Url::parse(fabric_url.trim())
+        .map_err(|e| CommandError::Io(format!("Invalid Fabric URL: {e}")))?;
+    let mut segments = url.path_segments().into_iter().flatten();
+    let workspace_id = segments
+        .by_ref()
+        .find(|s| s.eq_ignore_ascii_case("groups") || s.eq_ignore_ascii_case("workspaces"))
+        .and_then(|_| segments.next())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| {
+            CommandError::Io("Fabric URL does not contain a workspace (/groups/<id>)".to_string())
+        })?
+        .to_string();
+    let tenant_id = url
+        .query_pairs()
+        .find(|(key, _)| key == "ctid")
+        .map(|(_, value)| value.into_owned())
+        .filter(|v| !v.is_empty());
+    Ok(FabricWorkspaceRef {
+        workspace_id,
+        tenant_id,
+    })
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FabricItem {
+    pub id: String,
+    pub display_name: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub description: Option<String>,
+    pub folder_id: Option<String>,
+}
+
+/// SQL connection details of a warehouse (`GET /workspaces/{id}/warehouses/{id}`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WarehouseProperties {
+    pub connection_string: Option<String>,
+    pub collation_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Page<T> {
+    value: Vec<T>,
+    continuation_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+/// Minimal Fabric REST client authenticated as a service principal.
+pub struct FabricClient {
+    http: reqwest::Client,
+    endpoints: FabricEndpoints,
+    principal: ServicePrincipal,
+    tokens: HashMap<String, (String, Instant)>,
+    operation_timeout: Duration,
+}
+
+impl FabricClient {
+    pub fn new(endpoints: FabricEndpoints, principal: ServicePrincipal) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoints,
+            principal,
+            tokens: HashMap::new(),
+            operation_timeout: MAX_OPERATION_WAIT,
+        }
+    }
+
+    /// Client-credentials token for `scope`, cached until shortly before it expires.
+    pub async fn access_token(&mut self, scope: &str) -> Result<String, CommandError> {
+        if let Some((token, expires_at)) = self.tokens.get(scope) {
+            if Instant::now() < *expires_at {
+                return Ok(token.clone());
+            }
+        }
+        let url = format!(
+            "{}/{}/oauth2/v2.0/token",
+            self.endpoints.authority_url, self.principal.tenant_id
+        );
+        let form = [
+            ("grant_type", "client_credentials"),
+            ("client_id", self.principal.client_id.as_str()),
+            ("client_secret", self.principal.client_secret.as_str()),
+            ("scope", scope),
+        ];
+        let response = self
+            .send_with_retry(|| self.http.post(&url).form(&form))
+            .await?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| CommandError::Io(format!("Failed to parse token response: {e}")))?;
+        if !status.is_success() {
+            let message = body["error_description"]
+                .as_str()
+                .or_else(|| body["error"].as_str())
+                .unwrap_or("Unknown error");
+            return Err(CommandError::Io(format!(
+                "Service principal sign-in failed ({status}): {message}"
+            )));
+        }
+        let token: TokenResponse = serde_json::from_value(body)
+            .map_err(|e| CommandError::Io(format!("Failed to parse token response: {e}")))?;
+        let lifetime = Duration::from_secs(token.expires_in.unwrap_or(3600));
+        let expires_at = Instant::now() + lifetime.saturating_sub(TOKEN_EXPIRY_MARGIN);
+        self.tokens
+            .insert(scope.to_string(), (token.access_token.clone(), expires_at));
+        Ok(token.access_token)
+    }
+
+    /// All items in a workspace, following `continuationToken` pages.
+    pub async fn list_items(
+        &mut self,
+        workspace_id: &str,
+    ) -> Result<Vec<FabricItem>, CommandError> {
+        let path = format!("/workspaces/{workspace_id}/items");
+        let mut items = Vec::new();
+        let mut continuation: Option<String> = None;
+        loop {
+            let mut query = Vec::new();
+            if let Some(token) = &continuation {
+                query.push(("continuationToken", token.clone()));
+            }
+            let page: Page<FabricItem> =
+                serde_json::from_value(self.api_json(Method::GET, &path, &query).await?)
+                    .map_err(|e| CommandError::Io(format!("Unexpected item list: {e}")))?;
+            items.extend(page.value);
+            match page.continuation_token.filter(|t| !t.is_empty()) {
+                Some(token) => continuation = Some(token),
+                None => break,
+            }
+        }
+        Ok(items)
+    }
+
+    pub async fn get_warehouse(
+        &mut self,
+        workspace_id: &str,
+        warehouse_id: &str,
+    ) -> Result<WarehouseProperties, CommandError> {
+        let path = format!("/workspaces/{workspace_id}/warehouses/{warehouse_id}");
+        let body = self.api_json(Method::GET, &path, &[]).await?;
+        let properties = &body["properties"];
+        Ok(WarehouseProperties {
+            connection_string: properties["connectionString"].as_str().map(str::to_string),
+            collation_type: properties["collationType"].as_str().map(str::to_string),
+        })
+    }
+
+    /// Decoded definition parts of an item keyed by part path (e.g. `pipeline-content.json`).
+    /// Handles the long-running-operation form of `getDefinition`.
+    pub async fn get_item_definition(
+        &mut self,
+        workspace_id: &str,
+        item_id: &str,
+    ) -> Result<HashMap<String, String>, CommandError> {
+        let path = format!("/workspaces/{workspace_id}/items/{item_id}/getDefinition");
+        let token = self.access_token(FABRIC_API_SCOPE).await?;
+        let url = self.api_url(&path);
+        let response = self
+            .send_with_retry(|| self.http.post(&url).bearer_auth(&token).json(&Value::Null))
+            .await?;
+        let body = if response.status() == StatusCode::ACCEPTED {
+            let operation_id = header(&response, "x-ms-operation-id").ok_or_else(|| {
+                CommandError::Io(format!(
+                    "getDefinition for {item_id} returned no operation id"
+                ))
+            })?;
+            let delay = retry_after(&response).unwrap_or(Duration::from_secs(1));
+            self.wait_for_operation(&operation_id, delay).await?
+        } else {
+            read_json(response).await?
+        };
+        decode_definition_parts(&body)
+    }
+
+    async fn wait_for_operation(
+        &mut self,
+        operation_id: &str,
+        mut delay: Duration,
+    ) -> Result<Value, CommandError> {
+        let path = format!("/operations/{operation_id}");
+        let started = Instant::now();
+        loop {
+            tokio::time::sleep(delay.min(MAX_RETRY_DELAY)).await;
+            let state = self.api_json(Method::GET, &path, &[]).await?;
+            match state["status"].as_str().unwrap_or_default() {
+                "Succeeded" => {
+                    return self
+                        .api_json(Method::GET, &format!("{path}/result"), &[])
+                        .await
+                }
+                "Failed" | "Undefined" => {
+                    let message = state["error"]["message"]
+                        .as_str()
+                        .unwrap_or("unknown error");
+                    return Err(CommandError::Io(format!(
+                        "Fabric operation {operation_id} failed: {message}"
+                    )));
+                }
+                status => {
+                    if started.elapsed() >= self.operation_timeout {
+                        return Err(CommandError::Io(format!(
+                            "Fabric operation {operation_id} did not finish within {}s (last status: {})",
+                            self.operation_timeout.as_secs(),
+                            if status.is_empty() { "unknown" } else { status }
+                        )));
+                    }
+                    delay = Duration::from_secs(1);
+                }
+            }
+        }
+    }
+
+    async fn api_json(
+        &mut self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Value, CommandError> {
+        let token = self.access_token(FABRIC_API_SCOPE).await?;
+        let url = self.api_url(path);
+        let response = self
+            .send_with_retry(|| {
+                self.http
+                    .request(method.clone(), &url)
+                    .bearer_auth(&token)
+                    .query(query)
+            })
+            .await?;
+        read_json(response).await
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}{}", self.endpoints.api_base_url, path)
+    }
+
+    /// Send a request, retrying on 429 and 503 after the server's `Retry-After`
+    /// (or an exponential backoff when the header is missing).
+    async fn send_with_retry(
+        &self,
+        build: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, CommandError> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let response = build()
+                .send()
+                .await
+                .map_err(|e| CommandError::Io(format!("Fabric request failed: {e}")))?;
+            let status = response.status();
+            if !(status == StatusCode::TOO_MANY_REQUESTS
+                || status == StatusCode::SERVICE_UNAVAILABLE)
+                || attempt >= MAX_ATTEMPTS
+            {
+                return Ok(response);
+            }
+            let delay = retry_after(&response)
+                .unwrap_or_else(|| Duration::from_secs(1 << attempt))
+                .min(MAX_RETRY_DELAY);
+            log::warn!(
+                "fabric: {} returned {status}; retrying in {}s (attempt {attempt}/{MAX_ATTEMPTS})",
+                response.url().path(),
+                delay.as_secs()
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+fn header(response: &Response, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    header(response, "retry-after")?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+async fn read_json(response: Response) -> Result<Value, CommandError> {
+    let status = response.status();
+    let path = response.url().path().to_string();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| CommandError::Io(format!("Failed to read Fabric response: {e}")))?;
+    if !status.is_success() {
+        let message = serde_json::from_str::<Value>(&text)
+            .ok()
+            .and_then(|body| body["message"].as_str().map(str::to_string))
+            .unwrap_or(text);
+        return Err(CommandError::Io(format!(
+            "Fabric request {path} failed ({status}): {message}"
+        )));
+    }
+    if text.trim().is_empty() {
+        return Ok(Value::Null);
+    }
+    serde_json::from_str(&text)
+        .map_err(|e| CommandError::Io(format!("Invalid JSON from Fabric {path}: {e}")))
+}
+
+fn decode_definition_parts(body: &Value) -> Result<HashMap<String, String>, CommandError> {
+    let parts = body["definition"]["parts"]
+        .as_array()
+        .ok_or_else(|| CommandError::Io("Item definition has no parts".to_string()))?;
+    let mut decoded = HashMap::new();
+    for part in parts {
+        let (Some(path), Some(payload)) = (part["path"].as_str(), part["payload"].as_str()) else {
+            continue;
+        };
+        if part["payloadType"].as_str().unwrap_or("InlineBase64") != "InlineBase64" {
+            continue;
+        }
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(payload)
+            .map_err(|e| CommandError::Io(format!("Invalid payload for {path}: {e}")))?;
+        let text = String::from_utf8(bytes)
+            .map_err(|e| CommandError::Io(format!("Payload for {path} is not UTF-8: {e}")))?;
+        decoded.insert(path.to_string(), text);
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn client(server: &mockito::ServerGuard) -> FabricClient {
+        FabricClient::new(
+            FabricEndpoints {
+                api_base_url: format!("{}/v1", server.url()),
+                authority_url: server.url(),
+            },
+            ServicePrincipal {
+                tenant_id: "tenant".to_string(),
+                client_id: "client".to_string(),
+                client_secret: "secret".to_string(),
+            },
+        )
+    }
+
+    async fn mock_token(server: &mut mockito::ServerGuard) -> mockito::Mock {
+        server
+            .mock("POST", "/tenant/oauth2/v2.0/token")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("grant_type".into(), "client_credentials".into()),
+                Matcher::UrlEncoded("client_id".into(), "client".into()),
+                Matcher::UrlEncoded("scope".into(), FABRIC_API_SCOPE.into()),
+            ]))
+            .with_body(r#"{"access_token":"tok","expires_in":3600}"#)
+            .expect(1)
+            .create_async()
+            .await
+    }
+
+    #[test]
+    fn parse_fabric_url_reads_workspace_and_tenant() {
+        assert_eq!(
+            parse_fabric_url(
+                "https://app.fabric.microsoft.com/groups/ws-1/list?experience=fabric-developer&ctid=t-1"
+            )
+            .unwrap(),
+            FabricWorkspaceRef {
+                workspace_id: "ws-1".to_string(),
+                tenant_id: Some("t-1".to_string()),
+            }
+        );
+        assert_eq!(
+            parse_fabric_url("https://api.fabric.microsoft.com/v1/workspaces/ws-2")
+                .unwrap()
+                .tenant_id,
+            None
+        );
+        assert!(parse_fabric_url("https://app.fabric.microsoft.com/home").is_err());
+        assert!(parse_fabric_url("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn list_items_follows_pages_and_retries_throttling() {
+        let mut server = mockito::Server::new_async().await;
+        let token = mock_token(&mut server).await;
+        let throttled = server
+            .mock("GET", "/v1/workspaces/ws/items")
+            .match_query(Matcher::Missing)
+            .with_status(429)
+            .with_header("Retry-After", "0")
+            .expect(1)
+            .create_async()
+            .await;
+        let first = server
+            .mock("GET", "/v1/workspaces/ws/items")
+            .match_query(Matcher::Missing)
+            .match_header("authorization", "Bearer tok")
+            .with_body(
+                r#"{"value":[{"id":"wh","displayName":"WH","type":"Warehouse"}],
+                    "continuationToken":"page2","continuationUri":"https://elsewhere/ignored"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/v1/workspaces/ws/items")
+            .match_query(Matcher::UrlEncoded(
+                "continuationToken".into(),
+                "page2".into(),
+            ))
+            .with_body(
+                r#"{"value":[{"id":"pl","displayName":"Nightly","type":"DataPipeline","folderId":"f"}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut client = client(&server);
+        let items = client.list_items("ws").await.unwrap();
+        let ids: Vec<&str> = items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec!["wh", "pl"]);
+        assert_eq!(items[1].item_type, "DataPipeline");
+        assert_eq!(items[1].folder_id.as_deref(), Some("f"));
+
+        token.assert_async().await;
+        throttled.assert_async().await;
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn get_item_definition_polls_long_running_operation() {
+        let mut server = mockito::Server::new_async().await;
+        let _token = mock_token(&mut server).await;
+        let payload = base64::engine::general_purpose::STANDARD.encode(r#"{"activities":[]}"#);
+        let _start = server
+            .mock("POST", "/v1/workspaces/ws/items/pl/getDefinition")
+            .with_status(202)
+            .with_header("x-ms-operation-id", "op-1")
+            .with_header("Retry-After", "0")
+            .create_async()
+            .await;
+        let _running = server
+            .mock("GET", "/v1/operations/op-1")
+            .with_body(r#"{"status":"Running"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let _done = server
+            .mock("GET", "/v1/operations/op-1")
+            .with_body(r#"{"status":"Succeeded"}"#)
+            .create_async()
+            .await;
+        let _result = server
+            .mock("GET", "/v1/operations/op-1/result")
+            .with_body(format!(
+                r#"{{"definition":{{"parts":[{{"path":"pipeline-content.json","payload":"{payload}","payloadType":"InlineBase64"}}]}}}}"#
+            ))
+            .create_async()
+            .await;
+
+        let parts = client(&server)
+            .get_item_definition("ws", "pl")
+            .await
+            .unwrap();
+        assert_eq!(parts["pipeline-content.json"], r#"{"activities":[]}"#);
+    }
+
+    #[tokio::test]
+    async fn wait_for_operation_gives_up_on_stuck_operations() {
+        let mut server = mockito::Server::new_async().await;
+        let _token = mock_token(&mut server).await;
+        let _start = server
+            .mock("POST", "/v1/workspaces/ws/items/pl/getDefinition")
+            .with_status(202)
+            .with_header("x-ms-operation-id", "op-1")
+            .with_header("Retry-After", "0")
+            .create_async()
+            .await;
+        let running = server
+            .mock("GET", "/v1/operations/op-1")
+            .with_body(r#"{"status":"Running"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut client = client(&server);
+        client.operation_timeout = Duration::ZERO;
+        let err = client.get_item_definition("ws", "pl").await.unwrap_err();
+        assert!(matches!(err, CommandError::Io(_)));
+        assert!(err.to_string().contains("did not finish"), "{err}");
+        running.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn errors_surface_service_messages() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/tenant/oauth2/v2.0/token")
+            .with_status(401)
+            .with_body(r#"{"error":"invalid_client","error_description":"bad secret"}"#)
+            .create_async()
+            .await;
+        let err = client(&server).list_items("ws").await.unwrap_err();
+        assert!(err.to_string().contains("bad secret"), "{err}");
+
+        let mut server = mockito::Server::new_async().await;
+        let _token = mock_token(&mut server).await;
+        server
+            .mock("GET", "/v1/workspaces/ws/warehouses/wh")
+            .with_status(404)
+            .with_body(
+                r#"{"errorCode":"ItemNotFound","message":"The requested item was not found"}"#,
+            )
+            .create_async()
+            .await;
+        let err = client(&server).get_warehouse("ws", "wh").await.unwrap_err();
+        assert!(err.to_string().contains("ItemNotFound") || err.to_string().contains("not found"));
+    }
+}
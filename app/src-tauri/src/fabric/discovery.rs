@@ -0,0 +1,190 @@
+use super::client::{FabricClient, FabricItem, WarehouseProperties};
+use crate::types::CommandError;
+
+/// Item types the local model stores (see the `items.item_type` check constraint).
+pub const SUPPORTED_ITEM_TYPES: [&str; 3] = ["Warehouse", "DataPipeline", "Notebook"];
+
+const PIPELINE_CONTENT_PART: &str = "pipeline-content.json";
+/// Fabric returns `notebook-content.py` by default and `.ipynb` when asked for that format.
+const NOTEBOOK_CONTENT_PARTS: [&str; 2] = ["notebook-content.py", "notebook-content.ipynb"];
+
+/// Everything read from the Fabric REST API for one workspace.
+#[derive(Default)]
+pub struct FabricSnapshot {
+    pub items: Vec<FabricItem>,
+    pub warehouses: Vec<(String, WarehouseProperties)>,
+    /// `(pipeline item id, pipeline-content.json)`.
+    pub pipeline_definitions: Vec<(String, String)>,
+    /// `(notebook item id, notebook content)`.
+    pub notebook_definitions: Vec<(String, String)>,
+    /// Per-item failures; the rest of the workspace is still returned.
+    pub warnings: Vec<String>,
+}
+
+/// List the workspace's supported items, then read warehouse properties and pipeline and
+/// notebook definitions. Failing to list items is an error; failures on single items become warnings.
+pub async fn fetch_workspace_snapshot(
+    client: &mut FabricClient,
+    fabric_workspace_id: &str,
+) -> Result<FabricSnapshot, CommandError> {
+    let mut snapshot = FabricSnapshot::default();
+    let items = client.list_items(fabric_workspace_id).await?;
+    let skipped = items.len();
+    snapshot.items = items
+        .into_iter()
+        .filter(|item| SUPPORTED_ITEM_TYPES.contains(&item.item_type.as_str()))
+        .collect();
+    log::info!(
+        "fabric: workspace={} items={} unsupported={}",
+        fabric_workspace_id,
+        snapshot.items.len(),
+        skipped - snapshot.items.len()
+    );
+
+    for item in &snapshot.items {
+        match item.item_type.as_str() {
+            "Warehouse" => match client.get_warehouse(fabric_workspace_id, &item.id).await {
+                Ok(properties) => snapshot.warehouses.push((item.id.clone(), properties)),
+                Err(e) => snapshot
+                    .warnings
+                    .push(format!("warehouse {}: {e}", item.display_name)),
+            },
+            "DataPipeline" => {
+                match client
+                    .get_item_definition(fabric_workspace_id, &item.id)
+                    .await
+                {
+                    Ok(mut parts) => match parts.remove(PIPELINE_CONTENT_PART) {
+                        Some(content) => snapshot
+                            .pipeline_definitions
+                            .push((item.id.clone(), content)),
+                        None => snapshot.warnings.push(format!(
+                            "pipeline {}: definition has no {PIPELINE_CONTENT_PART}",
+                            item.display_name
+                        )),
+                    },
+                    Err(e) => snapshot
+                        .warnings
+                        .push(format!("pipeline {}: {e}", item.display_name)),
+                }
+            }
+            "Notebook" => {
+                match client
+                    .get_item_definition(fabric_workspace_id, &item.id)
+                    .await
+                {
+                    Ok(mut parts) => {
+                        match NOTEBOOK_CONTENT_PARTS.iter().find_map(|p| parts.remove(*p)) {
+                            Some(content) => snapshot
+                                .notebook_definitions
+                                .push((item.id.clone(), content)),
+                            None => snapshot.warnings.push(format!(
+                                "notebook {}: definition has no notebook-content part",
+                                item.display_name
+                            )),
+                        }
+                    }
+                    Err(e) => snapshot
+                        .warnings
+                        .push(format!("notebook {}: {e}", item.display_name)),
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine;
+
+    use super::*;
+    use crate::fabric::client::{FabricEndpoints, ServicePrincipal};
+
+    #[tokio::test]
+    async fn snapshot_reads_supported_items_and_collects_item_failures() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/tenant/oauth2/v2.0/token")
+            .with_body(r#"{"access_token":"tok","expires_in":3600}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/v1/workspaces/ws/items")
+            .with_body(
+                r#"{"value":[
+                    {"id":"wh","displayName":"WH","type":"Warehouse"},
+                    {"id":"pl","displayName":"Nightly","type":"DataPipeline"},
+                    {"id":"broken","displayName":"Broken","type":"DataPipeline"},
+                    {"id":"nb","displayName":"Enrich","type":"Notebook"},
+                    {"id":"lh","displayName":"Lake","type":"Lakehouse"}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/v1/workspaces/ws/warehouses/wh")
+            .with_body(
+                r#"{"properties":{"connectionString":"x.datawarehouse.fabric.microsoft.com",
+                    "collationType":"Latin1_General_100_BIN2_UTF8"}}"#,
+            )
+            .create_async()
+            .await;
+        let payload = base64::engine::general_purpose::STANDARD.encode(r#"{"activities":[]}"#);
+        server
+            .mock("POST", "/v1/workspaces/ws/items/pl/getDefinition")
+            .with_body(format!(
+                r#"{{"definition":{{"parts":[
+                    {{"path":"pipeline-content.json","payload":"{payload}","payloadType":"InlineBase64"}},
+                    {{"path":".platform","payload":"e30=","payloadType":"InlineBase64"}}]}}}}"#
+            ))
+            .create_async()
+            .await;
+        let notebook = base64::engine::general_purpose::STANDARD.encode("# Fabric notebook source");
+        server
+            .mock("POST", "/v1/workspaces/ws/items/nb/getDefinition")
+            .with_body(format!(
+                r#"{{"definition":{{"parts":[
+                    {{"path":"notebook-content.py","payload":"{notebook}","payloadType":"InlineBase64"}}]}}}}"#
+            ))
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/v1/workspaces/ws/items/broken/getDefinition")
+            .with_status(403)
+            .with_body(r#"{"errorCode":"Forbidden","message":"no access"}"#)
+            .create_async()
+            .await;
+
+        let mut client = FabricClient::new(
+            FabricEndpoints {
+                api_base_url: format!("{}/v1", server.url()),
+                authority_url: server.url(),
+            },
+            ServicePrincipal {
+                tenant_id: "tenant".to_string(),
+                client_id: "client".to_string(),
+                client_secret: "secret".to_string(),
+            },
+        );
+        let snapshot = fetch_workspace_snapshot(&mut client, "ws").await.unwrap();
+        assert_eq!(snapshot.items.len(), 4);
+        assert_eq!(snapshot.warehouses.len(), 1);
+        assert_eq!(
+            snapshot.warehouses[0].1.collation_type.as_deref(),
+            Some("Latin1_General_100_BIN2_UTF8")
+        );
+        assert_eq!(
+            snapshot.pipeline_definitions,
+            vec![("pl".to_string(), r#"{"activities":[]}"#.to_string())]
+        );
+        assert_eq!(
+            snapshot.notebook_definitions,
+            vec![("nb".to_string(), "# Fabric notebook source".to_string())]
+        );
+        assert_eq!(snapshot.warnings.len(), 1);
+        assert!(snapshot.warnings[0].starts_with("pipeline Broken:"));
+        assert!(snapshot.warnings[0].contains("no access"));
+    }
+}
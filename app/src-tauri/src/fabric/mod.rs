@@ -0,0 +1,5 @@
+pub mod client;
+pub mod discovery;
+pub mod notebook;
+pub mod pipeline_definition;
+pub mod warehouse;
@@ -0,0 +1,844 @@
+use std::collections::{BTreeSet, HashSet};
+
+use serde_json::Value;
+
+use crate::tsql::lexer::{next_significant, render, tokenize, Token};
+use crate::tsql::references::{extract_table_references, AccessKind, TableReference};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellLanguage {
+    Sql,
+    Python,
+    Scala,
+    R,
+    Other,
+}
+
+impl CellLanguage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CellLanguage::Sql => "sparksql",
+            CellLanguage::Python => "pyspark",
+            CellLanguage::Scala => "scala",
+            CellLanguage::R => "sparkr",
+            CellLanguage::Other => "other",
+        }
+    }
+
+    fn from_name(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "sparksql" | "sql" => CellLanguage::Sql,
+            "python" | "pyspark" | "synapse_pyspark" => CellLanguage::Python,
+            "scala" | "spark" => CellLanguage::Scala,
+            "r" | "sparkr" => CellLanguage::R,
+            _ => CellLanguage::Other,
+        }
+    }
+}
+
+/// A code cell. `index` is the 1-based position among all cells, markdown included, so it
+/// matches what the notebook editor shows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotebookCell {
+    pub index: usize,
+    pub language: CellLanguage,
+    pub source: String,
+}
+
+/// A table read or written by a notebook cell.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NotebookTableAccess {
+    pub cell_index: usize,
+    pub reference: TableReference,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct NotebookScan {
+    pub accesses: Vec<NotebookTableAccess>,
+    /// Cells with a `spark.sql(...)` or table call whose argument is not a string literal.
+    pub dynamic_cells: Vec<usize>,
+}
+
+/// Storage formats that follow `USING` in Spark `CREATE TABLE` and are not tables.
+const DATA_SOURCE_FORMATS: [&str; 9] = [
+    "delta", "parquet", "csv", "json", "orc", "avro", "text", "jdbc", "iceberg",
+];
+
+/// Parse a Fabric notebook definition: Jupyter `.ipynb` JSON or the `notebook-content.py`
+/// source format with `# CELL` / `# METADATA` markers. Markdown cells are dropped.
+pub fn parse_notebook(content: &str) -> Result<Vec<NotebookCell>, String> {
+    if content.trim_start().starts_with('{') {
+        parse_ipynb(content)
+    } else {
+        Ok(parse_fabric_source(content))
+    }
+}
+
+fn parse_ipynb(content: &str) -> Result<Vec<NotebookCell>, String> {
+    let root: Value = serde_json::from_str(content).map_err(|e| format!("invalid JSON: {e}"))?;
+    let cells = root["cells"]
+        .as_array()
+        .ok_or_else(|| "notebook has no cells array".to_string())?;
+    let metadata = &root["metadata"];
+    let default_language = metadata["language_info"]["name"]
+        .as_str()
+        .or_else(|| metadata["kernel_info"]["name"].as_str())
+        .or_else(|| metadata["kernelspec"]["language"].as_str())
+        .map(CellLanguage::from_name)
+        .unwrap_or(CellLanguage::Python);
+
+    let mut parsed = Vec::new();
+    for (position, cell) in cells.iter().enumerate() {
+        if cell["cell_type"].as_str() != Some("code") {
+            continue;
+        }
+        let source = match &cell["source"] {
+            Value::String(text) => text.clone(),
+            Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+            _ => String::new(),
+        };
+        let language = cell["metadata"]["microsoft"]["language"]
+            .as_str()
+            .map(CellLanguage::from_name)
+            .unwrap_or(default_language);
+        parsed.push(code_cell(position + 1, language, &source));
+    }
+    Ok(parsed)
+}
+
+type SourceCell = (usize, Option<CellLanguage>, String);
+
+fn parse_fabric_source(content: &str) -> Vec<NotebookCell> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Block {
+        None,
+        Code,
+        Markdown,
+        Metadata,
+    }
+    let mut cells: Vec<SourceCell> = Vec::new();
+    let mut position = 0;
+    let mut block = Block::None;
+    // The block a METADATA section describes.
+    let mut described = Block::None;
+    let mut metadata = String::new();
+    let mut default_language = CellLanguage::Python;
+
+    for line in content.lines() {
+        let marker = line
+            .strip_prefix("# ")
+            .and_then(|rest| rest.split_once(' '))
+            .filter(|(_, stars)| !stars.is_empty() && stars.trim().chars().all(|c| c == '*'))
+            .map(|(name, _)| name);
+        if let Some(name) = marker {
+            if block == Block::Metadata {
+                apply_metadata(
+                    &metadata,
+                    described == Block::Code,
+                    &mut cells,
+                    &mut default_language,
+                );
+                metadata.clear();
+            } else {
+                described = block;
+            }
+            block = match name {
+                "CELL" | "PARAMETERS" => {
+                    position += 1;
+                    cells.push((position, None, String::new()));
+                    Block::Code
+                }
+                "MARKDOWN" => {
+                    position += 1;
+                    Block::Markdown
+                }
+                "METADATA" => Block::Metadata,
+                _ => Block::None,
+            };
+            continue;
+        }
+        match block {
+            Block::Code => {
+                let (_, _, source) = cells.last_mut().expect("code block has a cell");
+                let line = match line.strip_prefix("# MAGIC") {
+                    Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+                    None => line,
+                };
+                source.push_str(line);
+                source.push('\n');
+            }
+            Block::Metadata => {
+                if let Some(json) = line.strip_prefix("# META") {
+                    metadata.push_str(json);
+                    metadata.push('\n');
+                }
+            }
+            Block::Markdown | Block::None => {}
+        }
+    }
+    if block == Block::Metadata {
+        apply_metadata(
+            &metadata,
+            described == Block::Code,
+            &mut cells,
+            &mut default_language,
+        );
+    }
+
+    cells
+        .into_iter()
+        .map(|(position, language, source)| {
+            code_cell(
+                position,
+                language.unwrap_or(default_language),
+                source.trim_matches('\n'),
+            )
+        })
+        .collect()
+}
+
+/// Notebook-level metadata sets the kernel language; cell metadata sets that cell's language.
+fn apply_metadata(
+    metadata: &str,
+    describes_code: bool,
+    cells: &mut [SourceCell],
+    default_language: &mut CellLanguage,
+) {
+    let Ok(value) = serde_json::from_str::<Value>(metadata) else {
+        return;
+    };
+    if describes_code {
+        if let (Some(language), Some((_, slot, _))) = (value["language"].as_str(), cells.last_mut())
+        {
+            *slot = Some(CellLanguage::from_name(language));
+        }
+    } else if let Some(kernel) = value["kernel_info"]["name"].as_str() {
+        *default_language = CellLanguage::from_name(kernel);
+    }
+}
+
+/// Apply a leading cell magic (`%%sql`, `%%pyspark`, ...) and strip it from the source.
+fn code_cell(index: usize, language: CellLanguage, source: &str) -> NotebookCell {
+    let trimmed = source.trim_start();
+    let (first, rest) = trimmed.split_once('\n').unwrap_or((trimmed, ""));
+    let magic = first
+        .trim()
+        .strip_prefix("%%")
+        .map(|m| m.split_whitespace().next().unwrap_or_default());
+    let (language, source) = match magic {
+        Some(magic) => (CellLanguage::from_name(magic), rest.to_string()),
+        None => (language, source.to_string()),
+    };
+    NotebookCell {
+        index,
+        language,
+        source,
+    }
+}
+
+/// Tables read and written by the notebook's Spark SQL and PySpark cells.
+///
+/// SQL comes from `%%sql` cells and literal `spark.sql(...)` arguments. DataFrame calls are
+/// matched by name: `spark.table` / `read.table` / `load("Tables/...")` read,
+/// `saveAsTable` / `insertInto` / `toTable` / `save("Tables/...")` / `DeltaTable.forName`
+/// write. Temporary views are not tables and are dropped.
+pub fn notebook_table_accesses(cells: &[NotebookCell]) -> NotebookScan {
+    let mut temp_views = HashSet::new();
+    let mut found: BTreeSet<NotebookTableAccess> = BTreeSet::new();
+    let mut dynamic = BTreeSet::new();
+    let mut add = |cell: usize, references: Vec<TableReference>| {
+        for reference in references {
+            found.insert(NotebookTableAccess {
+                cell_index: cell,
+                reference,
+            });
+        }
+    };
+
+    for cell in cells {
+        match cell.language {
+            CellLanguage::Sql => {
+                add(
+                    cell.index,
+                    spark_sql_references(&cell.source, &mut temp_views),
+                );
+            }
+            CellLanguage::Python => {
+                for call in python_table_calls(&cell.source) {
+                    match call {
+                        PythonCall::Sql(Some(sql)) => {
+                            add(cell.index, spark_sql_references(&sql, &mut temp_views));
+                        }
+                        PythonCall::Table(access, Some(name)) => {
+                            add(
+                                cell.index,
+                                name_reference(&name, access).into_iter().collect(),
+                            );
+                        }
+                        PythonCall::Path(access, path) => {
+                            add(
+                                cell.index,
+                                path_reference(&path, access).into_iter().collect(),
+                            );
+                        }
+                        PythonCall::TempView(name) => {
+                            temp_views.insert(name.to_lowercase());
+                        }
+                        PythonCall::Sql(None) | PythonCall::Table(_, None) => {
+                            dynamic.insert(cell.index);
+                        }
+                    }
+                }
+            }
+            CellLanguage::Scala | CellLanguage::R | CellLanguage::Other => {}
+        }
+    }
+
+    NotebookScan {
+        accesses: found
+            .into_iter()
+            .filter(|a| {
+                a.reference.schema.is_some()
+                    || !temp_views.contains(&a.reference.name.to_lowercase())
+            })
+            .collect(),
+        dynamic_cells: dynamic.into_iter().collect(),
+    }
+}
+
+/// Table references in Spark SQL. Spark-only syntax is rewritten into the T-SQL forms the
+/// reference scanner understands; `CREATE TABLE` / `CREATE VIEW` targets are writes.
+fn spark_sql_references(sql: &str, temp_views: &mut HashSet<String>) -> Vec<TableReference> {
+    let tokens = tokenize(&backticks_to_brackets(sql));
+    let mut kept: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut created = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let prev = kept.iter().rposition(|t| !t.is_trivia()).map(|p| &kept[p]);
+        // INSERT OVERWRITE [TABLE] t / INSERT INTO TABLE t
+        if (token.is_word("OVERWRITE") && prev.is_some_and(|p| p.is_word("INSERT")))
+            || (token.is_word("TABLE")
+                && prev.is_some_and(|p| p.is_word("INTO") || p.is_word("INSERT")))
+        {
+            if token.is_word("OVERWRITE") {
+                kept.push(Token {
+                    text: "INTO".to_string(),
+                    ..token.clone()
+                });
+            }
+            i += 1;
+            continue;
+        }
+        if token.is_word("CREATE") {
+            if let Some((name, temporary, next)) = created_object(&tokens, i) {
+                if temporary {
+                    temp_views.insert(name.name.to_lowercase());
+                } else {
+                    created.push(name);
+                }
+                i = next;
+                continue;
+            }
+        }
+        kept.push(token.clone());
+        i += 1;
+    }
+
+    let mut references: Vec<TableReference> = extract_table_references(&render(&kept))
+        .references
+        .into_iter()
+        .filter(|r| {
+            r.schema.is_some() || !DATA_SOURCE_FORMATS.contains(&r.name.to_lowercase().as_str())
+        })
+        .collect();
+    references.extend(created);
+    references
+}
+
+/// `CREATE [OR REPLACE] [GLOBAL] [TEMP|TEMPORARY] TABLE|VIEW [IF NOT EXISTS] name` at
+/// `start`: the created name, whether it is temporary, and the index after the name.
+fn created_object(tokens: &[Token], start: usize) -> Option<(TableReference, bool, usize)> {
+    let mut temporary = false;
+    let mut i = next_significant(tokens, start + 1)?;
+    loop {
+        let token = &tokens[i];
+        if token.is_word("OR") || token.is_word("REPLACE") || token.is_word("GLOBAL") {
+        } else if token.is_word("TEMP") || token.is_word("TEMPORARY") {
+            temporary = true;
+        } else if token.is_word("TABLE") || token.is_word("VIEW") {
+            break;
+        } else {
+            return None;
+        }
+        i = next_significant(tokens, i + 1)?;
+    }
+    i = next_significant(tokens, i + 1)?;
+    if tokens[i].is_word("IF") {
+        for keyword in ["NOT", "EXISTS"] {
+            i = next_significant(tokens, i + 1).filter(|&n| tokens[n].is_word(keyword))?;
+        }
+        i = next_significant(tokens, i + 1)?;
+    }
+    let mut parts = Vec::new();
+    loop {
+        parts.push(tokens.get(i)?.identifier()?);
+        match tokens.get(i + 1) {
+            Some(dot) if dot.is_symbol(".") => i += 2,
+            _ => break,
+        }
+    }
+    let name = parts.pop()?;
+    Some((
+        TableReference {
+            schema: parts.pop(),
+            name,
+            access: AccessKind::Write,
+        },
+        temporary,
+        i + 1,
+    ))
+}
+
+/// Rewrite Spark's `` `name` `` quoting as `[name]`, leaving string literals untouched.
+fn backticks_to_brackets(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut open = false;
+    let mut quote: Option<char> = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) if c == q => {
+                quote = None;
+                out.push(c);
+            }
+            (Some(_), _) => out.push(c),
+            (None, '\'') if !open => {
+                quote = Some(c);
+                out.push(c);
+            }
+            (None, '`') if open && chars.peek() == Some(&'`') => {
+                chars.next();
+                out.push('`');
+            }
+            (None, '`') => {
+                out.push(if open { ']' } else { '[' });
+                open = !open;
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `schema.table`, `lakehouse.schema.table` or `table` as a reference.
+fn name_reference(name: &str, access: AccessKind) -> Option<TableReference> {
+    let mut parts: Vec<&str> = name
+        .split('.')
+        .map(|p| p.trim().trim_matches('`'))
+        .collect();
+    let table = parts.pop().filter(|t| !t.is_empty())?;
+    Some(TableReference {
+        schema: parts.pop().filter(|s| !s.is_empty()).map(str::to_string),
+        name: table.to_string(),
+        access,
+    })
+}
+
+/// A lakehouse path such as `Tables/orders`, `Tables/dbo/orders` or an
+/// `abfss://.../Tables/...` URL as a reference; other paths are files, not tables.
+fn path_reference(path: &str, access: AccessKind) -> Option<TableReference> {
+    let segments: Vec<&str> = path
+        .trim_end_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let tables = segments.iter().rposition(|s| *s == "Tables")?;
+    match &segments[tables + 1..] {
+        [table] => name_reference(table, access),
+        [schema, table] => Some(TableReference {
+            schema: Some(schema.to_string()),
+            name: table.to_string(),
+            access,
+        }),
+        _ => None,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PythonCall {
+    /// `spark.sql(...)`; `None` when the argument is not a string literal.
+    Sql(Option<String>),
+    Table(AccessKind, Option<String>),
+    Path(AccessKind, String),
+    TempView(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PyToken {
+    Name(String),
+    Str(String),
+    /// An f-string with at least one replacement field; never a literal argument.
+    FormattedStr,
+    Punct(char),
+}
+
+/// Scan PySpark source for the DataFrame and SQL calls that touch tables.
+fn python_table_calls(source: &str) -> Vec<PythonCall> {
+    let tokens = python_tokens(source);
+    let mut calls = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let PyToken::Name(method) = token else {
+            continue;
+        };
+        if tokens.get(i + 1) != Some(&PyToken::Punct('(')) {
+            continue;
+        }
+        if i == 0 || tokens[i - 1] != PyToken::Punct('.') {
+            continue;
+        }
+        let receiver = match &tokens[..i - 1] {
+            [.., PyToken::Name(receiver)] => receiver.as_str(),
+            _ => "",
+        };
+        let mut argument_at = i + 2;
+        if method == "forName" || method == "forPath" {
+            // DeltaTable.forName(spark, "name")
+            match tokens.get(argument_at + 1) {
+                Some(PyToken::Punct(',')) => argument_at += 2,
+                _ => continue,
+            }
+        }
+        let literal = string_argument(&tokens, argument_at);
+        let call = match method.as_str() {
+            "sql" => PythonCall::Sql(literal),
+            "table" if matches!(receiver, "spark" | "read" | "readStream") => {
+                PythonCall::Table(AccessKind::Read, literal)
+            }
+            "saveAsTable" | "insertInto" | "toTable" => {
+                PythonCall::Table(AccessKind::Write, literal)
+            }
+            "forName" if receiver == "DeltaTable" => PythonCall::Table(AccessKind::Write, literal),
+            "forPath" if receiver == "DeltaTable" => match literal {
+                Some(path) => PythonCall::Path(AccessKind::Write, path),
+                None => continue,
+            },
+            "save" | "load" => match literal {
+                Some(path) => PythonCall::Path(
+                    if method == "save" {
+                        AccessKind::Write
+                    } else {
+                        AccessKind::Read
+                    },
+                    path,
+                ),
+                None => continue,
+            },
+            "createOrReplaceTempView"
+            | "createTempView"
+            | "createOrReplaceGlobalTempView"
+            | "createGlobalTempView" => match literal {
+                Some(name) => PythonCall::TempView(name),
+                None => continue,
+            },
+            _ => continue,
+        };
+        calls.push(call);
+    }
+    calls
+}
+
+/// Adjacent string literals at `at` joined, as Python does, if the argument ends there.
+/// Interpolated f-strings and `.format()` / `%` results are not literals and give `None`.
+fn string_argument(tokens: &[PyToken], at: usize) -> Option<String> {
+    let mut text = String::new();
+    let mut i = at;
+    while let Some(PyToken::Str(part)) = tokens.get(i) {
+        text.push_str(part);
+        i += 1;
+    }
+    let complete = i > at && matches!(tokens.get(i), Some(PyToken::Punct(')' | ',')));
+    complete.then_some(text)
+}
+
+/// A small Python tokenizer: names, string literals (contents only; f-strings with replacement
+/// fields become [`PyToken::FormattedStr`]) and single-character punctuation. Comments are
+/// skipped.
+fn python_tokens(source: &str) -> Vec<PyToken> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let is_prefix = word.len() <= 2
+                && word.chars().all(|p| "rRbBfFuU".contains(p))
+                && matches!(chars.get(i), Some('\'' | '"'));
+            if is_prefix {
+                let formatted = word.contains(['f', 'F']);
+                let (text, next, interpolated) = python_string(&chars, i, formatted);
+                tokens.push(if interpolated {
+                    PyToken::FormattedStr
+                } else {
+                    PyToken::Str(text)
+                });
+                i = next;
+            } else {
+                tokens.push(PyToken::Name(word));
+            }
+        } else if c == '\'' || c == '"' {
+            let (text, next, _) = python_string(&chars, i, false);
+            tokens.push(PyToken::Str(text));
+            i = next;
+        } else {
+            tokens.push(PyToken::Punct(c));
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// Read the string literal whose opening quote is at `start`; the flag is set when an f-string
+/// had a replacement field.
+fn python_string(chars: &[char], start: usize, formatted: bool) -> (String, usize, bool) {
+    let quote = chars[start];
+    let triple = chars.get(start + 1) == Some(&quote) && chars.get(start + 2) == Some(&quote);
+    let mut i = start + if triple { 3 } else { 1 };
+    let mut text = String::new();
+    let mut field_depth = 0usize;
+    let mut interpolated = false;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' {
+            if let Some(&escaped) = chars.get(i + 1) {
+                text.push(match escaped {
+                    'n' => '\n',
+                    't' => '\t',
+                    other => other,
+                });
+            }
+            i += 2;
+            continue;
+        }
+        let closes = if triple {
+            c == quote && chars.get(i + 1) == Some(&quote) && chars.get(i + 2) == Some(&quote)
+        } else {
+            c == quote || c == '\n'
+        };
+        if closes && field_depth == 0 {
+            return (text, i + if triple { 3 } else { 1 }, interpolated);
+        }
+        if formatted && c == '{' && chars.get(i + 1) != Some(&'{') {
+            field_depth += 1;
+            interpolated = true;
+        } else if formatted && c == '}' && field_depth > 0 {
+            field_depth -= 1;
+        } else if field_depth == 0 {
+            text.push(c);
+            if formatted && (c == '{' || c == '}') {
+                i += 1;
+            }
+        }
+        i += 1;
+    }
+    (text, i, interpolated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(schema: Option<&str>, name: &str, access: AccessKind) -> TableReference {
+        TableReference {
+            schema: schema.map(str::to_string),
+            name: name.to_string(),
+            access,
+        }
+    }
+
+    const FABRIC_SOURCE: &str = r#"# Fabric notebook source
+
+# METADATA ********************
+
+# META {
+# META   "kernel_info": {
+# META     "name": "synapse_pyspark"
+# META   }
+# META }
+
+# MARKDOWN ********************
+
+# # Load orders
+
+# CELL ********************
+
+orders = spark.read.table("bronze.orders")
+orders.createOrReplaceTempView("orders_v")
+spark.sql(f"""
+    INSERT OVERWRITE TABLE silver.orders
+    SELECT * FROM orders_v JOIN `ref`.`regions` r ON r.id = orders_v.region_id
+""")
+spark.sql(query)  # built elsewhere
+orders.write.mode("overwrite").format("delta").saveAsTable("silver.orders_snapshot")
+
+# METADATA ********************
+
+# META {
+# META   "language": "python",
+# META   "language_group": "synapse_pyspark"
+# META }
+
+# CELL ********************
+
+# MAGIC %%sql
+# MAGIC CREATE OR REPLACE TABLE gold.daily USING delta AS
+# MAGIC SELECT d, count(*) FROM silver.orders GROUP BY d
+
+# METADATA ********************
+
+# META {
+# META   "language": "sparksql",
+# META   "language_group": "synapse_pyspark"
+# META }
+"#;
+
+    #[test]
+    fn parses_fabric_source_cells_with_languages() {
+        let cells = parse_notebook(FABRIC_SOURCE).unwrap();
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0].index, 2);
+        assert_eq!(cells[0].language, CellLanguage::Python);
+        assert!(cells[0].source.starts_with("orders = spark.read"));
+        assert_eq!(cells[1].index, 3);
+        assert_eq!(cells[1].language, CellLanguage::Sql);
+        assert!(cells[1]
+            .source
+            .starts_with("CREATE OR REPLACE TABLE gold.daily"));
+    }
+
+    #[test]
+    fn derives_reads_and_writes_from_sql_and_dataframe_calls() {
+        let scan = notebook_table_accesses(&parse_notebook(FABRIC_SOURCE).unwrap());
+        let found: Vec<(usize, TableReference)> = scan
+            .accesses
+            .into_iter()
+            .map(|a| (a.cell_index, a.reference))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, reference(Some("bronze"), "orders", AccessKind::Read)),
+                (2, reference(Some("ref"), "regions", AccessKind::Read)),
+                (2, reference(Some("silver"), "orders", AccessKind::Write)),
+                (
+                    2,
+                    reference(Some("silver"), "orders_snapshot", AccessKind::Write)
+                ),
+                (3, reference(Some("gold"), "daily", AccessKind::Write)),
+                (3, reference(Some("silver"), "orders", AccessKind::Read)),
+            ]
+        );
+        assert_eq!(scan.dynamic_cells, vec![2]);
+    }
+
+    #[test]
+    fn parses_ipynb_with_magics_and_lakehouse_paths() {
+        let notebook = r##"{
+          "metadata": {"language_info": {"name": "python"}},
+          "cells": [
+            {"cell_type": "markdown", "source": ["# Title"]},
+            {"cell_type": "code", "source": ["%%sql\n", "MERGE INTO dbo.customers t\n",
+              "USING staging.customers s ON t.id = s.id\n", "WHEN MATCHED THEN UPDATE SET *"]},
+            {"cell_type": "code", "metadata": {"microsoft": {"language": "python"}},
+             "source": "df = spark.read.format('delta').load('abfss://ws@onelake.dfs.fabric.microsoft.com/lh.Lakehouse/Tables/dbo/events')\ndf.write.format('delta').save('Tables/events_clean')\ndf.write.save('Files/raw/out.parquet')\nDeltaTable.forName(spark, 'dbo.customers')"},
+            {"cell_type": "code", "metadata": {"microsoft": {"language": "scala"}},
+             "source": "spark.table(\"ignored\")"}
+          ]
+        }"##;
+        let cells = parse_notebook(notebook).unwrap();
+        assert_eq!(
+            cells
+                .iter()
+                .map(|c| (c.index, c.language))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, CellLanguage::Sql),
+                (3, CellLanguage::Python),
+                (4, CellLanguage::Scala)
+            ]
+        );
+        let found: Vec<(usize, TableReference)> = notebook_table_accesses(&cells)
+            .accesses
+            .into_iter()
+            .map(|a| (a.cell_index, a.reference))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, reference(Some("dbo"), "customers", AccessKind::Write)),
+                (2, reference(Some("staging"), "customers", AccessKind::Read)),
+                (3, reference(None, "events_clean", AccessKind::Write)),
+                (3, reference(Some("dbo"), "customers", AccessKind::Write)),
+                (3, reference(Some("dbo"), "events", AccessKind::Read)),
+            ]
+        );
+        assert!(parse_notebook("{\"metadata\": {}}").is_err());
+    }
+
+    #[test]
+    fn interpolated_sql_arguments_are_dynamic() {
+        let cells = parse_notebook(
+            r#"{"cells": [
+              {"cell_type": "code", "source": "spark.sql(f\"SELECT * FROM {tbl}\")"},
+              {"cell_type": "code", "source": "spark.sql(\"SELECT * FROM {}\".format(tbl))"},
+              {"cell_type": "code", "source": "spark.sql(f\"SELECT * FROM dbo.orders\")"}
+            ]}"#,
+        )
+        .unwrap();
+        let scan = notebook_table_accesses(&cells);
+        assert_eq!(scan.dynamic_cells, vec![1, 2]);
+        let found: Vec<(usize, TableReference)> = scan
+            .accesses
+            .into_iter()
+            .map(|a| (a.cell_index, a.reference))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(3, reference(Some("dbo"), "orders", AccessKind::Read))]
+        );
+    }
+
+    #[test]
+    fn python_tokens_handle_prefixes_and_fields() {
+        assert_eq!(
+            python_tokens(r#"spark.sql(rf"SELECT * FROM {tbl} -- {{x}}")"#),
+            vec![
+                PyToken::Name("spark".to_string()),
+                PyToken::Punct('.'),
+                PyToken::Name("sql".to_string()),
+                PyToken::Punct('('),
+                PyToken::FormattedStr,
+                PyToken::Punct(')'),
+            ]
+        );
+        assert_eq!(
+            python_tokens(r#"F'SELECT 1 -- {{x}}'"#),
+            vec![PyToken::Str("SELECT 1 -- {x}".to_string())]
+        );
+        assert_eq!(
+            python_table_calls("spark.sql('SELECT 1 FROM a' 'b')"),
+            vec![PythonCall::Sql(Some("SELECT 1 FROM ab".to_string()))]
+        );
+    }
+}
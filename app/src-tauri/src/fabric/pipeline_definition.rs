@@ -0,0 +1,442 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use crate::tsql::lexer::{next_significant, tokenize, Token, TokenKind};
+use crate::types::{PipelineActivity, PipelineDefinitionImport};
+
+/// Resolved target of a single activity.
+#[derive(Default)]
+struct ActivityTarget {
+    warehouse_item_id: Option<String>,
+    schema_name: Option<String>,
+    procedure_name: Option<String>,
+    parameters: Option<Value>,
+    /// The procedure name is a pipeline expression evaluated at run time.
+    dynamic: bool,
+}
+
+/// Parse a Fabric Data Pipeline or ADF pipeline definition (`pipeline-content.json`).
+///
+/// Activities nested in ForEach, IfCondition, Until and Switch containers are flattened with
+/// `parent_activity_name` set to their container. Stored procedure, Script and Copy activities
+/// are resolved to the warehouse item (from the linked service `artifactId`), schema and
+/// procedure they run.
+pub fn parse_pipeline_definition(
+    pipeline_item_id: &str,
+    definition: &str,
+) -> Result<PipelineDefinitionImport, String> {
+    let root: Value = serde_json::from_str(definition).map_err(|e| format!("invalid JSON: {e}"))?;
+    let properties = root.get("properties").unwrap_or(&root);
+    let activities = properties
+        .get("activities")
+        .and_then(Value::as_array)
+        .ok_or_else(|| "definition has no activities array".to_string())?;
+
+    let mut import = PipelineDefinitionImport {
+        pipeline_item_id: pipeline_item_id.to_string(),
+        parameters_json: properties
+            .get("parameters")
+            .filter(|p| p.as_object().is_some_and(|o| !o.is_empty()))
+            .map(Value::to_string),
+        activities: Vec::new(),
+        unresolved: Vec::new(),
+    };
+    let mut seen = HashSet::new();
+    collect_activities(activities, None, &mut seen, &mut import)?;
+    Ok(import)
+}
+
+fn collect_activities(
+    activities: &[Value],
+    parent: Option<&str>,
+    seen: &mut HashSet<String>,
+    import: &mut PipelineDefinitionImport,
+) -> Result<(), String> {
+    for activity in activities {
+        let name = activity
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| "activity without a name".to_string())?;
+        if !seen.insert(name.to_lowercase()) {
+            return Err(format!("duplicate activity name {name}"));
+        }
+        let activity_type = activity
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("Unknown");
+        let type_properties = activity.get("typeProperties").unwrap_or(&Value::Null);
+        let target = resolve_target(activity_type, activity, type_properties);
+        if target.dynamic {
+            import.unresolved.push(name.to_string());
+        }
+        let depends_on_json = activity
+            .get("dependsOn")
+            .filter(|d| d.as_array().is_some_and(|a| !a.is_empty()))
+            .map(Value::to_string);
+
+        import.activities.push(PipelineActivity {
+            id: None,
+            pipeline_item_id: import.pipeline_item_id.clone(),
+            activity_name: name.to_string(),
+            activity_type: activity_type.to_string(),
+            target_warehouse_item_id: target.warehouse_item_id,
+            target_schema_name: target.schema_name,
+            target_procedure_name: target.procedure_name,
+            parameters_json: target.parameters.map(|p| p.to_string()),
+            depends_on_json,
+            parent_activity_name: parent.map(str::to_string),
+        });
+
+        for children in child_activity_lists(activity_type, type_properties) {
+            collect_activities(children, Some(name), seen, import)?;
+        }
+    }
+    Ok(())
+}
+
+fn child_activity_lists<'a>(activity_type: &str, type_properties: &'a Value) -> Vec<&'a [Value]> {
+    let list = |value: &'a Value| value.as_array().map(Vec::as_slice);
+    match activity_type {
+        "ForEach" | "Until" => list(&type_properties["activities"]).into_iter().collect(),
+        "IfCondition" => [
+            list(&type_properties["ifTrueActivities"]),
+            list(&type_properties["ifFalseActivities"]),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        "Switch" => type_properties["cases"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|case| list(&case["activities"]))
+            .chain(list(&type_properties["defaultActivities"]))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn resolve_target(
+    activity_type: &str,
+    activity: &Value,
+    type_properties: &Value,
+) -> ActivityTarget {
+    let warehouse_item_id = linked_service_artifact(&activity["linkedService"]);
+    match activity_type {
+        "SqlServerStoredProcedure" => {
+            let mut target = procedure_target(&type_properties["storedProcedureName"]);
+            target.warehouse_item_id = warehouse_item_id;
+            target.parameters = non_empty(&type_properties["storedProcedureParameters"]);
+            target
+        }
+        "Script" => {
+            let scripts = type_properties["scripts"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let (schema_name, procedure_name) = scripts
+                .iter()
+                .filter_map(|script| literal_string(&script["text"]))
+                .find_map(exec_target)
+                .unzip();
+            let parameters: Vec<Value> = scripts
+                .iter()
+                .filter_map(|script| script["parameters"].as_array())
+                .flatten()
+                .cloned()
+                .collect();
+            ActivityTarget {
+                warehouse_item_id,
+                schema_name: schema_name.flatten(),
+                procedure_name,
+                parameters: (!parameters.is_empty()).then_some(Value::Array(parameters)),
+                dynamic: false,
+            }
+        }
+        "Copy" => {
+            let sink = &type_properties["sink"];
+            let dataset = &sink["datasetSettings"];
+            let mut target = if sink.get("sqlWriterStoredProcedureName").is_some() {
+                procedure_target(&sink["sqlWriterStoredProcedureName"])
+            } else {
+                ActivityTarget::default()
+            };
+            target.warehouse_item_id =
+                linked_service_artifact(&dataset["linkedService"]).or(warehouse_item_id);
+            if target.schema_name.is_none() {
+                target.schema_name = literal_string(&dataset["typeProperties"]["schema"]);
+            }
+            target.parameters = non_empty(&sink["storedProcedureParameters"]);
+            target
+        }
+        _ => ActivityTarget {
+            warehouse_item_id,
+            parameters: non_empty(&type_properties["parameters"]),
+            ..ActivityTarget::default()
+        },
+    }
+}
+
+fn procedure_target(value: &Value) -> ActivityTarget {
+    let Some(text) = literal_string(value) else {
+        return ActivityTarget {
+            dynamic: !value.is_null(),
+            ..ActivityTarget::default()
+        };
+    };
+    let tokens = tokenize(&text);
+    match next_significant(&tokens, 0).and_then(|start| qualified_name(&tokens, start)) {
+        Some((schema_name, procedure_name)) => ActivityTarget {
+            schema_name,
+            procedure_name: Some(procedure_name),
+            ..ActivityTarget::default()
+        },
+        None => ActivityTarget {
+            dynamic: true,
+            ..ActivityTarget::default()
+        },
+    }
+}
+
+/// The procedure run by the first `EXEC` / `EXECUTE` statement in a script.
+fn exec_target(script: String) -> Option<(Option<String>, String)> {
+    let tokens = tokenize(&script);
+    let mut i = 0;
+    while let Some(k) = next_significant(&tokens, i) {
+        i = k + 1;
+        if !(tokens[k].is_word("EXEC") || tokens[k].is_word("EXECUTE")) {
+            continue;
+        }
+        let mut start = next_significant(&tokens, k + 1)?;
+        // EXEC @rc = dbo.proc
+        if tokens[start].kind == TokenKind::Variable {
+            let eq = next_significant(&tokens, start + 1)?;
+            if !tokens[eq].is_symbol("=") {
+                continue;
+            }
+            start = next_significant(&tokens, eq + 1)?;
+        }
+        if let Some(name) = qualified_name(&tokens, start) {
+            return Some(name);
+        }
+    }
+    None
+}
+
+/// `[db.]schema.name` or `name` starting at `start`; returns the schema and object name.
+fn qualified_name(tokens: &[Token], start: usize) -> Option<(Option<String>, String)> {
+    let mut parts = Vec::new();
+    let mut i = start;
+    loop {
+        let token = tokens.get(i)?;
+        if !matches!(
+            token.kind,
+            TokenKind::Word | TokenKind::BracketIdent | TokenKind::QuotedIdent
+        ) {
+            return None;
+        }
+        parts.push(token.identifier()?);
+        match tokens.get(i + 1) {
+            Some(dot) if dot.is_symbol(".") => i += 2,
+            _ => break,
+        }
+    }
+    let name = parts.pop()?;
+    Some((parts.pop().filter(|s| !s.is_empty()), name))
+}
+
+/// A literal string property. Pipeline expressions (`@...` strings or `{"type": "Expression"}`
+/// objects) are evaluated at run time and return `None`.
+fn literal_string(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(text) => text.as_str(),
+        Value::Object(map) if map.get("type").and_then(Value::as_str) != Some("Expression") => {
+            map.get("value")?.as_str()?
+        }
+        _ => return None,
+    };
+    let trimmed = text.trim();
+    (!trimmed.is_empty() && !trimmed.starts_with('@')).then(|| trimmed.to_string())
+}
+
+fn linked_service_artifact(linked_service: &Value) -> Option<String> {
+    literal_string(&linked_service["properties"]["typeProperties"]["artifactId"])
+}
+
+fn non_empty(value: &Value) -> Option<Value> {
+    let empty = match value {
+        Value::Object(map) => map.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => true,
+    };
+    (!empty).then(|| value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFINITION: &str = r#"{
+      "properties": {
+        "parameters": { "run_date": { "type": "string" } },
+        "activities": [
+          {
+            "name": "Stage",
+            "type": "Copy",
+            "dependsOn": [],
+            "typeProperties": {
+              "sink": {
+                "type": "DataWarehouseSink",
+                "datasetSettings": {
+                  "linkedService": { "properties": { "type": "DataWarehouse",
+                    "typeProperties": { "artifactId": "wh-1" } } },
+                  "typeProperties": { "schema": "stg", "table": "orders" }
+                }
+              }
+            }
+          },
+          {
+            "name": "Load",
+            "type": "SqlServerStoredProcedure",
+            "dependsOn": [{ "activity": "Stage", "dependencyConditions": ["Succeeded"] }],
+            "linkedService": { "properties": { "typeProperties": { "artifactId": "wh-1" } } },
+            "typeProperties": {
+              "storedProcedureName": "[dbo].[sp_load_orders]",
+              "storedProcedureParameters": { "run_date": { "value": "@pipeline().parameters.run_date", "type": "String" } }
+            }
+          },
+          {
+            "name": "PerRegion",
+            "type": "ForEach",
+            "dependsOn": [{ "activity": "Load", "dependencyConditions": ["Succeeded"] }],
+            "typeProperties": {
+              "activities": [
+                {
+                  "name": "CheckRegion",
+                  "type": "IfCondition",
+                  "typeProperties": {
+                    "ifTrueActivities": [
+                      { "name": "RunScript", "type": "Script",
+                        "typeProperties": { "scripts": [
+                          { "type": "Query", "text": "DECLARE @rc int; EXEC @rc = rpt.sp_region @r = 1;",
+                            "parameters": [{ "name": "r", "type": "Int32", "value": 1 }] } ] } }
+                    ],
+                    "ifFalseActivities": [
+                      { "name": "Dynamic", "type": "SqlServerStoredProcedure",
+                        "typeProperties": { "storedProcedureName": { "value": "@item().proc", "type": "Expression" } } }
+                    ]
+                  }
+                },
+                {
+                  "name": "Route",
+                  "type": "Switch",
+                  "typeProperties": {
+                    "cases": [ { "value": "a", "activities": [ { "name": "CaseA", "type": "Wait" } ] } ],
+                    "defaultActivities": [ { "name": "Fallback", "type": "Wait" } ]
+                  }
+                }
+              ]
+            }
+          }
+        ]
+      }
+    }"#;
+
+    fn find<'a>(import: &'a PipelineDefinitionImport, name: &str) -> &'a PipelineActivity {
+        import
+            .activities
+            .iter()
+            .find(|a| a.activity_name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_nested_activities_and_targets() {
+        let import = parse_pipeline_definition("pl-1", DEFINITION).unwrap();
+        let names: Vec<&str> = import
+            .activities
+            .iter()
+            .map(|a| a.activity_name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "Stage",
+                "Load",
+                "PerRegion",
+                "CheckRegion",
+                "RunScript",
+                "Dynamic",
+                "Route",
+                "CaseA",
+                "Fallback"
+            ]
+        );
+        assert_eq!(
+            import.parameters_json.as_deref(),
+            Some(r#"{"run_date":{"type":"string"}}"#)
+        );
+
+        let stage = find(&import, "Stage");
+        assert_eq!(stage.target_warehouse_item_id.as_deref(), Some("wh-1"));
+        assert_eq!(stage.target_schema_name.as_deref(), Some("stg"));
+        assert_eq!(stage.target_procedure_name, None);
+        assert_eq!(stage.depends_on_json, None);
+
+        let load = find(&import, "Load");
+        assert_eq!(load.target_warehouse_item_id.as_deref(), Some("wh-1"));
+        assert_eq!(load.target_schema_name.as_deref(), Some("dbo"));
+        assert_eq!(
+            load.target_procedure_name.as_deref(),
+            Some("sp_load_orders")
+        );
+        assert!(load
+            .parameters_json
+            .as_deref()
+            .unwrap()
+            .contains("run_date"));
+        assert!(load.depends_on_json.as_deref().unwrap().contains("Stage"));
+
+        let script = find(&import, "RunScript");
+        assert_eq!(script.parent_activity_name.as_deref(), Some("CheckRegion"));
+        assert_eq!(script.target_schema_name.as_deref(), Some("rpt"));
+        assert_eq!(script.target_procedure_name.as_deref(), Some("sp_region"));
+        assert!(script.parameters_json.is_some());
+
+        assert_eq!(
+            find(&import, "CheckRegion").parent_activity_name.as_deref(),
+            Some("PerRegion")
+        );
+        assert_eq!(
+            find(&import, "Fallback").parent_activity_name.as_deref(),
+            Some("Route")
+        );
+        assert_eq!(import.unresolved, vec!["Dynamic"]);
+    }
+
+    #[test]
+    fn rejects_malformed_definitions() {
+        assert!(parse_pipeline_definition("pl", "not json").is_err());
+        assert!(parse_pipeline_definition("pl", r#"{"properties": {}}"#).is_err());
+        let duplicate =
+            r#"{"activities": [{"name": "A", "type": "Wait"}, {"name": "a", "type": "Wait"}]}"#;
+        assert_eq!(
+            parse_pipeline_definition("pl", duplicate).unwrap_err(),
+            "duplicate activity name a"
+        );
+    }
+
+    #[test]
+    fn exec_target_skips_return_code_and_dynamic_exec() {
+        assert_eq!(
+            exec_target("EXEC (@sql); EXECUTE [etl].[load]".to_string()),
+            Some((Some("etl".to_string()), "load".to_string()))
+        );
+        assert_eq!(
+            exec_target("exec sp_refresh".to_string()),
+            Some((None, "sp_refresh".to_string()))
+        );
+        assert_eq!(exec_target("SELECT 1".to_string()), None);
+    }
+}
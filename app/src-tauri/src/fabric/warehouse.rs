@@ -0,0 +1,116 @@
+use tiberius::{AuthMethod, Client, Config, EncryptionLevel, Row};
+use tokio::net::TcpStream;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use crate::source_sql::{resolve_source_query, should_log_source_sql, SourceQuery};
+use crate::types::{CommandError, WarehouseProcedure, WarehouseSchema, WarehouseTable};
+
+const SQL_ENDPOINT_PORT: u16 = 1433;
+
+/// Schemas, tables and procedures of one warehouse; `warehouse_item_id` is filled in.
+#[derive(Default)]
+pub struct WarehouseCatalog {
+    pub schemas: Vec<WarehouseSchema>,
+    pub tables: Vec<WarehouseTable>,
+    pub procedures: Vec<WarehouseProcedure>,
+}
+
+/// Read a warehouse catalog over its SQL endpoint using an Entra access token.
+///
+/// `connection_string` is the SQL endpoint host reported by the Fabric API; the database is
+/// the warehouse display name.
+pub async fn fetch_warehouse_catalog(
+    warehouse_item_id: &str,
+    connection_string: &str,
+    database: &str,
+    access_token: &str,
+) -> Result<WarehouseCatalog, CommandError> {
+    let host = connection_string
+        .trim()
+        .trim_start_matches("tcp:")
+        .split([',', ';'])
+        .next()
+        .unwrap_or_default();
+    let mut config = Config::new();
+    config.host(host);
+    config.port(SQL_ENDPOINT_PORT);
+    config.database(database);
+    config.authentication(AuthMethod::aad_token(access_token));
+    config.encryption(EncryptionLevel::Required);
+
+    let tcp = TcpStream::connect(config.get_addr()).await.map_err(|e| {
+        log::error!("fetch_warehouse_catalog: failed to connect tcp to {host}: {e}");
+        CommandError::Io(format!(
+            "Could not connect to warehouse endpoint {host}: {e}"
+        ))
+    })?;
+    tcp.set_nodelay(true)
+        .map_err(|e| CommandError::Io(format!("Could not configure socket: {e}")))?;
+    let mut client = Client::connect(config, tcp.compat_write())
+        .await
+        .map_err(|e| {
+            log::error!("fetch_warehouse_catalog: failed to authenticate to {host}: {e}");
+            CommandError::Io(format!("Could not sign in to warehouse {database}: {e}"))
+        })?;
+
+    let mut catalog = WarehouseCatalog::default();
+    for row in query(&mut client, SourceQuery::DiscoverSchemas).await? {
+        catalog.schemas.push(WarehouseSchema {
+            warehouse_item_id: warehouse_item_id.to_string(),
+            schema_name: text(&row, 1, "schema")?,
+            schema_id_local: row.get::<i64, _>(0),
+        });
+    }
+    for row in query(&mut client, SourceQuery::DiscoverTables).await? {
+        catalog.tables.push(WarehouseTable {
+            warehouse_item_id: warehouse_item_id.to_string(),
+            schema_name: text(&row, 0, "schema")?,
+            table_name: text(&row, 1, "table")?,
+            object_id_local: row.get::<i64, _>(2),
+        });
+    }
+    for row in query(&mut client, SourceQuery::DiscoverProcedures).await? {
+        catalog.procedures.push(WarehouseProcedure {
+            warehouse_item_id: warehouse_item_id.to_string(),
+            schema_name: text(&row, 0, "schema")?,
+            procedure_name: text(&row, 1, "procedure")?,
+            object_id_local: row.get::<i64, _>(2),
+            sql_body: row.get::<&str, _>(3).map(str::to_string),
+        });
+    }
+    Ok(catalog)
+}
+
+async fn query(
+    client: &mut Client<Compat<TcpStream>>,
+    source_query: SourceQuery,
+) -> Result<Vec<Row>, CommandError> {
+    let sql = resolve_source_query("fabric_warehouse", source_query)?;
+    if should_log_source_sql() {
+        log::debug!(
+            "fetch_warehouse_catalog: executing query={} sql={}",
+            source_query.name(),
+            sql.trim()
+        );
+    }
+    let failed = |e: tiberius::error::Error| {
+        log::error!(
+            "fetch_warehouse_catalog: query {} failed: {e}",
+            source_query.name()
+        );
+        CommandError::Io(format!("Warehouse discovery failed: {e}"))
+    };
+    client
+        .simple_query(sql)
+        .await
+        .map_err(failed)?
+        .into_first_result()
+        .await
+        .map_err(failed)
+}
+
+fn text(row: &Row, index: usize, what: &str) -> Result<String, CommandError> {
+    row.get::<&str, _>(index)
+        .map(str::to_string)
+        .ok_or_else(|| CommandError::Io(format!("Warehouse discovery returned invalid {what}")))
+}
@@ -0,0 +1,621 @@
+use std::cell::RefCell;
+use std::path::Path;
+
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{
+    BranchType, Cred, CredentialType, ErrorCode, FetchOptions, IndexAddOption, PushOptions,
+    RemoteCallbacks, Repository, Signature, StatusOptions,
+};
+
+use crate::commands::workspace::{parse_remote_url, redact_clone_error};
+use crate::types::CommandError;
+
+/// Name and email recorded as author and committer of generated commits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitAuthor {
+    pub name: String,
+    pub email: String,
+}
+
+/// Objects received so far out of the total the remote announced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferProgress {
+    pub received: usize,
+    pub total: usize,
+}
+
+/// URL of a repo under a GitHub web base such as `https://github.com`. Credentials are
+/// supplied through callbacks, never the URL.
+pub fn github_url(web_base_url: &str, repo_name: &str) -> String {
+    format!("{web_base_url}/{repo_name}.git")
+}
+
+/// Web base of the GitHub `host` a remote points at. An `http://` remote stays on http;
+/// everything else, ssh remotes included, is reached over HTTPS.
+pub fn remote_web_base(remote_url: &str, host: &str) -> String {
+    let http = remote_url
+        .trim()
+        .get(..7)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("http://"));
+    format!("{}://{host}", if http { "http" } else { "https" })
+}
+
+/// Callbacks answering credential requests with the OAuth token. A second request for the
+/// same operation means the token was rejected, so it fails instead of retrying forever.
+fn callbacks<'a>(token: Option<&'a str>) -> RemoteCallbacks<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+    let mut attempted = false;
+    callbacks.credentials(move |_url, _username, allowed| {
+        let token = token.filter(|t| !t.is_empty());
+        match token {
+            Some(token) if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) && !attempted => {
+                attempted = true;
+                Cred::userpass_plaintext("x-access-token", token)
+            }
+            Some(_) => Err(git2::Error::new(
+                ErrorCode::Auth,
+                git2::ErrorClass::Http,
+                "the stored GitHub token was rejected; sign in to GitHub again",
+            )),
+            None => Err(git2::Error::new(
+                ErrorCode::Auth,
+                git2::ErrorClass::Http,
+                "the remote requires credentials; sign in to GitHub",
+            )),
+        }
+    });
+    callbacks
+}
+
+pub fn open(repo: &Path) -> Result<Repository, CommandError> {
+    Repository::open(repo).map_err(|e| {
+        CommandError::Git(format!(
+            "{} is not a git repository: {}",
+            repo.display(),
+            e.message()
+        ))
+    })
+}
+
+/// Shallow clone of `url` into `path`, reporting transfer progress.
+pub fn clone(
+    url: &str,
+    path: &Path,
+    token: Option<&str>,
+    progress: &mut dyn FnMut(TransferProgress),
+) -> Result<(), CommandError> {
+    let mut callbacks = callbacks(token);
+    callbacks.transfer_progress(|stats| {
+        progress(TransferProgress {
+            received: stats.received_objects(),
+            total: stats.total_objects(),
+        });
+        true
+    });
+    let mut fetch = FetchOptions::new();
+    fetch.remote_callbacks(callbacks).depth(1);
+    RepoBuilder::new().fetch_options(fetch).clone(url, path)?;
+    log::info!("git: cloned into {}", path.display());
+    Ok(())
+}
+
+/// Update the remote-tracking branches of origin.
+pub fn fetch(repo: &Path, token: Option<&str>) -> Result<(), CommandError> {
+    let repository = open(repo)?;
+    let mut remote = repository.find_remote("origin")?;
+    let mut fetch = FetchOptions::new();
+    fetch.remote_callbacks(callbacks(token));
+    remote.fetch(&[] as &[&str], Some(&mut fetch), None)?;
+    log::info!("git: fetched origin in {}", repo.display());
+    Ok(())
+}
+
+/// URL of the origin remote; `None` when there is no origin.
+pub fn origin_url(repo: &Path) -> Result<Option<String>, CommandError> {
+    let repository = open(repo)?;
+    let url = match repository.find_remote("origin") {
+        Ok(remote) => remote.url().map(str::to_string),
+        Err(e) if e.code() == ErrorCode::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    Ok(url)
+}
+
+pub fn set_origin_url(repo: &Path, url: &str) -> Result<(), CommandError> {
+    open(repo)?.remote_set_url("origin", url)?;
+    Ok(())
+}
+
+/// Paths with uncommitted changes, including untracked files, relative to the repo root.
+pub fn dirty_paths(repo: &Path) -> Result<Vec<String>, CommandError> {
+    let repository = open(repo)?;
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let statuses = repository.statuses(Some(&mut options))?;
+    Ok(statuses
+        .iter()
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .collect())
+}
+
+/// Checked-out branch, uncommitted paths and divergence from origin's copy of the branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoStatus {
+    /// `None` when HEAD is detached.
+    pub branch: Option<String>,
+    pub dirty_paths: Vec<String>,
+    /// Local commits not on `origin/<branch>`.
+    pub ahead: usize,
+    /// Commits on `origin/<branch>` (as of the last fetch) missing locally.
+    pub behind: usize,
+}
+
+/// Status of the repo at `repo`; `None` when the directory is not a git repository.
+pub fn status(repo: &Path) -> Result<Option<RepoStatus>, CommandError> {
+    let repository = match Repository::open(repo) {
+        Ok(repository) => repository,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let head = repository.find_reference("HEAD")?;
+    let branch = head
+        .symbolic_target()
+        .and_then(|target| target.strip_prefix("refs/heads/"))
+        .map(str::to_string);
+    let (ahead, behind) = match &branch {
+        Some(branch) => ahead_behind(&repository, branch)?.unwrap_or_default(),
+        None => (0, 0),
+    };
+    Ok(Some(RepoStatus {
+        branch,
+        dirty_paths: dirty_paths(repo)?,
+        ahead,
+        behind,
+    }))
+}
+
+/// A local branch and how much of it exists nowhere else.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalBranch {
+    pub name: String,
+    /// Whether `origin/<name>` exists (as of the last fetch).
+    pub has_upstream: bool,
+    /// Commits on the branch that no remote-tracking ref contains.
+    pub unpushed: usize,
+}
+
+/// Every local branch with its commits that are not on any remote-tracking ref.
+pub fn local_branches(repo: &Path) -> Result<Vec<LocalBranch>, CommandError> {
+    let repository = open(repo)?;
+    let mut remote_tips = Vec::new();
+    for reference in repository.references_glob("refs/remotes/*")? {
+        if let Some(oid) = reference?.resolve()?.target() {
+            remote_tips.push(oid);
+        }
+    }
+    let mut branches = Vec::new();
+    for entry in repository.branches(Some(BranchType::Local))? {
+        let (branch, _) = entry?;
+        let (Some(name), Some(tip)) = (branch.name()?, branch.get().target()) else {
+            continue;
+        };
+        let mut walk = repository.revwalk()?;
+        walk.push(tip)?;
+        for oid in &remote_tips {
+            walk.hide(*oid)?;
+        }
+        branches.push(LocalBranch {
+            name: name.to_string(),
+            has_upstream: repository
+                .refname_to_id(&format!("refs/remotes/origin/{name}"))
+                .is_ok(),
+            unpushed: walk.count(),
+        });
+    }
+    branches.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(branches)
+}
+
+/// Check out `branch`. A missing branch is created from `origin/<branch>` when the remote
+/// has it, otherwise from origin's default branch, falling back to the current HEAD. An
+/// existing branch is fast-forwarded to `origin/<branch>`; one that has diverged from it
+/// fails. Uncommitted changes that the checkout would overwrite make it fail too.
+pub fn checkout_branch(repo: &Path, branch: &str) -> Result<(), CommandError> {
+    if !git2::Branch::name_is_valid(branch)? {
+        return Err(CommandError::Git(format!(
+            "'{branch}' is not a valid branch name"
+        )));
+    }
+    let repository = open(repo)?;
+    let refname = format!("refs/heads/{branch}");
+    let upstream = repository
+        .refname_to_id(&format!("refs/remotes/origin/{branch}"))
+        .ok();
+    match repository.find_branch(branch, BranchType::Local) {
+        Ok(existing) => {
+            let local = existing.get().peel_to_commit()?.id();
+            let target = match upstream {
+                Some(upstream) => match repository.graph_ahead_behind(local, upstream)? {
+                    (_, 0) => local,
+                    (0, _) => upstream,
+                    (ahead, behind) => {
+                        return Err(CommandError::Git(format!(
+                            "{branch} has diverged from origin/{branch} ({ahead} local and \
+                             {behind} remote commit(s)); reconcile them before writing the plan"
+                        )));
+                    }
+                },
+                None => local,
+            };
+            let commit = repository.find_commit(target)?;
+            repository.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))?;
+            if target != local {
+                repository.reference(&refname, target, true, "fast-forward to origin")?;
+                log::info!("git: fast-forwarded {branch} to origin/{branch}");
+            }
+        }
+        Err(e) if e.code() == ErrorCode::NotFound => {
+            let default_branch = repository
+                .find_reference("refs/remotes/origin/HEAD")
+                .and_then(|reference| reference.resolve())
+                .ok()
+                .and_then(|reference| reference.target());
+            let head = match repository.head() {
+                Ok(head) => Some(head.peel_to_commit()?.id()),
+                // An empty repo: HEAD simply moves to the unborn branch.
+                Err(e) if e.code() == ErrorCode::UnbornBranch => None,
+                Err(e) => return Err(e.into()),
+            };
+            if let Some(base) = upstream.or(default_branch).or(head) {
+                let commit = repository.find_commit(base)?;
+                repository
+                    .checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))?;
+                repository.branch(branch, &commit, false)?;
+            }
+        }
+        Err(e) => return Err(e.into()),
+    }
+    repository.set_head(&refname)?;
+    log::info!("git: on branch {branch} in {}", repo.display());
+    Ok(())
+}
+
+/// Commits of local `branch` missing from `origin/<branch>` and the reverse, as of the last
+/// fetch; `None` when either branch does not exist.
+pub fn branch_divergence(
+    repo: &Path,
+    branch: &str,
+) -> Result<Option<(usize, usize)>, CommandError> {
+    ahead_behind(&open(repo)?, branch)
+}
+
+fn ahead_behind(
+    repository: &Repository,
+    branch: &str,
+) -> Result<Option<(usize, usize)>, CommandError> {
+    let local = repository.refname_to_id(&format!("refs/heads/{branch}"));
+    let upstream = repository.refname_to_id(&format!("refs/remotes/origin/{branch}"));
+    match (local, upstream) {
+        (Ok(local), Ok(upstream)) => Ok(Some(repository.graph_ahead_behind(local, upstream)?)),
+        _ => Ok(None),
+    }
+}
+
+/// Stage additions, changes and deletions under `paths`; paths that do not exist in the
+/// working tree or the index are skipped.
+pub fn stage(repo: &Path, paths: &[&str]) -> Result<(), CommandError> {
+    let repository = open(repo)?;
+    let mut index = repository.index()?;
+    index.add_all(paths.iter(), IndexAddOption::DEFAULT, None)?;
+    index.update_all(paths.iter(), None)?;
+    index.write()?;
+    Ok(())
+}
+
+/// Commit the staged changes as `author`. Returns the new commit sha, or `None` when
+/// nothing is staged.
+pub fn commit(
+    repo: &Path,
+    message: &str,
+    author: &GitAuthor,
+) -> Result<Option<String>, CommandError> {
+    let repository = open(repo)?;
+    let mut index = repository.index()?;
+    let tree_id = index.write_tree()?;
+    let parent = match repository.head() {
+        Ok(head) => Some(head.peel_to_commit()?),
+        Err(e) if e.code() == ErrorCode::UnbornBranch => None,
+        Err(e) => return Err(e.into()),
+    };
+    let unchanged = match &parent {
+        Some(parent) => parent.tree_id() == tree_id,
+        None => index.is_empty(),
+    };
+    if unchanged {
+        log::info!("git: nothing to commit in {}", repo.display());
+        return Ok(None);
+    }
+
+    let tree = repository.find_tree(tree_id)?;
+    let signature = Signature::now(&author.name, &author.email)?;
+    let parents: Vec<_> = parent.iter().collect();
+    let sha = repository
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )?
+        .to_string();
+    log::info!("git: committed {sha} as {}", author.name);
+    Ok(Some(sha))
+}
+
+/// `path` as of the HEAD commit; `None` when there is no commit or no such file.
+pub fn read_head_file(repo: &Path, path: &str) -> Option<String> {
+    read_ref_file(repo, "HEAD", path)
+}
+
+/// `path` as of the commit `refname` points at; `None` when there is no such ref or file.
+pub fn read_ref_file(repo: &Path, refname: &str, path: &str) -> Option<String> {
+    let repository = Repository::open(repo).ok()?;
+    let tree = repository
+        .find_reference(refname)
+        .ok()?
+        .peel_to_tree()
+        .ok()?;
+    let blob = tree
+        .get_path(Path::new(path))
+        .ok()?
+        .to_object(&repository)
+        .ok()?
+        .peel_to_blob()
+        .ok()?;
+    String::from_utf8(blob.content().to_vec()).ok()
+}
+
+/// Push the checked-out branch to `branch` on origin. GitHub remotes are pushed over
+/// HTTP(S) with the OAuth token; any other origin (such as a local bare repo) is pushed to as
+/// configured.
+pub fn push(repo: &Path, branch: &str, token: Option<&str>) -> Result<(), CommandError> {
+    let repository = open(repo)?;
+    let origin = origin_url(repo)?
+        .ok_or_else(|| CommandError::Git(format!("{} has no origin remote", repo.display())))?;
+    let head = repository.head()?;
+    let source = head
+        .name()
+        .filter(|name| name.starts_with("refs/heads/"))
+        .ok_or_else(|| CommandError::Git("HEAD is not on a branch".to_string()))?;
+    let refspec = format!("{source}:refs/heads/{branch}");
+
+    let github = parse_remote_url(&origin)
+        .map(|(host, repo_name)| (remote_web_base(&origin, &host), repo_name));
+    let mut remote = match &github {
+        Some((web_base_url, repo_name)) => {
+            repository.remote_anonymous(&github_url(web_base_url, repo_name))?
+        }
+        None => repository.find_remote("origin")?,
+    };
+    let rejected = RefCell::new(None);
+    let mut callbacks = callbacks(token);
+    callbacks.push_update_reference(|refname, status| {
+        if let Some(status) = status {
+            *rejected.borrow_mut() = Some(format!("{refname} was rejected: {status}"));
+        }
+        Ok(())
+    });
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+
+    let redact = |message: String| match (token.filter(|t| !t.is_empty()), &github) {
+        (Some(token), Some((web_base_url, repo_name))) => {
+            redact_clone_error(&message, token, web_base_url, repo_name)
+        }
+        (Some(token), None) => message.replace(token, "***"),
+        (None, _) => message,
+    };
+    remote
+        .push(&[refspec.as_str()], Some(&mut options))
+        .map_err(|e| match CommandError::from(e) {
+            CommandError::Git(message) => CommandError::Git(redact(message)),
+            other => other,
+        })
+        .inspect_err(|e| log::error!("git push failed: {e}"))?;
+    let rejected = rejected.borrow_mut().take();
+    if let Some(message) = rejected {
+        log::error!("git push failed: {message}");
+        return Err(CommandError::Git(redact(message)));
+    }
+    log::info!("git: pushed {branch} from {}", repo.display());
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::fs;
+
+    pub(crate) fn test_author() -> GitAuthor {
+        GitAuthor {
+            name: "test".to_string(),
+            email: "test@example.com".to_string(),
+        }
+    }
+
+    /// Stage and commit every file in the working tree.
+    pub(crate) fn commit_all(repo: &Path, message: &str) -> String {
+        let repository = Repository::open(repo).unwrap();
+        let mut index = repository.index().unwrap();
+        index
+            .add_all(["*"].iter(), IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        commit(repo, message, &test_author()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn commits_and_pushes_a_branch_to_a_bare_origin() {
+        let tmp = tempfile::tempdir().unwrap();
+        let origin = tmp.path().join("origin.git");
+        let repo = tmp.path().join("repo");
+        Repository::init_bare(&origin).unwrap();
+        let mut updates = Vec::new();
+        clone(origin.to_str().unwrap(), &repo, None, &mut |p| {
+            updates.push(p)
+        })
+        .unwrap();
+        assert_eq!(origin_url(&repo).unwrap().as_deref(), origin.to_str());
+        fs::write(repo.join("README.md"), "migration repo\n").unwrap();
+        commit_all(&repo, "init");
+
+        checkout_branch(&repo, "migration-utility/plan").unwrap();
+        fs::write(repo.join("plan.md"), "# Plan\n").unwrap();
+        fs::create_dir_all(repo.join("lineage")).unwrap();
+        fs::write(repo.join("lineage/lineage.dot"), "digraph {}\n").unwrap();
+        fs::write(repo.join("notes.txt"), "not generated\n").unwrap();
+        assert_eq!(
+            dirty_paths(&repo).unwrap(),
+            vec!["lineage/lineage.dot", "notes.txt", "plan.md"]
+        );
+        stage(&repo, &["plan.md", "lineage", "dbt"]).unwrap();
+        let author = GitAuthor {
+            name: "octocat".to_string(),
+            email: "octocat@users.noreply.github.com".to_string(),
+        };
+        let sha = commit(&repo, "Update migration plan", &author)
+            .unwrap()
+            .expect("a commit");
+        assert_eq!(
+            commit(&repo, "Update migration plan", &author).unwrap(),
+            None
+        );
+        push(&repo, "migration-utility/plan", Some("gho_unused")).unwrap();
+
+        let bare = Repository::open_bare(&origin).unwrap();
+        let pushed = bare
+            .find_reference("refs/heads/migration-utility/plan")
+            .unwrap()
+            .peel_to_commit()
+            .unwrap();
+        assert_eq!(pushed.id().to_string(), sha);
+        assert_eq!(pushed.author().name(), Some("octocat"));
+        assert_eq!(
+            pushed.author().email(),
+            Some("octocat@users.noreply.github.com")
+        );
+        assert_eq!(pushed.message(), Some("Update migration plan"));
+        let tree = pushed.tree().unwrap();
+        assert!(tree.get_path(Path::new("lineage/lineage.dot")).is_ok());
+        assert!(tree.get_path(Path::new("notes.txt")).is_err());
+        assert_eq!(
+            read_head_file(&repo, "plan.md").as_deref(),
+            Some("# Plan\n")
+        );
+
+        // Checking the branch out again keeps its history.
+        let main = Repository::open(&repo)
+            .unwrap()
+            .branches(Some(BranchType::Local))
+            .unwrap()
+            .filter_map(|b| b.ok()?.0.name().ok().flatten().map(str::to_string))
+            .find(|name| name != "migration-utility/plan")
+            .unwrap();
+        checkout_branch(&repo, &main).unwrap();
+        checkout_branch(&repo, "migration-utility/plan").unwrap();
+        let head = Repository::open(&repo).unwrap().head().unwrap().target();
+        assert_eq!(head.unwrap().to_string(), sha);
+        assert!(checkout_branch(&repo, "bad..name").is_err());
+
+        fetch(&repo, None).unwrap();
+    }
+
+    #[test]
+    fn checkout_follows_the_remote_plan_branch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let origin = tmp.path().join("origin.git");
+        let (ours, theirs) = (tmp.path().join("ours"), tmp.path().join("theirs"));
+        Repository::init_bare(&origin).unwrap();
+        clone(origin.to_str().unwrap(), &theirs, None, &mut |_| {}).unwrap();
+        fs::write(theirs.join("README.md"), "migration repo\n").unwrap();
+        commit_all(&theirs, "init");
+        let main = status(&theirs).unwrap().unwrap().branch.unwrap();
+        push(&theirs, &main, None).unwrap();
+        checkout_branch(&theirs, "plan").unwrap();
+        fs::write(theirs.join("plan.md"), "# Plan v1\n").unwrap();
+        commit_all(&theirs, "v1");
+        push(&theirs, "plan", None).unwrap();
+
+        // A branch missing locally starts from its remote counterpart.
+        // The local transport cannot clone shallowly once the origin has history.
+        Repository::clone(origin.to_str().unwrap(), &ours).unwrap();
+        checkout_branch(&ours, "plan").unwrap();
+        assert_eq!(
+            read_head_file(&ours, "plan.md").as_deref(),
+            Some("# Plan v1\n")
+        );
+
+        // A local branch behind origin is fast-forwarded.
+        fs::write(theirs.join("plan.md"), "# Plan v2\n").unwrap();
+        let v2 = commit_all(&theirs, "v2");
+        push(&theirs, "plan", None).unwrap();
+        checkout_branch(&ours, &main).unwrap();
+        fetch(&ours, None).unwrap();
+        assert_eq!(branch_divergence(&ours, "plan").unwrap(), Some((0, 1)));
+        checkout_branch(&ours, "plan").unwrap();
+        let head = Repository::open(&ours).unwrap().head().unwrap().target();
+        assert_eq!(head.unwrap().to_string(), v2);
+        assert_eq!(
+            fs::read_to_string(ours.join("plan.md")).unwrap(),
+            "# Plan v2\n"
+        );
+
+        // Diverged branches are refused rather than overwritten.
+        fs::write(ours.join("plan.md"), "# Plan ours\n").unwrap();
+        commit_all(&ours, "ours");
+        fs::write(theirs.join("plan.md"), "# Plan theirs\n").unwrap();
+        commit_all(&theirs, "theirs");
+        push(&theirs, "plan", None).unwrap();
+        fetch(&ours, None).unwrap();
+        let err = checkout_branch(&ours, "plan").unwrap_err().to_string();
+        assert!(err.contains("diverged from origin/plan"), "{err}");
+    }
+
+    #[test]
+    fn empty_repos_commit_on_an_unborn_branch() {
+        let tmp = tempfile::tempdir().unwrap();
+        Repository::init(tmp.path()).unwrap();
+        checkout_branch(tmp.path(), "plan").unwrap();
+        stage(tmp.path(), &["plan.md"]).unwrap();
+        assert_eq!(commit(tmp.path(), "empty", &test_author()).unwrap(), None);
+        fs::write(tmp.path().join("plan.md"), "# Plan\n").unwrap();
+        stage(tmp.path(), &["plan.md"]).unwrap();
+        assert!(commit(tmp.path(), "plan", &test_author())
+            .unwrap()
+            .is_some());
+        assert_eq!(origin_url(tmp.path()).unwrap(), None);
+        assert!(push(tmp.path(), "plan", None).is_err());
+    }
+
+    #[test]
+    fn push_failures_do_not_leak_the_token() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repository = Repository::init(tmp.path()).unwrap();
+        repository
+            .remote("origin", "file:///nonexistent/gho_secret_token/repo.git")
+            .unwrap();
+        fs::write(tmp.path().join("plan.md"), "# Plan\n").unwrap();
+        commit_all(tmp.path(), "init");
+
+        let err = push(tmp.path(), "plan", Some("gho_secret_token")).unwrap_err();
+        let CommandError::Git(message) = err else {
+            panic!("expected a git error");
+        };
+        assert!(!message.contains("gho_secret_token"));
+        assert!(message.contains("***"), "{message}");
+    }
+}
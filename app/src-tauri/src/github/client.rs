@@ -0,0 +1,657 @@
+use base64::Engine;
+use reqwest::header::LINK;
+use reqwest::{Method, RequestBuilder, Response, StatusCode, Url};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::types::{CommandError, GitHubRepo};
+
+pub const DEFAULT_API_BASE_URL: &str = "https://api.github.com";
+const API_VERSION: &str = "2022-11-28";
+const USER_AGENT: &str = "MigrationUtility";
+/// Largest page size the REST API accepts.
+const PAGE_SIZE: &str = "100";
+/// Pages read before a listing gives up, so a huge org cannot stall the picker.
+const MAX_PAGES: usize = 50;
+
+/// Where the client sends REST requests: `https://ghe.example.com/api/v3` for GitHub
+/// Enterprise Server, or a local mock server in tests.
+#[derive(Clone, Debug)]
+pub struct GitHubEndpoints {
+    pub api_base_url: String,
+}
+
+impl Default for GitHubEndpoints {
+    fn default() -> Self {
+        Self {
+            api_base_url: DEFAULT_API_BASE_URL.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PullRequest {
+    pub number: i64,
+    pub html_url: String,
+}
+
+pub struct GitHubClient {
+    http: reqwest::Client,
+    endpoints: GitHubEndpoints,
+    token: String,
+}
+
+impl GitHubClient {
+    pub fn new(endpoints: GitHubEndpoints, token: String) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoints,
+            token,
+        }
+    }
+
+    pub async fn default_branch(&self, repo: &str) -> Result<String, CommandError> {
+        let body = self
+            .send(self.request(Method::GET, &format!("/repos/{repo}")))
+            .await?;
+        body["default_branch"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| CommandError::Io(format!("GitHub repo {repo} has no default branch")))
+    }
+
+    /// Text content of `path` at `git_ref`, or `None` when the file does not exist there.
+    pub async fn file_content(
+        &self,
+        repo: &str,
+        path: &str,
+        git_ref: &str,
+    ) -> Result<Option<String>, CommandError> {
+        let response = self
+            .request(Method::GET, &format!("/repos/{repo}/contents/{path}"))
+            .query(&[("ref", git_ref)])
+            .send()
+            .await
+            .map_err(request_failed)?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body = read_json(response).await?;
+        let encoded: String = body["content"]
+            .as_str()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| CommandError::Io(format!("Invalid content for {path}: {e}")))?;
+        String::from_utf8(bytes)
+            .map(Some)
+            .map_err(|e| CommandError::Io(format!("{path} is not UTF-8: {e}")))
+    }
+
+    /// The open pull request from `branch` of the same repo, if there is one.
+    pub async fn find_open_pull_request(
+        &self,
+        repo: &str,
+        branch: &str,
+    ) -> Result<Option<PullRequest>, CommandError> {
+        let owner = repo.split('/').next().unwrap_or_default();
+        let body = self
+            .send(
+                self.request(Method::GET, &format!("/repos/{repo}/pulls"))
+                    .query(&[
+                        ("head", format!("{owner}:{branch}").as_str()),
+                        ("state", "open"),
+                    ]),
+            )
+            .await?;
+        let pulls: Vec<PullRequest> = serde_json::from_value(body)
+            .map_err(|e| CommandError::Io(format!("Unexpected pull request list: {e}")))?;
+        Ok(pulls.into_iter().next())
+    }
+
+    pub async fn create_pull_request(
+        &self,
+        repo: &str,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+    ) -> Result<PullRequest, CommandError> {
+        let payload = json!({ "title": title, "head": head, "base": base, "body": body });
+        let response = self
+            .send(
+                self.request(Method::POST, &format!("/repos/{repo}/pulls"))
+                    .json(&payload),
+            )
+            .await?;
+        parse_pull_request(response)
+    }
+
+    pub async fn update_pull_request(
+        &self,
+        repo: &str,
+        number: i64,
+        title: &str,
+        body: &str,
+    ) -> Result<PullRequest, CommandError> {
+        let payload = json!({ "title": title, "body": body });
+        let response = self
+            .send(
+                self.request(Method::PATCH, &format!("/repos/{repo}/pulls/{number}"))
+                    .json(&payload),
+            )
+            .await?;
+        parse_pull_request(response)
+    }
+
+    /// Repos the user can access, most recently updated first, whose full name contains
+    /// `query` (case-insensitive). With `org`, only that organization's repos are listed.
+    /// Follows the `Link` header until `limit` repos match or the listing ends.
+    pub async fn list_repos(
+        &self,
+        org: Option<&str>,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubRepo>, CommandError> {
+        let first = match org {
+            Some(org) => self
+                .request(Method::GET, &format!("/orgs/{}/repos", path_segment(org)?))
+                .query(&[
+                    ("type", "all"),
+                    ("sort", "updated"),
+                    ("per_page", PAGE_SIZE),
+                ]),
+            None => self.request(Method::GET, "/user/repos").query(&[
+                ("sort", "updated"),
+                ("affiliation", "owner,collaborator,organization_member"),
+                ("per_page", PAGE_SIZE),
+            ]),
+        };
+        let query = query.trim().to_lowercase();
+        let mut repos = Vec::new();
+        let mut next = Some(first);
+        for _ in 0..MAX_PAGES {
+            let Some(request) = next.take() else {
+                break;
+            };
+            let (body, next_url) = self.send_page(request).await?;
+            let page = body
+                .as_array()
+                .ok_or_else(|| CommandError::Io("Unexpected repo list from GitHub".to_string()))?;
+            repos.extend(
+                page.iter().filter_map(parse_repo).filter(|repo| {
+                    query.is_empty() || repo.full_name.to_lowercase().contains(&query)
+                }),
+            );
+            if repos.len() >= limit {
+                repos.truncate(limit);
+                break;
+            }
+            next = next_url.map(|url| self.request_url(Method::GET, &url));
+        }
+        Ok(repos)
+    }
+
+    /// Logins of the organizations the user belongs to.
+    pub async fn list_orgs(&self) -> Result<Vec<String>, CommandError> {
+        let mut orgs = Vec::new();
+        let mut next = Some(
+            self.request(Method::GET, "/user/orgs")
+                .query(&[("per_page", PAGE_SIZE)]),
+        );
+        for _ in 0..MAX_PAGES {
+            let Some(request) = next.take() else {
+                break;
+            };
+            let (body, next_url) = self.send_page(request).await?;
+            orgs.extend(
+                body.as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|org| org["login"].as_str().map(str::to_string)),
+            );
+            next = next_url.map(|url| self.request_url(Method::GET, &url));
+        }
+        Ok(orgs)
+    }
+
+    /// Create a private repo `owner/name`. With a `template` (`owner/repo`), the repo is
+    /// generated from it; otherwise it starts with an initial commit on the default branch.
+    /// `owner` may be the signed-in user's login or an organization.
+    pub async fn create_repo(
+        &self,
+        owner: &str,
+        name: &str,
+        description: &str,
+        template: Option<&str>,
+        user_login: &str,
+    ) -> Result<GitHubRepo, CommandError> {
+        let request = match template {
+            Some(template) => self
+                .request(
+                    Method::POST,
+                    &format!("/repos/{}/generate", repo_path(template)?),
+                )
+                .json(&json!({
+                    "owner": owner,
+                    "name": name,
+                    "description": description,
+                    "private": true,
+                })),
+            None => {
+                let path = if owner.eq_ignore_ascii_case(user_login) {
+                    "/user/repos".to_string()
+                } else {
+                    format!("/orgs/{}/repos", path_segment(owner)?)
+                };
+                self.request(Method::POST, &path).json(&json!({
+                    "name": name,
+                    "description": description,
+                    "private": true,
+                    "auto_init": true,
+                }))
+            }
+        };
+        let body = self.send(request).await?;
+        parse_repo(&body)
+            .ok_or_else(|| CommandError::Io("Unexpected repo response from GitHub".to_string()))
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.request_url(method, &format!("{}{}", self.endpoints.api_base_url, path))
+    }
+
+    fn request_url(&self, method: Method, url: &str) -> RequestBuilder {
+        self.http
+            .request(method, url)
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", USER_AGENT)
+            .header("X-GitHub-Api-Version", API_VERSION)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Value, CommandError> {
+        read_json(request.send().await.map_err(request_failed)?).await
+    }
+
+    /// A page of a listing and the URL of the next page, if any. A next page on another
+    /// origin than the API is refused, since following it would send the token there.
+    async fn send_page(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<(Value, Option<String>), CommandError> {
+        let response = request.send().await.map_err(request_failed)?;
+        let next = response
+            .headers()
+            .get(LINK)
+            .and_then(|value| value.to_str().ok())
+            .and_then(next_link);
+        if let Some(url) = &next {
+            if !same_origin(url, &self.endpoints.api_base_url) {
+                log::error!("GitHub listing points its next page at another origin: {url}");
+                return Err(CommandError::Io(format!(
+                    "GitHub returned a next page outside {}: {url}",
+                    self.endpoints.api_base_url
+                )));
+            }
+        }
+        Ok((read_json(response).await?, next))
+    }
+}
+
+/// The `rel="next"` URL of a `Link` header.
+fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let (url, params) = link.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="next""#)
+            .then(|| {
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            })
+    })
+}
+
+fn same_origin(url: &str, base: &str) -> bool {
+    match (Url::parse(url), Url::parse(base)) {
+        (Ok(url), Ok(base)) => url.origin() == base.origin(),
+        _ => false,
+    }
+}
+
+/// An owner or repo name checked before it goes into a request path. GitHub names are
+/// letters, digits, `-`, `_` and `.`.
+fn path_segment(name: &str) -> Result<&str, CommandError> {
+    let valid = !name.is_empty()
+        && name != "."
+        && name != ".."
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(name)
+    } else {
+        Err(CommandError::Io(format!("Invalid GitHub name: {name}")))
+    }
+}
+
+/// An `owner/repo` full name checked segment by segment.
+fn repo_path(full_name: &str) -> Result<String, CommandError> {
+    let (owner, repo) = full_name
+        .split_once('/')
+        .ok_or_else(|| CommandError::Io(format!("Invalid GitHub repo: {full_name}")))?;
+    Ok(format!("{}/{}", path_segment(owner)?, path_segment(repo)?))
+}
+
+fn parse_repo(repo: &Value) -> Option<GitHubRepo> {
+    Some(GitHubRepo {
+        id: repo["id"].as_i64()?,
+        full_name: repo["full_name"].as_str()?.to_string(),
+        private: repo["private"].as_bool().unwrap_or(false),
+    })
+}
+
+fn request_failed(e: reqwest::Error) -> CommandError {
+    CommandError::Io(format!("GitHub request failed: {e}"))
+}
+
+fn parse_pull_request(body: Value) -> Result<PullRequest, CommandError> {
+    serde_json::from_value(body)
+        .map_err(|e| CommandError::Io(format!("Unexpected pull request response: {e}")))
+}
+
+async fn read_json(response: Response) -> Result<Value, CommandError> {
+    let status = response.status();
+    let path = response.url().path().to_string();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| CommandError::Io(format!("Failed to read GitHub response: {e}")))?;
+    if !status.is_success() {
+        let message = serde_json::from_str::<Value>(&text)
+            .ok()
+            .and_then(|body| body["message"].as_str().map(str::to_string))
+            .unwrap_or(text);
+        return Err(CommandError::Io(format!(
+            "GitHub request {path} failed ({status}): {message}"
+        )));
+    }
+    serde_json::from_str(&text)
+        .map_err(|e| CommandError::Io(format!("Invalid JSON from GitHub {path}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn client(server: &mockito::ServerGuard) -> GitHubClient {
+        GitHubClient::new(
+            GitHubEndpoints {
+                api_base_url: format!("{}/api/v3", server.url()),
+            },
+            "gho_token".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn reads_repo_metadata_and_file_content() {
+        let mut server = mockito::Server::new_async().await;
+        let repo = server
+            .mock("GET", "/api/v3/repos/acme/plans")
+            .match_header("authorization", "Bearer gho_token")
+            .with_body(r#"{"default_branch":"main"}"#)
+            .create_async()
+            .await;
+        let content = server
+            .mock("GET", "/api/v3/repos/acme/plans/contents/plan.json")
+            .match_query(Matcher::UrlEncoded("ref".into(), "main".into()))
+            .with_body(r#"{"encoding":"base64","content":"eyJh\nIjoxfQ==\n"}"#)
+            .create_async()
+            .await;
+        let missing = server
+            .mock("GET", "/api/v3/repos/acme/plans/contents/plan.json")
+            .match_query(Matcher::UrlEncoded("ref".into(), "empty".into()))
+            .with_status(404)
+            .with_body(r#"{"message":"Not Found"}"#)
+            .create_async()
+            .await;
+
+        let client = client(&server);
+        assert_eq!(client.default_branch("acme/plans").await.unwrap(), "main");
+        assert_eq!(
+            client
+                .file_content("acme/plans", "plan.json", "main")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(r#"{"a":1}"#)
+        );
+        assert_eq!(
+            client
+                .file_content("acme/plans", "plan.json", "empty")
+                .await
+                .unwrap(),
+            None
+        );
+        repo.assert_async().await;
+        content.assert_async().await;
+        missing.assert_async().await;
+    }
+
+    #[test]
+    fn next_link_reads_the_next_relation() {
+        let header = r#"<https://api.github.com/user/repos?page=3>; rel="next", <https://api.github.com/user/repos?page=9>; rel="last""#;
+        assert_eq!(
+            next_link(header).as_deref(),
+            Some("https://api.github.com/user/repos?page=3")
+        );
+        assert_eq!(
+            next_link(r#"<https://api.github.com/user/repos?page=1>; rel="prev""#),
+            None
+        );
+    }
+
+    #[test]
+    fn request_paths_only_take_github_names() {
+        assert_eq!(path_segment("acme-data.io").unwrap(), "acme-data.io");
+        for name in ["", "..", "acme/other", "acme?type=all", "acme#x", "a b"] {
+            assert!(path_segment(name).is_err(), "{name}");
+        }
+        assert_eq!(repo_path("acme/migration_t").unwrap(), "acme/migration_t");
+        assert!(repo_path("acme").is_err());
+        assert!(repo_path("acme/../../user").is_err());
+        assert!(same_origin(
+            "https://api.github.com/user/repos?page=2",
+            "https://api.github.com"
+        ));
+        assert!(!same_origin(
+            "https://evil.example.com/user/repos?page=2",
+            "https://api.github.com"
+        ));
+        assert!(!same_origin(
+            "http://api.github.com/user/repos",
+            "https://api.github.com"
+        ));
+    }
+
+    #[tokio::test]
+    async fn listings_refuse_a_next_page_on_another_origin() {
+        let mut server = mockito::Server::new_async().await;
+        let page = server
+            .mock("GET", "/api/v3/user/orgs")
+            .match_query(Matcher::Any)
+            .with_header(
+                "link",
+                r#"<https://evil.example.com/user/orgs?page=2>; rel="next""#,
+            )
+            .with_body(r#"[{"login":"acme"}]"#)
+            .create_async()
+            .await;
+
+        let err = client(&server).list_orgs().await.unwrap_err();
+        assert!(
+            matches!(&err, CommandError::Io(message) if message.contains("evil.example.com")),
+            "{err:?}"
+        );
+        page.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn list_repos_follows_pages_until_the_limit() {
+        let mut server = mockito::Server::new_async().await;
+        let next = format!("{}/api/v3/organizations/9/repos?page=2", server.url());
+        let first = server
+            .mock("GET", "/api/v3/orgs/acme/repos")
+            .match_query(Matcher::UrlEncoded("per_page".into(), "100".into()))
+            .with_header("link", &format!(r#"<{next}>; rel="next""#))
+            .with_body(
+                r#"[{"id":1,"full_name":"acme/web","private":false},
+                    {"id":2,"full_name":"acme/migration-sales","private":true}]"#,
+            )
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/api/v3/organizations/9/repos")
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_body(
+                r#"[{"id":3,"full_name":"acme/Migration-HR","private":true},
+                    {"id":4,"full_name":"acme/migration-ops","private":true}]"#,
+            )
+            .create_async()
+            .await;
+
+        let repos = client(&server)
+            .list_repos(Some("acme"), "migration", 2)
+            .await
+            .unwrap();
+        let names: Vec<_> = repos.iter().map(|repo| repo.full_name.as_str()).collect();
+        assert_eq!(names, vec!["acme/migration-sales", "acme/Migration-HR"]);
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn create_repo_generates_from_a_template_or_starts_empty() {
+        let mut server = mockito::Server::new_async().await;
+        let generate = server
+            .mock("POST", "/api/v3/repos/acme/migration-template/generate")
+            .match_body(Matcher::PartialJson(json!({
+                "owner": "acme",
+                "name": "migration-sales",
+                "private": true,
+            })))
+            .with_status(201)
+            .with_body(r#"{"id":5,"full_name":"acme/migration-sales","private":true}"#)
+            .create_async()
+            .await;
+        let personal = server
+            .mock("POST", "/api/v3/user/repos")
+            .match_body(Matcher::PartialJson(json!({
+                "name": "scratch",
+                "private": true,
+                "auto_init": true,
+            })))
+            .with_status(201)
+            .with_body(r#"{"id":6,"full_name":"octocat/scratch","private":true}"#)
+            .create_async()
+            .await;
+
+        let client = client(&server);
+        let generated = client
+            .create_repo(
+                "acme",
+                "migration-sales",
+                "Sales",
+                Some("acme/migration-template"),
+                "octocat",
+            )
+            .await
+            .unwrap();
+        assert_eq!(generated.full_name, "acme/migration-sales");
+        let created = client
+            .create_repo("OctoCat", "scratch", "", None, "octocat")
+            .await
+            .unwrap();
+        assert_eq!(created.id, 6);
+        generate.assert_async().await;
+        personal.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn creates_and_updates_pull_requests() {
+        let mut server = mockito::Server::new_async().await;
+        let list = server
+            .mock("GET", "/api/v3/repos/acme/plans/pulls")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("head".into(), "acme:migration-utility/plan".into()),
+                Matcher::UrlEncoded("state".into(), "open".into()),
+            ]))
+            .with_body("[]")
+            .create_async()
+            .await;
+        let create = server
+            .mock("POST", "/api/v3/repos/acme/plans/pulls")
+            .match_body(Matcher::PartialJson(json!({
+                "head": "migration-utility/plan",
+                "base": "main",
+                "title": "Plan",
+            })))
+            .with_status(201)
+            .with_body(r#"{"number":7,"html_url":"https://github.com/acme/plans/pull/7"}"#)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PATCH", "/api/v3/repos/acme/plans/pulls/7")
+            .match_body(Matcher::PartialJson(json!({ "body": "updated" })))
+            .with_body(r#"{"number":7,"html_url":"https://github.com/acme/plans/pull/7"}"#)
+            .create_async()
+            .await;
+        let failing = server
+            .mock("PATCH", "/api/v3/repos/acme/plans/pulls/8")
+            .with_status(422)
+            .with_body(r#"{"message":"Validation Failed"}"#)
+            .create_async()
+            .await;
+
+        let client = client(&server);
+        assert_eq!(
+            client
+                .find_open_pull_request("acme/plans", "migration-utility/plan")
+                .await
+                .unwrap(),
+            None
+        );
+        let created = client
+            .create_pull_request(
+                "acme/plans",
+                "migration-utility/plan",
+                "main",
+                "Plan",
+                "body",
+            )
+            .await
+            .unwrap();
+        assert_eq!(created.number, 7);
+        let updated = client
+            .update_pull_request("acme/plans", 7, "Plan", "updated")
+            .await
+            .unwrap();
+        assert_eq!(updated, created);
+        let err = client
+            .update_pull_request("acme/plans", 8, "Plan", "body")
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("(422 Unprocessable Entity): Validation Failed"));
+        for mock in [list, create, update, failing] {
+            mock.assert_async().await;
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use rusqlite::Connection;
+
+use super::client::{GitHubEndpoints, DEFAULT_API_BASE_URL};
+use crate::types::GitHubConfig;
+
+pub const DEFAULT_HOST: &str = "github.com";
+pub const DEFAULT_OAUTH_CLIENT_ID: &str = "Ov23lioPbQz4gAFxEfhM";
+
+/// The GitHub installation the app talks to: github.com or a GitHub Enterprise Server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitHubInstance {
+    /// Host (and port) of the web UI and HTTPS git remotes.
+    pub host: String,
+    /// Base URL of the web UI, where the OAuth device flow endpoints live.
+    pub web_base_url: String,
+    pub api_base_url: String,
+    pub oauth_client_id: String,
+}
+
+impl Default for GitHubInstance {
+    fn default() -> Self {
+        Self {
+            host: DEFAULT_HOST.to_string(),
+            web_base_url: format!("https://{DEFAULT_HOST}"),
+            api_base_url: DEFAULT_API_BASE_URL.to_string(),
+            oauth_client_id: DEFAULT_OAUTH_CLIENT_ID.to_string(),
+        }
+    }
+}
+
+fn non_blank(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(|v| v.trim().trim_end_matches('/'))
+        .filter(|v| !v.is_empty())
+}
+
+impl GitHubInstance {
+    /// Resolve saved settings, filling blanks with github.com defaults. A GHES host without
+    /// an explicit API base uses `https://<host>/api/v3`.
+    pub fn resolve(config: &GitHubConfig) -> Result<Self, String> {
+        let defaults = Self::default();
+        let (scheme, host) = match non_blank(&config.host) {
+            None => ("https", defaults.host.clone()),
+            Some(host) => match host.split_once("://") {
+                Some((scheme @ ("https" | "http"), host)) => (scheme, host.to_lowercase()),
+                Some((scheme, _)) => {
+                    return Err(format!("Unsupported GitHub host scheme: {scheme}"));
+                }
+                None => ("https", host.to_lowercase()),
+            },
+        };
+        if host.is_empty() || host.contains(['/', '@', ' ']) {
+            return Err(format!("Invalid GitHub host: {host}"));
+        }
+        let web_base_url = format!("{scheme}://{host}");
+        let api_base_url = match non_blank(&config.api_base_url) {
+            Some(url) if url.starts_with("https://") || url.starts_with("http://") => {
+                url.to_string()
+            }
+            Some(url) => return Err(format!("GitHub API base must be an http(s) URL: {url}")),
+            None if host == DEFAULT_HOST => defaults.api_base_url,
+            None => format!("{web_base_url}/api/v3"),
+        };
+        let oauth_client_id = non_blank(&config.oauth_client_id)
+            .map(str::to_string)
+            .unwrap_or(defaults.oauth_client_id);
+        Ok(Self {
+            host,
+            web_base_url,
+            api_base_url,
+            oauth_client_id,
+        })
+    }
+
+    /// The installation configured in the settings table.
+    pub fn load(conn: &Connection) -> Result<Self, String> {
+        Self::resolve(&crate::db::read_github_config(conn)?)
+    }
+
+    pub fn endpoints(&self) -> GitHubEndpoints {
+        GitHubEndpoints {
+            api_base_url: self.api_base_url.clone(),
+        }
+    }
+
+    /// Commit email GitHub attributes to `login` without exposing a real address.
+    pub fn noreply_email(&self, login: &str) -> String {
+        format!("{login}@users.noreply.{}", self.host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(host: &str, api: &str, client_id: &str) -> GitHubConfig {
+        let field = |v: &str| Some(v.to_string()).filter(|v| !v.is_empty());
+        GitHubConfig {
+            host: field(host),
+            api_base_url: field(api),
+            oauth_client_id: field(client_id),
+        }
+    }
+
+    #[test]
+    fn resolve_defaults_to_github_com_and_derives_ghes_urls() {
+        assert_eq!(
+            GitHubInstance::resolve(&GitHubConfig::default()).unwrap(),
+            GitHubInstance::default()
+        );
+
+        let ghes = GitHubInstance::resolve(&config(" GHE.example.com/ ", "", "Iv1.abc")).unwrap();
+        assert_eq!(ghes.host, "ghe.example.com");
+        assert_eq!(ghes.web_base_url, "https://ghe.example.com");
+        assert_eq!(ghes.api_base_url, "https://ghe.example.com/api/v3");
+        assert_eq!(ghes.oauth_client_id, "Iv1.abc");
+        assert_eq!(
+            ghes.noreply_email("octocat"),
+            "octocat@users.noreply.ghe.example.com"
+        );
+
+        let custom = GitHubInstance::resolve(&config(
+            "http://localhost:8080",
+            "http://localhost:8080/api/",
+            "",
+        ))
+        .unwrap();
+        assert_eq!(custom.host, "localhost:8080");
+        assert_eq!(custom.api_base_url, "http://localhost:8080/api");
+        assert_eq!(custom.oauth_client_id, DEFAULT_OAUTH_CLIENT_ID);
+
+        assert!(GitHubInstance::resolve(&config("ssh://ghe.example.com", "", "")).is_err());
+        assert!(GitHubInstance::resolve(&config("ghe.example.com/org", "", "")).is_err());
+        assert!(GitHubInstance::resolve(&config("", "ghe.example.com/api", "")).is_err());
+    }
+
+    #[test]
+    fn load_reads_the_saved_config() {
+        let conn = crate::db::open_in_memory().unwrap();
+        assert_eq!(
+            GitHubInstance::load(&conn).unwrap(),
+            GitHubInstance::default()
+        );
+        crate::db::write_github_config(&conn, &config("ghe.example.com", "", "")).unwrap();
+        assert_eq!(GitHubInstance::load(&conn).unwrap().host, "ghe.example.com");
+    }
+}
@@ -0,0 +1,568 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use rusqlite::{params, Connection};
+
+use super::pipeline::parse_dependencies;
+use crate::types::{
+    GraphEdge, GraphEdgeRelation, GraphNeighbor, GraphNode, GraphNodeKind, ImpactAnalysis,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Upstream,
+    Downstream,
+}
+
+/// Workspace dependency graph over data objects, procedure lineage, orchestration activity
+/// links and pipeline activity dependencies. Edges point in data-flow direction.
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    nodes: Vec<GraphNode>,
+    index: HashMap<String, usize>,
+    edges: Vec<GraphEdge>,
+    outgoing: Vec<Vec<usize>>,
+    incoming: Vec<Vec<usize>>,
+}
+
+impl DependencyGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a node, keeping the first definition when the id is already present.
+    pub fn add_node(&mut self, node: GraphNode) {
+        if self.index.contains_key(&node.id) {
+            return;
+        }
+        self.index.insert(node.id.clone(), self.nodes.len());
+        self.nodes.push(node);
+        self.outgoing.push(Vec::new());
+        self.incoming.push(Vec::new());
+    }
+
+    /// Add an edge between two known nodes. Unknown endpoints and duplicates are ignored.
+    pub fn add_edge(&mut self, from: &str, to: &str, relation: GraphEdgeRelation) {
+        let (Some(&f), Some(&t)) = (self.index.get(from), self.index.get(to)) else {
+            return;
+        };
+        if self.outgoing[f].iter().any(|&e| {
+            let edge = &self.edges[e];
+            edge.to == to && edge.relation == relation
+        }) {
+            return;
+        }
+        let edge_index = self.edges.len();
+        self.edges.push(GraphEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            relation,
+        });
+        self.outgoing[f].push(edge_index);
+        self.incoming[t].push(edge_index);
+    }
+
+    pub fn nodes(&self) -> &[GraphNode] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[GraphEdge] {
+        &self.edges
+    }
+
+    pub fn node(&self, id: &str) -> Option<&GraphNode> {
+        self.index.get(id).map(|&i| &self.nodes[i])
+    }
+
+    fn neighbours(&self, node: usize, direction: Direction) -> impl Iterator<Item = usize> + '_ {
+        let (edges, pick): (&[usize], fn(&GraphEdge) -> &str) = match direction {
+            Direction::Upstream => (&self.incoming[node], |e| &e.from),
+            Direction::Downstream => (&self.outgoing[node], |e| &e.to),
+        };
+        edges.iter().map(move |&e| self.index[pick(&self.edges[e])])
+    }
+
+    /// Breadth-first walk from `id`, excluding the start node. Results are ordered by depth,
+    /// then id. `max_depth` of `None` walks the full closure.
+    pub fn traverse(
+        &self,
+        id: &str,
+        direction: Direction,
+        max_depth: Option<usize>,
+    ) -> Vec<GraphNeighbor> {
+        let Some(&start) = self.index.get(id) else {
+            return Vec::new();
+        };
+        let mut depth = vec![usize::MAX; self.nodes.len()];
+        depth[start] = 0;
+        let mut queue = VecDeque::from([start]);
+        let mut reached = Vec::new();
+        while let Some(current) = queue.pop_front() {
+            let next_depth = depth[current] + 1;
+            if max_depth.is_some_and(|max| next_depth > max) {
+                continue;
+            }
+            for next in self.neighbours(current, direction) {
+                if depth[next] == usize::MAX {
+                    depth[next] = next_depth;
+                    reached.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+        let mut result: Vec<GraphNeighbor> = reached
+            .into_iter()
+            .map(|i| GraphNeighbor {
+                node: self.nodes[i].clone(),
+                depth: depth[i],
+            })
+            .collect();
+        result.sort_by(|a, b| {
+            a.depth
+                .cmp(&b.depth)
+                .then_with(|| a.node.id.cmp(&b.node.id))
+        });
+        result
+    }
+
+    /// Strongly connected components that form a cycle: more than one node, or a single node
+    /// with an edge to itself. Members and components are sorted by id.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<GraphNode>> {
+        let mut components: Vec<Vec<GraphNode>> = tarjan(self)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || self
+                        .neighbours(component[0], Direction::Downstream)
+                        .any(|n| n == component[0])
+            })
+            .map(|component| {
+                let mut members: Vec<GraphNode> = component
+                    .into_iter()
+                    .map(|i| self.nodes[i].clone())
+                    .collect();
+                members.sort_by(|a, b| a.id.cmp(&b.id));
+                members
+            })
+            .collect();
+        components.sort_by(|a, b| a[0].id.cmp(&b[0].id));
+        components
+    }
+
+    /// What must move with `id` (its upstream closure) and what it affects downstream.
+    pub fn impact(&self, id: &str) -> Option<ImpactAnalysis> {
+        let root = self.node(id)?.clone();
+        let required = self.traverse(id, Direction::Upstream, None);
+        let affected = self.traverse(id, Direction::Downstream, None);
+        let involved: BTreeSet<&str> = required
+            .iter()
+            .map(|n| n.node.id.as_str())
+            .chain(std::iter::once(id))
+            .collect();
+        let cycles = self
+            .strongly_connected_components()
+            .into_iter()
+            .filter(|component| component.iter().any(|n| involved.contains(n.id.as_str())))
+            .collect();
+        Some(ImpactAnalysis {
+            root,
+            required,
+            affected,
+            cycles,
+        })
+    }
+
+    /// Build the graph for one workspace from SQLite.
+    pub fn load(conn: &Connection, workspace_id: &str) -> rusqlite::Result<Self> {
+        let mut graph = Self::new();
+        load_data_objects(conn, workspace_id, &mut graph)?;
+        load_procedure_lineage(conn, workspace_id, &mut graph)?;
+        load_orchestration_links(conn, workspace_id, &mut graph)?;
+        load_pipeline_activities(conn, workspace_id, &mut graph)?;
+        Ok(graph)
+    }
+}
+
+/// Iterative Tarjan; returns every component as node indices.
+fn tarjan(graph: &DependencyGraph) -> Vec<Vec<usize>> {
+    let n = graph.nodes.len();
+    let mut index = vec![usize::MAX; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+
+    for root in 0..n {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        // (node, position in its outgoing edge list)
+        let mut work = vec![(root, 0usize)];
+        while let Some(&mut (v, ref mut pos)) = work.last_mut() {
+            if *pos == 0 && index[v] == usize::MAX {
+                index[v] = next_index;
+                lowlink[v] = next_index;
+                next_index += 1;
+                stack.push(v);
+                on_stack[v] = true;
+            }
+            if let Some(&edge) = graph.outgoing[v].get(*pos) {
+                *pos += 1;
+                let w = graph.index[&graph.edges[edge].to];
+                if index[w] == usize::MAX {
+                    work.push((w, 0));
+                } else if on_stack[w] {
+                    lowlink[v] = lowlink[v].min(index[w]);
+                }
+                continue;
+            }
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[v]);
+            }
+            if lowlink[v] == index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+const WORKSPACE_OBJECTS_JOIN: &str = "JOIN namespaces n ON n.id = o.namespace_id
+     JOIN containers c ON c.id = n.container_id
+     JOIN sources s ON s.id = c.source_id";
+
+fn load_data_objects(
+    conn: &Connection,
+    workspace_id: &str,
+    graph: &mut DependencyGraph,
+) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT o.id, n.namespace_name, o.object_name, o.object_type
+         FROM data_objects o {WORKSPACE_OBJECTS_JOIN}
+         WHERE s.workspace_id = ?1
+         ORDER BY o.id"
+    ))?;
+    let rows = stmt.query_map(params![workspace_id], |row| {
+        let object_type: String = row.get(3)?;
+        Ok(GraphNode {
+            id: row.get(0)?,
+            kind: object_kind(&object_type),
+            schema_name: Some(row.get(1)?),
+            name: row.get(2)?,
+        })
+    })?;
+    for node in rows {
+        graph.add_node(node?);
+    }
+    Ok(())
+}
+
+fn object_kind(object_type: &str) -> GraphNodeKind {
+    match object_type {
+        "table" => GraphNodeKind::Table,
+        "view" => GraphNodeKind::View,
+        "procedure" => GraphNodeKind::Procedure,
+        "function" => GraphNodeKind::Function,
+        _ => GraphNodeKind::Unknown,
+    }
+}
+
+fn load_procedure_lineage(
+    conn: &Connection,
+    workspace_id: &str,
+    graph: &mut DependencyGraph,
+) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT l.procedure_data_object_id, l.table_data_object_id, l.lineage_type
+         FROM sqlserver_procedure_lineage l
+         JOIN data_objects o ON o.id = l.procedure_data_object_id
+         {WORKSPACE_OBJECTS_JOIN}
+         WHERE s.workspace_id = ?1
+         ORDER BY l.id"
+    ))?;
+    let rows = stmt.query_map(params![workspace_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (procedure, table, lineage_type) = row?;
+        if lineage_type.as_deref() == Some("write") {
+            graph.add_edge(&procedure, &table, GraphEdgeRelation::Writes);
+        } else {
+            graph.add_edge(&table, &procedure, GraphEdgeRelation::Reads);
+        }
+    }
+    Ok(())
+}
+
+fn load_orchestration_links(
+    conn: &Connection,
+    workspace_id: &str,
+    graph: &mut DependencyGraph,
+) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT a.id, i.orchestration_name, a.activity_name
+         FROM orchestration_activities a
+         JOIN orchestration_items i ON i.id = a.orchestration_item_id
+         JOIN sources s ON s.id = i.source_id
+         WHERE s.workspace_id = ?1
+         ORDER BY a.id",
+    )?;
+    let rows = stmt.query_map(params![workspace_id], |row| {
+        Ok(GraphNode {
+            id: row.get(0)?,
+            kind: GraphNodeKind::OrchestrationActivity,
+            schema_name: None,
+            name: format!("{}/{}", row.get::<_, String>(1)?, row.get::<_, String>(2)?),
+        })
+    })?;
+    for node in rows {
+        graph.add_node(node?);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT l.orchestration_activity_id, l.data_object_id, l.access_type
+         FROM activity_object_links l
+         JOIN orchestration_activities a ON a.id = l.orchestration_activity_id
+         JOIN orchestration_items i ON i.id = a.orchestration_item_id
+         JOIN sources s ON s.id = i.source_id
+         WHERE s.workspace_id = ?1
+         ORDER BY l.id",
+    )?;
+    let rows = stmt.query_map(params![workspace_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (activity, object, access_type) = row?;
+        match access_type.as_str() {
+            "write" => graph.add_edge(&activity, &object, GraphEdgeRelation::Writes),
+            "read" => graph.add_edge(&object, &activity, GraphEdgeRelation::Reads),
+            _ => graph.add_edge(&object, &activity, GraphEdgeRelation::References),
+        }
+    }
+    Ok(())
+}
+
+/// Node id for a row in `pipeline_activities`.
+pub fn pipeline_activity_node_id(activity_id: i64) -> String {
+    format!("pipeline-activity-{activity_id}")
+}
+
+struct PipelineActivityRow {
+    id: i64,
+    pipeline_item_id: String,
+    pipeline_name: String,
+    activity_name: String,
+    target_schema_name: Option<String>,
+    target_procedure_name: Option<String>,
+    depends_on_json: Option<String>,
+}
+
+fn load_pipeline_activities(
+    conn: &Connection,
+    workspace_id: &str,
+    graph: &mut DependencyGraph,
+) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT pa.id, pa.pipeline_item_id, i.display_name, pa.activity_name,
+                pa.target_schema_name, pa.target_procedure_name, pa.depends_on_json
+         FROM pipeline_activities pa
+         JOIN items i ON i.id = pa.pipeline_item_id
+         WHERE i.workspace_id = ?1
+         ORDER BY pa.id",
+    )?;
+    let activities = stmt
+        .query_map(params![workspace_id], |row| {
+            Ok(PipelineActivityRow {
+                id: row.get(0)?,
+                pipeline_item_id: row.get(1)?,
+                pipeline_name: row.get(2)?,
+                activity_name: row.get(3)?,
+                target_schema_name: row.get(4)?,
+                target_procedure_name: row.get(5)?,
+                depends_on_json: row.get(6)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let procedures: HashMap<(String, String), String> = graph
+        .nodes
+        .iter()
+        .filter(|n| n.kind == GraphNodeKind::Procedure)
+        .filter_map(|n| {
+            let schema = n.schema_name.as_ref()?.to_lowercase();
+            Some(((schema, n.name.to_lowercase()), n.id.clone()))
+        })
+        .collect();
+    let by_name: HashMap<(&str, String), i64> = activities
+        .iter()
+        .map(|a| {
+            (
+                (a.pipeline_item_id.as_str(), a.activity_name.to_lowercase()),
+                a.id,
+            )
+        })
+        .collect();
+
+    for activity in &activities {
+        graph.add_node(GraphNode {
+            id: pipeline_activity_node_id(activity.id),
+            kind: GraphNodeKind::PipelineActivity,
+            schema_name: None,
+            name: format!("{}/{}", activity.pipeline_name, activity.activity_name),
+        });
+    }
+    for activity in &activities {
+        let node_id = pipeline_activity_node_id(activity.id);
+        if let Some(procedure) = activity.target_procedure_name.as_deref() {
+            let schema = activity.target_schema_name.as_deref().unwrap_or("dbo");
+            if let Some(target) = procedures.get(&(schema.to_lowercase(), procedure.to_lowercase()))
+            {
+                graph.add_edge(&node_id, target, GraphEdgeRelation::Executes);
+            }
+        }
+        for upstream in parse_depends_on(activity.depends_on_json.as_deref()) {
+            let key = (activity.pipeline_item_id.as_str(), upstream.to_lowercase());
+            if let Some(&upstream_id) = by_name.get(&key) {
+                graph.add_edge(
+                    &pipeline_activity_node_id(upstream_id),
+                    &node_id,
+                    GraphEdgeRelation::Precedes,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Activity names from a `dependsOn` array; see [`parse_dependencies`] for accepted shapes.
+/// Unparseable values are logged and treated as no dependencies.
+pub fn parse_depends_on(depends_on_json: Option<&str>) -> Vec<String> {
+    match parse_dependencies(depends_on_json) {
+        Ok(dependencies) => dependencies.into_iter().map(|d| d.activity).collect(),
+        Err(reason) => {
+            log::warn!("graph: ignoring depends_on_json: {reason}");
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, kind: GraphNodeKind) -> GraphNode {
+        GraphNode {
+            id: id.to_string(),
+            kind,
+            schema_name: Some("dbo".to_string()),
+            name: id.to_string(),
+        }
+    }
+
+    fn ids(neighbors: &[GraphNeighbor]) -> Vec<(&str, usize)> {
+        neighbors
+            .iter()
+            .map(|n| (n.node.id.as_str(), n.depth))
+            .collect()
+    }
+
+    /// stg -> load_fact -> fact -> load_agg -> agg, plus a self-feeding load_fact over fact.
+    fn sample_graph() -> DependencyGraph {
+        let mut graph = DependencyGraph::new();
+        for (id, kind) in [
+            ("stg", GraphNodeKind::Table),
+            ("fact", GraphNodeKind::Table),
+            ("agg", GraphNodeKind::Table),
+            ("load_fact", GraphNodeKind::Procedure),
+            ("load_agg", GraphNodeKind::Procedure),
+        ] {
+            graph.add_node(node(id, kind));
+        }
+        graph.add_edge("stg", "load_fact", GraphEdgeRelation::Reads);
+        graph.add_edge("load_fact", "fact", GraphEdgeRelation::Writes);
+        graph.add_edge("fact", "load_fact", GraphEdgeRelation::Reads);
+        graph.add_edge("fact", "load_agg", GraphEdgeRelation::Reads);
+        graph.add_edge("load_agg", "agg", GraphEdgeRelation::Writes);
+        graph.add_edge("load_agg", "agg", GraphEdgeRelation::Writes);
+        graph.add_edge("load_agg", "missing", GraphEdgeRelation::Writes);
+        graph
+    }
+
+    #[test]
+    fn traverse_walks_both_directions_with_depth_limit() {
+        let graph = sample_graph();
+        assert_eq!(graph.edges().len(), 5);
+        assert_eq!(
+            ids(&graph.traverse("agg", Direction::Upstream, None)),
+            vec![("load_agg", 1), ("fact", 2), ("load_fact", 3), ("stg", 4)]
+        );
+        assert_eq!(
+            ids(&graph.traverse("stg", Direction::Downstream, Some(2))),
+            vec![("load_fact", 1), ("fact", 2)]
+        );
+        assert!(graph.traverse("nope", Direction::Upstream, None).is_empty());
+    }
+
+    #[test]
+    fn strongly_connected_components_report_cycles_only() {
+        let mut graph = sample_graph();
+        graph.add_node(node("self_loop", GraphNodeKind::Procedure));
+        graph.add_edge("self_loop", "self_loop", GraphEdgeRelation::Precedes);
+        let components: Vec<Vec<String>> = graph
+            .strongly_connected_components()
+            .into_iter()
+            .map(|c| c.into_iter().map(|n| n.id).collect())
+            .collect();
+        assert_eq!(
+            components,
+            vec![
+                vec!["fact".to_string(), "load_fact".to_string()],
+                vec!["self_loop".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn impact_lists_required_affected_and_cycles() {
+        let graph = sample_graph();
+        let impact = graph.impact("agg").unwrap();
+        assert_eq!(impact.required.len(), 4);
+        assert!(impact.affected.is_empty());
+        assert_eq!(impact.cycles.len(), 1);
+
+        let impact = graph.impact("stg").unwrap();
+        assert!(impact.required.is_empty());
+        assert_eq!(impact.affected.len(), 4);
+        assert!(impact.cycles.is_empty());
+        assert!(graph.impact("nope").is_none());
+    }
+
+    #[test]
+    fn parse_depends_on_accepts_pipeline_and_plain_shapes() {
+        assert_eq!(
+            parse_depends_on(Some(
+                r#"[{"activity":"Stage","dependencyConditions":["Succeeded"]},"Load"]"#
+            )),
+            vec!["Stage".to_string(), "Load".to_string()]
+        );
+        assert!(parse_depends_on(Some("not json")).is_empty());
+        assert!(parse_depends_on(None).is_empty());
+    }
+}
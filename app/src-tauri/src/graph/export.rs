@@ -0,0 +1,283 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as FmtWrite;
+
+use super::dependency::DependencyGraph;
+use crate::types::{GraphEdgeRelation, GraphNode, GraphNodeKind, LineageGraph};
+
+/// Most table names listed in a Mermaid section heading before it is abbreviated.
+const HEADING_TABLES: usize = 3;
+
+fn relation_label(relation: GraphEdgeRelation) -> &'static str {
+    match relation {
+        GraphEdgeRelation::Reads => "reads",
+        GraphEdgeRelation::Writes => "writes",
+        GraphEdgeRelation::Executes => "executes",
+        GraphEdgeRelation::Precedes => "precedes",
+        GraphEdgeRelation::References => "references",
+    }
+}
+
+fn node_label(node: &GraphNode) -> String {
+    match &node.schema_name {
+        Some(schema) => format!("{schema}.{}", node.name),
+        None => node.name.clone(),
+    }
+}
+
+fn is_dataset(kind: GraphNodeKind) -> bool {
+    matches!(kind, GraphNodeKind::Table | GraphNodeKind::View)
+}
+
+/// Graphviz DOT, left to right: tables as cylinders, procedures as boxes, activities as
+/// hexagons. Activity ordering edges are dashed.
+pub fn to_dot(graph: &DependencyGraph) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut out = String::new();
+    writeln!(out, "digraph lineage {{").ok();
+    writeln!(out, "  rankdir=LR;").ok();
+    writeln!(out, "  node [fontname=\"Helvetica\", fontsize=10];").ok();
+    writeln!(out, "  edge [fontname=\"Helvetica\", fontsize=9];").ok();
+    for node in graph.nodes() {
+        let shape = match node.kind {
+            GraphNodeKind::Table => "cylinder",
+            GraphNodeKind::View => "note",
+            GraphNodeKind::Procedure | GraphNodeKind::Function | GraphNodeKind::Unknown => "box",
+            GraphNodeKind::OrchestrationActivity | GraphNodeKind::PipelineActivity => "hexagon",
+        };
+        writeln!(
+            out,
+            "  {} [label={}, shape={}];",
+            quote(&node.id),
+            quote(&node_label(node)),
+            shape
+        )
+        .ok();
+    }
+    for edge in graph.edges() {
+        let style = if edge.relation == GraphEdgeRelation::Precedes {
+            ", style=dashed"
+        } else {
+            ""
+        };
+        writeln!(
+            out,
+            "  {} -> {} [label={}{}];",
+            quote(&edge.from),
+            quote(&edge.to),
+            quote(relation_label(edge.relation)),
+            style
+        )
+        .ok();
+    }
+    writeln!(out, "}}").ok();
+    out
+}
+
+/// Markdown with one Mermaid flowchart per connected part of the graph, so large workspaces
+/// stay within what GitHub renders and unrelated flows are not drawn as one tangle.
+pub fn to_mermaid_markdown(graph: &DependencyGraph) -> String {
+    let mut out = String::new();
+    writeln!(out, "# Lineage").ok();
+    writeln!(out).ok();
+    if graph.nodes().is_empty() {
+        writeln!(out, "No selected tables.").ok();
+        return out;
+    }
+
+    let quote = |s: &str| format!("\"{}\"", s.replace('"', "#quot;"));
+    let ids: HashMap<&str, String> = graph
+        .nodes()
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.id.as_str(), format!("n{i}")))
+        .collect();
+    for component in connected_components(graph) {
+        let mut tables: Vec<String> = component
+            .iter()
+            .filter(|n| is_dataset(n.kind))
+            .map(|n| node_label(n))
+            .collect();
+        tables.sort();
+        let mut heading = tables
+            .iter()
+            .take(HEADING_TABLES)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if tables.len() > HEADING_TABLES {
+            write!(heading, " and {} more", tables.len() - HEADING_TABLES).ok();
+        }
+        if heading.is_empty() {
+            heading = node_label(component[0]);
+        }
+        writeln!(out, "## {heading}").ok();
+        writeln!(out).ok();
+        writeln!(out, "```mermaid").ok();
+        writeln!(out, "flowchart LR").ok();
+        for node in &component {
+            let label = quote(&node_label(node));
+            let shape = match node.kind {
+                GraphNodeKind::Table => format!("[({label})]"),
+                GraphNodeKind::View => format!("[/{label}/]"),
+                GraphNodeKind::Procedure | GraphNodeKind::Function | GraphNodeKind::Unknown => {
+                    format!("([{label}])")
+                }
+                GraphNodeKind::OrchestrationActivity | GraphNodeKind::PipelineActivity => {
+                    format!("{{{{{label}}}}}")
+                }
+            };
+            writeln!(out, "  {}{}", ids[node.id.as_str()], shape).ok();
+        }
+        for edge in graph
+            .edges()
+            .iter()
+            .filter(|e| component.iter().any(|n| n.id == e.from))
+        {
+            let arrow = if edge.relation == GraphEdgeRelation::Precedes {
+                "-.->"
+            } else {
+                "-->"
+            };
+            writeln!(
+                out,
+                "  {} {}|{}| {}",
+                ids[edge.from.as_str()],
+                arrow,
+                relation_label(edge.relation),
+                ids[edge.to.as_str()]
+            )
+            .ok();
+        }
+        writeln!(out, "```").ok();
+        writeln!(out).ok();
+    }
+    out
+}
+
+/// JSON graph; node and edge order follow the graph so regenerated files diff cleanly.
+pub fn to_json(graph: &DependencyGraph, workspace_id: &str) -> serde_json::Result<String> {
+    let lineage = LineageGraph {
+        workspace_id: workspace_id.to_string(),
+        nodes: graph.nodes().to_vec(),
+        edges: graph.edges().to_vec(),
+    };
+    serde_json::to_string_pretty(&lineage).map(|json| json + "\n")
+}
+
+/// Weakly connected components in node order.
+fn connected_components(graph: &DependencyGraph) -> Vec<Vec<&GraphNode>> {
+    let nodes = graph.nodes();
+    let position: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.id.as_str(), i))
+        .collect();
+    let mut parent: Vec<usize> = (0..nodes.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for edge in graph.edges() {
+        let a = root(&mut parent, position[edge.from.as_str()]);
+        let b = root(&mut parent, position[edge.to.as_str()]);
+        if a != b {
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+    let mut components: BTreeMap<usize, Vec<&GraphNode>> = BTreeMap::new();
+    for (i, node) in nodes.iter().enumerate() {
+        let r = root(&mut parent, i);
+        components.entry(r).or_default().push(node);
+    }
+    components.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> DependencyGraph {
+        let mut graph = DependencyGraph::new();
+        for (id, kind, schema, name) in [
+            (
+                "table:dbo.orders",
+                GraphNodeKind::Table,
+                Some("dbo"),
+                "orders",
+            ),
+            (
+                "procedure:dbo.load",
+                GraphNodeKind::Procedure,
+                Some("dbo"),
+                "load \"orders\"",
+            ),
+            (
+                "activity:nightly/load",
+                GraphNodeKind::PipelineActivity,
+                None,
+                "Nightly/Load",
+            ),
+            (
+                "table:dbo.lonely",
+                GraphNodeKind::Table,
+                Some("dbo"),
+                "lonely",
+            ),
+        ] {
+            graph.add_node(GraphNode {
+                id: id.to_string(),
+                kind,
+                schema_name: schema.map(str::to_string),
+                name: name.to_string(),
+            });
+        }
+        graph.add_edge(
+            "procedure:dbo.load",
+            "table:dbo.orders",
+            GraphEdgeRelation::Writes,
+        );
+        graph.add_edge(
+            "activity:nightly/load",
+            "procedure:dbo.load",
+            GraphEdgeRelation::Executes,
+        );
+        graph
+    }
+
+    #[test]
+    fn dot_escapes_labels_and_lists_edges() {
+        let dot = to_dot(&sample());
+        assert!(dot.starts_with("digraph lineage {\n  rankdir=LR;"));
+        assert!(
+            dot.contains("\"procedure:dbo.load\" [label=\"dbo.load \\\"orders\\\"\", shape=box];")
+        );
+        assert!(dot.contains("\"procedure:dbo.load\" -> \"table:dbo.orders\" [label=\"writes\"];"));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn mermaid_splits_disconnected_flows() {
+        let markdown = to_mermaid_markdown(&sample());
+        assert_eq!(markdown.matches("```mermaid").count(), 2);
+        assert!(markdown.contains("## dbo.orders\n"));
+        assert!(markdown.contains("## dbo.lonely\n"));
+        assert!(markdown.contains("  n0[(\"dbo.orders\")]"));
+        assert!(markdown.contains("  n1([\"dbo.load #quot;orders#quot;\"])"));
+        assert!(markdown.contains("  n2{{\"Nightly/Load\"}}"));
+        assert!(markdown.contains("  n1 -->|writes| n0"));
+        assert!(markdown.contains("  n2 -->|executes| n1"));
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let json = to_json(&sample(), "ws-1").unwrap();
+        let parsed: LineageGraph = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.workspace_id, "ws-1");
+        assert_eq!(parsed.nodes.len(), 4);
+        assert_eq!(parsed.edges.len(), 2);
+        assert!(json.contains("\"relation\": \"executes\""));
+    }
+}
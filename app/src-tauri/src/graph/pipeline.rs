@@ -0,0 +1,388 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value;
+
+use super::dependency::DependencyGraph;
+use crate::types::{
+    ActivityDependency, DanglingDependency, DependencyCondition, GraphEdgeRelation, GraphNode,
+    GraphNodeKind, PipelineActivityEdge, PipelineExecutionPlan,
+};
+
+/// One activity of a pipeline as stored in `pipeline_activities`.
+pub struct PipelineActivityInput {
+    pub name: String,
+    pub depends_on_json: Option<String>,
+    /// Enclosing ForEach / IfCondition / Until / Switch activity, if any.
+    pub parent: Option<String>,
+}
+
+/// Parse a `dependsOn` array. Entries are either pipeline JSON objects
+/// (`{"activity": "A", "dependencyConditions": ["Succeeded"]}`) or plain activity names;
+/// a missing condition list means `Succeeded`, which is the pipeline default.
+pub fn parse_dependencies(
+    depends_on_json: Option<&str>,
+) -> Result<Vec<ActivityDependency>, String> {
+    let Some(raw) = depends_on_json.filter(|s| !s.trim().is_empty()) else {
+        return Ok(Vec::new());
+    };
+    let value: Value = serde_json::from_str(raw).map_err(|e| format!("invalid JSON: {e}"))?;
+    let entries = match value {
+        Value::Array(entries) => entries,
+        Value::Null => return Ok(Vec::new()),
+        other => return Err(format!("expected an array, found {other}")),
+    };
+    entries
+        .iter()
+        .map(|entry| match entry {
+            Value::String(name) => Ok(ActivityDependency {
+                activity: name.clone(),
+                conditions: vec![DependencyCondition::Succeeded],
+            }),
+            Value::Object(map) => {
+                let activity = map
+                    .get("activity")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| format!("entry without an activity name: {entry}"))?;
+                let conditions = match map.get("dependencyConditions") {
+                    None | Some(Value::Null) => vec![DependencyCondition::Succeeded],
+                    Some(Value::Array(values)) if values.is_empty() => {
+                        vec![DependencyCondition::Succeeded]
+                    }
+                    Some(Value::Array(values)) => values
+                        .iter()
+                        .map(parse_condition)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    Some(other) => {
+                        return Err(format!("dependencyConditions is not an array: {other}"))
+                    }
+                };
+                Ok(ActivityDependency {
+                    activity: activity.to_string(),
+                    conditions,
+                })
+            }
+            other => Err(format!("unexpected dependsOn entry: {other}")),
+        })
+        .collect()
+}
+
+fn parse_condition(value: &Value) -> Result<DependencyCondition, String> {
+    let text = value.as_str().unwrap_or_default();
+    match text.to_ascii_lowercase().as_str() {
+        "succeeded" => Ok(DependencyCondition::Succeeded),
+        "failed" => Ok(DependencyCondition::Failed),
+        "completed" => Ok(DependencyCondition::Completed),
+        "skipped" => Ok(DependencyCondition::Skipped),
+        _ => Err(format!("unknown dependency condition {value}")),
+    }
+}
+
+/// Build the activity DAG of one pipeline and schedule it.
+///
+/// Stages are computed with Kahn's algorithm: every activity in a stage only depends on
+/// activities in earlier stages. Dependency names are matched case-insensitively within the
+/// pipeline; references to unknown activities are reported as dangling and otherwise ignored.
+/// A nested activity runs after its container starts, and anything depending on the container
+/// also waits for everything inside it.
+pub fn plan_pipeline(
+    pipeline_item_id: &str,
+    pipeline_name: &str,
+    activities: &[PipelineActivityInput],
+) -> PipelineExecutionPlan {
+    let mut names: Vec<&str> = activities.iter().map(|a| a.name.as_str()).collect();
+    names.sort_unstable();
+    let by_lower: HashMap<String, &str> = names.iter().map(|n| (n.to_lowercase(), *n)).collect();
+
+    let mut edges: BTreeMap<(String, String), Vec<DependencyCondition>> = BTreeMap::new();
+    let mut dangling = Vec::new();
+    let mut parse_errors = Vec::new();
+    for activity in activities {
+        let dependencies = match parse_dependencies(activity.depends_on_json.as_deref()) {
+            Ok(dependencies) => dependencies,
+            Err(reason) => {
+                parse_errors.push(format!("{}: {reason}", activity.name));
+                continue;
+            }
+        };
+        for dependency in dependencies {
+            let Some(&upstream) = by_lower.get(&dependency.activity.to_lowercase()) else {
+                dangling.push(DanglingDependency {
+                    activity: activity.name.clone(),
+                    missing: dependency.activity,
+                });
+                continue;
+            };
+            let conditions = edges
+                .entry((activity.name.clone(), upstream.to_string()))
+                .or_default();
+            for condition in dependency.conditions {
+                if !conditions.contains(&condition) {
+                    conditions.push(condition);
+                }
+            }
+        }
+    }
+    add_containment_edges(activities, &by_lower, &mut edges);
+    for conditions in edges.values_mut() {
+        conditions.sort();
+    }
+    dangling.sort_by(|a, b| (&a.activity, &a.missing).cmp(&(&b.activity, &b.missing)));
+
+    let mut graph = DependencyGraph::new();
+    for name in &names {
+        graph.add_node(GraphNode {
+            id: name.to_string(),
+            kind: GraphNodeKind::PipelineActivity,
+            schema_name: None,
+            name: name.to_string(),
+        });
+    }
+    for (activity, upstream) in edges.keys() {
+        graph.add_edge(upstream, activity, GraphEdgeRelation::Precedes);
+    }
+    let cycles: Vec<Vec<String>> = graph
+        .strongly_connected_components()
+        .into_iter()
+        .map(|component| component.into_iter().map(|n| n.id).collect())
+        .collect();
+
+    let mut remaining: HashMap<&str, usize> = names.iter().map(|n| (*n, 0)).collect();
+    for (activity, _) in edges.keys() {
+        *remaining
+            .get_mut(activity.as_str())
+            .expect("edge endpoints are activities") += 1;
+    }
+    let mut stages: Vec<Vec<String>> = Vec::new();
+    loop {
+        let mut ready: Vec<&str> = remaining
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(name, _)| *name)
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+        ready.sort_unstable();
+        for name in &ready {
+            remaining.remove(name);
+        }
+        for (activity, upstream) in edges.keys() {
+            if ready.contains(&upstream.as_str()) {
+                if let Some(count) = remaining.get_mut(activity.as_str()) {
+                    *count -= 1;
+                }
+            }
+        }
+        stages.push(ready.into_iter().map(str::to_string).collect());
+    }
+    let mut unscheduled: Vec<String> = remaining.into_keys().map(str::to_string).collect();
+    unscheduled.sort();
+
+    PipelineExecutionPlan {
+        pipeline_item_id: pipeline_item_id.to_string(),
+        pipeline_name: pipeline_name.to_string(),
+        order: stages.iter().flatten().cloned().collect(),
+        stages,
+        edges: edges
+            .into_iter()
+            .map(
+                |((activity, depends_on), conditions)| PipelineActivityEdge {
+                    activity,
+                    depends_on,
+                    conditions,
+                },
+            )
+            .collect(),
+        cycles,
+        dangling,
+        unscheduled,
+        parse_errors,
+    }
+}
+
+/// Edges from each container to its children (with no conditions) and from every descendant
+/// of a container to the activities that depend on the container.
+fn add_containment_edges(
+    activities: &[PipelineActivityInput],
+    by_lower: &HashMap<String, &str>,
+    edges: &mut BTreeMap<(String, String), Vec<DependencyCondition>>,
+) {
+    let resolve = |name: &str| by_lower.get(&name.to_lowercase()).map(|n| n.to_string());
+    let parent_of: HashMap<String, String> = activities
+        .iter()
+        .filter_map(|a| Some((resolve(&a.name)?, resolve(a.parent.as_deref()?)?)))
+        .collect();
+    for (child, parent) in &parent_of {
+        edges.entry((child.clone(), parent.clone())).or_default();
+    }
+
+    let dependents: Vec<(String, String, Vec<DependencyCondition>)> = edges
+        .iter()
+        .filter(|(_, conditions)| !conditions.is_empty())
+        .map(|((activity, upstream), conditions)| {
+            (activity.clone(), upstream.clone(), conditions.clone())
+        })
+        .collect();
+    for (activity, container, conditions) in dependents {
+        for descendant in parent_of.keys() {
+            if *descendant == activity {
+                continue;
+            }
+            // Walk up at most one step per activity so a malformed parent loop terminates.
+            let mut ancestor = parent_of.get(descendant);
+            for _ in 0..parent_of.len() {
+                let Some(current) = ancestor else { break };
+                if *current == container {
+                    let entry = edges
+                        .entry((activity.clone(), descendant.clone()))
+                        .or_default();
+                    for condition in &conditions {
+                        if !entry.contains(condition) {
+                            entry.push(*condition);
+                        }
+                    }
+                    break;
+                }
+                ancestor = parent_of.get(current);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(name: &str, depends_on: &str) -> PipelineActivityInput {
+        PipelineActivityInput {
+            name: name.to_string(),
+            depends_on_json: Some(depends_on.to_string()),
+            parent: None,
+        }
+    }
+
+    #[test]
+    fn parse_dependencies_reads_conditions() {
+        let parsed = parse_dependencies(Some(
+            r#"[{"activity":"A","dependencyConditions":["Succeeded","failed"]},
+                {"activity":"B"}, "C"]"#,
+        ))
+        .unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                ActivityDependency {
+                    activity: "A".to_string(),
+                    conditions: vec![DependencyCondition::Succeeded, DependencyCondition::Failed],
+                },
+                ActivityDependency {
+                    activity: "B".to_string(),
+                    conditions: vec![DependencyCondition::Succeeded],
+                },
+                ActivityDependency {
+                    activity: "C".to_string(),
+                    conditions: vec![DependencyCondition::Succeeded],
+                },
+            ]
+        );
+        assert!(parse_dependencies(None).unwrap().is_empty());
+        assert!(parse_dependencies(Some(
+            r#"[{"activity":"A","dependencyConditions":["Maybe"]}]"#
+        ))
+        .unwrap_err()
+        .contains("unknown dependency condition"));
+        assert!(parse_dependencies(Some("{}")).is_err());
+    }
+
+    #[test]
+    fn plan_pipeline_orders_activities_into_parallel_stages() {
+        let plan = plan_pipeline(
+            "pl",
+            "Nightly",
+            &[
+                activity(
+                    "Load",
+                    r#"[{"activity":"stage","dependencyConditions":["Succeeded"]}]"#,
+                ),
+                activity("Stage", "[]"),
+                activity(
+                    "Audit",
+                    r#"[{"activity":"Stage","dependencyConditions":["Completed"]}]"#,
+                ),
+                activity("Notify", r#"["Load", "Audit", "Ghost"]"#),
+            ],
+        );
+        assert_eq!(
+            plan.stages,
+            vec![
+                vec!["Stage".to_string()],
+                vec!["Audit".to_string(), "Load".to_string()],
+                vec!["Notify".to_string()],
+            ]
+        );
+        assert_eq!(plan.order, vec!["Stage", "Audit", "Load", "Notify"]);
+        assert_eq!(
+            plan.dangling,
+            vec![DanglingDependency {
+                activity: "Notify".to_string(),
+                missing: "Ghost".to_string(),
+            }]
+        );
+        assert_eq!(plan.edges.len(), 4);
+        assert_eq!(
+            plan.edges[0].conditions,
+            vec![DependencyCondition::Completed]
+        );
+        assert!(plan.cycles.is_empty());
+        assert!(plan.unscheduled.is_empty());
+    }
+
+    #[test]
+    fn plan_pipeline_reports_cycles_and_blocked_activities() {
+        let plan = plan_pipeline(
+            "pl",
+            "Loop",
+            &[
+                activity("A", r#"["B"]"#),
+                activity("B", r#"["A"]"#),
+                activity("C", r#"["B"]"#),
+                activity("D", "[]"),
+                activity("E", "not json"),
+            ],
+        );
+        assert_eq!(plan.cycles, vec![vec!["A".to_string(), "B".to_string()]]);
+        assert_eq!(plan.order, vec!["D", "E"]);
+        assert_eq!(plan.unscheduled, vec!["A", "B", "C"]);
+        assert_eq!(plan.parse_errors.len(), 1);
+        assert!(plan.parse_errors[0].starts_with("E: invalid JSON"));
+    }
+
+    #[test]
+    fn plan_pipeline_schedules_nested_activities_inside_their_container() {
+        let mut inner = activity("Inner", "[]");
+        inner.parent = Some("Loop".to_string());
+        let mut deeper = activity("Deeper", r#"["Inner"]"#);
+        deeper.parent = Some("inner".to_string());
+        let plan = plan_pipeline(
+            "pl",
+            "Nested",
+            &[
+                activity("Loop", "[]"),
+                inner,
+                deeper,
+                activity("After", r#"["Loop"]"#),
+            ],
+        );
+        assert_eq!(plan.order, vec!["Loop", "Inner", "Deeper", "After"]);
+        let container_edge = plan
+            .edges
+            .iter()
+            .find(|e| e.activity == "Inner" && e.depends_on == "Loop")
+            .unwrap();
+        assert!(container_edge.conditions.is_empty());
+        assert!(plan
+            .edges
+            .iter()
+            .any(|e| e.activity == "After" && e.depends_on == "Deeper"));
+    }
+}
@@ -1,8 +1,16 @@
 mod agent_sources;
 mod commands;
 mod db;
+mod dbt;
+mod fabric;
+mod git;
+mod github;
+mod graph;
 mod logging;
+mod plan;
+mod secrets;
 mod source_sql;
+mod tsql;
 mod types;
 
 use std::sync::Mutex;
@@ -25,6 +33,15 @@ pub fn run() {
                 log::error!("db::open failed: {e}");
                 e
             })?;
+            match secrets::init(&conn) {
+                Ok(status) if !status.unlocked => {
+                    log::warn!(
+                        "secrets: stored credentials are locked until a passphrase is entered"
+                    )
+                }
+                Ok(_) => {}
+                Err(e) => log::error!("secrets::init failed: {e}"),
+            }
             app.manage(db::DbState(Mutex::new(conn)));
             agent_sources::deploy_on_startup(app.handle()).map_err(|e| {
                 log::error!("agent_sources deploy failed on startup: {e}");
@@ -44,6 +61,8 @@ pub fn run() {
             commands::settings::get_settings,
             commands::settings::save_anthropic_api_key,
             commands::settings::test_api_key,
+            commands::settings::secrets_status,
+            commands::settings::secrets_unlock,
             commands::settings::app_hydrate_phase,
             commands::settings::app_set_phase,
             commands::settings::app_set_phase_flags,
@@ -52,6 +71,10 @@ pub fn run() {
             commands::workspace::workspace_apply_start,
             commands::workspace::workspace_apply_status,
             commands::workspace::workspace_get,
+            commands::workspace::workspace_list,
+            commands::workspace::workspace_switch,
+            commands::workspace::workspace_rename,
+            commands::workspace::workspace_delete,
             commands::workspace::workspace_test_source_connection,
             commands::workspace::workspace_discover_source_databases,
             commands::workspace::workspace_reset_state,
@@ -60,19 +83,45 @@ pub fn run() {
             commands::fabric::fabric_upsert_tables,
             commands::fabric::fabric_upsert_procedures,
             commands::fabric::fabric_upsert_pipeline_activities,
+            commands::fabric::fabric_import_pipeline_definition,
+            commands::fabric::fabric_import_notebook_definition,
+            commands::fabric::fabric_sync_workspace,
             commands::migration::migration_save_selected_tables,
             commands::migration::migration_save_table_artifact,
             commands::migration::migration_save_candidacy,
             commands::migration::migration_override_candidacy,
+            commands::migration::migration_list_candidacy_overrides,
+            commands::migration::migration_revert_candidacy_override,
             commands::migration::migration_list_candidacy,
             commands::migration::migration_save_table_config,
             commands::migration::migration_get_table_config,
             commands::plan::plan_serialize,
+            commands::plan::plan_generate_dbt,
+            commands::plan::plan_import,
+            commands::plan::plan_validate,
+            commands::plan::plan_publish,
+            commands::plan::plan_get_pull_request,
+            commands::repo_health::workspace_repo_health,
+            commands::lineage::lineage_rebuild_procedures,
+            commands::lineage::lineage_list_dynamic_sql,
+            commands::search::search_object_bodies,
+            commands::graph::graph_upstream,
+            commands::graph::graph_downstream,
+            commands::graph::graph_strongly_connected_components,
+            commands::graph::graph_impact,
+            commands::graph::graph_scope_warnings,
+            commands::pipeline::pipeline_execution_plan,
+            commands::transpile::transpile_procedure,
+            commands::transpile::transpile_sql,
             commands::github_auth::github_start_device_flow,
             commands::github_auth::github_poll_for_token,
             commands::github_auth::github_get_user,
             commands::github_auth::github_logout,
             commands::github_auth::github_list_repos,
+            commands::github_auth::github_list_orgs,
+            commands::github_auth::github_create_repo,
+            commands::github_auth::github_get_config,
+            commands::github_auth::github_save_config,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as FmtWrite;
+
+use super::file::{PlanFile, PlanTable};
+use crate::types::{PlanDiff, PlanValueChange};
+
+type TableIndex<'a> = BTreeMap<(String, String, String), &'a PlanTable>;
+
+fn index(plan: Option<&PlanFile>) -> TableIndex<'_> {
+    plan.map(|plan| plan.tables.iter().map(|t| (t.key(), t)).collect())
+        .unwrap_or_default()
+}
+
+fn tier(table: &PlanTable) -> Option<String> {
+    table.candidacy.as_ref().map(|c| c.tier.clone())
+}
+
+fn load_strategy(table: &PlanTable) -> Option<String> {
+    table.config.as_ref().and_then(|c| c.load_strategy.clone())
+}
+
+fn confirmed_at(table: &PlanTable) -> Option<String> {
+    table.config.as_ref().and_then(|c| c.confirmed_at.clone())
+}
+
+/// Compare two plan generations. Without a previous plan every table counts as added.
+pub fn diff_plans(previous: Option<&PlanFile>, current: &PlanFile) -> PlanDiff {
+    let before = index(previous);
+    let after = index(Some(current));
+
+    let mut diff = PlanDiff {
+        previous_generated_at: previous.map(|plan| plan.generated_at.clone()),
+        ..PlanDiff::default()
+    };
+    for (key, table) in &after {
+        let Some(old) = before.get(key) else {
+            diff.tables_added.push(table.table_ref());
+            continue;
+        };
+        let change = |from: Option<String>, to: Option<String>| {
+            (from != to).then(|| PlanValueChange {
+                table: table.table_ref(),
+                from,
+                to,
+            })
+        };
+        diff.tier_changes.extend(change(tier(old), tier(table)));
+        diff.load_strategy_changes
+            .extend(change(load_strategy(old), load_strategy(table)));
+        diff.confirmation_changes
+            .extend(change(confirmed_at(old), confirmed_at(table)));
+    }
+    diff.tables_removed = before
+        .iter()
+        .filter(|(key, _)| !after.contains_key(*key))
+        .map(|(_, table)| table.table_ref())
+        .collect();
+    diff
+}
+
+pub fn is_empty(diff: &PlanDiff) -> bool {
+    diff.tables_added.is_empty()
+        && diff.tables_removed.is_empty()
+        && diff.tier_changes.is_empty()
+        && diff.load_strategy_changes.is_empty()
+        && diff.confirmation_changes.is_empty()
+}
+
+fn value(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("none")
+}
+
+/// Markdown changelog section for `plan.md`.
+pub fn render_changelog(diff: &PlanDiff) -> String {
+    let mut out = String::new();
+    writeln!(out, "## Changelog").ok();
+    writeln!(out).ok();
+    match &diff.previous_generated_at {
+        Some(at) => writeln!(out, "Changes since the plan generated at {at}.").ok(),
+        None => writeln!(out, "No previous plan; this is the first generation.").ok(),
+    };
+    writeln!(out).ok();
+    if diff.previous_generated_at.is_some() && is_empty(diff) {
+        writeln!(out, "No changes.").ok();
+        return out;
+    }
+
+    let mut list = |title: &str, tables: &[String]| {
+        if tables.is_empty() {
+            return;
+        }
+        writeln!(out, "### {title} ({})", tables.len()).ok();
+        writeln!(out).ok();
+        for table in tables {
+            writeln!(out, "- {table}").ok();
+        }
+        writeln!(out).ok();
+    };
+    list("Tables added", &diff.tables_added);
+    list("Tables removed", &diff.tables_removed);
+
+    let mut changes = |title: &str, changes: &[PlanValueChange]| {
+        if changes.is_empty() {
+            return;
+        }
+        writeln!(out, "### {title} ({})", changes.len()).ok();
+        writeln!(out).ok();
+        writeln!(out, "| table | from | to |").ok();
+        writeln!(out, "|---|---|---|").ok();
+        for change in changes {
+            writeln!(
+                out,
+                "| {} | {} | {} |",
+                change.table,
+                value(&change.from),
+                value(&change.to)
+            )
+            .ok();
+        }
+        writeln!(out).ok();
+    };
+    changes("Tier changes", &diff.tier_changes);
+    changes("Load strategy changes", &diff.load_strategy_changes);
+    changes("Config confirmations", &diff.confirmation_changes);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::file::{PlanCandidacy, PlanTableConfig, PLAN_SCHEMA_VERSION};
+
+    fn table(name: &str, tier: &str, load_strategy: &str, confirmed_at: Option<&str>) -> PlanTable {
+        PlanTable {
+            warehouse_item_id: "wh".to_string(),
+            schema_name: "dbo".to_string(),
+            table_name: name.to_string(),
+            artifact: None,
+            candidacy: Some(PlanCandidacy {
+                tier: tier.to_string(),
+                reasoning: None,
+                overridden: false,
+                override_reason: None,
+            }),
+            config: Some(PlanTableConfig {
+                table_type: None,
+                load_strategy: Some(load_strategy.to_string()),
+                grain_columns: None,
+                relationships_json: None,
+                incremental_column: None,
+                date_column: None,
+                snapshot_strategy: "sample_1day".to_string(),
+                pii_columns: None,
+                pii_masking_json: None,
+                confirmed_at: confirmed_at.map(str::to_string),
+            }),
+        }
+    }
+
+    fn plan(generated_at: &str, tables: Vec<PlanTable>) -> PlanFile {
+        PlanFile {
+            schema_version: PLAN_SCHEMA_VERSION,
+            workspace_id: "ws".to_string(),
+            generated_at: generated_at.to_string(),
+            tables,
+        }
+    }
+
+    #[test]
+    fn diff_reports_membership_tier_strategy_and_confirmation_changes() {
+        let previous = plan(
+            "2026-01-01T00:00:00Z",
+            vec![
+                table("orders", "review", "full_refresh", None),
+                table("legacy", "reject", "full_refresh", None),
+                table("customers", "migrate", "snapshot", Some("2026-01-01")),
+            ],
+        );
+        let current = plan(
+            "2026-01-08T00:00:00Z",
+            vec![
+                table("Orders", "migrate", "incremental", Some("2026-01-05")),
+                table("customers", "migrate", "snapshot", Some("2026-01-01")),
+                table("regions", "migrate", "full_refresh", None),
+            ],
+        );
+        let diff = diff_plans(Some(&previous), &current);
+        assert_eq!(diff.tables_added, vec!["dbo.regions"]);
+        assert_eq!(diff.tables_removed, vec!["dbo.legacy"]);
+        let change = |from: &str, to: &str| PlanValueChange {
+            table: "dbo.Orders".to_string(),
+            from: Some(from.to_string()),
+            to: Some(to.to_string()),
+        };
+        assert_eq!(diff.tier_changes, vec![change("review", "migrate")]);
+        assert_eq!(
+            diff.load_strategy_changes,
+            vec![change("full_refresh", "incremental")]
+        );
+        assert_eq!(diff.confirmation_changes[0].from, None);
+
+        let markdown = render_changelog(&diff);
+        assert!(markdown.contains("Changes since the plan generated at 2026-01-01T00:00:00Z."));
+        assert!(markdown.contains("### Tables removed (1)\n\n- dbo.legacy\n"));
+        assert!(markdown.contains("| dbo.Orders | review | migrate |"));
+        assert!(markdown.contains("| dbo.Orders | none | 2026-01-05 |"));
+    }
+
+    #[test]
+    fn changelog_for_first_and_unchanged_generations() {
+        let current = plan("t1", vec![table("orders", "migrate", "snapshot", None)]);
+        let first = diff_plans(None, &current);
+        assert_eq!(first.tables_added, vec!["dbo.orders"]);
+        assert!(render_changelog(&first).contains("first generation"));
+
+        let unchanged = diff_plans(Some(&current), &current);
+        assert!(is_empty(&unchanged));
+        assert!(render_changelog(&unchanged).ends_with("No changes.\n"));
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::BTreeMap;
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// File name of the machine-readable plan inside the migration repo.
+pub const PLAN_FILE: &str = "plan.json";
+
+/// Bumped whenever a field is added, removed or changes meaning. Files from older versions
+/// are still read; fields they lack take their defaults.
+pub const PLAN_SCHEMA_VERSION: u32 = 2;
+
+const LOAD_STRATEGIES: &[&str] = &["incremental", "full_refresh", "snapshot"];
+const SNAPSHOT_STRATEGIES: &[&str] = &["sample_1day", "full", "full_flagged"];
+const TABLE_TYPES: &[&str] = &["fact", "dimension", "unknown"];
+const DISCOVERY_STATUSES: &[&str] = &["resolved", "orphan", "duplicate_writer"];
+const TIERS: &[&str] = &["migrate", "review", "reject"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PlanFile {
+    pub schema_version: u32,
+    pub workspace_id: String,
+    pub generated_at: String,
+    pub tables: Vec<PlanTable>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PlanTable {
+    pub warehouse_item_id: String,
+    pub schema_name: String,
+    pub table_name: String,
+    #[serde(default)]
+    pub artifact: Option<PlanArtifact>,
+    #[serde(default)]
+    pub candidacy: Option<PlanCandidacy>,
+    #[serde(default)]
+    pub config: Option<PlanTableConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PlanArtifact {
+    pub schema_name: String,
+    pub procedure_name: String,
+    #[serde(default)]
+    pub pipeline_activity_id: Option<i64>,
+    pub discovery_status: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PlanCandidacy {
+    pub tier: String,
+    #[serde(default)]
+    pub reasoning: Option<String>,
+    #[serde(default)]
+    pub overridden: bool,
+    #[serde(default)]
+    pub override_reason: Option<String>,
+}
+
+/// `table_config` as stored: column lists keep the raw text the app saved.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PlanTableConfig {
+    #[serde(default)]
+    pub table_type: Option<String>,
+    #[serde(default)]
+    pub load_strategy: Option<String>,
+    #[serde(default)]
+    pub grain_columns: Option<String>,
+    #[serde(default)]
+    pub relationships_json: Option<String>,
+    #[serde(default)]
+    pub incremental_column: Option<String>,
+    #[serde(default)]
+    pub date_column: Option<String>,
+    #[serde(default = "default_snapshot_strategy")]
+    pub snapshot_strategy: String,
+    #[serde(default)]
+    pub pii_columns: Option<String>,
+    /// Added in schema version 2.
+    #[serde(default)]
+    pub pii_masking_json: Option<String>,
+    #[serde(default)]
+    pub confirmed_at: Option<String>,
+}
+
+fn default_snapshot_strategy() -> String {
+    "sample_1day".to_string()
+}
+
+/// Every field of a table entry, flattened to `group.field` for field-level comparison.
+pub type PlanFields = BTreeMap<&'static str, Option<String>>;
+
+impl PlanTable {
+    /// Case-insensitive identity of the table across the app and the file.
+    pub fn key(&self) -> (String, String, String) {
+        (
+            self.warehouse_item_id.clone(),
+            self.schema_name.to_lowercase(),
+            self.table_name.to_lowercase(),
+        )
+    }
+
+    pub fn table_ref(&self) -> String {
+        format!("{}.{}", self.schema_name, self.table_name)
+    }
+
+    pub fn fields(&self) -> PlanFields {
+        let mut fields = PlanFields::new();
+        let artifact = self.artifact.as_ref();
+        fields.insert(
+            "artifact.schema_name",
+            artifact.map(|a| a.schema_name.clone()),
+        );
+        fields.insert(
+            "artifact.procedure_name",
+            artifact.map(|a| a.procedure_name.clone()),
+        );
+        fields.insert(
+            "artifact.pipeline_activity_id",
+            artifact.and_then(|a| a.pipeline_activity_id.map(|id| id.to_string())),
+        );
+        fields.insert(
+            "artifact.discovery_status",
+            artifact.map(|a| a.discovery_status.clone()),
+        );
+        let candidacy = self.candidacy.as_ref();
+        fields.insert("candidacy.tier", candidacy.map(|c| c.tier.clone()));
+        fields.insert(
+            "candidacy.reasoning",
+            candidacy.and_then(|c| c.reasoning.clone()),
+        );
+        fields.insert(
+            "candidacy.overridden",
+            candidacy.map(|c| c.overridden.to_string()),
+        );
+        fields.insert(
+            "candidacy.override_reason",
+            candidacy.and_then(|c| c.override_reason.clone()),
+        );
+        let config = self.config.as_ref();
+        fields.insert(
+            "config.table_type",
+            config.and_then(|c| c.table_type.clone()),
+        );
+        fields.insert(
+            "config.load_strategy",
+            config.and_then(|c| c.load_strategy.clone()),
+        );
+        fields.insert(
+            "config.grain_columns",
+            config.and_then(|c| c.grain_columns.clone()),
+        );
+        fields.insert(
+            "config.relationships_json",
+            config.and_then(|c| c.relationships_json.clone()),
+        );
+        fields.insert(
+            "config.incremental_column",
+            config.and_then(|c| c.incremental_column.clone()),
+        );
+        fields.insert(
+            "config.date_column",
+            config.and_then(|c| c.date_column.clone()),
+        );
+        fields.insert(
+            "config.snapshot_strategy",
+            config.map(|c| c.snapshot_strategy.clone()),
+        );
+        fields.insert(
+            "config.pii_columns",
+            config.and_then(|c| c.pii_columns.clone()),
+        );
+        fields.insert(
+            "config.pii_masking_json",
+            config.and_then(|c| c.pii_masking_json.clone()),
+        );
+        fields.insert(
+            "config.confirmed_at",
+            config.and_then(|c| c.confirmed_at.clone()),
+        );
+        fields
+    }
+
+    /// Rebuild a table entry from flattened fields. A group is present when its required
+    /// field is: `artifact.procedure_name`, `candidacy.tier` and `config.snapshot_strategy`.
+    pub fn from_fields(
+        warehouse_item_id: &str,
+        schema_name: &str,
+        table_name: &str,
+        fields: &PlanFields,
+    ) -> PlanTable {
+        let get = |name: &str| fields.get(name).cloned().flatten();
+        let artifact = get("artifact.procedure_name").map(|procedure_name| PlanArtifact {
+            schema_name: get("artifact.schema_name").unwrap_or_default(),
+            procedure_name,
+            pipeline_activity_id: get("artifact.pipeline_activity_id")
+                .and_then(|id| id.parse().ok()),
+            discovery_status: get("artifact.discovery_status")
+                .unwrap_or_else(|| "resolved".to_string()),
+        });
+        let candidacy = get("candidacy.tier").map(|tier| PlanCandidacy {
+            tier,
+            reasoning: get("candidacy.reasoning"),
+            overridden: get("candidacy.overridden").as_deref() == Some("true"),
+            override_reason: get("candidacy.override_reason"),
+        });
+        let config = get("config.snapshot_strategy").map(|snapshot_strategy| PlanTableConfig {
+            table_type: get("config.table_type"),
+            load_strategy: get("config.load_strategy"),
+            grain_columns: get("config.grain_columns"),
+            relationships_json: get("config.relationships_json"),
+            incremental_column: get("config.incremental_column"),
+            date_column: get("config.date_column"),
+            snapshot_strategy,
+            pii_columns: get("config.pii_columns"),
+            pii_masking_json: get("config.pii_masking_json"),
+            confirmed_at: get("config.confirmed_at"),
+        });
+        PlanTable {
+            warehouse_item_id: warehouse_item_id.to_string(),
+            schema_name: schema_name.to_string(),
+            table_name: table_name.to_string(),
+            artifact,
+            candidacy,
+            config,
+        }
+    }
+}
+
+/// Read the current plan of a workspace from SQLite.
+pub fn load_plan(
+    conn: &Connection,
+    workspace_id: &str,
+    generated_at: &str,
+) -> rusqlite::Result<PlanFile> {
+    let mut stmt = conn.prepare(
+        "SELECT st.warehouse_item_id, st.schema_name, st.table_name,
+                ta.schema_name, ta.procedure_name, ta.pipeline_activity_id, ta.discovery_status,
+                c.tier, c.reasoning, c.overridden, c.override_reason,
+                tc.selected_table_id, tc.table_type, tc.load_strategy, tc.grain_columns,
+                tc.relationships_json, tc.incremental_column, tc.date_column,
+                tc.snapshot_strategy, tc.pii_columns, tc.pii_masking_json, tc.confirmed_at
+         FROM selected_tables st
+         LEFT JOIN table_artifacts ta ON ta.selected_table_id = st.id
+         LEFT JOIN candidacy c
+           ON c.warehouse_item_id = ta.warehouse_item_id
+           AND c.schema_name = ta.schema_name
+           AND c.procedure_name = ta.procedure_name
+         LEFT JOIN table_config tc ON tc.selected_table_id = st.id
+         WHERE st.workspace_id = ?1
+         ORDER BY st.schema_name, st.table_name, st.warehouse_item_id",
+    )?;
+    let tables = stmt
+        .query_map(params![workspace_id], |row| {
+            let artifact = match row.get::<_, Option<String>>(4)? {
+                Some(procedure_name) => Some(PlanArtifact {
+                    schema_name: row.get(3)?,
+                    procedure_name,
+                    pipeline_activity_id: row.get(5)?,
+                    discovery_status: row.get(6)?,
+                }),
+                None => None,
+            };
+            let candidacy = match row.get::<_, Option<String>>(7)? {
+                Some(tier) => Some(PlanCandidacy {
+                    tier,
+                    reasoning: row.get(8)?,
+                    overridden: row.get::<_, i64>(9)? != 0,
+                    override_reason: row.get(10)?,
+                }),
+                None => None,
+            };
+            let config = match row.get::<_, Option<String>>(11)? {
+                Some(_) => Some(PlanTableConfig {
+                    table_type: row.get(12)?,
+                    load_strategy: row.get(13)?,
+                    grain_columns: row.get(14)?,
+                    relationships_json: row.get(15)?,
+                    incremental_column: row.get(16)?,
+                    date_column: row.get(17)?,
+                    snapshot_strategy: row.get(18)?,
+                    pii_columns: row.get(19)?,
+                    pii_masking_json: row.get(20)?,
+                    confirmed_at: row.get(21)?,
+                }),
+                None => None,
+            };
+            Ok(PlanTable {
+                warehouse_item_id: row.get(0)?,
+                schema_name: row.get(1)?,
+                table_name: row.get(2)?,
+                artifact,
+                candidacy,
+                config,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(PlanFile {
+        schema_version: PLAN_SCHEMA_VERSION,
+        workspace_id: workspace_id.to_string(),
+        generated_at: generated_at.to_string(),
+        tables,
+    })
+}
+
+pub fn render_plan(plan: &PlanFile) -> String {
+    let mut content = serde_json::to_string_pretty(plan).unwrap_or_default();
+    content.push('\n');
+    content
+}
+
+/// Parse and validate a plan file. The schema version is checked before the body so a file
+/// written by a newer app gets a clear error rather than an unknown-field one. The parsed
+/// plan keeps the file's version.
+pub fn parse_plan(content: &str) -> Result<PlanFile, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("invalid {PLAN_FILE}: {e}"))?;
+    let version = value
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| format!("{PLAN_FILE} has no schema_version"))?;
+    if version == 0 || version > u64::from(PLAN_SCHEMA_VERSION) {
+        return Err(format!(
+            "{PLAN_FILE} schema_version {version} is not supported (expected at most {PLAN_SCHEMA_VERSION})"
+        ));
+    }
+    let plan: PlanFile =
+        serde_json::from_value(value).map_err(|e| format!("invalid {PLAN_FILE}: {e}"))?;
+    let problems = validate_values(&plan);
+    if !problems.is_empty() {
+        return Err(format!("invalid {PLAN_FILE}: {}", problems.join("; ")));
+    }
+    Ok(plan)
+}
+
+/// Values the database CHECK constraints would reject, reported per table.
+fn validate_values(plan: &PlanFile) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut check = |table: &PlanTable, field: &str, value: Option<&str>, allowed: &[&str]| {
+        if let Some(value) = value {
+            if !allowed.contains(&value) {
+                problems.push(format!(
+                    "{} {field} '{value}' must be one of {}",
+                    table.table_ref(),
+                    allowed.join(", ")
+                ));
+            }
+        }
+    };
+    for table in &plan.tables {
+        if let Some(config) = &table.config {
+            check(
+                table,
+                "table_type",
+                config.table_type.as_deref(),
+                TABLE_TYPES,
+            );
+            check(
+                table,
+                "load_strategy",
+                config.load_strategy.as_deref(),
+                LOAD_STRATEGIES,
+            );
+            check(
+                table,
+                "snapshot_strategy",
+                Some(&config.snapshot_strategy),
+                SNAPSHOT_STRATEGIES,
+            );
+        }
+        if let Some(artifact) = &table.artifact {
+            check(
+                table,
+                "discovery_status",
+                Some(&artifact.discovery_status),
+                DISCOVERY_STATUSES,
+            );
+        }
+        if let Some(candidacy) = &table.candidacy {
+            check(table, "tier", Some(&candidacy.tier), TIERS);
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    for table in &plan.tables {
+        if !seen.insert(table.key()) {
+            problems.push(format!("{} is listed more than once", table.table_ref()));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_table() -> PlanTable {
+        PlanTable {
+            warehouse_item_id: "wh".to_string(),
+            schema_name: "dbo".to_string(),
+            table_name: "orders".to_string(),
+            artifact: Some(PlanArtifact {
+                schema_name: "dbo".to_string(),
+                procedure_name: "sp_load_orders".to_string(),
+                pipeline_activity_id: Some(7),
+                discovery_status: "resolved".to_string(),
+            }),
+            candidacy: Some(PlanCandidacy {
+                tier: "migrate".to_string(),
+                reasoning: Some("simple merge".to_string()),
+                overridden: false,
+                override_reason: None,
+            }),
+            config: Some(PlanTableConfig {
+                table_type: Some("fact".to_string()),
+                load_strategy: Some("incremental".to_string()),
+                grain_columns: Some("[\"order_id\"]".to_string()),
+                relationships_json: None,
+                incremental_column: Some("modified_at".to_string()),
+                date_column: None,
+                snapshot_strategy: "sample_1day".to_string(),
+                pii_columns: None,
+                pii_masking_json: None,
+                confirmed_at: Some("2026-01-01T00:00:00Z".to_string()),
+            }),
+        }
+    }
+
+    #[test]
+    fn plan_round_trips_through_json_and_fields() {
+        let plan = PlanFile {
+            schema_version: PLAN_SCHEMA_VERSION,
+            workspace_id: "ws".to_string(),
+            generated_at: "2026-01-01T00:00:00Z".to_string(),
+            tables: vec![sample_table()],
+        };
+        assert_eq!(parse_plan(&render_plan(&plan)).unwrap(), plan);
+
+        let table = sample_table();
+        let rebuilt = PlanTable::from_fields("wh", "dbo", "orders", &table.fields());
+        assert_eq!(rebuilt, table);
+        let bare = PlanTable {
+            artifact: None,
+            candidacy: None,
+            config: None,
+            ..table
+        };
+        assert_eq!(
+            PlanTable::from_fields("wh", "dbo", "orders", &bare.fields()),
+            bare
+        );
+    }
+
+    #[test]
+    fn parse_plan_rejects_unknown_versions_fields_and_values() {
+        let err = parse_plan(r#"{"schema_version": 3, "tables": []}"#).unwrap_err();
+        assert!(err.contains("schema_version 3 is not supported"));
+        let v1 = parse_plan(
+            r#"{"schema_version": 1, "workspace_id": "ws", "generated_at": "",
+                "tables": [{"warehouse_item_id": "wh", "schema_name": "dbo",
+                            "table_name": "orders", "config": {"pii_columns": "email"}}]}"#,
+        )
+        .unwrap();
+        let config = v1.tables[0].config.as_ref().unwrap();
+        assert_eq!(config.pii_masking_json, None);
+        assert_eq!(config.snapshot_strategy, "sample_1day");
+        assert!(parse_plan(r#"{"tables": []}"#)
+            .unwrap_err()
+            .contains("no schema_version"));
+        let err = parse_plan(
+            r#"{"schema_version": 1, "workspace_id": "ws", "generated_at": "", "tables": [],
+                "extra": true}"#,
+        )
+        .unwrap_err();
+        assert!(err.contains("unknown field `extra`"));
+
+        let mut table = sample_table();
+        table.config.as_mut().unwrap().load_strategy = Some("append".to_string());
+        let plan = PlanFile {
+            schema_version: PLAN_SCHEMA_VERSION,
+            workspace_id: "ws".to_string(),
+            generated_at: String::new(),
+            tables: vec![table.clone(), table],
+        };
+        let err = parse_plan(&render_plan(&plan)).unwrap_err();
+        assert!(err.contains("dbo.orders load_strategy 'append' must be one of"));
+        assert!(err.contains("dbo.orders is listed more than once"));
+    }
+}
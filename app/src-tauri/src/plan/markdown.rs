@@ -0,0 +1,531 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as FmtWrite;
+
+use super::diff::render_changelog;
+use super::file::{PlanFile, PlanTable};
+use super::validate::parse_masking;
+use crate::dbt::project::split_columns;
+use crate::dbt::schema_tests::parse_relationships;
+use crate::types::{PlanDiff, PlanIssueSeverity, PlanValidation};
+
+/// Discovery status shown for tables no writer procedure was recorded for.
+const NO_ARTIFACT: &str = "no artifact";
+const UNSET: &str = "—";
+/// Validation issues listed in a pull request body before the rest are summarized.
+const MAX_LISTED_ISSUES: usize = 20;
+
+/// Markdown table cell: pipes would split the cell and newlines would end the row.
+fn cell(value: &str) -> String {
+    value.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn or_unset(value: Option<&str>) -> String {
+    value
+        .filter(|v| !v.trim().is_empty())
+        .map(cell)
+        .unwrap_or_else(|| UNSET.to_string())
+}
+
+fn column_list(value: Option<&str>) -> String {
+    let columns = split_columns(value);
+    if columns.is_empty() {
+        return UNSET.to_string();
+    }
+    cell(&columns.join(", "))
+}
+
+fn procedure(table: &PlanTable) -> Option<String> {
+    table
+        .artifact
+        .as_ref()
+        .map(|a| format!("{}.{}", a.schema_name, a.procedure_name))
+}
+
+fn discovery_status(table: &PlanTable) -> &str {
+    table
+        .artifact
+        .as_ref()
+        .map_or(NO_ARTIFACT, |a| a.discovery_status.as_str())
+}
+
+fn tier(table: &PlanTable) -> &str {
+    table
+        .candidacy
+        .as_ref()
+        .map_or("unknown", |c| c.tier.as_str())
+}
+
+fn load_strategy(table: &PlanTable) -> &str {
+    table
+        .config
+        .as_ref()
+        .and_then(|c| c.load_strategy.as_deref())
+        .unwrap_or("unknown")
+}
+
+fn confirmed(table: &PlanTable) -> bool {
+    table
+        .config
+        .as_ref()
+        .is_some_and(|c| c.confirmed_at.is_some())
+}
+
+/// Anchor GitHub generates for a `### schema.table` heading.
+fn anchor(table: &PlanTable) -> String {
+    table
+        .table_ref()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_' || *c == '-' || *c == ' ')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+fn write_counts(out: &mut String, title: &str, counts: &BTreeMap<&str, usize>) {
+    writeln!(out, "### {title}").ok();
+    writeln!(out).ok();
+    writeln!(out, "| {} | tables |", title.to_lowercase()).ok();
+    writeln!(out, "|---|---|").ok();
+    for (value, count) in counts {
+        writeln!(out, "| {value} | {count} |").ok();
+    }
+    writeln!(out).ok();
+}
+
+fn write_summary(out: &mut String, plan: &PlanFile) {
+    let mut tiers = BTreeMap::new();
+    let mut strategies = BTreeMap::new();
+    let mut statuses = BTreeMap::new();
+    for table in &plan.tables {
+        *tiers.entry(tier(table)).or_insert(0) += 1;
+        *strategies.entry(load_strategy(table)).or_insert(0) += 1;
+        *statuses.entry(discovery_status(table)).or_insert(0) += 1;
+    }
+    let confirmed = plan.tables.iter().filter(|t| confirmed(t)).count();
+
+    writeln!(out, "## Summary").ok();
+    writeln!(out).ok();
+    writeln!(out, "| tables | confirmed | unconfirmed |").ok();
+    writeln!(out, "|---|---|---|").ok();
+    writeln!(
+        out,
+        "| {} | {confirmed} | {} |",
+        plan.tables.len(),
+        plan.tables.len() - confirmed
+    )
+    .ok();
+    writeln!(out).ok();
+    write_counts(out, "Tier", &tiers);
+    write_counts(out, "Load strategy", &strategies);
+    write_counts(out, "Discovery status", &statuses);
+}
+
+fn write_table_section(out: &mut String, table: &PlanTable) {
+    writeln!(out, "### {}", table.table_ref()).ok();
+    writeln!(out).ok();
+    writeln!(out, "| field | value |").ok();
+    writeln!(out, "|---|---|").ok();
+    let mut row = |field: &str, value: String| {
+        writeln!(out, "| {field} | {value} |").ok();
+    };
+    row("procedure", or_unset(procedure(table).as_deref()));
+    row("discovery_status", discovery_status(table).to_string());
+    if let Some(id) = table.artifact.as_ref().and_then(|a| a.pipeline_activity_id) {
+        row("pipeline_activity_id", id.to_string());
+    }
+    match &table.candidacy {
+        Some(candidacy) if candidacy.overridden => row(
+            "candidacy",
+            format!("{} (overridden)", cell(&candidacy.tier)),
+        ),
+        Some(candidacy) => row("candidacy", cell(&candidacy.tier)),
+        None => row("candidacy", "unknown".to_string()),
+    }
+    let config = table.config.as_ref();
+    row(
+        "table_type",
+        or_unset(config.and_then(|c| c.table_type.as_deref())),
+    );
+    row("load_strategy", load_strategy(table).to_string());
+    row(
+        "grain_columns",
+        column_list(config.and_then(|c| c.grain_columns.as_deref())),
+    );
+    row(
+        "incremental_column",
+        or_unset(config.and_then(|c| c.incremental_column.as_deref())),
+    );
+    row(
+        "date_column",
+        or_unset(config.and_then(|c| c.date_column.as_deref())),
+    );
+    if let Some(config) = config.filter(|c| c.load_strategy.as_deref() == Some("snapshot")) {
+        row("snapshot_strategy", cell(&config.snapshot_strategy));
+    }
+    row(
+        "pii_columns",
+        column_list(config.and_then(|c| c.pii_columns.as_deref())),
+    );
+    if let Some(masking) = config.and_then(|c| c.pii_masking_json.as_deref()) {
+        let value = match parse_masking(Some(masking)) {
+            Ok(decisions) if !decisions.is_empty() => decisions
+                .iter()
+                .map(|(column, decision)| format!("{column}: {decision}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => masking.to_string(),
+        };
+        row("pii_masking", cell(&value));
+    }
+    row(
+        "confirmed_at",
+        config
+            .and_then(|c| c.confirmed_at.as_deref())
+            .map_or_else(|| "not confirmed".to_string(), cell),
+    );
+    writeln!(out).ok();
+
+    let relationships_json = config.and_then(|c| c.relationships_json.as_deref());
+    let relationships = parse_relationships(relationships_json);
+    if !relationships.is_empty() {
+        writeln!(out, "Relationships:").ok();
+        writeln!(out).ok();
+        for relationship in &relationships {
+            writeln!(
+                out,
+                "- `{}` → `{}.{}`",
+                relationship.column, relationship.to, relationship.field
+            )
+            .ok();
+        }
+        writeln!(out).ok();
+    } else if let Some(raw) = relationships_json.filter(|v| !v.trim().is_empty()) {
+        // Keep whatever was entered visible even when it is not in a recognised shape.
+        writeln!(out, "Relationships: `{}`", raw.replace('`', "'")).ok();
+        writeln!(out).ok();
+    }
+    if let Some(candidacy) = &table.candidacy {
+        if let Some(reasoning) = candidacy.reasoning.as_deref().filter(|r| !r.is_empty()) {
+            writeln!(out, "**Reasoning:** {reasoning}").ok();
+            writeln!(out).ok();
+        }
+        if let Some(reason) = candidacy
+            .override_reason
+            .as_deref()
+            .filter(|r| !r.is_empty())
+        {
+            writeln!(out, "**Override reason:** {reason}").ok();
+            writeln!(out).ok();
+        }
+    }
+}
+
+fn write_appendix(out: &mut String, plan: &PlanFile) {
+    writeln!(out, "## Appendix: Orphan and Duplicate Writer Tables").ok();
+    writeln!(out).ok();
+    let sections = [
+        (
+            "orphan",
+            "Orphan tables",
+            "No procedure was found that writes these tables.",
+        ),
+        (
+            "duplicate_writer",
+            "Duplicate writer tables",
+            "More than one procedure writes these tables; the recorded procedure needs review.",
+        ),
+    ];
+    let mut any = false;
+    for (status, title, description) in sections {
+        let tables: Vec<_> = plan
+            .tables
+            .iter()
+            .filter(|t| discovery_status(t) == status)
+            .collect();
+        if tables.is_empty() {
+            continue;
+        }
+        any = true;
+        writeln!(out, "### {title} ({})", tables.len()).ok();
+        writeln!(out).ok();
+        writeln!(out, "{description}").ok();
+        writeln!(out).ok();
+        writeln!(out, "| table | procedure | candidacy |").ok();
+        writeln!(out, "|---|---|---|").ok();
+        for table in tables {
+            writeln!(
+                out,
+                "| [{}](#{}) | {} | {} |",
+                table.table_ref(),
+                anchor(table),
+                or_unset(procedure(table).as_deref()),
+                tier(table)
+            )
+            .ok();
+        }
+        writeln!(out).ok();
+    }
+    if !any {
+        writeln!(out, "Every table has a single resolved writer.").ok();
+        writeln!(out).ok();
+    }
+}
+
+/// Full `plan.md`: summary dashboard, overview, one section per table with its config and
+/// rationale, the changelog against the previous plan, and the unresolved-writer appendix.
+pub fn render_plan_markdown(plan: &PlanFile, diff: &PlanDiff, lineage_dir: &str) -> String {
+    let mut out = String::new();
+    writeln!(out, "---").ok();
+    writeln!(out, "workspace_id: {}", plan.workspace_id).ok();
+    writeln!(out, "generated_at: {}", plan.generated_at).ok();
+    writeln!(out, "schema_version: {}", plan.schema_version).ok();
+    writeln!(out, "---").ok();
+    writeln!(out).ok();
+    writeln!(out, "# Migration Plan").ok();
+    writeln!(out).ok();
+    write_summary(&mut out, plan);
+
+    writeln!(out, "## Selected Tables").ok();
+    writeln!(out).ok();
+    writeln!(
+        out,
+        "| table | procedure | candidacy | load_strategy | discovery_status | confirmed |"
+    )
+    .ok();
+    writeln!(out, "|---|---|---|---|---|---|").ok();
+    for table in &plan.tables {
+        writeln!(
+            out,
+            "| [{}](#{}) | {} | {} | {} | {} | {} |",
+            table.table_ref(),
+            anchor(table),
+            or_unset(procedure(table).as_deref()),
+            tier(table),
+            load_strategy(table),
+            discovery_status(table),
+            if confirmed(table) { "yes" } else { "no" }
+        )
+        .ok();
+    }
+    writeln!(out).ok();
+    for table in &plan.tables {
+        write_table_section(&mut out, table);
+    }
+
+    out.push_str(&render_changelog(diff));
+    writeln!(out).ok();
+    write_appendix(&mut out, plan);
+
+    writeln!(out, "## Lineage").ok();
+    writeln!(out).ok();
+    writeln!(
+        out,
+        "Diagrams: [{lineage_dir}/lineage.md]({lineage_dir}/lineage.md). \
+         Graphviz: `{lineage_dir}/lineage.dot`. JSON: `{lineage_dir}/lineage.json`."
+    )
+    .ok();
+    out
+}
+
+/// Pull request body for a plan branch: the summary dashboard, open validation issues and
+/// the changelog against the plan on the base branch.
+pub fn render_pull_request_body(
+    display_name: &str,
+    plan: &PlanFile,
+    diff: &PlanDiff,
+    validation: &PlanValidation,
+) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "Migration plan for **{display_name}**, generated at {}.",
+        plan.generated_at
+    )
+    .ok();
+    writeln!(out).ok();
+    writeln!(
+        out,
+        "The full plan is in `plan.md`. Edits to `plan.json` on this branch are imported back \
+         into the app."
+    )
+    .ok();
+    writeln!(out).ok();
+    write_summary(&mut out, plan);
+
+    writeln!(out, "## Validation").ok();
+    writeln!(out).ok();
+    let errors = validation
+        .issues
+        .iter()
+        .filter(|issue| issue.severity == PlanIssueSeverity::Error)
+        .count();
+    if validation.issues.is_empty() {
+        writeln!(out, "No issues.").ok();
+    } else {
+        writeln!(
+            out,
+            "{errors} error(s) and {} warning(s); errors block finalizing the plan.",
+            validation.issues.len() - errors
+        )
+        .ok();
+        writeln!(out).ok();
+        for issue in validation.issues.iter().take(MAX_LISTED_ISSUES) {
+            let severity = match issue.severity {
+                PlanIssueSeverity::Error => "error",
+                PlanIssueSeverity::Warning => "warning",
+            };
+            writeln!(out, "- **{severity}** `{}`: {}", issue.table, issue.message).ok();
+        }
+        if validation.issues.len() > MAX_LISTED_ISSUES {
+            writeln!(
+                out,
+                "- … and {} more",
+                validation.issues.len() - MAX_LISTED_ISSUES
+            )
+            .ok();
+        }
+    }
+    writeln!(out).ok();
+    out.push_str(&render_changelog(diff));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::file::{PlanArtifact, PlanCandidacy, PlanTableConfig, PLAN_SCHEMA_VERSION};
+
+    fn table(name: &str, status: Option<&str>) -> PlanTable {
+        PlanTable {
+            warehouse_item_id: "wh".to_string(),
+            schema_name: "dbo".to_string(),
+            table_name: name.to_string(),
+            artifact: status.map(|status| PlanArtifact {
+                schema_name: "dbo".to_string(),
+                procedure_name: format!("sp_load_{name}"),
+                pipeline_activity_id: None,
+                discovery_status: status.to_string(),
+            }),
+            candidacy: None,
+            config: None,
+        }
+    }
+
+    fn sample_plan() -> PlanFile {
+        let mut orders = table("orders", Some("resolved"));
+        orders.candidacy = Some(PlanCandidacy {
+            tier: "migrate".to_string(),
+            reasoning: Some("Plain MERGE on order_id".to_string()),
+            overridden: true,
+            override_reason: Some("Signed off by | finance".to_string()),
+        });
+        orders.config = Some(PlanTableConfig {
+            table_type: Some("fact".to_string()),
+            load_strategy: Some("incremental".to_string()),
+            grain_columns: Some("[\"order_id\",\"line_no\"]".to_string()),
+            relationships_json: Some(
+                "[{\"column\":\"customer_id\",\"to\":\"dbo.customers\"}]".to_string(),
+            ),
+            incremental_column: Some("modified_at".to_string()),
+            date_column: None,
+            snapshot_strategy: "sample_1day".to_string(),
+            pii_columns: Some("email, phone".to_string()),
+            pii_masking_json: Some("{\"email\":\"hash\"}".to_string()),
+            confirmed_at: Some("2026-01-05T00:00:00Z".to_string()),
+        });
+        PlanFile {
+            schema_version: PLAN_SCHEMA_VERSION,
+            workspace_id: "ws".to_string(),
+            generated_at: "2026-01-08T00:00:00Z".to_string(),
+            tables: vec![
+                orders,
+                table("staging", Some("orphan")),
+                table("totals", Some("duplicate_writer")),
+                table("manual", None),
+            ],
+        }
+    }
+
+    #[test]
+    fn renders_dashboard_sections_and_appendix() {
+        let markdown = render_plan_markdown(&sample_plan(), &PlanDiff::default(), "lineage");
+        assert!(markdown.starts_with("---\nworkspace_id: ws\ngenerated_at: 2026-01-08T00:00:00Z\n"));
+        assert!(
+            markdown.contains("| tables | confirmed | unconfirmed |\n|---|---|---|\n| 4 | 1 | 3 |")
+        );
+        assert!(markdown.contains("| migrate | 1 |\n| unknown | 3 |"));
+        assert!(markdown.contains(
+            "| duplicate_writer | 1 |\n| no artifact | 1 |\n| orphan | 1 |\n| resolved | 1 |"
+        ));
+        assert!(markdown.contains(
+            "| [dbo.orders](#dboorders) | dbo.sp_load_orders | migrate | incremental | resolved | yes |"
+        ));
+
+        let section = &markdown[markdown.find("### dbo.orders").unwrap()..];
+        assert!(section.contains("| candidacy | migrate (overridden) |"));
+        assert!(section.contains("| grain_columns | order_id, line_no |"));
+        assert!(section.contains("| incremental_column | modified_at |"));
+        assert!(section.contains("| date_column | — |"));
+        assert!(!section[..section.find("### dbo.staging").unwrap()].contains("snapshot_strategy"));
+        assert!(section.contains("| pii_columns | email, phone |\n| pii_masking | email: hash |"));
+        assert!(section.contains("| confirmed_at | 2026-01-05T00:00:00Z |"));
+        assert!(section.contains("- `customer_id` → `dbo.customers.customer_id`"));
+        assert!(section.contains("**Reasoning:** Plain MERGE on order_id"));
+        assert!(section.contains("**Override reason:** Signed off by | finance"));
+        assert!(markdown.contains("### dbo.manual\n\n| field | value |\n|---|---|\n| procedure | — |\n| discovery_status | no artifact |"));
+
+        let appendix = &markdown[markdown.find("## Appendix").unwrap()..];
+        assert!(appendix.contains("### Orphan tables (1)"));
+        assert!(appendix.contains("| [dbo.staging](#dbostaging) | dbo.sp_load_staging | unknown |"));
+        assert!(appendix.contains("### Duplicate writer tables (1)"));
+        assert!(markdown.contains("## Changelog"));
+        assert!(markdown.contains("## Lineage"));
+    }
+
+    #[test]
+    fn appendix_notes_when_every_writer_resolved() {
+        let mut plan = sample_plan();
+        plan.tables.truncate(1);
+        let markdown = render_plan_markdown(&plan, &PlanDiff::default(), "lineage");
+        assert!(markdown.contains("Every table has a single resolved writer."));
+        assert_eq!(cell("a|b\nc"), "a\\|b c");
+    }
+
+    #[test]
+    fn pull_request_body_lists_issues_and_changes() {
+        let plan = sample_plan();
+        let issues: Vec<_> = plan
+            .tables
+            .iter()
+            .flat_map(|table| crate::plan::validate::check_table(table, None))
+            .collect();
+        let validation = PlanValidation {
+            can_finalize: false,
+            issues,
+        };
+        let diff = PlanDiff {
+            previous_generated_at: Some("2026-01-01T00:00:00Z".to_string()),
+            tables_added: vec!["dbo.manual".to_string()],
+            ..PlanDiff::default()
+        };
+
+        let body = render_pull_request_body("Sales", &plan, &diff, &validation);
+        assert!(
+            body.starts_with("Migration plan for **Sales**, generated at 2026-01-08T00:00:00Z.")
+        );
+        assert!(body.contains("## Summary"));
+        assert!(body.contains("4 error(s) and 3 warning(s)"));
+        assert!(
+            body.contains("- **error** `dbo.orders`: PII column 'phone' has no masking decision")
+        );
+        assert!(body.contains("- **warning** `dbo.manual`: No table config has been saved"));
+        assert!(body.ends_with("### Tables added (1)\n\n- dbo.manual\n\n"));
+
+        let clean = PlanValidation {
+            can_finalize: true,
+            issues: Vec::new(),
+        };
+        assert!(render_pull_request_body("Sales", &plan, &diff, &clean)
+            .contains("## Validation\n\nNo issues.\n"));
+    }
+}
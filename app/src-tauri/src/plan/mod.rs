@@ -0,0 +1,5 @@
+pub mod diff;
+pub mod file;
+pub mod markdown;
+pub mod reconcile;
+pub mod validate;
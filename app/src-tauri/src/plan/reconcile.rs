@@ -0,0 +1,639 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::file::{load_plan, parse_plan, PlanFields, PlanFile, PlanTable, PLAN_FILE};
+use crate::commands::migration::{append_override_history, read_candidacy_decision};
+use crate::types::{CommandError, PlanImportConflict, PlanImportSummary};
+
+/// Reason recorded in the override history when a tier change arrives without one.
+const IMPORT_OVERRIDE_REASON: &str = "Imported from plan.json";
+
+/// Remember the plan that was just written to the repo as the base for the next import.
+pub fn record_export(
+    conn: &Connection,
+    workspace_id: &str,
+    content: &str,
+    exported_at: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO plan_exports(workspace_id, content, exported_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(workspace_id) DO UPDATE SET content=excluded.content,
+           exported_at=excluded.exported_at",
+        params![workspace_id, content, exported_at],
+    )?;
+    Ok(())
+}
+
+/// `plan.json` exactly as last written to the repo.
+pub fn exported_content(conn: &Connection, workspace_id: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT content FROM plan_exports WHERE workspace_id=?1",
+        params![workspace_id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// The last exported plan, or `None` when nothing was exported or it no longer parses.
+pub fn exported_plan(conn: &Connection, workspace_id: &str) -> rusqlite::Result<Option<PlanFile>> {
+    Ok(exported_content(conn, workspace_id)?.and_then(|content| {
+        parse_plan(&content)
+            .inspect_err(|e| log::warn!("plan_import: ignoring stored export: {e}"))
+            .ok()
+    }))
+}
+
+enum FieldMerge {
+    Keep,
+    Apply,
+    Conflict(&'static str),
+}
+
+/// Three-way merge of one field: the repo file against the app, using the last export as
+/// the common base. Without a base there is no way to tell who changed a value.
+fn merge_field(
+    repo: &Option<String>,
+    app: &Option<String>,
+    base: Option<&Option<String>>,
+) -> FieldMerge {
+    match base {
+        _ if repo == app => FieldMerge::Keep,
+        Some(base) if repo == base => FieldMerge::Keep,
+        Some(base) if app == base => FieldMerge::Apply,
+        Some(_) => FieldMerge::Conflict("changed in both the app and plan.json"),
+        None => FieldMerge::Conflict("no exported plan to compare against"),
+    }
+}
+
+fn conflict(
+    table: &PlanTable,
+    field: Option<&str>,
+    repo_value: Option<String>,
+    app_value: Option<String>,
+    reason: impl Into<String>,
+) -> PlanImportConflict {
+    PlanImportConflict {
+        table: table.table_ref(),
+        field: field.map(str::to_string),
+        repo_value,
+        app_value,
+        reason: reason.into(),
+    }
+}
+
+/// Reconcile an edited plan file into SQLite. Fields changed only in the file are applied,
+/// fields changed only in the app are kept, and everything else is reported as a conflict
+/// with the app value left in place. Tables are never deleted; run inside a transaction.
+pub fn import_plan(
+    conn: &Connection,
+    workspace_id: &str,
+    plan: &PlanFile,
+    base: Option<&PlanFile>,
+    actor: Option<&str>,
+) -> Result<PlanImportSummary, CommandError> {
+    if plan.workspace_id != workspace_id {
+        return Err(CommandError::Io(format!(
+            "{PLAN_FILE} belongs to workspace '{}', not '{workspace_id}'",
+            plan.workspace_id
+        )));
+    }
+    let current = load_plan(conn, workspace_id, "")?;
+    let current_tables: HashMap<_, _> = current.tables.iter().map(|t| (t.key(), t)).collect();
+    let base_tables: Option<HashMap<_, _>> =
+        base.map(|base| base.tables.iter().map(|t| (t.key(), t)).collect());
+
+    let mut summary = PlanImportSummary::default();
+    for table in &plan.tables {
+        let key = table.key();
+        let (selected_table_id, app_table) = match current_tables.get(&key) {
+            Some(app_table) => (
+                selected_table_id(conn, workspace_id, table)?,
+                (*app_table).clone(),
+            ),
+            None => {
+                if !warehouse_in_workspace(conn, workspace_id, &table.warehouse_item_id)? {
+                    summary.conflicts.push(conflict(
+                        table,
+                        None,
+                        None,
+                        None,
+                        format!(
+                            "warehouse item '{}' is not part of this workspace",
+                            table.warehouse_item_id
+                        ),
+                    ));
+                    continue;
+                }
+                let id = uuid::Uuid::new_v4().to_string();
+                conn.execute(
+                    "INSERT INTO selected_tables(id, workspace_id, warehouse_item_id, schema_name, table_name)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        id,
+                        workspace_id,
+                        table.warehouse_item_id,
+                        table.schema_name,
+                        table.table_name
+                    ],
+                )?;
+                summary.tables_added += 1;
+                let empty = PlanTable {
+                    artifact: None,
+                    candidacy: None,
+                    config: None,
+                    ..table.clone()
+                };
+                (id, empty)
+            }
+        };
+        let added = !current_tables.contains_key(&key);
+        let app_fields = app_table.fields();
+        let base_fields = base_tables
+            .as_ref()
+            .and_then(|tables| tables.get(&key))
+            .map(|t| t.fields());
+
+        let mut merged = app_fields.clone();
+        for (name, repo_value) in table.fields() {
+            let app_value = &app_fields[name];
+            let outcome = if added {
+                FieldMerge::Apply
+            } else {
+                merge_field(
+                    &repo_value,
+                    app_value,
+                    base_fields.as_ref().map(|fields| &fields[name]),
+                )
+            };
+            match outcome {
+                FieldMerge::Keep => {}
+                FieldMerge::Apply => {
+                    merged.insert(name, repo_value);
+                }
+                FieldMerge::Conflict(reason) => summary.conflicts.push(conflict(
+                    table,
+                    Some(name),
+                    repo_value,
+                    app_value.clone(),
+                    reason,
+                )),
+            }
+        }
+        check_groups(conn, table, &app_fields, &mut merged, &mut summary)?;
+
+        let applied = merged
+            .iter()
+            .filter(|(name, value)| app_fields[*name] != **value)
+            .count();
+        if applied == 0 {
+            continue;
+        }
+        let merged_table = PlanTable::from_fields(
+            &table.warehouse_item_id,
+            &app_table.schema_name,
+            &app_table.table_name,
+            &merged,
+        );
+        write_table(conn, &selected_table_id, &app_table, &merged_table, actor)?;
+        summary.fields_applied += applied;
+        if !added {
+            summary.tables_updated += 1;
+        }
+    }
+
+    let file_keys: std::collections::HashSet<_> = plan.tables.iter().map(|t| t.key()).collect();
+    for app_table in &current.tables {
+        let key = app_table.key();
+        let in_base = base_tables
+            .as_ref()
+            .is_none_or(|tables| tables.contains_key(&key));
+        if !file_keys.contains(&key) && in_base {
+            summary.conflicts.push(conflict(
+                app_table,
+                None,
+                None,
+                None,
+                format!("missing from {PLAN_FILE}; the table stays selected in the app"),
+            ));
+        }
+    }
+    Ok(summary)
+}
+
+/// Undo merged fields that cannot be written as a group and report them instead.
+fn check_groups(
+    conn: &Connection,
+    table: &PlanTable,
+    app_fields: &PlanFields,
+    merged: &mut PlanFields,
+    summary: &mut PlanImportSummary,
+) -> Result<(), CommandError> {
+    let mut restore = |merged: &mut PlanFields, prefix: &str, reason: &str| {
+        let names: Vec<_> = merged
+            .keys()
+            .filter(|name| name.starts_with(prefix))
+            .copied()
+            .collect();
+        for name in names {
+            if merged[name] != app_fields[name] {
+                summary.conflicts.push(conflict(
+                    table,
+                    Some(name),
+                    merged[name].clone(),
+                    app_fields[name].clone(),
+                    reason,
+                ));
+                merged.insert(name, app_fields[name].clone());
+            }
+        }
+    };
+
+    if let Some(id) = merged["artifact.pipeline_activity_id"].clone() {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM pipeline_activities WHERE CAST(id AS TEXT)=?1)",
+            params![id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            restore(
+                merged,
+                "artifact.pipeline_activity_id",
+                "pipeline activity does not exist",
+            );
+        }
+    }
+    if app_fields["candidacy.tier"].is_some() && merged["candidacy.tier"].is_none() {
+        restore(
+            merged,
+            "candidacy.",
+            "candidacy is shared by every table the procedure writes and cannot be removed",
+        );
+    }
+    if merged["candidacy.tier"].is_some() && merged["artifact.procedure_name"].is_none() {
+        restore(
+            merged,
+            "candidacy.",
+            "candidacy needs an artifact procedure",
+        );
+    }
+    Ok(())
+}
+
+fn selected_table_id(
+    conn: &Connection,
+    workspace_id: &str,
+    table: &PlanTable,
+) -> rusqlite::Result<String> {
+    conn.query_row(
+        "SELECT id FROM selected_tables
+         WHERE workspace_id=?1 AND warehouse_item_id=?2
+           AND lower(schema_name)=lower(?3) AND lower(table_name)=lower(?4)",
+        params![
+            workspace_id,
+            table.warehouse_item_id,
+            table.schema_name,
+            table.table_name
+        ],
+        |row| row.get(0),
+    )
+}
+
+fn warehouse_in_workspace(
+    conn: &Connection,
+    workspace_id: &str,
+    warehouse_item_id: &str,
+) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM items WHERE id=?1 AND workspace_id=?2 AND item_type='Warehouse')",
+        params![warehouse_item_id, workspace_id],
+        |row| row.get(0),
+    )
+}
+
+fn write_table(
+    conn: &Connection,
+    selected_table_id: &str,
+    current: &PlanTable,
+    merged: &PlanTable,
+    actor: Option<&str>,
+) -> Result<(), CommandError> {
+    if merged.artifact != current.artifact {
+        match &merged.artifact {
+            Some(artifact) => {
+                conn.execute(
+                    "INSERT INTO table_artifacts(selected_table_id, warehouse_item_id, schema_name,
+                       procedure_name, pipeline_activity_id, discovery_status)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                     ON CONFLICT(selected_table_id) DO UPDATE SET
+                       schema_name=excluded.schema_name, procedure_name=excluded.procedure_name,
+                       pipeline_activity_id=excluded.pipeline_activity_id,
+                       discovery_status=excluded.discovery_status",
+                    params![
+                        selected_table_id,
+                        merged.warehouse_item_id,
+                        artifact.schema_name,
+                        artifact.procedure_name,
+                        artifact.pipeline_activity_id,
+                        artifact.discovery_status
+                    ],
+                )?;
+            }
+            None => {
+                conn.execute(
+                    "DELETE FROM table_artifacts WHERE selected_table_id=?1",
+                    params![selected_table_id],
+                )?;
+            }
+        }
+    }
+
+    if let (Some(candidacy), Some(artifact)) = (&merged.candidacy, &merged.artifact) {
+        let procedure_moved = merged.artifact != current.artifact;
+        if procedure_moved || merged.candidacy != current.candidacy {
+            let previous = read_candidacy_decision(
+                conn,
+                &merged.warehouse_item_id,
+                &artifact.schema_name,
+                &artifact.procedure_name,
+            )?;
+            match previous {
+                Some(previous) => {
+                    conn.execute(
+                        "UPDATE candidacy SET tier=?1, reasoning=?2, overridden=?3, override_reason=?4
+                         WHERE warehouse_item_id=?5 AND schema_name=?6 AND procedure_name=?7",
+                        params![
+                            candidacy.tier,
+                            candidacy.reasoning,
+                            candidacy.overridden as i64,
+                            candidacy.override_reason,
+                            merged.warehouse_item_id,
+                            artifact.schema_name,
+                            artifact.procedure_name
+                        ],
+                    )?;
+                    if previous.0 != candidacy.tier {
+                        append_override_history(
+                            conn,
+                            &merged.warehouse_item_id,
+                            &artifact.schema_name,
+                            &artifact.procedure_name,
+                            "override",
+                            &previous,
+                            &candidacy.tier,
+                            candidacy
+                                .override_reason
+                                .as_deref()
+                                .unwrap_or(IMPORT_OVERRIDE_REASON),
+                            actor,
+                            None,
+                        )?;
+                    }
+                }
+                None => {
+                    conn.execute(
+                        "INSERT INTO candidacy(warehouse_item_id, schema_name, procedure_name, tier,
+                           reasoning, overridden, override_reason)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![
+                            merged.warehouse_item_id,
+                            artifact.schema_name,
+                            artifact.procedure_name,
+                            candidacy.tier,
+                            candidacy.reasoning,
+                            candidacy.overridden as i64,
+                            candidacy.override_reason
+                        ],
+                    )?;
+                }
+            }
+        }
+    }
+
+    if merged.config != current.config {
+        match &merged.config {
+            Some(config) => {
+                conn.execute(
+                    "INSERT INTO table_config(selected_table_id, table_type, load_strategy,
+                       grain_columns, relationships_json, incremental_column, date_column,
+                       snapshot_strategy, pii_columns, pii_masking_json, confirmed_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                     ON CONFLICT(selected_table_id) DO UPDATE SET
+                       table_type=excluded.table_type, load_strategy=excluded.load_strategy,
+                       grain_columns=excluded.grain_columns,
+                       relationships_json=excluded.relationships_json,
+                       incremental_column=excluded.incremental_column,
+                       date_column=excluded.date_column,
+                       snapshot_strategy=excluded.snapshot_strategy,
+                       pii_columns=excluded.pii_columns,
+                       pii_masking_json=excluded.pii_masking_json,
+                       confirmed_at=excluded.confirmed_at",
+                    params![
+                        selected_table_id,
+                        config.table_type,
+                        config.load_strategy,
+                        config.grain_columns,
+                        config.relationships_json,
+                        config.incremental_column,
+                        config.date_column,
+                        config.snapshot_strategy,
+                        config.pii_columns,
+                        config.pii_masking_json,
+                        config.confirmed_at
+                    ],
+                )?;
+            }
+            None => {
+                conn.execute(
+                    "DELETE FROM table_config WHERE selected_table_id=?1",
+                    params![selected_table_id],
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn seed(conn: &Connection) {
+        conn.execute_batch(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at)
+               VALUES ('ws', 'WS', '/tmp/repo', '2026-01-01T00:00:00Z');
+             INSERT INTO items(id, workspace_id, display_name, item_type) VALUES ('wh', 'ws', 'WH', 'Warehouse');
+             INSERT INTO selected_tables(id, workspace_id, warehouse_item_id, schema_name, table_name) VALUES
+               ('st1', 'ws', 'wh', 'dbo', 'orders'), ('st2', 'ws', 'wh', 'dbo', 'customers');
+             INSERT INTO table_artifacts(selected_table_id, warehouse_item_id, schema_name, procedure_name, discovery_status)
+               VALUES ('st1', 'wh', 'dbo', 'sp_load_orders', 'resolved');
+             INSERT INTO candidacy(warehouse_item_id, schema_name, procedure_name, tier, reasoning)
+               VALUES ('wh', 'dbo', 'sp_load_orders', 'review', 'dynamic sql');
+             INSERT INTO table_config(selected_table_id, load_strategy, grain_columns)
+               VALUES ('st1', 'full_refresh', 'order_id'), ('st2', 'full_refresh', 'customer_id');",
+        )
+        .unwrap();
+    }
+
+    fn table_mut<'a>(plan: &'a mut PlanFile, table: &str) -> &'a mut PlanTable {
+        plan.tables
+            .iter_mut()
+            .find(|t| t.table_name == table)
+            .unwrap()
+    }
+
+    #[test]
+    fn import_applies_repo_edits_and_reports_conflicts() {
+        let conn = db::open_in_memory().unwrap();
+        seed(&conn);
+        let base = load_plan(&conn, "ws", "t0").unwrap();
+
+        let mut repo = base.clone();
+        let orders = table_mut(&mut repo, "orders");
+        orders.config.as_mut().unwrap().load_strategy = Some("incremental".to_string());
+        orders.config.as_mut().unwrap().incremental_column = Some("modified_at".to_string());
+        let candidacy = orders.candidacy.as_mut().unwrap();
+        candidacy.tier = "migrate".to_string();
+        candidacy.overridden = true;
+        candidacy.override_reason = Some("reviewed in PR".to_string());
+        let customers = table_mut(&mut repo, "customers");
+        customers.config.as_mut().unwrap().grain_columns = Some("customer_key".to_string());
+        repo.tables.push(PlanTable {
+            warehouse_item_id: "wh".to_string(),
+            schema_name: "dbo".to_string(),
+            table_name: "regions".to_string(),
+            artifact: None,
+            candidacy: None,
+            config: None,
+        });
+
+        // Edited in the app since the export: same field conflicts, other fields are kept.
+        conn.execute(
+            "UPDATE table_config SET grain_columns='id', pii_columns='email' WHERE selected_table_id='st2'",
+            [],
+        )
+        .unwrap();
+
+        let summary = import_plan(&conn, "ws", &repo, Some(&base), Some("octocat")).unwrap();
+        assert_eq!(summary.tables_added, 1);
+        assert_eq!(summary.tables_updated, 1);
+        assert_eq!(summary.fields_applied, 5);
+        assert_eq!(
+            summary.conflicts,
+            vec![PlanImportConflict {
+                table: "dbo.customers".to_string(),
+                field: Some("config.grain_columns".to_string()),
+                repo_value: Some("customer_key".to_string()),
+                app_value: Some("id".to_string()),
+                reason: "changed in both the app and plan.json".to_string(),
+            }]
+        );
+
+        let (strategy, column): (String, String) = conn
+            .query_row(
+                "SELECT load_strategy, incremental_column FROM table_config WHERE selected_table_id='st1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (strategy.as_str(), column.as_str()),
+            ("incremental", "modified_at")
+        );
+        let (pii, grain): (String, String) = conn
+            .query_row(
+                "SELECT pii_columns, grain_columns FROM table_config WHERE selected_table_id='st2'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((pii.as_str(), grain.as_str()), ("email", "id"));
+        let history: (String, String, String, String) = conn
+            .query_row(
+                "SELECT previous_tier, new_tier, reason, actor FROM candidacy_override_history",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            history,
+            (
+                "review".to_string(),
+                "migrate".to_string(),
+                "reviewed in PR".to_string(),
+                "octocat".to_string()
+            )
+        );
+        let regions: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM selected_tables WHERE table_name='regions'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(regions, 1);
+
+        // Re-importing the same file is a no-op apart from the unresolved conflict.
+        let again = import_plan(&conn, "ws", &repo, Some(&base), None).unwrap();
+        assert_eq!((again.fields_applied, again.conflicts.len()), (0, 1));
+    }
+
+    #[test]
+    fn import_without_base_reports_every_difference() {
+        let conn = db::open_in_memory().unwrap();
+        seed(&conn);
+        let mut repo = load_plan(&conn, "ws", "t0").unwrap();
+        repo.tables.retain(|t| t.table_name == "orders");
+        table_mut(&mut repo, "orders")
+            .config
+            .as_mut()
+            .unwrap()
+            .date_column = Some("order_date".to_string());
+        repo.tables.push(PlanTable {
+            warehouse_item_id: "other-wh".to_string(),
+            schema_name: "dbo".to_string(),
+            table_name: "ghost".to_string(),
+            artifact: None,
+            candidacy: None,
+            config: None,
+        });
+
+        let summary = import_plan(&conn, "ws", &repo, None, None).unwrap();
+        assert_eq!(summary.fields_applied, 0);
+        let reasons: Vec<_> = summary
+            .conflicts
+            .iter()
+            .map(|c| (c.table.as_str(), c.reason.as_str()))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("dbo.orders", "no exported plan to compare against"),
+                (
+                    "dbo.ghost",
+                    "warehouse item 'other-wh' is not part of this workspace"
+                ),
+                (
+                    "dbo.customers",
+                    "missing from plan.json; the table stays selected in the app"
+                ),
+            ]
+        );
+
+        repo.workspace_id = "other".to_string();
+        assert!(import_plan(&conn, "ws", &repo, None, None).is_err());
+    }
+
+    #[test]
+    fn exported_plan_round_trips_through_plan_exports() {
+        let conn = db::open_in_memory().unwrap();
+        seed(&conn);
+        assert!(exported_plan(&conn, "ws").unwrap().is_none());
+        let plan = load_plan(&conn, "ws", "t0").unwrap();
+        let content = super::super::file::render_plan(&plan);
+        record_export(&conn, "ws", &content, "t0").unwrap();
+        record_export(&conn, "ws", &content, "t1").unwrap();
+        assert_eq!(exported_plan(&conn, "ws").unwrap(), Some(plan));
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use rusqlite::{params, Connection};
+
+use super::file::{load_plan, PlanTable};
+use crate::dbt::project::split_columns;
+use crate::types::{PlanIssue, PlanIssueSeverity, PlanValidation};
+
+/// Accepted values in `pii_masking_json`.
+pub const MASKING_DECISIONS: &[&str] = &["keep", "hash", "redact", "drop"];
+
+/// Parse `pii_masking_json`: an object mapping column names to a masking decision.
+pub fn parse_masking(value: Option<&str>) -> Result<BTreeMap<String, String>, String> {
+    match value.map(str::trim).filter(|v| !v.is_empty()) {
+        None => Ok(BTreeMap::new()),
+        Some(value) => serde_json::from_str(value)
+            .map_err(|e| format!("pii_masking_json must map columns to decisions: {e}")),
+    }
+}
+
+fn issue(
+    table: &PlanTable,
+    code: &str,
+    severity: PlanIssueSeverity,
+    field: Option<&str>,
+    message: String,
+) -> PlanIssue {
+    PlanIssue {
+        table: table.table_ref(),
+        code: code.to_string(),
+        severity,
+        field: field.map(str::to_string),
+        message,
+    }
+}
+
+/// Lower-cased column names per lower-cased `(schema, table)` of the workspace's source
+/// objects. Objects without discovered columns are left out so they are not checked.
+fn source_columns(
+    conn: &Connection,
+    workspace_id: &str,
+) -> rusqlite::Result<HashMap<(String, String), HashSet<String>>> {
+    let mut stmt = conn.prepare(
+        "SELECT n.namespace_name, o.object_name, col.column_name
+         FROM sqlserver_object_columns col
+         JOIN data_objects o ON o.id = col.data_object_id
+         JOIN namespaces n ON n.id = o.namespace_id
+         JOIN containers c ON c.id = n.container_id
+         JOIN sources s ON s.id = c.source_id
+         WHERE s.workspace_id = ?1 AND o.object_type IN ('table', 'view')",
+    )?;
+    let mut columns: HashMap<(String, String), HashSet<String>> = HashMap::new();
+    let rows = stmt.query_map(params![workspace_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (schema, table, column) = row?;
+        columns
+            .entry((schema.to_lowercase(), table.to_lowercase()))
+            .or_default()
+            .insert(column.to_lowercase());
+    }
+    Ok(columns)
+}
+
+/// Checks for one table. `columns` are the source table's columns when they are known.
+pub fn check_table(table: &PlanTable, columns: Option<&HashSet<String>>) -> Vec<PlanIssue> {
+    use PlanIssueSeverity::{Error, Warning};
+    let mut issues = Vec::new();
+    match &table.artifact {
+        None => issues.push(issue(
+            table,
+            "missing_artifact",
+            Error,
+            None,
+            "No procedure writing this table has been recorded".to_string(),
+        )),
+        Some(artifact) if artifact.discovery_status != "resolved" => issues.push(issue(
+            table,
+            "unresolved_artifact",
+            Error,
+            Some("discovery_status"),
+            format!(
+                "Writer discovery is {}; choose the procedure that loads this table",
+                artifact.discovery_status
+            ),
+        )),
+        Some(_) => {}
+    }
+
+    let Some(config) = &table.config else {
+        issues.push(issue(
+            table,
+            "unconfirmed_config",
+            Warning,
+            None,
+            "No table config has been saved".to_string(),
+        ));
+        return issues;
+    };
+    let blank = |value: &Option<String>| value.as_deref().is_none_or(|v| v.trim().is_empty());
+    if config.load_strategy.as_deref() == Some("incremental") && blank(&config.incremental_column) {
+        issues.push(issue(
+            table,
+            "incremental_without_column",
+            Error,
+            Some("incremental_column"),
+            "Incremental load strategy needs an incremental_column".to_string(),
+        ));
+    }
+    if let Some(columns) = columns.filter(|c| !c.is_empty()) {
+        for column in split_columns(config.grain_columns.as_deref()) {
+            if !columns.contains(&column.to_lowercase()) {
+                issues.push(issue(
+                    table,
+                    "unknown_grain_column",
+                    Error,
+                    Some("grain_columns"),
+                    format!("Grain column '{column}' does not exist on the source table"),
+                ));
+            }
+        }
+    }
+    if blank(&config.confirmed_at) {
+        issues.push(issue(
+            table,
+            "unconfirmed_config",
+            Warning,
+            Some("confirmed_at"),
+            "Table config has not been confirmed".to_string(),
+        ));
+    }
+
+    let pii_columns = split_columns(config.pii_columns.as_deref());
+    if pii_columns.is_empty() {
+        return issues;
+    }
+    let masking = match parse_masking(config.pii_masking_json.as_deref()) {
+        Ok(masking) => masking,
+        Err(message) => {
+            issues.push(issue(
+                table,
+                "invalid_pii_masking",
+                Error,
+                Some("pii_masking_json"),
+                message,
+            ));
+            return issues;
+        }
+    };
+    let decisions: HashMap<_, _> = masking
+        .iter()
+        .map(|(column, decision)| (column.to_lowercase(), decision.as_str()))
+        .collect();
+    for column in pii_columns {
+        match decisions.get(&column.to_lowercase()) {
+            None => issues.push(issue(
+                table,
+                "pii_without_masking",
+                Error,
+                Some("pii_masking_json"),
+                format!("PII column '{column}' has no masking decision"),
+            )),
+            Some(decision) if !MASKING_DECISIONS.contains(decision) => issues.push(issue(
+                table,
+                "invalid_pii_masking",
+                Error,
+                Some("pii_masking_json"),
+                format!(
+                    "PII column '{column}' masking '{decision}' must be one of {}",
+                    MASKING_DECISIONS.join(", ")
+                ),
+            )),
+            Some(_) => {}
+        }
+    }
+    issues
+}
+
+/// Validate every selected table of a workspace.
+pub fn validate_plan(conn: &Connection, workspace_id: &str) -> rusqlite::Result<PlanValidation> {
+    let plan = load_plan(conn, workspace_id, "")?;
+    let columns = source_columns(conn, workspace_id)?;
+    let issues: Vec<_> = plan
+        .tables
+        .iter()
+        .flat_map(|table| {
+            let key = (
+                table.schema_name.to_lowercase(),
+                table.table_name.to_lowercase(),
+            );
+            check_table(table, columns.get(&key))
+        })
+        .collect();
+    let can_finalize = !issues
+        .iter()
+        .any(|issue| issue.severity == PlanIssueSeverity::Error);
+    Ok(PlanValidation {
+        issues,
+        can_finalize,
+    })
+}
+
+/// Errors in the workspace's plan that block setting its plan-finalized flag.
+pub fn finalization_blockers(
+    conn: &Connection,
+    workspace_id: &str,
+) -> rusqlite::Result<Vec<PlanIssue>> {
+    Ok(validate_plan(conn, workspace_id)?
+        .issues
+        .into_iter()
+        .filter(|issue| issue.severity == PlanIssueSeverity::Error)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn seed(conn: &Connection) {
+        conn.execute_batch(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, created_at)
+               VALUES ('ws', 'WS', '/tmp/repo', '2026-01-01T00:00:00Z');
+             INSERT INTO sources(id, workspace_id, source_type, external_source_id) VALUES ('src', 'ws', 'sql_server', 'ext');
+             INSERT INTO containers(id, source_id, container_type, external_container_id, container_name)
+               VALUES ('db', 'src', 'database', '5', 'SalesDW');
+             INSERT INTO namespaces(id, container_id, namespace_name) VALUES ('ns', 'db', 'dbo');
+             INSERT INTO data_objects(id, namespace_id, object_name, object_type) VALUES ('t1', 'ns', 'Orders', 'table');
+             INSERT INTO sqlserver_object_columns(id, data_object_id, column_name, column_id) VALUES
+               ('c1', 't1', 'order_id', 1), ('c2', 't1', 'Email', 2), ('c3', 't1', 'phone', 3);
+             INSERT INTO items(id, workspace_id, display_name, item_type) VALUES ('wh', 'ws', 'WH', 'Warehouse');
+             INSERT INTO selected_tables(id, workspace_id, warehouse_item_id, schema_name, table_name) VALUES
+               ('st1', 'ws', 'wh', 'dbo', 'orders'), ('st2', 'ws', 'wh', 'dbo', 'staging');
+             INSERT INTO table_artifacts(selected_table_id, warehouse_item_id, schema_name, procedure_name, discovery_status)
+               VALUES ('st1', 'wh', 'dbo', 'sp_load_orders', 'resolved'),
+                      ('st2', 'wh', 'dbo', 'sp_load_staging', 'orphan');",
+        )
+        .unwrap();
+    }
+
+    fn codes(validation: &PlanValidation) -> Vec<(&str, &str)> {
+        validation
+            .issues
+            .iter()
+            .map(|i| (i.table.as_str(), i.code.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn validate_plan_reports_each_check() {
+        let conn = db::open_in_memory().unwrap();
+        seed(&conn);
+        conn.execute(
+            "INSERT INTO table_config(selected_table_id, load_strategy, grain_columns, pii_columns, pii_masking_json)
+             VALUES ('st1', 'incremental', 'order_id, line_no', 'email, phone, fax',
+                     '{\"EMAIL\": \"hash\", \"phone\": \"scramble\"}')",
+            [],
+        )
+        .unwrap();
+
+        let validation = validate_plan(&conn, "ws").unwrap();
+        assert!(!validation.can_finalize);
+        assert_eq!(
+            codes(&validation),
+            vec![
+                ("dbo.orders", "incremental_without_column"),
+                ("dbo.orders", "unknown_grain_column"),
+                ("dbo.orders", "unconfirmed_config"),
+                ("dbo.orders", "invalid_pii_masking"),
+                ("dbo.orders", "pii_without_masking"),
+                ("dbo.staging", "unresolved_artifact"),
+                ("dbo.staging", "unconfirmed_config"),
+            ]
+        );
+        assert_eq!(
+            validation.issues[1].message,
+            "Grain column 'line_no' does not exist on the source table"
+        );
+        assert_eq!(validation.issues[2].severity, PlanIssueSeverity::Warning);
+        assert_eq!(finalization_blockers(&conn, "ws").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn clean_plan_can_finalize_with_warnings_only() {
+        let conn = db::open_in_memory().unwrap();
+        seed(&conn);
+        conn.execute_batch(
+            "UPDATE table_artifacts SET discovery_status = 'resolved';
+             INSERT INTO table_config(selected_table_id, load_strategy, incremental_column, grain_columns,
+                                      pii_columns, pii_masking_json, confirmed_at)
+               VALUES ('st1', 'incremental', 'modified_at', '[\"ORDER_ID\"]', 'email',
+                       '{\"email\": \"redact\"}', '2026-01-02T00:00:00Z'),
+                      ('st2', 'full_refresh', NULL, 'unknown_columns_unchecked', NULL, NULL, NULL);",
+        )
+        .unwrap();
+
+        let validation = validate_plan(&conn, "ws").unwrap();
+        assert!(validation.can_finalize);
+        assert_eq!(
+            codes(&validation),
+            vec![("dbo.staging", "unconfirmed_config")]
+        );
+        assert!(finalization_blockers(&conn, "ws").unwrap().is_empty());
+
+        assert!(parse_masking(Some("[\"email\"]")).is_err());
+        assert!(parse_masking(Some("  ")).unwrap().is_empty());
+    }
+}
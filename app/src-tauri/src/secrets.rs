@@ -0,0 +1,395 @@
+use std::sync::RwLock;
+
+use argon2::Argon2;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rusqlite::{params, Connection};
+
+use crate::db::{read_settings_value, write_settings_value};
+use crate::types::SecretsStatus;
+
+/// Marks a stored value as sealed with ChaCha20-Poly1305. Values without it are plaintext
+/// written by earlier versions and are re-encrypted once the key is available.
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+
+const KEYRING_SERVICE: &str = "com.vibedata.migration-utility";
+const KEYRING_USER: &str = "secrets-master-key";
+/// Headless installs can supply the passphrase instead of calling `secrets_unlock`.
+const PASSPHRASE_ENV: &str = "MIGRATION_UTILITY_SECRETS_PASSPHRASE";
+
+const KEY_SOURCE_KEY: &str = "secrets_key_source";
+const KDF_SALT_KEY: &str = "secrets_kdf_salt";
+const KEY_CHECK_KEY: &str = "secrets_key_check";
+const KEY_CHECK_VALUE: &str = "migration-utility";
+
+const KEY_SOURCE_KEYRING: &str = "keyring";
+const KEY_SOURCE_PASSPHRASE: &str = "passphrase";
+
+const LOCKED: &str = "Stored credentials are locked; unlock them with your passphrase";
+
+/// Per-install key: kept in the OS keyring, or derived from a passphrase with Argon2id and
+/// held in memory after `unlock`. Shared by every connection in the process.
+static CIPHER: KeySlot = KeySlot::new();
+
+fn b64() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+#[derive(Clone)]
+pub struct Cipher(ChaCha20Poly1305);
+
+impl Cipher {
+    fn from_key(key: &[u8]) -> Result<Self, String> {
+        if key.len() != 32 {
+            return Err("Secrets key must be 32 bytes".to_string());
+        }
+        Ok(Self(ChaCha20Poly1305::new(Key::from_slice(key))))
+    }
+
+    /// Derive the key from a passphrase with Argon2id.
+    fn derive(passphrase: &str, salt: &[u8]) -> Result<Self, String> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| format!("Failed to derive secrets key: {e}"))?;
+        Self::from_key(&key)
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, String> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = self
+            .0
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| "Failed to encrypt secret".to_string())?;
+        let mut bytes = nonce.to_vec();
+        bytes.extend(sealed);
+        Ok(format!("{PREFIX}{}", b64().encode(bytes)))
+    }
+
+    pub fn decrypt(&self, stored: &str) -> Result<String, String> {
+        let Some(encoded) = stored.strip_prefix(PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let bytes = b64()
+            .decode(encoded)
+            .map_err(|e| format!("Stored secret is not valid base64: {e}"))?;
+        if bytes.len() < NONCE_LEN {
+            return Err("Stored secret is truncated".to_string());
+        }
+        let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+        let plaintext = self
+            .0
+            .decrypt(Nonce::from_slice(nonce), sealed)
+            .map_err(|_| "Stored secret could not be decrypted with this key".to_string())?;
+        String::from_utf8(plaintext).map_err(|e| format!("Stored secret is not UTF-8: {e}"))
+    }
+}
+
+/// Holds the key once it is loaded; empty while the store is locked.
+struct KeySlot(RwLock<Option<Cipher>>);
+
+impl KeySlot {
+    const fn new() -> Self {
+        Self(RwLock::new(None))
+    }
+
+    fn get(&self) -> Result<Cipher, String> {
+        self.0
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| LOCKED.to_string())
+    }
+
+    fn set(&self, cipher: Cipher) {
+        *self.0.write().unwrap() = Some(cipher);
+    }
+
+    fn is_set(&self) -> bool {
+        self.0.read().unwrap().is_some()
+    }
+}
+
+fn cipher() -> Result<Cipher, String> {
+    CIPHER.get()
+}
+
+/// Install a fixed key so tests can read and write stored credentials.
+#[cfg(test)]
+pub(crate) fn install_test_key() {
+    CIPHER.set(Cipher::from_key(&[7u8; 32]).unwrap());
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+pub fn encrypt(plaintext: &str) -> Result<String, String> {
+    cipher()?.encrypt(plaintext)
+}
+
+/// Plaintext of a stored value. Values written before encryption pass through unchanged.
+pub fn decrypt(stored: &str) -> Result<String, String> {
+    if !is_encrypted(stored) {
+        return Ok(stored.to_string());
+    }
+    cipher()?.decrypt(stored)
+}
+
+pub fn encrypt_opt(plaintext: Option<&str>) -> Result<Option<String>, String> {
+    plaintext.map(encrypt).transpose()
+}
+
+pub fn decrypt_opt(stored: Option<&str>) -> Result<Option<String>, String> {
+    stored.map(decrypt).transpose()
+}
+
+/// The key stored in the OS keyring, created on first use. Reads it back after creating
+/// it, since some platform stores accept writes they do not keep.
+fn keyring_cipher(create: bool) -> Result<Cipher, String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|e| e.to_string())?;
+    let encoded = match entry.get_password() {
+        Ok(encoded) => encoded,
+        Err(keyring::Error::NoEntry) if create => {
+            let encoded = b64().encode(ChaCha20Poly1305::generate_key(&mut OsRng));
+            entry.set_password(&encoded).map_err(|e| e.to_string())?;
+            let stored = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+                .and_then(|entry| entry.get_password())
+                .map_err(|e| e.to_string())?;
+            if stored != encoded {
+                return Err("the keyring did not keep the secrets key".to_string());
+            }
+            encoded
+        }
+        Err(keyring::Error::NoEntry) => {
+            return Err("the secrets key is missing from the keyring".to_string());
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    let key = b64()
+        .decode(encoded.trim())
+        .map_err(|e| format!("the keyring holds an invalid secrets key: {e}"))?;
+    Cipher::from_key(&key)
+}
+
+/// Derive the passphrase key and check it against the stored check value. The first
+/// passphrase sets the salt and check value.
+fn passphrase_cipher(conn: &Connection, passphrase: &str) -> Result<Cipher, String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase is required".to_string());
+    }
+    let salt = match read_settings_value(conn, KDF_SALT_KEY)? {
+        Some(encoded) => b64()
+            .decode(encoded)
+            .map_err(|e| format!("Stored passphrase salt is invalid: {e}"))?,
+        None => {
+            let mut salt = vec![0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            write_settings_value(conn, KDF_SALT_KEY, &b64().encode(&salt))?;
+            salt
+        }
+    };
+    let cipher = Cipher::derive(passphrase, &salt)?;
+    match read_settings_value(conn, KEY_CHECK_KEY)? {
+        Some(check) => {
+            if cipher.decrypt(&check).ok().as_deref() != Some(KEY_CHECK_VALUE) {
+                return Err("Incorrect passphrase".to_string());
+            }
+        }
+        None => write_settings_value(conn, KEY_CHECK_KEY, &cipher.encrypt(KEY_CHECK_VALUE)?)?,
+    }
+    Ok(cipher)
+}
+
+fn install(conn: &Connection, cipher: Cipher) -> Result<(), String> {
+    CIPHER.set(cipher);
+    let migrated = migrate_plaintext(conn)?;
+    if migrated > 0 {
+        log::info!("secrets: encrypted {migrated} stored credential(s)");
+    }
+    Ok(())
+}
+
+/// Load the key at startup: from the keyring, or from the passphrase environment variable
+/// when this install uses a passphrase. Leaves the store locked when neither is available.
+pub fn init(conn: &Connection) -> Result<SecretsStatus, String> {
+    let source = read_settings_value(conn, KEY_SOURCE_KEY)?;
+    if source.as_deref() != Some(KEY_SOURCE_PASSPHRASE) {
+        match keyring_cipher(source.is_none()) {
+            Ok(cipher) => {
+                write_settings_value(conn, KEY_SOURCE_KEY, KEY_SOURCE_KEYRING)?;
+                install(conn, cipher)?;
+                return status(conn);
+            }
+            Err(e) if source.is_none() => {
+                log::warn!("secrets: OS keyring unavailable, using a passphrase: {e}");
+                write_settings_value(conn, KEY_SOURCE_KEY, KEY_SOURCE_PASSPHRASE)?;
+            }
+            Err(e) => {
+                return Err(format!(
+                    "Failed to read the secrets key from the keyring: {e}"
+                ))
+            }
+        }
+    }
+    if let Some(passphrase) = std::env::var(PASSPHRASE_ENV).ok().filter(|p| !p.is_empty()) {
+        install(conn, passphrase_cipher(conn, &passphrase)?)?;
+    }
+    status(conn)
+}
+
+/// Unlock a passphrase-protected store, setting the passphrase on first use.
+pub fn unlock(conn: &Connection, passphrase: &str) -> Result<SecretsStatus, String> {
+    let source = read_settings_value(conn, KEY_SOURCE_KEY)?;
+    if source.as_deref() != Some(KEY_SOURCE_PASSPHRASE) {
+        return Err("This install keeps its secrets key in the OS keyring".to_string());
+    }
+    install(conn, passphrase_cipher(conn, passphrase)?)?;
+    status(conn)
+}
+
+pub fn status(conn: &Connection) -> Result<SecretsStatus, String> {
+    Ok(SecretsStatus {
+        key_source: read_settings_value(conn, KEY_SOURCE_KEY)?
+            .unwrap_or_else(|| KEY_SOURCE_KEYRING.to_string()),
+        unlocked: CIPHER.is_set(),
+        passphrase_set: read_settings_value(conn, KEY_CHECK_KEY)?.is_some(),
+    })
+}
+
+/// Encrypt credentials stored in plaintext by earlier versions. Returns how many values
+/// were rewritten.
+pub fn migrate_plaintext(conn: &Connection) -> Result<usize, String> {
+    let mut migrated = 0;
+    let stored = crate::db::read_stored_settings(conn)?;
+    let plaintext = [&stored.anthropic_api_key, &stored.github_oauth_token]
+        .into_iter()
+        .flatten()
+        .filter(|value| !is_encrypted(value))
+        .count();
+    if plaintext > 0 {
+        crate::db::write_settings(conn, &crate::db::read_settings(conn)?)?;
+        migrated += plaintext;
+    }
+
+    let rows = conn
+        .prepare("SELECT id, source_password, fabric_service_principal_secret FROM workspaces")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+    for (id, source_password, principal_secret) in rows {
+        for (column, value) in [
+            ("source_password", source_password),
+            ("fabric_service_principal_secret", principal_secret),
+        ] {
+            let Some(value) = value.filter(|v| !is_encrypted(v)) else {
+                continue;
+            };
+            conn.execute(
+                &format!("UPDATE workspaces SET {column} = ?1 WHERE id = ?2"),
+                params![encrypt(&value)?, id],
+            )
+            .map_err(|e| e.to_string())?;
+            migrated += 1;
+        }
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::types::AppSettings;
+
+    #[test]
+    fn values_round_trip_and_plaintext_passes_through() {
+        install_test_key();
+        let sealed = encrypt("hunter2").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("hunter2"));
+        assert_ne!(sealed, encrypt("hunter2").unwrap());
+        assert_eq!(decrypt(&sealed).unwrap(), "hunter2");
+        assert_eq!(decrypt("legacy").unwrap(), "legacy");
+
+        let other = Cipher::from_key(&[9u8; 32]).unwrap();
+        assert!(other.decrypt(&sealed).is_err());
+    }
+
+    #[test]
+    fn locked_store_refuses_to_read_or_write_secrets() {
+        let slot = KeySlot::new();
+        assert!(!slot.is_set());
+        assert_eq!(slot.get().err().as_deref(), Some(LOCKED));
+
+        let cipher = Cipher::from_key(&[7u8; 32]).unwrap();
+        let sealed = cipher.encrypt("hunter2").unwrap();
+        slot.set(cipher);
+        assert!(slot.is_set());
+        assert_eq!(slot.get().unwrap().decrypt(&sealed).unwrap(), "hunter2");
+    }
+
+    #[test]
+    fn passphrase_is_checked_after_first_use() {
+        let conn = db::open_in_memory().unwrap();
+        assert!(passphrase_cipher(&conn, "").is_err());
+        let first = passphrase_cipher(&conn, "correct horse").unwrap();
+        let sealed = first.encrypt("token").unwrap();
+
+        let again = passphrase_cipher(&conn, "correct horse").unwrap();
+        assert_eq!(again.decrypt(&sealed).unwrap(), "token");
+        assert_eq!(
+            passphrase_cipher(&conn, "wrong").err().as_deref(),
+            Some("Incorrect passphrase")
+        );
+    }
+
+    #[test]
+    fn migrate_plaintext_encrypts_settings_and_workspace_secrets() {
+        let conn = db::open_in_memory().unwrap();
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES ('app_settings', ?1)",
+            [r#"{"anthropicApiKey":"sk-ant-old","githubOauthToken":"gho_old","githubUserLogin":"octocat"}"#],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO workspaces(id, display_name, migration_repo_path, source_password, created_at)
+             VALUES ('ws', 'WS', '/tmp/repo', 'sql-old', '2026-01-01T00:00:00Z')",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(migrate_plaintext(&conn).unwrap(), 3);
+        let raw: String = conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = 'app_settings'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!raw.contains("sk-ant-old") && !raw.contains("gho_old"));
+        let password: String = conn
+            .query_row("SELECT source_password FROM workspaces", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(decrypt(&password).unwrap(), "sql-old");
+
+        let settings: AppSettings = db::read_settings(&conn).unwrap();
+        assert_eq!(settings.anthropic_api_key.as_deref(), Some("sk-ant-old"));
+        assert_eq!(settings.github_user_login.as_deref(), Some("octocat"));
+        assert_eq!(migrate_plaintext(&conn).unwrap(), 0);
+    }
+}
@@ -8,6 +8,8 @@ pub enum SourceQuery {
     DiscoverSchemas,
     DiscoverTables,
     DiscoverProcedures,
+    DiscoverColumns,
+    DiscoverCheckConstraints,
 }
 
 impl SourceQuery {
@@ -18,6 +20,8 @@ impl SourceQuery {
             SourceQuery::DiscoverSchemas => "discover_schemas",
             SourceQuery::DiscoverTables => "discover_tables",
             SourceQuery::DiscoverProcedures => "discover_procedures",
+            SourceQuery::DiscoverColumns => "discover_columns",
+            SourceQuery::DiscoverCheckConstraints => "discover_check_constraints",
         }
     }
 }
@@ -36,15 +40,21 @@ pub fn resolve_source_query(
         ("sql_server", SourceQuery::DiscoverContainerId) => Ok(include_str!(
             "../sql/source/sql_server/discover_container_id.sql"
         )),
-        ("sql_server", SourceQuery::DiscoverSchemas) => Ok(include_str!(
+        ("sql_server" | "fabric_warehouse", SourceQuery::DiscoverSchemas) => Ok(include_str!(
             "../sql/source/sql_server/discover_schemas.sql"
         )),
-        ("sql_server", SourceQuery::DiscoverTables) => {
+        ("sql_server" | "fabric_warehouse", SourceQuery::DiscoverTables) => {
             Ok(include_str!("../sql/source/sql_server/discover_tables.sql"))
         }
-        ("sql_server", SourceQuery::DiscoverProcedures) => Ok(include_str!(
+        ("sql_server" | "fabric_warehouse", SourceQuery::DiscoverProcedures) => Ok(include_str!(
             "../sql/source/sql_server/discover_procedures.sql"
         )),
+        ("sql_server" | "fabric_warehouse", SourceQuery::DiscoverColumns) => Ok(include_str!(
+            "../sql/source/sql_server/discover_columns.sql"
+        )),
+        ("sql_server", SourceQuery::DiscoverCheckConstraints) => Ok(include_str!(
+            "../sql/source/sql_server/discover_check_constraints.sql"
+        )),
         _ => Err(CommandError::Io(format!(
             "Unsupported source query lookup: source_type={source_type}, query={}",
             query.name()
@@ -106,6 +116,35 @@ mod tests {
         let procedures =
             resolve_source_query("sql_server", SourceQuery::DiscoverProcedures).unwrap();
         assert!(procedures.contains("sys.procedures"));
+
+        let columns = resolve_source_query("sql_server", SourceQuery::DiscoverColumns).unwrap();
+        assert!(columns.contains("sys.columns"));
+
+        let checks =
+            resolve_source_query("sql_server", SourceQuery::DiscoverCheckConstraints).unwrap();
+        assert!(checks.contains("sys.check_constraints"));
+    }
+
+    #[test]
+    fn fabric_warehouse_reuses_catalog_queries() {
+        for query in [
+            SourceQuery::DiscoverSchemas,
+            SourceQuery::DiscoverTables,
+            SourceQuery::DiscoverProcedures,
+            SourceQuery::DiscoverColumns,
+        ] {
+            assert_eq!(
+                resolve_source_query("fabric_warehouse", query).unwrap(),
+                resolve_source_query("sql_server", query).unwrap()
+            );
+        }
+        assert!(
+            resolve_source_query("fabric_warehouse", SourceQuery::DiscoverContainerId).is_err()
+        );
+        assert!(
+            resolve_source_query("fabric_warehouse", SourceQuery::DiscoverCheckConstraints)
+                .is_err()
+        );
     }
 
     #[test]
@@ -0,0 +1,789 @@
+use std::collections::HashMap;
+
+use super::lexer::{
+    matching_paren, next_significant, prev_significant, split_arguments, tokenize, Token, TokenKind,
+};
+
+/// Upper bound on alternative values tracked per variable before giving up on a site.
+const MAX_CANDIDATES: usize = 16;
+
+/// A dynamic SQL execution found in a procedure body.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DynamicSqlCall {
+    pub line: usize,
+    /// `exec` for `EXEC(...)` / `EXEC @proc`, `sp_executesql` otherwise.
+    pub exec_kind: &'static str,
+    /// `resolved` when every candidate is plain text, `partial` when some candidates still
+    /// contain `{@param}` placeholders, `opaque` when the text could not be reconstructed.
+    pub resolution: &'static str,
+    /// Possible statement texts, with unresolved parameters rendered as `{@name}`.
+    pub statements: Vec<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Param(String),
+}
+
+type Template = Vec<Segment>;
+
+#[derive(Clone, Debug)]
+enum Value {
+    Known(Vec<Template>),
+    Opaque(String),
+}
+
+impl Value {
+    fn text(text: impl Into<String>) -> Value {
+        Value::Known(vec![vec![Segment::Text(text.into())]])
+    }
+
+    fn concat(self, other: Value) -> Value {
+        match (self, other) {
+            (Value::Known(left), Value::Known(right)) => {
+                if left.len() * right.len() > MAX_CANDIDATES {
+                    return Value::Opaque("too many possible values".to_string());
+                }
+                let mut out = Vec::new();
+                for l in &left {
+                    for r in &right {
+                        let mut joined = l.clone();
+                        joined.extend(r.iter().cloned());
+                        out.push(normalize(joined));
+                    }
+                }
+                Value::Known(out)
+            }
+            (Value::Opaque(reason), _) | (_, Value::Opaque(reason)) => Value::Opaque(reason),
+        }
+    }
+
+    fn union(self, other: Value) -> Value {
+        match (self, other) {
+            (Value::Known(mut left), Value::Known(right)) => {
+                for candidate in right {
+                    if !left.contains(&candidate) {
+                        left.push(candidate);
+                    }
+                }
+                if left.len() > MAX_CANDIDATES {
+                    return Value::Opaque("too many possible values".to_string());
+                }
+                Value::Known(left)
+            }
+            (Value::Opaque(reason), _) | (_, Value::Opaque(reason)) => Value::Opaque(reason),
+        }
+    }
+
+    /// Apply a text transform to the literal parts of every candidate.
+    fn map_text(self, f: impl Fn(&str) -> String) -> Value {
+        match self {
+            Value::Known(candidates) => Value::Known(
+                candidates
+                    .into_iter()
+                    .map(|template| {
+                        template
+                            .into_iter()
+                            .map(|segment| match segment {
+                                Segment::Text(text) => Segment::Text(f(&text)),
+                                param => param,
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            ),
+            opaque => opaque,
+        }
+    }
+}
+
+/// Merge adjacent text segments so rendered placeholders sit inside contiguous text.
+fn normalize(template: Template) -> Template {
+    let mut out: Template = Vec::new();
+    for segment in template {
+        match (out.last_mut(), segment) {
+            (Some(Segment::Text(prev)), Segment::Text(text)) => prev.push_str(&text),
+            (_, segment) => out.push(segment),
+        }
+    }
+    out
+}
+
+fn render(template: &Template) -> String {
+    template
+        .iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.clone(),
+            Segment::Param(name) => format!("{{{name}}}"),
+        })
+        .collect()
+}
+
+/// Keywords that end a statement when seen outside parentheses.
+const STATEMENT_KEYWORDS: [&str; 24] = [
+    "SET",
+    "DECLARE",
+    "SELECT",
+    "EXEC",
+    "EXECUTE",
+    "IF",
+    "WHILE",
+    "BEGIN",
+    "END",
+    "ELSE",
+    "INSERT",
+    "UPDATE",
+    "DELETE",
+    "MERGE",
+    "TRUNCATE",
+    "CREATE",
+    "DROP",
+    "ALTER",
+    "RETURN",
+    "PRINT",
+    "RAISERROR",
+    "THROW",
+    "COMMIT",
+    "ROLLBACK",
+];
+
+/// Walks a procedure body, tracking string variables, and evaluates every dynamic execution.
+#[derive(Default)]
+struct Analyzer {
+    variables: HashMap<String, Value>,
+    calls: Vec<DynamicSqlCall>,
+}
+
+/// Find `EXEC(...)`, `EXEC @proc` and `sp_executesql` calls and reconstruct the SQL they run.
+///
+/// String variables are followed through `DECLARE`, `SET`, `SET +=` and `SELECT @v = ...`
+/// assignments built from literals, other variables, `CAST`/`CONVERT`, `QUOTENAME`, `CONCAT`,
+/// `REPLACE` and `CHAR`. Variables never assigned (procedure parameters) become `{@name}`
+/// placeholders. Assignments under `IF`/`ELSE`/`WHILE` add alternatives instead of replacing.
+pub fn find_dynamic_sql(sql: &str) -> Vec<DynamicSqlCall> {
+    let tokens = tokenize(sql);
+    let mut analyzer = Analyzer::default();
+    analyzer.walk(&tokens);
+    analyzer.calls
+}
+
+impl Analyzer {
+    fn walk(&mut self, tokens: &[Token]) {
+        // Each open BEGIN block records whether it is the body of a conditional.
+        let mut blocks: Vec<bool> = Vec::new();
+        let mut pending_conditional = false;
+        let mut case_depth = 0usize;
+        let mut i = 0;
+        while let Some(k) = next_significant(tokens, i) {
+            let token = &tokens[k];
+            i = k + 1;
+            if token.is_symbol("(") {
+                i = matching_paren(tokens, k).map_or(k + 1, |close| close + 1);
+                continue;
+            }
+            if token.kind != TokenKind::Word {
+                continue;
+            }
+            let upper = token.text.to_ascii_uppercase();
+            let in_conditional = blocks.iter().any(|&conditional| conditional);
+            match upper.as_str() {
+                "CASE" => case_depth += 1,
+                "END" if case_depth > 0 => case_depth -= 1,
+                "IF" | "WHILE" | "ELSE" => pending_conditional = true,
+                "BEGIN" => {
+                    let next = next_significant(tokens, k + 1).map(|n| &tokens[n]);
+                    if next.is_some_and(|t| {
+                        t.is_word("TRAN") || t.is_word("TRANSACTION") || t.is_word("DISTRIBUTED")
+                    }) {
+                        continue;
+                    }
+                    if next.is_some_and(|t| t.is_word("TRY") || t.is_word("CATCH")) {
+                        i = next_significant(tokens, k + 1).map_or(i, |n| n + 1);
+                    }
+                    blocks.push(pending_conditional || in_conditional);
+                    pending_conditional = false;
+                }
+                "END" => {
+                    blocks.pop();
+                    let next = next_significant(tokens, k + 1).map(|n| &tokens[n]);
+                    if next.is_some_and(|t| t.is_word("TRY") || t.is_word("CATCH")) {
+                        i = next_significant(tokens, k + 1).map_or(i, |n| n + 1);
+                    }
+                }
+                "SET" | "DECLARE" | "SELECT" | "EXEC" | "EXECUTE" => {
+                    let end = statement_end(tokens, k);
+                    let conditional = pending_conditional || in_conditional;
+                    pending_conditional = false;
+                    match upper.as_str() {
+                        "SET" => self.assign_set(tokens, k, end, conditional),
+                        "DECLARE" => self.assign_declare(tokens, k, end),
+                        "SELECT" => self.assign_select(tokens, k, end, conditional),
+                        _ => self.exec(tokens, k, end),
+                    }
+                    i = end;
+                }
+                "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "TRUNCATE" | "RETURN" | "PRINT"
+                | "RAISERROR" | "THROW" => pending_conditional = false,
+                _ => {}
+            }
+        }
+    }
+
+    fn assign(&mut self, name: &str, value: Value, append: bool, conditional: bool) {
+        let key = name.to_ascii_lowercase();
+        let value = if append {
+            self.lookup(name).concat(value)
+        } else {
+            value
+        };
+        let merged = match self.variables.remove(&key) {
+            Some(existing) if conditional => existing.union(value),
+            _ => value,
+        };
+        self.variables.insert(key, merged);
+    }
+
+    fn lookup(&self, name: &str) -> Value {
+        if name.starts_with("@@") {
+            return Value::Opaque(format!("system variable {name}"));
+        }
+        self.variables
+            .get(&name.to_ascii_lowercase())
+            .cloned()
+            .unwrap_or_else(|| Value::Known(vec![vec![Segment::Param(name.to_string())]]))
+    }
+
+    /// `SET @v = expr` / `SET @v += expr`
+    fn assign_set(&mut self, tokens: &[Token], k: usize, end: usize, conditional: bool) {
+        let Some(var) =
+            next_significant(tokens, k + 1).filter(|&v| tokens[v].kind == TokenKind::Variable)
+        else {
+            return;
+        };
+        let Some(op) = next_significant(tokens, var + 1) else {
+            return;
+        };
+        let append = tokens[op].is_symbol("+=");
+        if !append && !tokens[op].is_symbol("=") {
+            return;
+        }
+        let value = self.evaluate(&tokens[op + 1..end]);
+        self.assign(&tokens[var].text, value, append, conditional);
+    }
+
+    /// `DECLARE @a nvarchar(max) = expr, @b int`
+    fn assign_declare(&mut self, tokens: &[Token], k: usize, end: usize) {
+        for (start, stop) in top_level_segments(tokens, k + 1, end) {
+            let Some(var) = next_significant(tokens, start).filter(|&v| v < stop) else {
+                continue;
+            };
+            if tokens[var].kind != TokenKind::Variable {
+                continue;
+            }
+            let eq = (var + 1..stop).find(|&e| tokens[e].is_symbol("="));
+            if let Some(eq) = eq {
+                let value = self.evaluate(&tokens[eq + 1..stop]);
+                self.assign(&tokens[var].text, value, false, false);
+            } else {
+                // A declared but unassigned variable is NULL; forget any earlier value.
+                self.variables
+                    .remove(&tokens[var].text.to_ascii_lowercase());
+            }
+        }
+    }
+
+    /// `SELECT @v = expr [, @w = expr] [FROM ...]`
+    fn assign_select(&mut self, tokens: &[Token], k: usize, end: usize, conditional: bool) {
+        let first = next_significant(tokens, k + 1);
+        if !first.is_some_and(|f| tokens[f].kind == TokenKind::Variable) {
+            return;
+        }
+        let from_query = (k + 1..end).any(|i| {
+            (tokens[i].is_word("FROM") || tokens[i].is_word("WHERE")) && depth_at(tokens, k, i) == 0
+        });
+        for (start, stop) in top_level_segments(tokens, k + 1, end) {
+            let Some(var) = next_significant(tokens, start).filter(|&v| v < stop) else {
+                continue;
+            };
+            let Some(op) = next_significant(tokens, var + 1).filter(|&o| o < stop) else {
+                continue;
+            };
+            let append = tokens[op].is_symbol("+=");
+            if tokens[var].kind != TokenKind::Variable || (!append && !tokens[op].is_symbol("=")) {
+                continue;
+            }
+            let value = if from_query {
+                Value::Opaque(format!("{} is assigned from a query", tokens[var].text))
+            } else {
+                self.evaluate(&tokens[op + 1..stop])
+            };
+            self.assign(&tokens[var].text, value, append, conditional);
+        }
+    }
+
+    fn exec(&mut self, tokens: &[Token], k: usize, end: usize) {
+        let line = tokens[k].line;
+        let Some(first) = next_significant(tokens, k + 1).filter(|&f| f < end) else {
+            return;
+        };
+
+        if tokens[first].is_symbol("(") {
+            let Some(close) = matching_paren(tokens, first) else {
+                return;
+            };
+            let value = self.evaluate(&tokens[first + 1..close]);
+            self.record(line, "exec", value, |text| text.to_string());
+            return;
+        }
+
+        let mut name_start = first;
+        if tokens[first].kind == TokenKind::Variable {
+            match next_significant(tokens, first + 1) {
+                // EXEC @rc = dbo.proc
+                Some(eq) if tokens[eq].is_symbol("=") => {
+                    let Some(next) = next_significant(tokens, eq + 1) else {
+                        return;
+                    };
+                    name_start = next;
+                }
+                // EXEC @proc_name: the procedure itself is chosen at runtime.
+                _ => {
+                    let value = self.lookup(&tokens[first].text);
+                    self.record(line, "exec", value, |text| format!("EXEC {text}"));
+                    return;
+                }
+            }
+        }
+
+        let mut name_end = name_start;
+        while name_end < end
+            && (matches!(
+                tokens[name_end].kind,
+                TokenKind::Word | TokenKind::BracketIdent
+            ) || tokens[name_end].is_symbol("."))
+        {
+            name_end += 1;
+        }
+        let is_executesql = tokens[name_start..name_end]
+            .iter()
+            .rev()
+            .find_map(Token::identifier)
+            .is_some_and(|name| name.eq_ignore_ascii_case("sp_executesql"));
+        if !is_executesql {
+            return;
+        }
+        let Some((start, stop)) = top_level_segments(tokens, name_end, end).into_iter().next()
+        else {
+            self.calls.push(DynamicSqlCall {
+                line,
+                exec_kind: "sp_executesql",
+                resolution: "opaque",
+                statements: Vec::new(),
+                reason: Some("sp_executesql called without a statement".to_string()),
+            });
+            return;
+        };
+        // Named form: sp_executesql @stmt = N'...'
+        let mut expr_start = start;
+        if let Some(var) = next_significant(tokens, start).filter(|&v| v < stop) {
+            if tokens[var].text.eq_ignore_ascii_case("@stmt") {
+                if let Some(eq) =
+                    next_significant(tokens, var + 1).filter(|&e| tokens[e].is_symbol("="))
+                {
+                    expr_start = eq + 1;
+                }
+            }
+        }
+        let value = self.evaluate(&tokens[expr_start..stop]);
+        self.record(line, "sp_executesql", value, |text| text.to_string());
+    }
+
+    fn record(
+        &mut self,
+        line: usize,
+        exec_kind: &'static str,
+        value: Value,
+        wrap: impl Fn(&str) -> String,
+    ) {
+        let call = match value {
+            Value::Known(candidates) => {
+                let has_params = candidates
+                    .iter()
+                    .any(|t| t.iter().any(|s| matches!(s, Segment::Param(_))));
+                DynamicSqlCall {
+                    line,
+                    exec_kind,
+                    resolution: if has_params { "partial" } else { "resolved" },
+                    statements: candidates.iter().map(|t| wrap(&render(t))).collect(),
+                    reason: has_params.then(|| "statement text depends on parameters".to_string()),
+                }
+            }
+            Value::Opaque(reason) => DynamicSqlCall {
+                line,
+                exec_kind,
+                resolution: "opaque",
+                statements: Vec::new(),
+                reason: Some(reason),
+            },
+        };
+        self.calls.push(call);
+    }
+
+    /// Evaluate a string expression made of `+`-joined terms.
+    fn evaluate(&self, tokens: &[Token]) -> Value {
+        let mut result: Option<Value> = None;
+        let mut i = 0;
+        loop {
+            let Some(start) = next_significant(tokens, i) else {
+                return result.unwrap_or_else(|| Value::Opaque("empty expression".to_string()));
+            };
+            let (term, next) = self.term(tokens, start);
+            result = Some(match result {
+                Some(acc) => acc.concat(term),
+                None => term,
+            });
+            match next_significant(tokens, next) {
+                None => {
+                    return result.unwrap_or_else(|| Value::Opaque("empty expression".to_string()))
+                }
+                Some(op) if tokens[op].is_symbol("+") => i = op + 1,
+                Some(op) => {
+                    return Value::Opaque(format!(
+                        "unsupported expression near `{}`",
+                        tokens[op].text
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Evaluate one term starting at `start`; returns the value and the index after it.
+    fn term(&self, tokens: &[Token], start: usize) -> (Value, usize) {
+        let token = &tokens[start];
+        match token.kind {
+            TokenKind::StringLiteral => (
+                Value::text(token.string_value().unwrap_or_default()),
+                start + 1,
+            ),
+            TokenKind::Number => (Value::text(token.text.clone()), start + 1),
+            TokenKind::Variable => (self.lookup(&token.text), start + 1),
+            TokenKind::Symbol if token.is_symbol("(") => match matching_paren(tokens, start) {
+                Some(close) => (self.evaluate(&tokens[start + 1..close]), close + 1),
+                None => (
+                    Value::Opaque("unbalanced parentheses".to_string()),
+                    tokens.len(),
+                ),
+            },
+            TokenKind::Word if token.is_word("CASE") => self.case_expression(tokens, start),
+            TokenKind::Word => {
+                // Possibly qualified function name, e.g. dbo.fn_columns(...)
+                let mut last = start;
+                while tokens.get(last + 1).is_some_and(|t| t.is_symbol("."))
+                    && tokens
+                        .get(last + 2)
+                        .is_some_and(|t| t.kind == TokenKind::Word)
+                {
+                    last += 2;
+                }
+                let name: String = tokens[start..=last]
+                    .iter()
+                    .map(|t| t.text.as_str())
+                    .collect();
+                let open = next_significant(tokens, last + 1).filter(|&o| tokens[o].is_symbol("("));
+                let close = open.and_then(|o| matching_paren(tokens, o));
+                match (open, close) {
+                    (Some(open), Some(close)) => {
+                        (self.function(tokens, &name, open, close), close + 1)
+                    }
+                    _ => (
+                        Value::Opaque(format!("`{name}` is not a string literal or variable")),
+                        last + 1,
+                    ),
+                }
+            }
+            _ => (
+                Value::Opaque(format!("unsupported expression near `{}`", token.text)),
+                start + 1,
+            ),
+        }
+    }
+
+    /// Every `THEN` / `ELSE` branch of a CASE expression is a possible value.
+    fn case_expression(&self, tokens: &[Token], start: usize) -> (Value, usize) {
+        let mut depth = 0usize;
+        let mut nested = 0usize;
+        let mut branch_start: Option<usize> = None;
+        let mut value: Option<Value> = None;
+        for i in start + 1..tokens.len() {
+            let token = &tokens[i];
+            if token.is_symbol("(") {
+                depth += 1;
+                continue;
+            }
+            if token.is_symbol(")") {
+                depth = depth.saturating_sub(1);
+                continue;
+            }
+            if depth > 0 {
+                continue;
+            }
+            if token.is_word("CASE") {
+                nested += 1;
+                continue;
+            }
+            if token.is_word("END") && nested > 0 {
+                nested -= 1;
+                continue;
+            }
+            let boundary = nested == 0
+                && ["WHEN", "THEN", "ELSE", "END"]
+                    .iter()
+                    .any(|k| token.is_word(k));
+            if !boundary {
+                continue;
+            }
+            if let Some(branch) = branch_start.take() {
+                let branch = self.evaluate(&tokens[branch..i]);
+                value = Some(match value {
+                    Some(acc) => acc.union(branch),
+                    None => branch,
+                });
+            }
+            if token.is_word("THEN") || token.is_word("ELSE") {
+                branch_start = Some(i + 1);
+            }
+            if token.is_word("END") {
+                let value = value.unwrap_or_else(|| Value::Opaque("empty CASE".to_string()));
+                return (value, i + 1);
+            }
+        }
+        (Value::Opaque("unterminated CASE".to_string()), tokens.len())
+    }
+
+    fn function(&self, tokens: &[Token], name: &str, open: usize, close: usize) -> Value {
+        let args = split_arguments(tokens, open, close);
+        let upper = name.to_ascii_uppercase();
+        match (upper.as_str(), args.as_slice()) {
+            ("CAST" | "TRY_CAST", [arg]) => {
+                let as_idx = arg.iter().rposition(|t| t.is_word("AS"));
+                match as_idx {
+                    Some(as_idx) => self.evaluate(&arg[..as_idx]),
+                    None => Value::Opaque("malformed CAST".to_string()),
+                }
+            }
+            ("CONVERT" | "TRY_CONVERT", [_, value, ..]) => self.evaluate(value),
+            ("ISNULL" | "COALESCE", [value, ..]) => self.evaluate(value),
+            ("CONCAT", args) if !args.is_empty() => args
+                .iter()
+                .map(|arg| self.evaluate(arg))
+                .reduce(Value::concat)
+                .unwrap_or_else(|| Value::text("")),
+            ("QUOTENAME", [value, rest @ ..]) => {
+                let quote = rest
+                    .first()
+                    .and_then(|q| q.iter().find_map(Token::string_value))
+                    .unwrap_or_else(|| "[".to_string());
+                let (open_q, close_q) = match quote.as_str() {
+                    "\"" => ("\"", "\""),
+                    "'" => ("'", "'"),
+                    _ => ("[", "]"),
+                };
+                Value::text(open_q)
+                    .concat(
+                        self.evaluate(value)
+                            .map_text(|t| t.replace(close_q, &close_q.repeat(2))),
+                    )
+                    .concat(Value::text(close_q))
+            }
+            ("REPLACE", [value, from, to]) => {
+                let (Some(from), Some(to)) = (literal(from), literal(to)) else {
+                    return Value::Opaque("REPLACE with non-literal arguments".to_string());
+                };
+                self.evaluate(value).map_text(|t| t.replace(&from, &to))
+            }
+            ("LTRIM", [value]) => self
+                .evaluate(value)
+                .map_text(|t| t.trim_start().to_string()),
+            ("RTRIM", [value]) => self.evaluate(value).map_text(|t| t.trim_end().to_string()),
+            ("UPPER", [value]) => self.evaluate(value).map_text(str::to_uppercase),
+            ("LOWER", [value]) => self.evaluate(value).map_text(str::to_lowercase),
+            ("CHAR" | "NCHAR", [code]) => literal_number(code)
+                .and_then(char::from_u32)
+                .map(|c| Value::text(c.to_string()))
+                .unwrap_or_else(|| Value::Opaque("CHAR with a non-literal code".to_string())),
+            ("SPACE", [count]) => literal_number(count)
+                .map(|n| Value::text(" ".repeat(n as usize)))
+                .unwrap_or_else(|| Value::Opaque("SPACE with a non-literal count".to_string())),
+            _ => Value::Opaque(format!("function {name} is not evaluated statically")),
+        }
+    }
+}
+
+fn literal(tokens: &[Token]) -> Option<String> {
+    let mut significant = tokens.iter().filter(|t| !t.is_trivia());
+    let token = significant.next()?;
+    if significant.next().is_some() {
+        return None;
+    }
+    token.string_value()
+}
+
+fn literal_number(tokens: &[Token]) -> Option<u32> {
+    let mut significant = tokens.iter().filter(|t| !t.is_trivia());
+    let token = significant.next()?;
+    if significant.next().is_some() || token.kind != TokenKind::Number {
+        return None;
+    }
+    token.text.parse().ok()
+}
+
+/// Paren depth of `at` relative to `from`.
+fn depth_at(tokens: &[Token], from: usize, at: usize) -> usize {
+    let mut depth = 0usize;
+    for token in &tokens[from..at] {
+        if token.is_symbol("(") {
+            depth += 1;
+        } else if token.is_symbol(")") {
+            depth = depth.saturating_sub(1);
+        }
+    }
+    depth
+}
+
+/// Split `tokens[start..end]` at top-level commas into `(start, stop)` ranges.
+fn top_level_segments(tokens: &[Token], start: usize, end: usize) -> Vec<(usize, usize)> {
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut segment_start = start;
+    for (i, token) in tokens.iter().enumerate().take(end).skip(start) {
+        if token.is_symbol("(") {
+            depth += 1;
+        } else if token.is_symbol(")") {
+            depth = depth.saturating_sub(1);
+        } else if token.is_symbol(",") && depth == 0 {
+            segments.push((segment_start, i));
+            segment_start = i + 1;
+        }
+    }
+    if tokens[segment_start..end].iter().any(|t| !t.is_trivia()) {
+        segments.push((segment_start, end));
+    }
+    segments
+}
+
+/// Index just past the statement starting at `start`.
+fn statement_end(tokens: &[Token], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut case_depth = 0usize;
+    for i in start + 1..tokens.len() {
+        let token = &tokens[i];
+        if token.is_symbol("(") {
+            depth += 1;
+        } else if token.is_symbol(")") {
+            depth = depth.saturating_sub(1);
+        } else if depth > 0 {
+            continue;
+        } else if token.is_symbol(";") {
+            return i;
+        } else if token.is_word("CASE") {
+            case_depth += 1;
+        } else if token.is_word("END") && case_depth > 0 {
+            case_depth -= 1;
+        } else if case_depth == 0 && STATEMENT_KEYWORDS.iter().any(|k| token.is_word(k)) {
+            let after_set_op = prev_significant(tokens, i).is_some_and(|p| {
+                ["UNION", "ALL", "EXCEPT", "INTERSECT"]
+                    .iter()
+                    .any(|k| tokens[p].is_word(k))
+            });
+            if !(token.is_word("SELECT") && after_set_op) {
+                return i;
+            }
+        }
+    }
+    tokens.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconstructs_literal_concatenation() {
+        let sql = "DECLARE @sql NVARCHAR(MAX) = N'INSERT INTO dbo.target ';
+                   SET @sql += 'SELECT * FROM ' + QUOTENAME('stg') + '.' + QUOTENAME(N'orders');
+                   EXEC(@sql);";
+        let calls = find_dynamic_sql(sql);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].exec_kind, "exec");
+        assert_eq!(calls[0].resolution, "resolved");
+        assert_eq!(calls[0].line, 3);
+        assert_eq!(
+            calls[0].statements,
+            vec!["INSERT INTO dbo.target SELECT * FROM [stg].[orders]"]
+        );
+    }
+
+    #[test]
+    fn interpolated_parameters_become_placeholders() {
+        let sql = "CREATE PROCEDURE dbo.load @table sysname AS
+                   DECLARE @stmt NVARCHAR(MAX);
+                   SET @stmt = N'TRUNCATE TABLE stg.' + @table;
+                   EXEC sp_executesql @stmt, N'@id int', @id = 1;";
+        let calls = find_dynamic_sql(sql);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].exec_kind, "sp_executesql");
+        assert_eq!(calls[0].resolution, "partial");
+        assert_eq!(calls[0].statements, vec!["TRUNCATE TABLE stg.{@table}"]);
+    }
+
+    #[test]
+    fn conditional_assignments_produce_alternatives() {
+        let sql = "DECLARE @sql VARCHAR(200) = 'DELETE FROM dbo.a';
+                   IF @full = 1
+                   BEGIN
+                     SET @sql = 'DELETE FROM dbo.b';
+                   END
+                   EXECUTE sys.sp_executesql @stmt = @sql;";
+        let calls = find_dynamic_sql(sql);
+        assert_eq!(calls[0].resolution, "resolved");
+        assert_eq!(
+            calls[0].statements,
+            vec!["DELETE FROM dbo.a", "DELETE FROM dbo.b"]
+        );
+    }
+
+    #[test]
+    fn case_branches_produce_alternatives() {
+        let sql = "SET @sql = 'SELECT * FROM dbo.' + CASE WHEN @kind = 1 THEN 'orders' ELSE 'returns' END;
+                   EXEC(@sql)";
+        let calls = find_dynamic_sql(sql);
+        assert_eq!(
+            calls[0].statements,
+            vec!["SELECT * FROM dbo.orders", "SELECT * FROM dbo.returns"]
+        );
+    }
+
+    #[test]
+    fn query_assigned_sql_is_opaque() {
+        let sql = "DECLARE @sql NVARCHAR(MAX);
+                   SELECT @sql = definition FROM dbo.sql_templates WHERE id = 1;
+                   EXEC (@sql);
+                   EXEC dbo.static_proc @a = 1;";
+        let calls = find_dynamic_sql(sql);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].resolution, "opaque");
+        assert_eq!(
+            calls[0].reason.as_deref(),
+            Some("@sql is assigned from a query")
+        );
+    }
+
+    #[test]
+    fn non_string_expressions_are_opaque() {
+        let calls = find_dynamic_sql("EXEC('SELECT ' + dbo.fn_columns() + ' FROM t')");
+        assert_eq!(calls[0].resolution, "opaque");
+        assert!(calls[0].reason.as_deref().unwrap().contains("fn_columns"));
+    }
+}
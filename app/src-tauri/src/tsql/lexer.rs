@@ -0,0 +1,336 @@
+/// Token classes produced by [`tokenize`].
+///
+/// The lexer is lossless: concatenating every token's `text` reproduces the input exactly,
+/// which lets rewriters copy untouched regions verbatim.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Whitespace,
+    LineComment,
+    BlockComment,
+    /// `'...'` or `N'...'`, quotes included.
+    StringLiteral,
+    Number,
+    /// Bare identifier or keyword.
+    Word,
+    /// `@name` or `@@name`.
+    Variable,
+    /// `#name` or `##name`.
+    TempName,
+    /// `[name]`, brackets included.
+    BracketIdent,
+    /// `"name"`, quotes included.
+    QuotedIdent,
+    Symbol,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    /// 1-based line number of the token's first character.
+    pub line: usize,
+}
+
+impl Token {
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.kind,
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment
+        )
+    }
+
+    /// Case-insensitive keyword match for bare words.
+    pub fn is_word(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    pub fn is_symbol(&self, symbol: &str) -> bool {
+        self.kind == TokenKind::Symbol && self.text == symbol
+    }
+
+    /// Identifier text with brackets or double quotes removed.
+    pub fn identifier(&self) -> Option<String> {
+        match self.kind {
+            TokenKind::Word | TokenKind::TempName => Some(self.text.clone()),
+            TokenKind::BracketIdent => Some(
+                self.text
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .replace("]]", "]"),
+            ),
+            TokenKind::QuotedIdent => Some(
+                self.text
+                    .trim_start_matches('"')
+                    .trim_end_matches('"')
+                    .replace("\"\"", "\""),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Contents of a string literal with the `N` prefix, quotes and `''` escapes removed.
+    pub fn string_value(&self) -> Option<String> {
+        if self.kind != TokenKind::StringLiteral {
+            return None;
+        }
+        let body = self
+            .text
+            .strip_prefix(['N', 'n'])
+            .unwrap_or(&self.text)
+            .strip_prefix('\'')?;
+        let body = body.strip_suffix('\'').unwrap_or(body);
+        Some(body.replace("''", "'"))
+    }
+}
+
+const MULTI_CHAR_SYMBOLS: [&str; 11] = [
+    "<>", "!=", ">=", "<=", "!<", "!>", "+=", "-=", "*=", "/=", "::",
+];
+
+pub fn tokenize(sql: &str) -> Vec<Token> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let mut line = 1;
+
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        let kind = if c.is_whitespace() {
+            while i < chars.len() && chars[i].is_whitespace() {
+                i += 1;
+            }
+            TokenKind::Whitespace
+        } else if c == '-' && next == Some('-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            TokenKind::LineComment
+        } else if c == '/' && next == Some('*') {
+            // T-SQL block comments nest.
+            let mut depth = 0usize;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            TokenKind::BlockComment
+        } else if c == '\'' || ((c == 'N' || c == 'n') && next == Some('\'')) {
+            i += if c == '\'' { 1 } else { 2 };
+            i = scan_quoted(&chars, i, '\'');
+            TokenKind::StringLiteral
+        } else if c == '[' {
+            i = scan_quoted(&chars, i + 1, ']');
+            TokenKind::BracketIdent
+        } else if c == '"' {
+            i = scan_quoted(&chars, i + 1, '"');
+            TokenKind::QuotedIdent
+        } else if c.is_ascii_digit() || (c == '.' && next.is_some_and(|n| n.is_ascii_digit())) {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            TokenKind::Number
+        } else if c == '@' || c == '#' {
+            while i < chars.len() && (chars[i] == c) {
+                i += 1;
+            }
+            while i < chars.len() && is_identifier_char(chars[i]) {
+                i += 1;
+            }
+            if c == '@' {
+                TokenKind::Variable
+            } else {
+                TokenKind::TempName
+            }
+        } else if is_identifier_start(c) {
+            while i < chars.len() && is_identifier_char(chars[i]) {
+                i += 1;
+            }
+            TokenKind::Word
+        } else {
+            let pair: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+            i += if MULTI_CHAR_SYMBOLS.contains(&pair.as_str()) {
+                2
+            } else {
+                1
+            };
+            TokenKind::Symbol
+        };
+
+        let text: String = chars[start..i].iter().collect();
+        let token_line = line;
+        line += text.matches('\n').count();
+        tokens.push(Token {
+            kind,
+            text,
+            line: token_line,
+        });
+    }
+
+    tokens
+}
+
+/// Advance past a quoted run where the closing quote is escaped by doubling it.
+fn scan_quoted(chars: &[char], mut i: usize, close: char) -> usize {
+    while i < chars.len() {
+        if chars[i] == close {
+            if chars.get(i + 1) == Some(&close) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    i
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Index of the next non-trivia token at or after `from`.
+pub fn next_significant(tokens: &[Token], from: usize) -> Option<usize> {
+    (from..tokens.len()).find(|&i| !tokens[i].is_trivia())
+}
+
+/// Index of the closest non-trivia token before `before`.
+pub fn prev_significant(tokens: &[Token], before: usize) -> Option<usize> {
+    (0..before.min(tokens.len()))
+        .rev()
+        .find(|&i| !tokens[i].is_trivia())
+}
+
+/// Index of the `)` matching the `(` at `open`, if balanced.
+pub fn matching_paren(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        if token.is_symbol("(") {
+            depth += 1;
+        } else if token.is_symbol(")") {
+            depth = depth.checked_sub(1)?;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// Split the tokens strictly between `open` and `close` at top-level commas.
+pub fn split_arguments(tokens: &[Token], open: usize, close: usize) -> Vec<&[Token]> {
+    let inner = &tokens[open + 1..close];
+    if inner.iter().all(Token::is_trivia) {
+        return Vec::new();
+    }
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, token) in inner.iter().enumerate() {
+        if token.is_symbol("(") {
+            depth += 1;
+        } else if token.is_symbol(")") {
+            depth = depth.saturating_sub(1);
+        } else if token.is_symbol(",") && depth == 0 {
+            args.push(&inner[start..i]);
+            start = i + 1;
+        }
+    }
+    args.push(&inner[start..]);
+    args
+}
+
+/// Concatenate token text, trimming surrounding whitespace.
+pub fn render(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .map(|t| t.text.as_str())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(sql: &str) -> Vec<(TokenKind, String)> {
+        tokenize(sql)
+            .into_iter()
+            .filter(|t| !t.is_trivia())
+            .map(|t| (t.kind, t.text))
+            .collect()
+    }
+
+    #[test]
+    fn tokenize_is_lossless() {
+        let sql = "SELECT [a]], b], N'it''s', @x, ##tmp /* c /* nested */ */ -- tail\nFROM t;";
+        let joined: String = tokenize(sql).into_iter().map(|t| t.text).collect();
+        assert_eq!(joined, sql);
+    }
+
+    #[test]
+    fn tokenize_classifies_tsql_tokens() {
+        assert_eq!(
+            kinds("SELECT @@ROWCOUNT, #t.[col], N'x', 1.5e3 FROM \"q\" WHERE a <> b"),
+            vec![
+                (TokenKind::Word, "SELECT".to_string()),
+                (TokenKind::Variable, "@@ROWCOUNT".to_string()),
+                (TokenKind::Symbol, ",".to_string()),
+                (TokenKind::TempName, "#t".to_string()),
+                (TokenKind::Symbol, ".".to_string()),
+                (TokenKind::BracketIdent, "[col]".to_string()),
+                (TokenKind::Symbol, ",".to_string()),
+                (TokenKind::StringLiteral, "N'x'".to_string()),
+                (TokenKind::Symbol, ",".to_string()),
+                (TokenKind::Number, "1.5e3".to_string()),
+                (TokenKind::Word, "FROM".to_string()),
+                (TokenKind::QuotedIdent, "\"q\"".to_string()),
+                (TokenKind::Word, "WHERE".to_string()),
+                (TokenKind::Word, "a".to_string()),
+                (TokenKind::Symbol, "<>".to_string()),
+                (TokenKind::Word, "b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_tracks_line_numbers() {
+        let tokens = tokenize("SELECT 1\n/* a\nb */\nFROM t");
+        let from = tokens.iter().find(|t| t.is_word("FROM")).unwrap();
+        assert_eq!(from.line, 4);
+    }
+
+    #[test]
+    fn split_arguments_respects_nesting() {
+        let tokens = tokenize("f(a, g(b, c), 'x,y')");
+        let open = tokens.iter().position(|t| t.is_symbol("(")).unwrap();
+        let close = matching_paren(&tokens, open).unwrap();
+        let args: Vec<String> = split_arguments(&tokens, open, close)
+            .into_iter()
+            .map(render)
+            .collect();
+        assert_eq!(args, vec!["a", "g(b, c)", "'x,y'"]);
+    }
+
+    #[test]
+    fn string_value_unescapes_quotes() {
+        let tokens = tokenize("N'it''s'");
+        assert_eq!(tokens[0].string_value().as_deref(), Some("it's"));
+    }
+}
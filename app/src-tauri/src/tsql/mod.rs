@@ -0,0 +1,4 @@
+pub mod dynamic_sql;
+pub mod lexer;
+pub mod references;
+pub mod transpile;
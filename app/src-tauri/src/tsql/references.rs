@@ -0,0 +1,535 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use super::lexer::{
+    matching_paren, next_significant, prev_significant, tokenize, Token, TokenKind,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AccessKind {
+    Read,
+    Write,
+}
+
+impl AccessKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AccessKind::Read => "read",
+            AccessKind::Write => "write",
+        }
+    }
+}
+
+/// A permanent table (or view) touched by a statement. Names keep their source casing.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TableReference {
+    pub schema: Option<String>,
+    pub name: String,
+    pub access: AccessKind,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReferenceScan {
+    pub references: Vec<TableReference>,
+    /// Table positions whose name contains a `{@param}` placeholder and so cannot be resolved.
+    pub placeholders: usize,
+}
+
+/// Keywords that can never be a table alias.
+const NON_ALIAS_KEYWORDS: [&str; 44] = [
+    "WHERE",
+    "JOIN",
+    "INNER",
+    "LEFT",
+    "RIGHT",
+    "FULL",
+    "CROSS",
+    "OUTER",
+    "ON",
+    "GROUP",
+    "ORDER",
+    "HAVING",
+    "UNION",
+    "EXCEPT",
+    "INTERSECT",
+    "SET",
+    "WITH",
+    "OPTION",
+    "USING",
+    "WHEN",
+    "SELECT",
+    "INSERT",
+    "UPDATE",
+    "DELETE",
+    "MERGE",
+    "VALUES",
+    "OUTPUT",
+    "FOR",
+    "END",
+    "ELSE",
+    "IF",
+    "BEGIN",
+    "RETURN",
+    "DECLARE",
+    "EXEC",
+    "EXECUTE",
+    "AND",
+    "OR",
+    "PIVOT",
+    "UNPIVOT",
+    "TABLESAMPLE",
+    "WHILE",
+    "DEFAULT",
+    "TRUNCATE",
+];
+
+/// Keywords that start a new statement when they appear outside parentheses.
+const STATEMENT_KEYWORDS: [&str; 20] = [
+    "INSERT",
+    "UPDATE",
+    "DELETE",
+    "MERGE",
+    "TRUNCATE",
+    "DECLARE",
+    "IF",
+    "WHILE",
+    "BEGIN",
+    "EXEC",
+    "EXECUTE",
+    "CREATE",
+    "DROP",
+    "ALTER",
+    "RETURN",
+    "PRINT",
+    "RAISERROR",
+    "THROW",
+    "COMMIT",
+    "ROLLBACK",
+];
+
+/// Pseudo-tables that only exist inside triggers and OUTPUT clauses.
+const PSEUDO_TABLES: [&str; 2] = ["inserted", "deleted"];
+
+struct ParsedName {
+    parts: Vec<String>,
+    placeholder: bool,
+    next: usize,
+}
+
+/// Statement-local state: aliases seen in FROM/JOIN and alias-form UPDATE/DELETE targets.
+#[derive(Default)]
+struct StatementScope {
+    keyword: Option<String>,
+    aliases: HashMap<String, TableReference>,
+    pending_writes: Vec<TableReference>,
+}
+
+/// Find the permanent tables a T-SQL batch reads and writes.
+///
+/// Temp tables, table variables, CTE names and trigger pseudo-tables are skipped. `UPDATE a`
+/// and `DELETE a` targets are resolved through the aliases declared in the same statement.
+pub fn extract_table_references(sql: &str) -> ReferenceScan {
+    let tokens = tokenize(sql);
+    let ctes = cte_names(&tokens);
+    let mut found = BTreeSet::new();
+    let mut placeholders = 0;
+    let mut scope = StatementScope::default();
+    let mut depth = 0usize;
+    let mut case_depth = 0usize;
+
+    let mut i = 0;
+    while let Some(k) = next_significant(&tokens, i) {
+        let token = &tokens[k];
+        i = k + 1;
+        if token.is_symbol("(") {
+            depth += 1;
+            continue;
+        }
+        if token.is_symbol(")") {
+            depth = depth.saturating_sub(1);
+            continue;
+        }
+        if token.is_symbol(";") {
+            finish_statement(&mut scope, &mut found);
+            continue;
+        }
+        if token.kind != TokenKind::Word {
+            continue;
+        }
+        let upper = token.text.to_ascii_uppercase();
+        if upper == "CASE" {
+            case_depth += 1;
+        } else if upper == "END" && case_depth > 0 {
+            case_depth -= 1;
+        } else if depth == 0 && starts_statement(&tokens, k, &upper, &scope, case_depth) {
+            finish_statement(&mut scope, &mut found);
+            scope.keyword = Some(upper.clone());
+        }
+
+        let prev = prev_significant(&tokens, k).map(|p| &tokens[p]);
+        let prev_word = |keyword: &str| prev.is_some_and(|p| p.is_word(keyword));
+        let target = match upper.as_str() {
+            "FROM" if scope.keyword.as_deref() == Some("DELETE") && prev_word("DELETE") => {
+                Some((AccessKind::Write, false))
+            }
+            // FETCH NEXT FROM cursor
+            "FROM"
+                if ["NEXT", "PRIOR", "FIRST", "LAST"]
+                    .iter()
+                    .any(|kw| prev_word(kw)) =>
+            {
+                None
+            }
+            "FROM" | "JOIN" | "USING" | "APPLY" => Some((AccessKind::Read, true)),
+            "INTO" => Some((AccessKind::Write, false)),
+            "INSERT" | "MERGE" => {
+                let into =
+                    next_significant(&tokens, k + 1).is_some_and(|n| tokens[n].is_word("INTO"));
+                (!into).then_some((AccessKind::Write, false))
+            }
+            "TABLE" if prev_word("TRUNCATE") => Some((AccessKind::Write, false)),
+            "UPDATE" | "DELETE"
+                if scope.keyword.as_deref() == Some(upper.as_str()) && !prev_word("ON") =>
+            {
+                let next = next_significant(&tokens, k + 1);
+                if next.is_some_and(|n| tokens[n].is_word("FROM")) {
+                    None
+                } else {
+                    let start = next_significant(&tokens, skip_top(&tokens, k + 1));
+                    if let Some(parsed) = start.and_then(|n| parse_name(&tokens, n)) {
+                        if parsed.placeholder {
+                            placeholders += 1;
+                        } else if let Some(reference) =
+                            to_reference(&parsed.parts, AccessKind::Write, &ctes)
+                        {
+                            scope.pending_writes.push(reference);
+                        }
+                        i = parsed.next;
+                    }
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        let Some((access, list)) = target else {
+            continue;
+        };
+        let mut cursor = k + 1;
+        while let Some(parsed) =
+            next_significant(&tokens, cursor).and_then(|n| parse_name(&tokens, n))
+        {
+            cursor = parsed.next;
+            if parsed.placeholder {
+                placeholders += 1;
+            } else if let Some(reference) = to_reference(&parsed.parts, access, &ctes) {
+                if access == AccessKind::Read {
+                    let alias_end = record_alias(&tokens, cursor, &reference, &mut scope);
+                    cursor = alias_end;
+                }
+                found.insert(reference);
+            }
+            // Comma-separated FROM lists: FROM a, b
+            match next_significant(&tokens, cursor) {
+                Some(comma) if list && tokens[comma].is_symbol(",") => cursor = comma + 1,
+                _ => break,
+            }
+        }
+        i = i.max(cursor);
+    }
+    finish_statement(&mut scope, &mut found);
+
+    ReferenceScan {
+        references: found.into_iter().collect(),
+        placeholders,
+    }
+}
+
+fn finish_statement(scope: &mut StatementScope, found: &mut BTreeSet<TableReference>) {
+    for pending in scope.pending_writes.drain(..) {
+        let resolved = if pending.schema.is_none() {
+            scope
+                .aliases
+                .get(&pending.name.to_lowercase())
+                .map(|aliased| TableReference {
+                    access: AccessKind::Write,
+                    ..aliased.clone()
+                })
+        } else {
+            None
+        };
+        found.insert(resolved.unwrap_or(pending));
+    }
+    scope.aliases.clear();
+    scope.keyword = None;
+}
+
+fn starts_statement(
+    tokens: &[Token],
+    k: usize,
+    upper: &str,
+    scope: &StatementScope,
+    case_depth: usize,
+) -> bool {
+    let prev = prev_significant(tokens, k).map(|p| &tokens[p]);
+    let prev_word =
+        |keywords: &[&str]| prev.is_some_and(|p| keywords.iter().any(|kw| p.is_word(kw)));
+    match upper {
+        "SELECT" => {
+            !prev_word(&["UNION", "ALL", "EXCEPT", "INTERSECT"])
+                && !matches!(scope.keyword.as_deref(), Some("INSERT"))
+        }
+        "SET" => !matches!(scope.keyword.as_deref(), Some("UPDATE" | "MERGE")),
+        "END" | "ELSE" => case_depth == 0,
+        "INSERT" | "UPDATE" | "DELETE" => !prev_word(&["THEN", "ON"]),
+        _ => STATEMENT_KEYWORDS.contains(&upper),
+    }
+}
+
+/// Skip `TOP (n)` / `TOP n` after UPDATE or DELETE.
+fn skip_top(tokens: &[Token], from: usize) -> usize {
+    let Some(top) = next_significant(tokens, from).filter(|&t| tokens[t].is_word("TOP")) else {
+        return from;
+    };
+    match next_significant(tokens, top + 1) {
+        Some(open) if tokens[open].is_symbol("(") => matching_paren(tokens, open)
+            .map(|close| close + 1)
+            .unwrap_or(from),
+        Some(value) => value + 1,
+        None => from,
+    }
+}
+
+/// Parse a possibly qualified object name starting at `start`.
+///
+/// Returns `None` for derived tables, table variables, temp tables and function calls.
+fn parse_name(tokens: &[Token], start: usize) -> Option<ParsedName> {
+    let mut parts = Vec::new();
+    let mut placeholder = false;
+    let mut i = start;
+    loop {
+        let token = tokens.get(i)?;
+        if token.is_symbol(".") {
+            // `db..table` omits the schema.
+            parts.push(String::new());
+            i += 1;
+            continue;
+        }
+        if token.is_symbol("{") {
+            parts.push(String::new());
+        } else {
+            let part = match token.kind {
+                TokenKind::Word
+                    if parts.is_empty() && NON_ALIAS_KEYWORDS.iter().any(|k| token.is_word(k)) =>
+                {
+                    return None
+                }
+                TokenKind::Word | TokenKind::BracketIdent | TokenKind::QuotedIdent => {
+                    token.identifier()?
+                }
+                _ => return None,
+            };
+            parts.push(part);
+            i += 1;
+        }
+        // `{@param}` placeholders left by dynamic SQL reconstruction, possibly glued to
+        // surrounding text such as `stage_{@suffix}`.
+        while let Some(t) = tokens.get(i) {
+            if t.is_symbol("{") {
+                let close = (i..tokens.len()).find(|&c| tokens[c].is_symbol("}"))?;
+                placeholder = true;
+                i = close + 1;
+            } else if placeholder && matches!(t.kind, TokenKind::Word | TokenKind::Number) {
+                i += 1;
+            } else {
+                break;
+            }
+        }
+        match tokens.get(i) {
+            Some(t) if t.is_symbol(".") => i += 1,
+            _ => break,
+        }
+    }
+    if !placeholder && next_significant(tokens, i).is_some_and(|n| tokens[n].is_symbol("(")) {
+        // Table-valued function, unless this is an INSERT/MERGE target with a column list.
+        let prev = prev_significant(tokens, start).map(|p| &tokens[p]);
+        if !prev.is_some_and(|p| p.is_word("INTO") || p.is_word("INSERT") || p.is_word("MERGE")) {
+            return None;
+        }
+    }
+    Some(ParsedName {
+        parts,
+        placeholder,
+        next: i,
+    })
+}
+
+fn to_reference(
+    parts: &[String],
+    access: AccessKind,
+    ctes: &HashSet<String>,
+) -> Option<TableReference> {
+    let name = parts.last().filter(|n| !n.is_empty())?.clone();
+    let schema = parts
+        .len()
+        .checked_sub(2)
+        .map(|idx| parts[idx].clone())
+        .filter(|s| !s.is_empty());
+    if schema.is_none() {
+        let lower = name.to_lowercase();
+        if ctes.contains(&lower) || PSEUDO_TABLES.contains(&lower.as_str()) {
+            return None;
+        }
+    }
+    Some(TableReference {
+        schema,
+        name,
+        access,
+    })
+}
+
+/// Record the alias following a table source and return the index after it.
+fn record_alias(
+    tokens: &[Token],
+    from: usize,
+    reference: &TableReference,
+    scope: &mut StatementScope,
+) -> usize {
+    scope
+        .aliases
+        .insert(reference.name.to_lowercase(), reference.clone());
+    let mut cursor = from;
+    let mut next = next_significant(tokens, cursor);
+    if let Some(as_idx) = next.filter(|&n| tokens[n].is_word("AS")) {
+        cursor = as_idx + 1;
+        next = next_significant(tokens, cursor);
+    }
+    let Some(alias_idx) = next else {
+        return from;
+    };
+    let alias = &tokens[alias_idx];
+    let is_alias = match alias.kind {
+        TokenKind::Word => !NON_ALIAS_KEYWORDS.iter().any(|k| alias.is_word(k)),
+        TokenKind::BracketIdent | TokenKind::QuotedIdent => true,
+        _ => false,
+    };
+    if !is_alias {
+        return from;
+    }
+    if let Some(name) = alias.identifier() {
+        scope.aliases.insert(name.to_lowercase(), reference.clone());
+    }
+    alias_idx + 1
+}
+
+fn cte_names(tokens: &[Token]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for (with, _) in tokens.iter().enumerate().filter(|(_, t)| t.is_word("WITH")) {
+        let mut cursor = with;
+        while let Some(name_idx) = next_significant(tokens, cursor + 1) {
+            let Some(name) = tokens[name_idx].identifier() else {
+                break;
+            };
+            let mut after = next_significant(tokens, name_idx + 1);
+            if let Some(cols) = after.filter(|&c| tokens[c].is_symbol("(")) {
+                after = matching_paren(tokens, cols)
+                    .and_then(|close| next_significant(tokens, close + 1));
+            }
+            let Some(open) = after
+                .filter(|&a| tokens[a].is_word("AS"))
+                .and_then(|a| next_significant(tokens, a + 1))
+                .filter(|&o| tokens[o].is_symbol("("))
+            else {
+                break;
+            };
+            names.insert(name.to_lowercase());
+            let Some(close) = matching_paren(tokens, open) else {
+                break;
+            };
+            match next_significant(tokens, close + 1) {
+                Some(comma) if tokens[comma].is_symbol(",") => cursor = comma,
+                _ => break,
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn refs(sql: &str) -> Vec<(String, &'static str)> {
+        extract_table_references(sql)
+            .references
+            .into_iter()
+            .map(|r| {
+                let name = match r.schema {
+                    Some(schema) => format!("{schema}.{}", r.name),
+                    None => r.name,
+                };
+                (name, r.access.as_str())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extracts_reads_and_writes() {
+        let sql = "INSERT INTO dbo.fact_sales (id, amount)
+                   SELECT s.id, s.amount FROM [stg].[sales] s, dbo.extra e
+                   JOIN dbo.dim_date d ON d.date_key = s.date_key;
+                   TRUNCATE TABLE stg.sales;";
+        assert_eq!(
+            refs(sql),
+            vec![
+                ("dbo.dim_date".to_string(), "read"),
+                ("dbo.extra".to_string(), "read"),
+                ("dbo.fact_sales".to_string(), "write"),
+                ("stg.sales".to_string(), "read"),
+                ("stg.sales".to_string(), "write"),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_update_and_delete_aliases() {
+        let sql = "UPDATE t SET t.v = s.v FROM dbo.target t JOIN dbo.source s ON s.id = t.id;
+                   DELETE x FROM dbo.old_rows AS x WHERE x.stale = 1;
+                   DELETE FROM dbo.audit WHERE 1 = 0";
+        assert_eq!(
+            refs(sql),
+            vec![
+                ("dbo.audit".to_string(), "write"),
+                ("dbo.old_rows".to_string(), "read"),
+                ("dbo.old_rows".to_string(), "write"),
+                ("dbo.source".to_string(), "read"),
+                ("dbo.target".to_string(), "read"),
+                ("dbo.target".to_string(), "write"),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_temp_tables_variables_ctes_and_functions() {
+        let sql = "WITH recent AS (SELECT id FROM dbo.orders)
+                   INSERT INTO #work SELECT r.id FROM recent r
+                   CROSS APPLY dbo.fn_split(r.id) f JOIN @ids i ON i.id = r.id;
+                   MERGE dbo.customer AS tgt USING (SELECT * FROM stg.customer) AS src ON 1 = 1
+                   WHEN MATCHED THEN UPDATE SET tgt.x = src.x;";
+        assert_eq!(
+            refs(sql),
+            vec![
+                ("dbo.customer".to_string(), "write"),
+                ("dbo.orders".to_string(), "read"),
+                ("stg.customer".to_string(), "read"),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_placeholder_names() {
+        let scan = extract_table_references("SELECT * FROM dbo.{@table} JOIN dbo.known k ON 1 = 1");
+        assert_eq!(scan.placeholders, 1);
+        assert_eq!(scan.references.len(), 1);
+        assert_eq!(scan.references[0].name, "known");
+    }
+}
@@ -0,0 +1,1992 @@
+use std::collections::HashSet;
+
+use super::lexer::{
+    matching_paren, next_significant, prev_significant, render, split_arguments, tokenize, Token,
+    TokenKind,
+};
+use crate::types::{TranspileIssue, TranspileResult, TranspileSeverity, TranspileTarget};
+
+/// Mechanically rewrite a T-SQL batch for `target`.
+///
+/// The rewrite is token-based and deterministic: text that needs no change is copied verbatim,
+/// known constructs are rewritten in place, and everything that cannot be translated is left
+/// as-is and reported in `issues` so the output can be finished by hand.
+pub fn transpile(sql: &str, target: TranspileTarget) -> TranspileResult {
+    let tokens = tokenize(sql);
+    let mut transpiler = Transpiler {
+        target,
+        issues: Vec::new(),
+        reported: HashSet::new(),
+    };
+    let sql = transpiler.rewrite(&tokens, Context::Default);
+    let fully_translated = !transpiler
+        .issues
+        .iter()
+        .any(|issue| issue.severity == TranspileSeverity::Unsupported);
+    TranspileResult {
+        target,
+        sql,
+        issues: transpiler.issues,
+        fully_translated,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Context {
+    Default,
+    /// Column list of `CREATE TABLE` / `DECLARE @t TABLE`.
+    ColumnDefs,
+    /// Single argument of `CAST(expr AS type)`.
+    Cast,
+    /// First argument of `CONVERT(type, expr)`.
+    TypeArg,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TypeFamily {
+    String,
+    Date,
+    Timestamp,
+    Other,
+}
+
+struct TypeMapping {
+    /// Replacement text, or `None` to keep the original type spelling.
+    text: Option<String>,
+    issue: Option<(TranspileSeverity, String)>,
+}
+
+/// A `TOP n` waiting to be emitted as `LIMIT n` at the end of its statement.
+struct PendingLimit {
+    value: String,
+    line: usize,
+    /// `significant_end` when the `TOP` was consumed.
+    anchor: usize,
+}
+
+/// Output buffer and statement state for one token slice.
+#[derive(Default)]
+struct Scope {
+    out: String,
+    /// Byte offset just past the last significant text, so trailing clauses such as `LIMIT`
+    /// land before trailing whitespace and comments.
+    significant_end: usize,
+    statement: Option<String>,
+    pending_limit: Option<PendingLimit>,
+    case_depth: usize,
+    element_pos: usize,
+}
+
+impl Scope {
+    fn push_trivia(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    fn push(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.out.push_str(text);
+        self.significant_end = self.out.len();
+    }
+
+    /// Append the pending `LIMIT` after the statement's last significant text. Returns the
+    /// `TOP`'s line instead when nothing followed it, since the clause would land mid-statement.
+    fn flush_limit(&mut self) -> Option<usize> {
+        let limit = self.pending_limit.take()?;
+        if self.significant_end == limit.anchor {
+            return Some(limit.line);
+        }
+        let clause = format!(" LIMIT {}", limit.value);
+        self.out.insert_str(self.significant_end, &clause);
+        self.significant_end += clause.len();
+        None
+    }
+
+    fn statement_is(&self, keyword: &str) -> bool {
+        self.statement.as_deref() == Some(keyword)
+    }
+}
+
+struct Transpiler {
+    target: TranspileTarget,
+    issues: Vec<TranspileIssue>,
+    reported: HashSet<(usize, String)>,
+}
+
+const TABLE_HINTS: [&str; 17] = [
+    "NOLOCK",
+    "READUNCOMMITTED",
+    "READCOMMITTED",
+    "READPAST",
+    "HOLDLOCK",
+    "UPDLOCK",
+    "XLOCK",
+    "ROWLOCK",
+    "PAGLOCK",
+    "TABLOCK",
+    "TABLOCKX",
+    "SERIALIZABLE",
+    "REPEATABLEREAD",
+    "SNAPSHOT",
+    "NOWAIT",
+    "INDEX",
+    "FORCESEEK",
+];
+
+const PROCEDURAL_KEYWORDS: [&str; 12] = [
+    "IF",
+    "WHILE",
+    "BEGIN",
+    "COMMIT",
+    "ROLLBACK",
+    "RAISERROR",
+    "THROW",
+    "PRINT",
+    "RETURN",
+    "GOTO",
+    "EXEC",
+    "EXECUTE",
+];
+
+const SESSION_OPTIONS: [&str; 10] = [
+    "NOCOUNT",
+    "XACT_ABORT",
+    "ANSI_NULLS",
+    "ANSI_WARNINGS",
+    "QUOTED_IDENTIFIER",
+    "ROWCOUNT",
+    "TRANSACTION",
+    "IDENTITY_INSERT",
+    "DATEFIRST",
+    "LANGUAGE",
+];
+
+/// Words that end an operand chain when deciding whether `+` concatenates strings.
+const CHAIN_BOUNDARY_KEYWORDS: [&str; 33] = [
+    "SELECT", "FROM", "WHERE", "AND", "OR", "NOT", "AS", "CASE", "WHEN", "THEN", "ELSE", "END",
+    "ON", "JOIN", "SET", "ORDER", "GROUP", "BY", "HAVING", "INTO", "VALUES", "UNION", "IS", "IN",
+    "LIKE", "BETWEEN", "RETURN", "PRINT", "DECLARE", "IF", "WHILE", "EXEC", "USING",
+];
+
+const CHAIN_BOUNDARY_SYMBOLS: [&str; 10] = [",", ";", "=", "<", ">", "<>", "!=", "<=", ">=", "+="];
+
+const STRING_TYPE_WORDS: [&str; 4] = ["VARCHAR", "NVARCHAR", "CHAR", "NCHAR"];
+
+const FABRIC_UNSUPPORTED_FUNCTIONS: [(&str, &str); 3] = [
+    (
+        "SCOPE_IDENTITY",
+        "SCOPE_IDENTITY() is not supported in Fabric Warehouse; read the key back with a query on the natural key",
+    ),
+    (
+        "IDENT_CURRENT",
+        "IDENT_CURRENT() is not supported in Fabric Warehouse; use MAX() over the key column",
+    ),
+    (
+        "NEWSEQUENTIALID",
+        "NEWSEQUENTIALID() is not supported in Fabric Warehouse; use NEWID()",
+    ),
+];
+
+/// Functions [`Transpiler::spark_call`] knows how to rewrite.
+const SPARK_FUNCTIONS: [&str; 26] = [
+    "GETDATE",
+    "SYSDATETIME",
+    "CURRENT_TIMESTAMP",
+    "GETUTCDATE",
+    "SYSUTCDATETIME",
+    "NEWID",
+    "ISNULL",
+    "IIF",
+    "LEN",
+    "DATALENGTH",
+    "CHARINDEX",
+    "REPLICATE",
+    "SPACE",
+    "STUFF",
+    "CONCAT",
+    "STRING_AGG",
+    "SQUARE",
+    "ATN2",
+    "LOG",
+    "DATEFROMPARTS",
+    "EOMONTH",
+    "DATEADD",
+    "DATEDIFF",
+    "DATEDIFF_BIG",
+    "DATEPART",
+    "DATENAME",
+];
+
+const SPARK_UNSUPPORTED_FUNCTIONS: [(&str, &str); 18] = [
+    (
+        "PATINDEX",
+        "PATINDEX() has no Spark SQL equivalent; rewrite the pattern for regexp_instr()",
+    ),
+    (
+        "STRING_SPLIT",
+        "STRING_SPLIT() is table-valued; rewrite with explode(split(...))",
+    ),
+    (
+        "FORMAT",
+        "FORMAT() has no Spark SQL equivalent; use date_format() or format_number()",
+    ),
+    (
+        "ISDATE",
+        "ISDATE() has no Spark SQL equivalent; test try_to_timestamp(...) IS NOT NULL",
+    ),
+    (
+        "SYSDATETIMEOFFSET",
+        "SYSDATETIMEOFFSET() has no Spark SQL equivalent; Spark timestamps carry no offset",
+    ),
+    (
+        "SCOPE_IDENTITY",
+        "SCOPE_IDENTITY() has no Spark SQL equivalent",
+    ),
+    (
+        "IDENT_CURRENT",
+        "IDENT_CURRENT() has no Spark SQL equivalent",
+    ),
+    (
+        "NEWSEQUENTIALID",
+        "NEWSEQUENTIALID() has no Spark SQL equivalent; use uuid()",
+    ),
+    (
+        "OBJECT_ID",
+        "OBJECT_ID() has no Spark SQL equivalent; check the catalog instead",
+    ),
+    (
+        "ERROR_MESSAGE",
+        "error functions have no Spark SQL equivalent; handle errors in the notebook",
+    ),
+    (
+        "ERROR_NUMBER",
+        "error functions have no Spark SQL equivalent; handle errors in the notebook",
+    ),
+    (
+        "ERROR_LINE",
+        "error functions have no Spark SQL equivalent; handle errors in the notebook",
+    ),
+    (
+        "ERROR_SEVERITY",
+        "error functions have no Spark SQL equivalent; handle errors in the notebook",
+    ),
+    (
+        "ERROR_STATE",
+        "error functions have no Spark SQL equivalent; handle errors in the notebook",
+    ),
+    (
+        "HASHBYTES",
+        "HASHBYTES() returns binary; use sha2()/md5() and compare hex strings",
+    ),
+    (
+        "CHECKSUM",
+        "CHECKSUM() has no Spark SQL equivalent; use hash() or xxhash64()",
+    ),
+    (
+        "BINARY_CHECKSUM",
+        "BINARY_CHECKSUM() has no Spark SQL equivalent; use hash() or xxhash64()",
+    ),
+    (
+        "OPENJSON",
+        "OPENJSON() is table-valued; rewrite with from_json() and explode()",
+    ),
+];
+
+impl Transpiler {
+    fn report(
+        &mut self,
+        line: usize,
+        construct: &str,
+        severity: TranspileSeverity,
+        message: impl Into<String>,
+    ) {
+        if !self.reported.insert((line, construct.to_string())) {
+            return;
+        }
+        self.issues.push(TranspileIssue {
+            line,
+            construct: construct.to_string(),
+            severity,
+            message: message.into(),
+        });
+    }
+
+    /// Report `construct` only at its first occurrence in the batch, e.g. once per variable.
+    fn report_once(
+        &mut self,
+        line: usize,
+        construct: &str,
+        severity: TranspileSeverity,
+        message: String,
+    ) {
+        if self.reported.insert((0, construct.to_string())) {
+            self.issues.push(TranspileIssue {
+                line,
+                construct: construct.to_string(),
+                severity,
+                message,
+            });
+        }
+    }
+
+    fn spark(&self) -> bool {
+        self.target == TranspileTarget::SparkSql
+    }
+
+    fn flush_limit(&mut self, scope: &mut Scope) {
+        if let Some(line) = scope.flush_limit() {
+            self.report(
+                line,
+                "TOP",
+                TranspileSeverity::Unsupported,
+                "TOP could not be rewritten as a LIMIT at the end of the statement",
+            );
+        }
+    }
+
+    fn rewrite(&mut self, tokens: &[Token], ctx: Context) -> String {
+        let mut scope = Scope::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            if token.is_trivia() {
+                scope.push_trivia(&token.text);
+                i += 1;
+                continue;
+            }
+            if ctx == Context::ColumnDefs && token.is_symbol(",") {
+                scope.element_pos = 0;
+                scope.push(",");
+                i += 1;
+                continue;
+            }
+            let element_pos = scope.element_pos;
+            scope.element_pos += 1;
+
+            if token.is_symbol(";") {
+                self.flush_limit(&mut scope);
+                scope.statement = None;
+                scope.push(";");
+                i += 1;
+                continue;
+            }
+            if token.kind == TokenKind::Word && starts_statement(tokens, i, &scope) {
+                self.flush_limit(&mut scope);
+                let keyword = token.text.to_ascii_uppercase();
+                self.check_statement(tokens, i, &keyword);
+                scope.statement = Some(keyword);
+            }
+            i = self.rewrite_token(tokens, i, ctx, element_pos, &mut scope);
+        }
+        self.flush_limit(&mut scope);
+        scope.out
+    }
+
+    /// Rewrite the significant token at `i` and return the index of the next unprocessed token.
+    fn rewrite_token(
+        &mut self,
+        tokens: &[Token],
+        i: usize,
+        ctx: Context,
+        element_pos: usize,
+        scope: &mut Scope,
+    ) -> usize {
+        let token = &tokens[i];
+        if is_type_position(tokens, i, ctx, element_pos) {
+            if let Some(next) = self.rewrite_type(tokens, i, scope) {
+                return next;
+            }
+        }
+        match token.kind {
+            TokenKind::Word => self.rewrite_word(tokens, i, ctx, scope),
+            TokenKind::Symbol if token.is_symbol("(") => {
+                let Some(close) = matching_paren(tokens, i) else {
+                    scope.push("(");
+                    return i + 1;
+                };
+                let inner_ctx = paren_context(tokens, i, scope);
+                let inner = self.rewrite(&tokens[i + 1..close], inner_ctx);
+                scope.push(&format!("({inner})"));
+                close + 1
+            }
+            TokenKind::Symbol
+                if self.spark() && token.is_symbol("+") && is_string_concatenation(tokens, i) =>
+            {
+                scope.push("||");
+                i + 1
+            }
+            TokenKind::StringLiteral if self.spark() => {
+                let value = token.string_value().unwrap_or_default();
+                scope.push(&format!(
+                    "'{}'",
+                    value.replace('\\', "\\\\").replace('\'', "\\'")
+                ));
+                i + 1
+            }
+            TokenKind::BracketIdent | TokenKind::QuotedIdent if self.spark() => {
+                let name = token.identifier().unwrap_or_default();
+                scope.push(&format!("`{}`", name.replace('`', "``")));
+                i + 1
+            }
+            TokenKind::TempName => {
+                self.rewrite_temp_name(token, scope);
+                i + 1
+            }
+            TokenKind::Variable => {
+                self.check_variable(token);
+                scope.push(&token.text);
+                i + 1
+            }
+            _ => {
+                scope.push(&token.text);
+                i + 1
+            }
+        }
+    }
+
+    fn rewrite_word(
+        &mut self,
+        tokens: &[Token],
+        i: usize,
+        ctx: Context,
+        scope: &mut Scope,
+    ) -> usize {
+        let token = &tokens[i];
+        let upper = token.text.to_ascii_uppercase();
+        let line = token.line;
+        let next = next_significant(tokens, i + 1);
+        let prev = prev_significant(tokens, i).map(|p| &tokens[p]);
+        let next_is = |text: &str| next.is_some_and(|n| tokens[n].is_symbol(text));
+        let next_word = |keyword: &str| next.is_some_and(|n| tokens[n].is_word(keyword));
+        let prev_word = |keyword: &str| prev.is_some_and(|p| p.is_word(keyword));
+
+        if next_is("(") && !prev.is_some_and(|p| p.is_symbol(".")) {
+            let open = next.unwrap_or(i);
+            if let Some(next) = self.rewrite_call(tokens, i, open, scope) {
+                return next;
+            }
+        }
+
+        match upper.as_str() {
+            "CASE" => scope.case_depth += 1,
+            "END" if scope.case_depth > 0 => scope.case_depth -= 1,
+            "TOP" => {
+                if let Some(next) = self.rewrite_top(tokens, i, scope) {
+                    return next;
+                }
+            }
+            "OFFSET" if self.spark() => {
+                if let Some(next) = self.rewrite_offset(tokens, i, scope) {
+                    return next;
+                }
+            }
+            "IDENTITY" => return self.rewrite_identity(tokens, i, ctx, scope),
+            "MERGE" if scope.statement_is("MERGE") => {
+                if self.spark() {
+                    self.report(
+                        line,
+                        "MERGE",
+                        TranspileSeverity::Warning,
+                        "MERGE INTO requires a Delta target table",
+                    );
+                    scope.push(&token.text);
+                    if !next_word("INTO") {
+                        scope.push(" INTO");
+                    }
+                    return i + 1;
+                }
+                self.report(
+                    line,
+                    "MERGE",
+                    TranspileSeverity::Warning,
+                    "MERGE support in Fabric Warehouse is narrower than SQL Server; validate the statement or rewrite it as UPDATE + INSERT",
+                );
+            }
+            "BY" if scope.statement_is("MERGE") && prev_word("MATCHED") => {
+                if next_word("TARGET") && self.spark() {
+                    // Spark's WHEN NOT MATCHED already means "not matched by target".
+                    let target = next.unwrap_or(i);
+                    return skip_whitespace(tokens, target + 1);
+                }
+                if next_word("SOURCE") && self.spark() {
+                    self.report(
+                        line,
+                        "WHEN NOT MATCHED BY SOURCE",
+                        TranspileSeverity::Warning,
+                        "WHEN NOT MATCHED BY SOURCE requires Delta Lake 2.3 or later",
+                    );
+                }
+            }
+            "OUTPUT"
+                if self.spark()
+                    && matches!(
+                        scope.statement.as_deref(),
+                        Some("MERGE" | "INSERT" | "UPDATE" | "DELETE")
+                    ) =>
+            {
+                self.report(
+                    line,
+                    "OUTPUT",
+                    TranspileSeverity::Unsupported,
+                    "OUTPUT clauses have no Spark SQL equivalent; query the Delta table history or change data feed instead",
+                );
+            }
+            "WITH" if next_is("(") => {
+                let open = next.unwrap_or(i);
+                let hint = next_significant(tokens, open + 1).map(|h| &tokens[h]);
+                if let (Some(hint), Some(close)) = (hint, matching_paren(tokens, open)) {
+                    if TABLE_HINTS.iter().any(|h| hint.is_word(h)) {
+                        self.report(
+                            line,
+                            "table hint",
+                            TranspileSeverity::Warning,
+                            format!(
+                                "table hint {} removed; the target relies on snapshot isolation",
+                                render(&tokens[open..=close])
+                            ),
+                        );
+                        return close + 1;
+                    }
+                }
+            }
+            "WITH" if is_cte_start(tokens, i) => self.check_recursive_ctes(tokens, i),
+            "OPTION" if next_is("(") => {
+                let open = next.unwrap_or(i);
+                if let Some(close) = matching_paren(tokens, open) {
+                    self.report(
+                        line,
+                        "OPTION",
+                        TranspileSeverity::Warning,
+                        format!("query hint {} removed", render(&tokens[i..=close])),
+                    );
+                    return close + 1;
+                }
+            }
+            "FOR" if next_word("XML") => self.report(
+                line,
+                "FOR XML",
+                TranspileSeverity::Unsupported,
+                "FOR XML is not supported; build the document in the consuming application",
+            ),
+            "FOR" if next_word("JSON") && self.spark() => self.report(
+                line,
+                "FOR JSON",
+                TranspileSeverity::Unsupported,
+                "FOR JSON has no Spark SQL equivalent; use to_json(collect_list(struct(...)))",
+            ),
+            "UNION" | "EXCEPT" | "INTERSECT" if scope.pending_limit.is_some() => self.report(
+                line,
+                "TOP",
+                TranspileSeverity::Warning,
+                "TOP on the first branch of a set operation became a LIMIT on the whole result",
+            ),
+            "INTO" if self.spark() && scope.statement_is("SELECT") => self.report(
+                line,
+                "SELECT INTO",
+                TranspileSeverity::Unsupported,
+                "SELECT ... INTO is not supported in Spark SQL; use CREATE TABLE ... AS SELECT",
+            ),
+            "FROM"
+                if self.spark()
+                    && (scope.statement_is("UPDATE")
+                        || (scope.statement_is("DELETE") && !prev_word("DELETE"))) =>
+            {
+                self.report(
+                    line,
+                    "UPDATE/DELETE FROM",
+                    TranspileSeverity::Unsupported,
+                    "joined UPDATE/DELETE is not supported in Spark SQL; rewrite as MERGE INTO",
+                );
+            }
+            "APPLY" if self.spark() && (prev_word("CROSS") || prev_word("OUTER")) => self.report(
+                line,
+                "APPLY",
+                TranspileSeverity::Unsupported,
+                "CROSS/OUTER APPLY has no Spark SQL equivalent; rewrite as a LATERAL subquery or join",
+            ),
+            "PRIMARY" | "UNIQUE" | "FOREIGN" | "CHECK" | "DEFAULT"
+                if ctx == Context::ColumnDefs =>
+            {
+                self.check_constraint(line, &upper)
+            }
+            "CURRENT_TIMESTAMP" if self.spark() => {
+                scope.push("current_timestamp()");
+                return i + 1;
+            }
+            _ => {}
+        }
+        scope.push(&token.text);
+        i + 1
+    }
+
+    fn check_statement(&mut self, tokens: &[Token], i: usize, keyword: &str) {
+        let line = tokens[i].line;
+        let next = next_significant(tokens, i + 1).map(|n| &tokens[n]);
+        let after_next = next_significant(tokens, i + 1)
+            .and_then(|n| next_significant(tokens, n + 1))
+            .map(|n| &tokens[n]);
+
+        if keyword == "DECLARE" && after_next.is_some_and(|t| t.is_word("CURSOR")) {
+            self.report(
+                line,
+                "CURSOR",
+                TranspileSeverity::Unsupported,
+                "cursors are not supported; rewrite the loop as a set-based statement",
+            );
+            return;
+        }
+        if keyword == "CREATE" || keyword == "ALTER" {
+            if let Some(object) = created_object_kind(tokens, i) {
+                match object.as_str() {
+                    "INDEX" => self.report(
+                        line,
+                        "CREATE INDEX",
+                        TranspileSeverity::Unsupported,
+                        "indexes are not supported on the target; drop the statement",
+                    ),
+                    "TRIGGER" => self.report(
+                        line,
+                        "TRIGGER",
+                        TranspileSeverity::Unsupported,
+                        "triggers are not supported on the target",
+                    ),
+                    "PROC" | "PROCEDURE" | "FUNCTION" if self.spark() => self.report(
+                        line,
+                        &format!("CREATE {object}"),
+                        TranspileSeverity::Unsupported,
+                        "stored procedures and functions have no Spark SQL equivalent; run the body from a notebook",
+                    ),
+                    _ => {}
+                }
+            }
+            return;
+        }
+
+        match self.target {
+            TranspileTarget::FabricWarehouse => {
+                if keyword != "SET" {
+                    return;
+                }
+                if next.is_some_and(|t| t.is_word("IDENTITY_INSERT")) {
+                    self.report(
+                        line,
+                        "SET IDENTITY_INSERT",
+                        TranspileSeverity::Unsupported,
+                        "IDENTITY_INSERT is not supported in Fabric Warehouse; identity values are always generated",
+                    );
+                } else if next.is_some_and(|t| t.is_word("TRANSACTION")) {
+                    self.report(
+                        line,
+                        "SET TRANSACTION ISOLATION LEVEL",
+                        TranspileSeverity::Warning,
+                        "Fabric Warehouse only supports snapshot isolation; the isolation level is ignored",
+                    );
+                }
+            }
+            TranspileTarget::SparkSql => {
+                if keyword == "DECLARE" {
+                    self.report(
+                        line,
+                        "DECLARE",
+                        TranspileSeverity::Unsupported,
+                        "variable declarations have no Spark SQL equivalent; pass values as notebook parameters",
+                    );
+                } else if keyword == "SET"
+                    && next.is_some_and(|t| {
+                        t.kind == TokenKind::Variable
+                            || SESSION_OPTIONS.iter().any(|option| t.is_word(option))
+                    })
+                {
+                    self.report(
+                        line,
+                        "SET",
+                        TranspileSeverity::Unsupported,
+                        "variable assignments and session options have no Spark SQL equivalent",
+                    );
+                } else if keyword == "BEGIN"
+                    && next.is_some_and(|t| t.is_word("TRAN") || t.is_word("TRANSACTION"))
+                {
+                    self.report(
+                        line,
+                        "BEGIN TRANSACTION",
+                        TranspileSeverity::Unsupported,
+                        "explicit transactions are not supported in Spark SQL; each Delta write commits atomically",
+                    );
+                } else if PROCEDURAL_KEYWORDS.contains(&keyword) {
+                    self.report(
+                        line,
+                        keyword,
+                        TranspileSeverity::Unsupported,
+                        format!("{keyword} is procedural T-SQL; move the control flow into the notebook or pipeline"),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_variable(&mut self, token: &Token) {
+        let name = token.text.to_ascii_lowercase();
+        if self.spark() {
+            self.report_once(
+                token.line,
+                &token.text,
+                TranspileSeverity::Unsupported,
+                format!(
+                    "T-SQL variable {} has no Spark SQL equivalent; replace it with a notebook parameter or literal",
+                    token.text
+                ),
+            );
+        } else if name == "@@identity" {
+            self.report(
+                token.line,
+                "@@IDENTITY",
+                TranspileSeverity::Unsupported,
+                "@@IDENTITY is not supported in Fabric Warehouse",
+            );
+        }
+    }
+
+    fn check_constraint(&mut self, line: usize, keyword: &str) {
+        match (self.target, keyword) {
+            (TranspileTarget::FabricWarehouse, "PRIMARY" | "UNIQUE" | "FOREIGN") => self.report(
+                line,
+                &format!("{keyword} constraint"),
+                TranspileSeverity::Warning,
+                "Fabric Warehouse only accepts NONCLUSTERED ... NOT ENFORCED key constraints; they are not validated",
+            ),
+            (TranspileTarget::SparkSql, _) => self.report(
+                line,
+                &format!("{keyword} constraint"),
+                TranspileSeverity::Unsupported,
+                "column constraints are not supported in Spark SQL CREATE TABLE; remove them or use Delta CHECK constraints",
+            ),
+            _ => {}
+        }
+    }
+
+    fn rewrite_temp_name(&mut self, token: &Token, scope: &mut Scope) {
+        let name = token.text.trim_start_matches('#');
+        let global = token.text.starts_with("##");
+        match self.target {
+            TranspileTarget::FabricWarehouse => {
+                if global {
+                    self.report(
+                        token.line,
+                        "global temp table",
+                        TranspileSeverity::Unsupported,
+                        format!(
+                            "global temporary table {} is not supported in Fabric Warehouse; use a session-scoped #table or a staging table",
+                            token.text
+                        ),
+                    );
+                }
+                scope.push(&token.text);
+            }
+            TranspileTarget::SparkSql => {
+                let mapped = format!("tmp_{name}");
+                self.report_once(
+                    token.line,
+                    &token.text,
+                    TranspileSeverity::Warning,
+                    format!(
+                        "temporary table {} became table {mapped}; drop it after use or replace it with a temporary view",
+                        token.text
+                    ),
+                );
+                scope.push(&mapped);
+            }
+        }
+    }
+
+    fn rewrite_type(&mut self, tokens: &[Token], i: usize, scope: &mut Scope) -> Option<usize> {
+        let token = &tokens[i];
+        let name = token.identifier()?.to_ascii_lowercase();
+        let (args, next) = match next_significant(tokens, i + 1) {
+            Some(open) if tokens[open].is_symbol("(") => {
+                let close = matching_paren(tokens, open)?;
+                let args = split_arguments(tokens, open, close)
+                    .into_iter()
+                    .map(render)
+                    .collect::<Vec<_>>();
+                (args, close + 1)
+            }
+            _ => (Vec::new(), i + 1),
+        };
+        // Fabric Warehouse and Delta identity columns can only be BIGINT.
+        if next_significant(tokens, next).is_some_and(|n| tokens[n].is_word("IDENTITY")) {
+            if name != "bigint" {
+                self.report(
+                    token.line,
+                    &format!("type {name}"),
+                    TranspileSeverity::Warning,
+                    format!("identity columns must be BIGINT; {name} was widened"),
+                );
+            }
+            scope.push("BIGINT");
+            return Some(next);
+        }
+        let mapping = map_type(self.target, &name, &args)?;
+        if let Some((severity, message)) = mapping.issue {
+            self.report(token.line, &format!("type {name}"), severity, message);
+        }
+        match mapping.text {
+            Some(text) => scope.push(&text),
+            None => scope.push(&render(&tokens[i..next])),
+        }
+        Some(next)
+    }
+
+    fn rewrite_top(&mut self, tokens: &[Token], i: usize, scope: &mut Scope) -> Option<usize> {
+        let line = tokens[i].line;
+        let prev = prev_significant(tokens, i).map(|p| &tokens[p])?;
+        let in_select = ["SELECT", "DISTINCT", "ALL"]
+            .iter()
+            .any(|keyword| prev.is_word(keyword));
+        if !self.spark() {
+            return None;
+        }
+        if !in_select {
+            self.report(
+                line,
+                "TOP",
+                TranspileSeverity::Unsupported,
+                "TOP on INSERT/UPDATE/DELETE/MERGE has no Spark SQL equivalent",
+            );
+            return None;
+        }
+
+        let value = next_significant(tokens, i + 1)?;
+        let (limit, mut end) = if tokens[value].is_symbol("(") {
+            let close = matching_paren(tokens, value)?;
+            let inner = self.rewrite(&tokens[value + 1..close], Context::Default);
+            (inner.trim().to_string(), close)
+        } else {
+            let text = self.rewrite(&tokens[value..=value], Context::Default);
+            (text, value)
+        };
+        if let Some(percent) =
+            next_significant(tokens, end + 1).filter(|&p| tokens[p].is_word("PERCENT"))
+        {
+            self.report(
+                line,
+                "TOP PERCENT",
+                TranspileSeverity::Unsupported,
+                "TOP ... PERCENT has no Spark SQL equivalent; compute the row count first",
+            );
+            end = percent;
+        }
+        if let Some(with) = next_significant(tokens, end + 1).filter(|&w| tokens[w].is_word("WITH"))
+        {
+            if let Some(ties) =
+                next_significant(tokens, with + 1).filter(|&t| tokens[t].is_word("TIES"))
+            {
+                self.report(
+                    line,
+                    "TOP WITH TIES",
+                    TranspileSeverity::Unsupported,
+                    "WITH TIES has no Spark SQL equivalent; rewrite with rank() in a subquery",
+                );
+                end = ties;
+            }
+        }
+        scope.pending_limit = Some(PendingLimit {
+            value: limit,
+            line,
+            anchor: scope.significant_end,
+        });
+        Some(skip_whitespace(tokens, end + 1))
+    }
+
+    fn rewrite_identity(
+        &mut self,
+        tokens: &[Token],
+        i: usize,
+        ctx: Context,
+        scope: &mut Scope,
+    ) -> usize {
+        let line = tokens[i].line;
+        let args_range = next_significant(tokens, i + 1)
+            .filter(|&open| tokens[open].is_symbol("("))
+            .and_then(|open| matching_paren(tokens, open).map(|close| (open, close)));
+        let args: Vec<String> = args_range
+            .map(|(open, close)| {
+                split_arguments(tokens, open, close)
+                    .into_iter()
+                    .map(render)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let next = args_range.map(|(_, close)| close + 1).unwrap_or(i + 1);
+
+        if ctx != Context::ColumnDefs {
+            self.report(
+                line,
+                "IDENTITY()",
+                TranspileSeverity::Unsupported,
+                "the IDENTITY() function is only valid in SELECT INTO; generate keys with row_number()",
+            );
+            scope.push(&render(&tokens[i..next]));
+            return next;
+        }
+
+        match self.target {
+            TranspileTarget::FabricWarehouse => {
+                if !args.is_empty() {
+                    self.report(
+                        line,
+                        "IDENTITY",
+                        TranspileSeverity::Warning,
+                        format!(
+                            "IDENTITY({}) seed and increment are ignored; Fabric Warehouse identity values are unique but not sequential",
+                            args.join(", ")
+                        ),
+                    );
+                }
+                scope.push("IDENTITY");
+            }
+            TranspileTarget::SparkSql => {
+                self.report(
+                    line,
+                    "IDENTITY",
+                    TranspileSeverity::Warning,
+                    "identity columns require a Delta table; generated values are unique but may have gaps",
+                );
+                match args.as_slice() {
+                    [seed, increment] => scope.push(&format!(
+                        "GENERATED BY DEFAULT AS IDENTITY (START WITH {seed} INCREMENT BY {increment})"
+                    )),
+                    _ => scope.push("GENERATED BY DEFAULT AS IDENTITY"),
+                }
+            }
+        }
+        next
+    }
+
+    fn check_recursive_ctes(&mut self, tokens: &[Token], with: usize) {
+        let mut cursor = with;
+        loop {
+            let Some(name_idx) = next_significant(tokens, cursor + 1) else {
+                return;
+            };
+            let Some(name) = tokens[name_idx].identifier() else {
+                return;
+            };
+            let mut after = next_significant(tokens, name_idx + 1);
+            if let Some(cols) = after.filter(|&c| tokens[c].is_symbol("(")) {
+                let Some(close) = matching_paren(tokens, cols) else {
+                    return;
+                };
+                after = next_significant(tokens, close + 1);
+            }
+            let Some(open) = after
+                .filter(|&a| tokens[a].is_word("AS"))
+                .and_then(|a| next_significant(tokens, a + 1))
+                .filter(|&o| tokens[o].is_symbol("("))
+            else {
+                return;
+            };
+            let Some(close) = matching_paren(tokens, open) else {
+                return;
+            };
+            let recursive = tokens[open + 1..close].iter().any(|t| {
+                t.identifier()
+                    .is_some_and(|id| id.eq_ignore_ascii_case(&name))
+            });
+            if recursive {
+                self.report(
+                    tokens[name_idx].line,
+                    "recursive CTE",
+                    TranspileSeverity::Unsupported,
+                    format!(
+                        "recursive common table expression {name} is not supported on the target; unroll it or iterate in the orchestrator"
+                    ),
+                );
+            }
+            match next_significant(tokens, close + 1) {
+                Some(comma) if tokens[comma].is_symbol(",") => cursor = comma,
+                _ => return,
+            }
+        }
+    }
+
+    fn rewrite_call(
+        &mut self,
+        tokens: &[Token],
+        i: usize,
+        open: usize,
+        scope: &mut Scope,
+    ) -> Option<usize> {
+        let name = tokens[i].text.to_ascii_uppercase();
+        let line = tokens[i].line;
+        let unsupported: &[(&str, &str)] = match self.target {
+            TranspileTarget::FabricWarehouse => &FABRIC_UNSUPPORTED_FUNCTIONS,
+            TranspileTarget::SparkSql => &SPARK_UNSUPPORTED_FUNCTIONS,
+        };
+        if let Some((_, message)) = unsupported.iter().find(|(f, _)| *f == name) {
+            self.report(
+                line,
+                &format!("{name}()"),
+                TranspileSeverity::Unsupported,
+                *message,
+            );
+            return None;
+        }
+        if name == "IDENTITY" {
+            return None;
+        }
+
+        let close = matching_paren(tokens, open)?;
+        let raw_args = split_arguments(tokens, open, close);
+        let mut next = close + 1;
+        let text = match name.as_str() {
+            "CAST" | "TRY_CAST" if raw_args.len() == 1 => {
+                let inner = self.rewrite(raw_args[0], Context::Cast);
+                format!("{}({})", tokens[i].text, inner.trim())
+            }
+            "CONVERT" | "TRY_CONVERT" => self.rewrite_convert(tokens, i, &raw_args)?,
+            _ if self.spark() && SPARK_FUNCTIONS.contains(&name.as_str()) => {
+                let args: Vec<String> = raw_args
+                    .iter()
+                    .map(|arg| self.rewrite(arg, Context::Default).trim().to_string())
+                    .collect();
+                let raw: Vec<String> = raw_args.iter().map(|arg| render(arg)).collect();
+                let text = self.spark_call(&name, line, &args, &raw)?;
+                if name == "STRING_AGG" {
+                    next = self.skip_within_group(tokens, close, line);
+                }
+                text
+            }
+            _ => return None,
+        };
+        scope.push(&text);
+        Some(next)
+    }
+
+    fn rewrite_convert(
+        &mut self,
+        tokens: &[Token],
+        i: usize,
+        raw_args: &[&[Token]],
+    ) -> Option<String> {
+        if raw_args.len() < 2 || raw_args.len() > 3 {
+            return None;
+        }
+        let name = tokens[i].text.to_ascii_uppercase();
+        let line = tokens[i].line;
+        let type_text = self
+            .rewrite(raw_args[0], Context::TypeArg)
+            .trim()
+            .to_string();
+        let expr = self
+            .rewrite(raw_args[1], Context::Default)
+            .trim()
+            .to_string();
+        let style = raw_args.get(2).map(|arg| render(arg));
+
+        if !self.spark() {
+            return Some(match style {
+                Some(style) => format!("{}({type_text}, {expr}, {style})", tokens[i].text),
+                None => format!("{}({type_text}, {expr})", tokens[i].text),
+            });
+        }
+
+        let cast = if name == "TRY_CONVERT" {
+            "try_cast"
+        } else {
+            "CAST"
+        };
+        let Some(style) = style else {
+            return Some(format!("{cast}({expr} AS {type_text})"));
+        };
+        let family = raw_args[0]
+            .iter()
+            .find(|t| !t.is_trivia())
+            .and_then(Token::identifier)
+            .map(|t| type_family(&t.to_ascii_lowercase()))
+            .unwrap_or(TypeFamily::Other);
+        let text = match (convert_style_format(&style), family) {
+            (Some(format), TypeFamily::String) => {
+                // T-SQL cuts the styled text to the target length, e.g. varchar(10) with style
+                // 120 keeps only the date.
+                let formatted = format!("date_format({expr}, '{format}')");
+                match type_length(raw_args[0]) {
+                    Some(length) if length < format.replace('\'', "").len() => {
+                        format!("substr({formatted}, 1, {length})")
+                    }
+                    _ => formatted,
+                }
+            }
+            (Some(format), TypeFamily::Date) => format!("to_date({expr}, '{format}')"),
+            (Some(format), TypeFamily::Timestamp) => format!("to_timestamp({expr}, '{format}')"),
+            _ => {
+                self.report(
+                    line,
+                    &format!("{name} style {style}"),
+                    TranspileSeverity::Unsupported,
+                    format!(
+                        "{name} style {style} has no Spark SQL equivalent; the style was dropped"
+                    ),
+                );
+                format!("{cast}({expr} AS {type_text})")
+            }
+        };
+        Some(text)
+    }
+
+    fn spark_call(
+        &mut self,
+        name: &str,
+        line: usize,
+        args: &[String],
+        raw: &[String],
+    ) -> Option<String> {
+        let text = match (name, args) {
+            ("GETDATE" | "SYSDATETIME" | "CURRENT_TIMESTAMP", []) => {
+                "current_timestamp()".to_string()
+            }
+            ("GETUTCDATE" | "SYSUTCDATETIME", []) => {
+                "to_utc_timestamp(current_timestamp(), current_timezone())".to_string()
+            }
+            ("NEWID", []) => "uuid()".to_string(),
+            ("ISNULL", [value, fallback]) => format!("coalesce({value}, {fallback})"),
+            ("IIF", [condition, then, otherwise]) => {
+                format!("if({condition}, {then}, {otherwise})")
+            }
+            // LEN ignores trailing spaces; length() does not.
+            ("LEN", [value]) => format!("length(rtrim({value}))"),
+            ("DATALENGTH", [value]) => {
+                self.report(
+                    line,
+                    "DATALENGTH()",
+                    TranspileSeverity::Warning,
+                    "octet_length() counts UTF-8 bytes, not UTF-16 bytes as DATALENGTH does for nvarchar",
+                );
+                format!("octet_length({value})")
+            }
+            ("CHARINDEX", [_, _] | [_, _, _]) => format!("locate({})", args.join(", ")),
+            ("REPLICATE", [value, count]) => format!("repeat({value}, {count})"),
+            ("SPACE", [count]) => format!("repeat(' ', {count})"),
+            ("STUFF", [value, start, length, replacement]) => {
+                format!("overlay({value} PLACING {replacement} FROM {start} FOR {length})")
+            }
+            // T-SQL CONCAT treats NULL as an empty string; concat_ws does the same.
+            ("CONCAT", _) if !args.is_empty() => format!("concat_ws('', {})", args.join(", ")),
+            ("STRING_AGG", [value, separator]) => {
+                format!("concat_ws({separator}, collect_list({value}))")
+            }
+            ("SQUARE", [value]) => format!("power({value}, 2)"),
+            ("ATN2", [y, x]) => format!("atan2({y}, {x})"),
+            ("LOG", [value, base]) => format!("log({base}, {value})"),
+            ("DATEFROMPARTS", [year, month, day]) => format!("make_date({year}, {month}, {day})"),
+            ("EOMONTH", [date]) => format!("last_day({date})"),
+            ("EOMONTH", [date, months]) => format!("last_day(add_months({date}, {months}))"),
+            ("DATEADD", [_, amount, date]) => {
+                let part = self.date_part(name, line, &raw[0])?;
+                let part = match part {
+                    "DAYOFYEAR" | "DAYOFWEEK" => "DAY",
+                    "ISO_WEEK" => "WEEK",
+                    other => other,
+                };
+                format!("timestampadd({part}, {amount}, {date})")
+            }
+            ("DATEDIFF" | "DATEDIFF_BIG", [_, start, end]) => {
+                let part = self.date_part(name, line, &raw[0])?;
+                self.datediff(part, line, start, end)
+            }
+            ("DATEPART", [_, date]) => {
+                let part = self.date_part(name, line, &raw[0])?;
+                self.datepart(part, line, date)?
+            }
+            ("DATENAME", [_, date]) => match self.date_part(name, line, &raw[0])? {
+                "MONTH" => format!("date_format({date}, 'MMMM')"),
+                "DAYOFWEEK" => format!("date_format({date}, 'EEEE')"),
+                part => format!("CAST({} AS STRING)", self.datepart(part, line, date)?),
+            },
+            _ => return None,
+        };
+        Some(text)
+    }
+
+    fn date_part(&mut self, function: &str, line: usize, raw: &str) -> Option<&'static str> {
+        let part = normalize_date_part(raw);
+        if part.is_none() {
+            self.report(
+                line,
+                &format!("{function}()"),
+                TranspileSeverity::Unsupported,
+                format!("{function} date part {raw} has no Spark SQL equivalent"),
+            );
+        }
+        part
+    }
+
+    fn datediff(&mut self, part: &str, line: usize, start: &str, end: &str) -> String {
+        // T-SQL counts crossed boundaries, so compare truncated values rather than elapsed time.
+        match part {
+            "YEAR" => format!("(year({end}) - year({start}))"),
+            "QUARTER" => {
+                format!("((year({end}) - year({start})) * 4 + quarter({end}) - quarter({start}))")
+            }
+            "MONTH" => {
+                format!("((year({end}) - year({start})) * 12 + month({end}) - month({start}))")
+            }
+            "DAY" | "DAYOFYEAR" | "DAYOFWEEK" => format!("datediff({end}, {start})"),
+            "HOUR" | "MINUTE" | "SECOND" => format!(
+                "timestampdiff({part}, date_trunc('{part}', {start}), date_trunc('{part}', {end}))"
+            ),
+            "WEEK" | "ISO_WEEK" => {
+                self.report(
+                    line,
+                    "DATEDIFF(week)",
+                    TranspileSeverity::Warning,
+                    "timestampdiff(WEEK) counts elapsed weeks; DATEDIFF(week) counts Sunday boundaries",
+                );
+                format!("timestampdiff(WEEK, {start}, {end})")
+            }
+            _ => format!("timestampdiff({part}, {start}, {end})"),
+        }
+    }
+
+    fn datepart(&mut self, part: &str, line: usize, date: &str) -> Option<String> {
+        let text = match part {
+            "YEAR" => format!("year({date})"),
+            "QUARTER" => format!("quarter({date})"),
+            "MONTH" => format!("month({date})"),
+            "DAY" => format!("day({date})"),
+            "DAYOFYEAR" => format!("dayofyear({date})"),
+            "DAYOFWEEK" => {
+                self.report(
+                    line,
+                    "DATEPART(weekday)",
+                    TranspileSeverity::Warning,
+                    "dayofweek() assumes Sunday = 1, matching only the default DATEFIRST 7",
+                );
+                format!("dayofweek({date})")
+            }
+            "HOUR" => format!("hour({date})"),
+            "MINUTE" => format!("minute({date})"),
+            "SECOND" => format!("second({date})"),
+            "MILLISECOND" => format!("CAST(date_format({date}, 'SSS') AS INT)"),
+            "ISO_WEEK" => format!("weekofyear({date})"),
+            "WEEK" => {
+                self.report(
+                    line,
+                    "DATEPART(week)",
+                    TranspileSeverity::Warning,
+                    "weekofyear() returns the ISO week; DATEPART(week) numbers weeks from January 1",
+                );
+                format!("weekofyear({date})")
+            }
+            _ => {
+                self.report(
+                    line,
+                    "DATEPART()",
+                    TranspileSeverity::Unsupported,
+                    format!("DATEPART({part}) has no Spark SQL equivalent"),
+                );
+                return None;
+            }
+        };
+        Some(text)
+    }
+
+    /// Rewrite `OFFSET m ROWS [FETCH NEXT n ROWS ONLY]` as `LIMIT n OFFSET m`.
+    fn rewrite_offset(&mut self, tokens: &[Token], i: usize, scope: &mut Scope) -> Option<usize> {
+        let rows = find_rows_keyword(tokens, i + 1)?;
+        let offset = self.rewrite(&tokens[i + 1..rows], Context::Default);
+        let offset = offset.trim();
+
+        let fetch = next_significant(tokens, rows + 1).filter(|&f| tokens[f].is_word("FETCH"));
+        let Some(fetch) = fetch else {
+            scope.push(&format!("OFFSET {offset}"));
+            return Some(rows + 1);
+        };
+        let first = next_significant(tokens, fetch + 1)
+            .filter(|&n| tokens[n].is_word("NEXT") || tokens[n].is_word("FIRST"))?;
+        let fetch_rows = find_rows_keyword(tokens, first + 1)?;
+        let only =
+            next_significant(tokens, fetch_rows + 1).filter(|&o| tokens[o].is_word("ONLY"))?;
+        let limit = self.rewrite(&tokens[first + 1..fetch_rows], Context::Default);
+        scope.push(&format!("LIMIT {} OFFSET {offset}", limit.trim()));
+        Some(only + 1)
+    }
+
+    /// Skip a `WITHIN GROUP (ORDER BY ...)` clause following `STRING_AGG(...)`.
+    fn skip_within_group(&mut self, tokens: &[Token], close: usize, line: usize) -> usize {
+        let clause = next_significant(tokens, close + 1)
+            .filter(|&w| tokens[w].is_word("WITHIN"))
+            .and_then(|w| next_significant(tokens, w + 1))
+            .filter(|&g| tokens[g].is_word("GROUP"))
+            .and_then(|g| next_significant(tokens, g + 1))
+            .filter(|&o| tokens[o].is_symbol("("))
+            .and_then(|o| matching_paren(tokens, o));
+        match clause {
+            Some(end) => {
+                self.report(
+                    line,
+                    "STRING_AGG WITHIN GROUP",
+                    TranspileSeverity::Warning,
+                    "collect_list() does not preserve WITHIN GROUP ordering; sort with array_sort() if order matters",
+                );
+                end + 1
+            }
+            None => close + 1,
+        }
+    }
+}
+
+fn find_rows_keyword(tokens: &[Token], from: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate().skip(from) {
+        if token.is_symbol("(") {
+            depth += 1;
+        } else if token.is_symbol(")") {
+            depth = depth.checked_sub(1)?;
+        } else if depth == 0 && (token.is_word("ROWS") || token.is_word("ROW")) {
+            return Some(i);
+        } else if depth == 0 && token.is_symbol(";") {
+            return None;
+        }
+    }
+    None
+}
+
+fn skip_whitespace(tokens: &[Token], from: usize) -> usize {
+    match tokens.get(from) {
+        Some(token) if token.kind == TokenKind::Whitespace => from + 1,
+        _ => from,
+    }
+}
+
+fn starts_statement(tokens: &[Token], i: usize, scope: &Scope) -> bool {
+    let prev = prev_significant(tokens, i).map(|p| &tokens[p]);
+    // `x.print` is a qualified column name, not a PRINT statement.
+    if prev.is_some_and(|p| p.is_symbol(".")) {
+        return false;
+    }
+    let prev_word = |keywords: &[&str]| prev.is_some_and(|p| keywords.iter().any(|k| p.is_word(k)));
+    match tokens[i].text.to_ascii_uppercase().as_str() {
+        "SELECT" => {
+            !prev_word(&["UNION", "ALL", "EXCEPT", "INTERSECT"])
+                && !prev.is_some_and(|p| p.is_symbol("("))
+        }
+        "INSERT" | "UPDATE" | "DELETE" => {
+            !prev_word(&["THEN", "ON", "FOR", "AFTER", "OF"])
+                && !prev.is_some_and(|p| p.is_symbol(","))
+        }
+        "MERGE" | "DECLARE" | "WHILE" | "TRUNCATE" | "PRINT" | "RAISERROR" | "THROW" | "COMMIT"
+        | "ROLLBACK" | "RETURN" | "GOTO" | "EXEC" | "EXECUTE" | "CREATE" | "ALTER" | "BEGIN"
+        | "GO" => true,
+        "DROP" => !scope.statement_is("ALTER"),
+        "SET" => !scope.statement_is("UPDATE") && !scope.statement_is("MERGE"),
+        "IF" => !prev_word(&[
+            "TABLE",
+            "VIEW",
+            "PROCEDURE",
+            "PROC",
+            "FUNCTION",
+            "SCHEMA",
+            "INDEX",
+            "TRIGGER",
+        ]),
+        "ELSE" | "END" => scope.case_depth == 0,
+        "WITH" => is_cte_start(tokens, i),
+        _ => false,
+    }
+}
+
+/// True when the `WITH` at `i` introduces a common table expression.
+fn is_cte_start(tokens: &[Token], i: usize) -> bool {
+    let Some(name) = next_significant(tokens, i + 1) else {
+        return false;
+    };
+    let name_token = &tokens[name];
+    if !matches!(
+        name_token.kind,
+        TokenKind::Word | TokenKind::BracketIdent | TokenKind::QuotedIdent
+    ) || name_token.is_word("TIES")
+    {
+        return false;
+    }
+    let mut after = next_significant(tokens, name + 1);
+    if let Some(cols) = after.filter(|&c| tokens[c].is_symbol("(")) {
+        after = matching_paren(tokens, cols).and_then(|close| next_significant(tokens, close + 1));
+    }
+    after.is_some_and(|a| tokens[a].is_word("AS"))
+}
+
+/// Object kind following `CREATE` / `ALTER` / `CREATE OR ALTER`, skipping index modifiers.
+fn created_object_kind(tokens: &[Token], i: usize) -> Option<String> {
+    let mut cursor = next_significant(tokens, i + 1)?;
+    loop {
+        let token = &tokens[cursor];
+        let skip = [
+            "OR",
+            "ALTER",
+            "UNIQUE",
+            "CLUSTERED",
+            "NONCLUSTERED",
+            "COLUMNSTORE",
+        ]
+        .iter()
+        .any(|k| token.is_word(k));
+        if !skip {
+            return (token.kind == TokenKind::Word).then(|| token.text.to_ascii_uppercase());
+        }
+        cursor = next_significant(tokens, cursor + 1)?;
+    }
+}
+
+fn paren_context(tokens: &[Token], open: usize, scope: &Scope) -> Context {
+    let Some(prev) = prev_significant(tokens, open) else {
+        return Context::Default;
+    };
+    if tokens[prev].is_word("TABLE") {
+        // DECLARE @t TABLE (...) / RETURNS @t TABLE (...)
+        return Context::ColumnDefs;
+    }
+    if !scope.statement_is("CREATE") {
+        return Context::Default;
+    }
+    // CREATE TABLE [schema.]name (...)
+    let mut cursor = Some(prev);
+    for _ in 0..5 {
+        let Some(idx) = cursor else {
+            break;
+        };
+        let token = &tokens[idx];
+        if token.is_word("TABLE") {
+            return Context::ColumnDefs;
+        }
+        let is_name_part = token.is_symbol(".")
+            || matches!(
+                token.kind,
+                TokenKind::Word
+                    | TokenKind::BracketIdent
+                    | TokenKind::QuotedIdent
+                    | TokenKind::TempName
+            );
+        if !is_name_part {
+            break;
+        }
+        cursor = prev_significant(tokens, idx);
+    }
+    Context::Default
+}
+
+fn is_type_position(tokens: &[Token], i: usize, ctx: Context, element_pos: usize) -> bool {
+    let token = &tokens[i];
+    if !matches!(token.kind, TokenKind::Word | TokenKind::BracketIdent) {
+        return false;
+    }
+    let prev = prev_significant(tokens, i);
+    let prev_token = prev.map(|p| &tokens[p]);
+    match ctx {
+        Context::TypeArg => return prev.is_none(),
+        Context::Cast if prev_token.is_some_and(|p| p.is_word("AS")) => return true,
+        Context::ColumnDefs if element_pos == 1 => {
+            return prev_token.is_some_and(|p| {
+                matches!(
+                    p.kind,
+                    TokenKind::Word | TokenKind::BracketIdent | TokenKind::QuotedIdent
+                ) && ![
+                    "CONSTRAINT",
+                    "PRIMARY",
+                    "UNIQUE",
+                    "FOREIGN",
+                    "INDEX",
+                    "CHECK",
+                ]
+                .iter()
+                .any(|k| p.is_word(k))
+            });
+        }
+        _ => {}
+    }
+    match prev_token {
+        Some(p) if p.kind == TokenKind::Variable => true,
+        Some(p) if p.is_word("AS") => prev
+            .and_then(|p| prev_significant(tokens, p))
+            .is_some_and(|pp| tokens[pp].kind == TokenKind::Variable),
+        _ => false,
+    }
+}
+
+/// True when the `+` at `plus` joins string operands rather than numbers.
+fn is_string_concatenation(tokens: &[Token], plus: usize) -> bool {
+    chain_has_string(tokens[..plus].iter().rev(), ")", "(")
+        || chain_has_string(tokens[plus + 1..].iter(), "(", ")")
+}
+
+fn chain_has_string<'a>(tokens: impl Iterator<Item = &'a Token>, open: &str, close: &str) -> bool {
+    let mut depth = 0usize;
+    for token in tokens {
+        if token.is_trivia() {
+            continue;
+        }
+        if token.is_symbol(open) {
+            depth += 1;
+            continue;
+        }
+        if token.is_symbol(close) {
+            match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            }
+            continue;
+        }
+        if depth == 0 {
+            if token.kind == TokenKind::Symbol
+                && CHAIN_BOUNDARY_SYMBOLS.contains(&token.text.as_str())
+            {
+                return false;
+            }
+            if CHAIN_BOUNDARY_KEYWORDS.iter().any(|k| token.is_word(k)) {
+                return false;
+            }
+        }
+        if token.kind == TokenKind::StringLiteral
+            || STRING_TYPE_WORDS.iter().any(|k| token.is_word(k))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+fn normalize_date_part(raw: &str) -> Option<&'static str> {
+    let part = raw
+        .trim()
+        .trim_matches(|c| c == '\'' || c == '[' || c == ']' || c == '"')
+        .to_ascii_lowercase();
+    let normalized = match part.as_str() {
+        "year" | "yy" | "yyyy" => "YEAR",
+        "quarter" | "qq" | "q" => "QUARTER",
+        "month" | "mm" | "m" => "MONTH",
+        "dayofyear" | "dy" | "y" => "DAYOFYEAR",
+        "day" | "dd" | "d" => "DAY",
+        "week" | "wk" | "ww" => "WEEK",
+        "weekday" | "dw" | "w" => "DAYOFWEEK",
+        "hour" | "hh" => "HOUR",
+        "minute" | "mi" | "n" => "MINUTE",
+        "second" | "ss" | "s" => "SECOND",
+        "millisecond" | "ms" => "MILLISECOND",
+        "microsecond" | "mcs" => "MICROSECOND",
+        "iso_week" | "isowk" | "isoww" => "ISO_WEEK",
+        _ => return None,
+    };
+    Some(normalized)
+}
+
+/// The length in a type such as `varchar(10)`; `None` for `MAX` or no length.
+fn type_length(type_tokens: &[Token]) -> Option<usize> {
+    let mut significant = type_tokens.iter().filter(|t| !t.is_trivia());
+    significant.next()?;
+    if !significant.next()?.is_symbol("(") {
+        return None;
+    }
+    significant.next()?.text.parse().ok()
+}
+
+/// Spark datetime pattern for the date/time CONVERT styles with an exact equivalent.
+fn convert_style_format(style: &str) -> Option<&'static str> {
+    let format = match style.trim() {
+        "23" => "yyyy-MM-dd",
+        "101" => "MM/dd/yyyy",
+        "102" => "yyyy.MM.dd",
+        "103" => "dd/MM/yyyy",
+        "104" => "dd.MM.yyyy",
+        "105" => "dd-MM-yyyy",
+        "108" => "HH:mm:ss",
+        "110" => "MM-dd-yyyy",
+        "111" => "yyyy/MM/dd",
+        "112" => "yyyyMMdd",
+        "20" | "120" => "yyyy-MM-dd HH:mm:ss",
+        "21" | "121" => "yyyy-MM-dd HH:mm:ss.SSS",
+        "126" => "yyyy-MM-dd'T'HH:mm:ss.SSS",
+        _ => return None,
+    };
+    Some(format)
+}
+
+fn type_family(name: &str) -> TypeFamily {
+    match name {
+        "char" | "varchar" | "nchar" | "nvarchar" | "text" | "ntext" => TypeFamily::String,
+        "date" => TypeFamily::Date,
+        "datetime" | "datetime2" | "smalldatetime" | "datetimeoffset" => TypeFamily::Timestamp,
+        _ => TypeFamily::Other,
+    }
+}
+
+const UNSUPPORTED_TYPES: [&str; 6] = [
+    "sql_variant",
+    "hierarchyid",
+    "geography",
+    "geometry",
+    "rowversion",
+    "timestamp",
+];
+
+fn map_type(target: TranspileTarget, name: &str, args: &[String]) -> Option<TypeMapping> {
+    let with_args = |base: &str| {
+        if args.is_empty() {
+            base.to_string()
+        } else {
+            format!("{base}({})", args.join(", "))
+        }
+    };
+    let keep = || TypeMapping {
+        text: None,
+        issue: None,
+    };
+    let exact = |text: String| TypeMapping {
+        text: Some(text),
+        issue: None,
+    };
+    let warn = |text: String, message: String| TypeMapping {
+        text: Some(text),
+        issue: Some((TranspileSeverity::Warning, message)),
+    };
+
+    let mapping = match target {
+        TranspileTarget::FabricWarehouse => match name {
+            "bigint" | "int" | "smallint" | "bit" | "float" | "real" | "date"
+            | "uniqueidentifier" | "decimal" | "numeric" | "char" | "varchar" | "binary"
+            | "varbinary" => keep(),
+            "tinyint" => warn(
+                "smallint".to_string(),
+                "tinyint is not supported in Fabric Warehouse; widened to smallint".to_string(),
+            ),
+            "money" => exact("decimal(19, 4)".to_string()),
+            "smallmoney" => exact("decimal(10, 4)".to_string()),
+            "datetime" => exact("datetime2(6)".to_string()),
+            "smalldatetime" => exact("datetime2(0)".to_string()),
+            "datetime2" | "time" => {
+                let precision = args.first().and_then(|p| p.trim().parse::<u32>().ok());
+                match precision {
+                    Some(p) if p <= 6 => keep(),
+                    _ => warn(
+                        format!("{name}(6)"),
+                        format!("{name} precision reduced to 6, the Fabric Warehouse maximum"),
+                    ),
+                }
+            }
+            "datetimeoffset" => warn(
+                "datetime2(6)".to_string(),
+                "datetimeoffset is not supported in Fabric Warehouse; the offset is dropped, store values in UTC".to_string(),
+            ),
+            "nchar" | "nvarchar" => warn(
+                with_args(&name[1..]),
+                format!("{name} became {}; Fabric Warehouse stores UTF-8, so lengths are bytes and may need widening for multi-byte data", &name[1..]),
+            ),
+            "text" | "ntext" => exact("varchar(max)".to_string()),
+            "image" => exact("varbinary(max)".to_string()),
+            "xml" => warn(
+                "varchar(max)".to_string(),
+                "xml is stored as varchar(max); XML methods are unavailable".to_string(),
+            ),
+            _ if UNSUPPORTED_TYPES.contains(&name) => TypeMapping {
+                text: None,
+                issue: Some((
+                    TranspileSeverity::Unsupported,
+                    format!("type {name} is not supported in Fabric Warehouse"),
+                )),
+            },
+            _ => return None,
+        },
+        TranspileTarget::SparkSql => match name {
+            "bigint" | "int" | "smallint" | "date" => keep(),
+            // T-SQL tinyint is unsigned (0-255); Spark's is signed.
+            "tinyint" => exact("smallint".to_string()),
+            "bit" => exact("boolean".to_string()),
+            "decimal" | "numeric" => exact(with_args("decimal")),
+            "money" => exact("decimal(19, 4)".to_string()),
+            "smallmoney" => exact("decimal(10, 4)".to_string()),
+            "float" => {
+                let mantissa = args.first().and_then(|p| p.trim().parse::<u32>().ok());
+                match mantissa {
+                    Some(m) if m <= 24 => exact("float".to_string()),
+                    _ => exact("double".to_string()),
+                }
+            }
+            "real" => exact("float".to_string()),
+            "datetime" | "datetime2" | "smalldatetime" => exact("timestamp".to_string()),
+            "datetimeoffset" => warn(
+                "timestamp".to_string(),
+                "datetimeoffset became timestamp; offsets are normalized to the session time zone".to_string(),
+            ),
+            "time" => warn(
+                "string".to_string(),
+                "Spark SQL has no time type; values are stored as strings".to_string(),
+            ),
+            "char" | "varchar" | "nchar" | "nvarchar" | "text" | "ntext" | "uniqueidentifier" => {
+                exact("string".to_string())
+            }
+            "xml" => warn(
+                "string".to_string(),
+                "xml is stored as string; XML methods are unavailable".to_string(),
+            ),
+            "binary" | "varbinary" | "image" => exact("binary".to_string()),
+            "rowversion" | "timestamp" => warn(
+                "binary".to_string(),
+                format!("{name} became binary; values are no longer generated on write"),
+            ),
+            _ if UNSUPPORTED_TYPES.contains(&name) => TypeMapping {
+                text: None,
+                issue: Some((
+                    TranspileSeverity::Unsupported,
+                    format!("type {name} has no Spark SQL equivalent"),
+                )),
+            },
+            _ => return None,
+        },
+    };
+    Some(mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fabric(sql: &str) -> TranspileResult {
+        transpile(sql, TranspileTarget::FabricWarehouse)
+    }
+
+    fn spark(sql: &str) -> TranspileResult {
+        transpile(sql, TranspileTarget::SparkSql)
+    }
+
+    fn constructs(result: &TranspileResult, severity: TranspileSeverity) -> Vec<String> {
+        result
+            .issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .map(|issue| issue.construct.clone())
+            .collect()
+    }
+
+    #[test]
+    fn plain_select_is_copied_verbatim_for_fabric() {
+        let sql = "SELECT a, b -- keep\nFROM dbo.t WHERE a = 1;";
+        let result = fabric(sql);
+        assert_eq!(result.sql, sql);
+        assert!(result.issues.is_empty());
+        assert!(result.fully_translated);
+    }
+
+    #[test]
+    fn fabric_maps_unsupported_types_in_create_table() {
+        let result = fabric(
+            "CREATE TABLE dbo.t (id INT IDENTITY(1,1) NOT NULL, amount MONEY, name NVARCHAR(50), created DATETIME, flag TINYINT)",
+        );
+        assert_eq!(
+            result.sql,
+            "CREATE TABLE dbo.t (id BIGINT IDENTITY NOT NULL, amount decimal(19, 4), name varchar(50), created datetime2(6), flag smallint)"
+        );
+        assert_eq!(
+            constructs(&result, TranspileSeverity::Warning),
+            vec!["type int", "IDENTITY", "type nvarchar", "type tinyint"]
+        );
+        assert!(result.fully_translated);
+    }
+
+    #[test]
+    fn fabric_reports_untranslatable_constructs() {
+        let result = fabric(
+            "SET IDENTITY_INSERT dbo.t ON;\nSELECT * INTO ##g FROM dbo.t WITH (NOLOCK);\nSELECT SCOPE_IDENTITY();\nDECLARE @g geography;",
+        );
+        assert!(result.sql.contains("FROM dbo.t ;"));
+        assert_eq!(
+            constructs(&result, TranspileSeverity::Unsupported),
+            vec![
+                "SET IDENTITY_INSERT",
+                "global temp table",
+                "SCOPE_IDENTITY()",
+                "type geography"
+            ]
+        );
+        assert_eq!(
+            result
+                .issues
+                .iter()
+                .find(|i| i.construct == "SCOPE_IDENTITY()")
+                .unwrap()
+                .line,
+            3
+        );
+        assert!(!result.fully_translated);
+    }
+
+    #[test]
+    fn fabric_maps_cast_and_convert_types() {
+        let result = fabric("SELECT CAST(x AS DATETIME), CONVERT(NVARCHAR(10), d, 112) FROM t");
+        assert_eq!(
+            result.sql,
+            "SELECT CAST(x AS datetime2(6)), CONVERT(varchar(10), d, 112) FROM t"
+        );
+    }
+
+    #[test]
+    fn spark_rewrites_top_and_identifiers() {
+        let result =
+            spark("SELECT TOP 10 [Order Id], \"name\" FROM dbo.orders ORDER BY [Order Id] DESC");
+        assert_eq!(
+            result.sql,
+            "SELECT `Order Id`, `name` FROM dbo.orders ORDER BY `Order Id` DESC LIMIT 10"
+        );
+        assert!(result.fully_translated);
+    }
+
+    #[test]
+    fn spark_places_limit_per_statement_and_subquery() {
+        let result = spark(
+            "SELECT TOP (5) a FROM t;\nSELECT x FROM (SELECT TOP 1 y AS x FROM u ORDER BY y) s",
+        );
+        assert_eq!(
+            result.sql,
+            "SELECT a FROM t LIMIT 5;\nSELECT x FROM (SELECT y AS x FROM u ORDER BY y LIMIT 1) s"
+        );
+    }
+
+    #[test]
+    fn spark_rewrites_offset_fetch() {
+        let result = spark("SELECT a FROM t ORDER BY a OFFSET 20 ROWS FETCH NEXT 10 ROWS ONLY");
+        assert_eq!(result.sql, "SELECT a FROM t ORDER BY a LIMIT 10 OFFSET 20");
+    }
+
+    #[test]
+    fn spark_rewrites_date_functions() {
+        let result = spark(
+            "SELECT GETDATE(), DATEADD(dd, -1, d), DATEDIFF(month, a, b), DATEPART(yyyy, d), EOMONTH(d, 1) FROM t",
+        );
+        assert_eq!(
+            result.sql,
+            "SELECT current_timestamp(), timestampadd(DAY, -1, d), ((year(b) - year(a)) * 12 + month(b) - month(a)), year(d), last_day(add_months(d, 1)) FROM t"
+        );
+        assert!(result.issues.is_empty());
+    }
+
+    #[test]
+    fn spark_rewrites_string_functions_and_concatenation() {
+        let result = spark(
+            "SELECT ISNULL(a, N'n/a') + ' - ' + b, LEN(c), CHARINDEX(',', c), STUFF(c, 1, 2, 'x'), 'it''s' FROM t",
+        );
+        assert_eq!(
+            result.sql,
+            "SELECT coalesce(a, 'n/a') || ' - ' || b, length(rtrim(c)), locate(',', c), overlay(c PLACING 'x' FROM 1 FOR 2), 'it\\'s' FROM t"
+        );
+    }
+
+    #[test]
+    fn spark_keeps_numeric_addition() {
+        let result = spark("SELECT a + 1, b + c FROM t");
+        assert_eq!(result.sql, "SELECT a + 1, b + c FROM t");
+    }
+
+    #[test]
+    fn spark_rewrites_convert_styles() {
+        let result = spark(
+            "SELECT CONVERT(VARCHAR(10), d, 23), CONVERT(INT, x), CONVERT(DATE, s, 112), CONVERT(VARCHAR, m, 1) FROM t",
+        );
+        assert_eq!(
+            result.sql,
+            "SELECT date_format(d, 'yyyy-MM-dd'), CAST(x AS INT), to_date(s, 'yyyyMMdd'), CAST(m AS string) FROM t"
+        );
+        assert_eq!(
+            spark("SELECT CONVERT(varchar(10), d, 120), CONVERT(char(30), d, 120) FROM t").sql,
+            "SELECT substr(date_format(d, 'yyyy-MM-dd HH:mm:ss'), 1, 10), date_format(d, 'yyyy-MM-dd HH:mm:ss') FROM t"
+        );
+        assert_eq!(
+            constructs(&result, TranspileSeverity::Unsupported),
+            vec!["CONVERT style 1"]
+        );
+    }
+
+    #[test]
+    fn spark_rewrites_merge_and_temp_tables() {
+        let result = spark(
+            "MERGE dbo.target AS t USING #stage AS s ON t.id = s.id\nWHEN MATCHED THEN UPDATE SET t.v = s.v\nWHEN NOT MATCHED BY TARGET THEN INSERT (id, v) VALUES (s.id, s.v);",
+        );
+        assert_eq!(
+            result.sql,
+            "MERGE INTO dbo.target AS t USING tmp_stage AS s ON t.id = s.id\nWHEN MATCHED THEN UPDATE SET t.v = s.v\nWHEN NOT MATCHED THEN INSERT (id, v) VALUES (s.id, s.v);"
+        );
+        assert_eq!(
+            constructs(&result, TranspileSeverity::Warning),
+            vec!["MERGE", "#stage"]
+        );
+        assert!(result.fully_translated);
+    }
+
+    #[test]
+    fn spark_rewrites_identity_and_types() {
+        let result = spark(
+            "CREATE TABLE #t (id BIGINT IDENTITY(1, 1), active BIT, price MONEY, label NVARCHAR(MAX))",
+        );
+        assert_eq!(
+            result.sql,
+            "CREATE TABLE tmp_t (id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1 INCREMENT BY 1), active boolean, price decimal(19, 4), label string)"
+        );
+    }
+
+    #[test]
+    fn spark_identity_columns_are_bigint() {
+        let result = spark("CREATE TABLE dbo.t (id INT IDENTITY(1, 1) NOT NULL, name VARCHAR(20))");
+        assert_eq!(
+            result.sql,
+            "CREATE TABLE dbo.t (id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1 INCREMENT BY 1) NOT NULL, name string)"
+        );
+        assert_eq!(
+            constructs(&result, TranspileSeverity::Warning),
+            vec!["type int", "IDENTITY"]
+        );
+    }
+
+    #[test]
+    fn spark_limit_never_lands_mid_statement() {
+        let result = spark("SELECT TOP 5 t.a, x.Print FROM t\nGO\nSELECT TOP 2 b FROM u");
+        assert_eq!(
+            result.sql,
+            "SELECT t.a, x.Print FROM t LIMIT 5\nGO\nSELECT b FROM u LIMIT 2"
+        );
+        assert!(result.issues.is_empty());
+
+        // A column named like a statement keyword leaves nowhere to put the LIMIT.
+        let result = spark("SELECT TOP 5 Print FROM t");
+        assert!(!result.sql.contains("LIMIT"), "{}", result.sql);
+        assert!(constructs(&result, TranspileSeverity::Unsupported).contains(&"TOP".to_string()));
+    }
+
+    #[test]
+    fn spark_reports_procedural_statements_and_variables() {
+        let result = spark(
+            "DECLARE @n INT = 5;\nBEGIN TRAN;\nSELECT TOP (@n) a INTO dbo.copy FROM t;\nCOMMIT;",
+        );
+        assert_eq!(
+            constructs(&result, TranspileSeverity::Unsupported),
+            vec![
+                "DECLARE",
+                "@n",
+                "BEGIN TRANSACTION",
+                "SELECT INTO",
+                "COMMIT"
+            ]
+        );
+        assert!(result.sql.contains("DECLARE @n INT = 5;"));
+        assert!(result
+            .sql
+            .contains("SELECT a INTO dbo.copy FROM t LIMIT @n;"));
+    }
+
+    #[test]
+    fn recursive_cte_is_reported_for_both_targets() {
+        let sql = "WITH tree AS (SELECT id FROM n WHERE parent IS NULL UNION ALL SELECT n.id FROM n JOIN tree ON n.parent = tree.id) SELECT * FROM tree";
+        for result in [fabric(sql), spark(sql)] {
+            assert_eq!(
+                constructs(&result, TranspileSeverity::Unsupported),
+                vec!["recursive CTE"]
+            );
+        }
+    }
+}
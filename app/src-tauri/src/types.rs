@@ -63,6 +63,30 @@ impl AppPhase {
     }
 }
 
+/// App settings as shown to the frontend: whether each secret is set, never its value.
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsView {
+    pub has_anthropic_api_key: bool,
+    /// Last four characters of the saved API key, to tell keys apart.
+    pub anthropic_api_key_hint: Option<String>,
+    pub has_github_oauth_token: bool,
+    pub github_user_login: Option<String>,
+    pub github_user_avatar: Option<String>,
+    pub github_user_email: Option<String>,
+}
+
+/// Where the key protecting stored credentials comes from and whether it is loaded.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretsStatus {
+    /// `keyring` or `passphrase`.
+    pub key_source: String,
+    pub unlocked: bool,
+    /// Whether a passphrase was chosen; the first unlock sets it.
+    pub passphrase_set: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppPhaseState {
@@ -104,6 +128,21 @@ pub struct GitHubUser {
     pub email: Option<String>,
 }
 
+/// GitHub installation settings saved per app install. Blank fields use github.com.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubConfig {
+    /// Host of the web UI and git remotes, e.g. `ghe.example.com`. May include a scheme.
+    #[serde(default)]
+    pub host: Option<String>,
+    /// REST API base; defaults to `https://api.github.com` or `https://<host>/api/v3`.
+    #[serde(default)]
+    pub api_base_url: Option<String>,
+    /// Client ID of the OAuth app used for the device flow.
+    #[serde(default)]
+    pub oauth_client_id: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GitHubRepo {
@@ -133,7 +172,6 @@ pub enum CommandError {
     #[error("io error: {0}")]
     Io(String),
     #[error("git error: {0}")]
-    #[allow(dead_code)]
     Git(String),
 }
 
@@ -149,6 +187,27 @@ impl From<std::io::Error> for CommandError {
     }
 }
 
+impl From<git2::Error> for CommandError {
+    fn from(e: git2::Error) -> Self {
+        use git2::{ErrorClass, ErrorCode};
+        let message = e.message();
+        CommandError::Git(match (e.code(), e.class()) {
+            (ErrorCode::Auth, _) => format!("authentication failed: {message}"),
+            (ErrorCode::NotFastForward, _) => {
+                format!("the remote branch has commits that are not in the local branch: {message}")
+            }
+            (ErrorCode::Conflict, _) => {
+                format!("uncommitted changes would be overwritten: {message}")
+            }
+            (_, ErrorClass::Net | ErrorClass::Http | ErrorClass::Ssl) => {
+                format!("could not reach the remote: {message}")
+            }
+            (ErrorCode::NotFound, _) => format!("not found: {message}"),
+            _ => message.to_string(),
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Workspace {
@@ -158,6 +217,8 @@ pub struct Workspace {
     pub migration_repo_path: String,
     pub fabric_url: Option<String>,
     pub fabric_service_principal_id: Option<String>,
+    /// Never sent to the frontend; see `has_fabric_secret`.
+    #[serde(skip_serializing)]
     pub fabric_service_principal_secret: Option<String>,
     pub source_type: Option<String>,
     pub source_server: Option<String>,
@@ -165,10 +226,33 @@ pub struct Workspace {
     pub source_port: Option<i64>,
     pub source_authentication_mode: Option<String>,
     pub source_username: Option<String>,
+    /// Never sent to the frontend; see `has_source_secret`.
+    #[serde(skip_serializing)]
     pub source_password: Option<String>,
     pub source_encrypt: Option<bool>,
     pub source_trust_server_certificate: Option<bool>,
     pub created_at: String,
+    /// Whether a source password or secret is stored, so the frontend can leave the field
+    /// blank to keep it.
+    #[serde(default)]
+    pub has_source_secret: bool,
+    /// Whether a Fabric service principal secret is stored, likewise.
+    #[serde(default)]
+    pub has_fabric_secret: bool,
+}
+
+/// A workspace as listed in the workspace switcher.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSummary {
+    pub id: String,
+    pub display_name: String,
+    pub migration_repo_name: Option<String>,
+    pub migration_repo_path: String,
+    /// Phase as last stored for the workspace; reconciled when it becomes active.
+    pub app_phase: AppPhase,
+    pub is_active: bool,
+    pub created_at: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -223,6 +307,9 @@ pub struct PipelineActivity {
     pub target_procedure_name: Option<String>,
     pub parameters_json: Option<String>,
     pub depends_on_json: Option<String>,
+    /// Name of the ForEach / IfCondition / Until / Switch activity this one is nested in.
+    #[serde(default)]
+    pub parent_activity_name: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -270,5 +357,407 @@ pub struct TableConfig {
     pub date_column: Option<String>,
     pub snapshot_strategy: String,
     pub pii_columns: Option<String>,
+    /// JSON object mapping each PII column to its masking decision.
+    #[serde(default)]
+    pub pii_masking_json: Option<String>,
     pub confirmed_at: Option<String>,
 }
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DbtScaffoldSummary {
+    pub project_path: String,
+    pub source_tables: usize,
+    pub models_written: usize,
+    /// Existing model files left untouched so engineers' edits survive regeneration.
+    pub models_kept: usize,
+    pub schema_tests: usize,
+    /// `model.column -> target` relationships whose target is neither a model nor a source.
+    pub skipped_relationships: Vec<String>,
+    pub snapshots: usize,
+    /// Snapshot-strategy tables no snapshot could be generated for, with the reason.
+    pub skipped_snapshots: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanIssueSeverity {
+    /// Blocks plan finalization.
+    Error,
+    /// Shown before finalization but does not block it.
+    Warning,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanIssue {
+    pub table: String,
+    /// Stable identifier of the check, e.g. `incremental_without_column`.
+    pub code: String,
+    pub severity: PlanIssueSeverity,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanValidation {
+    pub issues: Vec<PlanIssue>,
+    /// True when no issue is an error.
+    pub can_finalize: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanValueChange {
+    pub table: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// What changed between the previously committed plan and a new generation.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanDiff {
+    /// `generated_at` of the previous plan; `None` on the first generation.
+    pub previous_generated_at: Option<String>,
+    pub tables_added: Vec<String>,
+    pub tables_removed: Vec<String>,
+    pub tier_changes: Vec<PlanValueChange>,
+    pub load_strategy_changes: Vec<PlanValueChange>,
+    /// `confirmed_at` changes: set when a config is confirmed, cleared when it is reopened.
+    pub confirmation_changes: Vec<PlanValueChange>,
+}
+
+/// A `plan.json` value that was not imported, with the value the app kept.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanImportConflict {
+    pub table: String,
+    /// `group.field`, e.g. `config.load_strategy`; `None` when the whole table conflicts.
+    pub field: Option<String>,
+    pub repo_value: Option<String>,
+    pub app_value: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanImportSummary {
+    pub tables_added: usize,
+    pub tables_updated: usize,
+    pub fields_applied: usize,
+    pub conflicts: Vec<PlanImportConflict>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanPublishResult {
+    pub branch: String,
+    /// `None` when the regenerated artifacts matched the branch head and nothing was committed.
+    pub commit_sha: Option<String>,
+    pub diff: PlanDiff,
+    /// Pull request for the branch; `None` when the workspace has no GitHub repo.
+    pub pull_request: Option<PlanPullRequest>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoIssue {
+    /// Stable identifier of the check, e.g. `behind_remote`.
+    pub code: String,
+    /// Blocking issues make the write fail; the rest are only reported.
+    pub blocking: bool,
+    pub message: String,
+}
+
+/// Git state of the migration repo as it matters before the app writes into it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoHealth {
+    pub is_repo: bool,
+    pub branch: Option<String>,
+    pub expected_branch: String,
+    pub dirty_paths: Vec<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    pub issues: Vec<RepoIssue>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanPullRequest {
+    pub number: i64,
+    pub url: String,
+    pub branch: String,
+    pub updated_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidacyOverride {
+    pub id: i64,
+    pub warehouse_item_id: String,
+    pub schema_name: String,
+    pub procedure_name: String,
+    pub action: String,
+    pub previous_tier: String,
+    pub new_tier: String,
+    pub reason: String,
+    pub actor: Option<String>,
+    pub reverts_override_id: Option<i64>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranspileTarget {
+    FabricWarehouse,
+    SparkSql,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranspileSeverity {
+    /// Translated, but semantics may differ and the output should be reviewed.
+    Warning,
+    /// Left as-is; the construct has to be rewritten by hand.
+    Unsupported,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranspileIssue {
+    pub line: usize,
+    pub construct: String,
+    pub severity: TranspileSeverity,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TranspileResult {
+    pub target: TranspileTarget,
+    pub sql: String,
+    pub issues: Vec<TranspileIssue>,
+    /// True when no construct was reported as unsupported.
+    pub fully_translated: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcedureLineageSummary {
+    pub procedures_analyzed: usize,
+    pub lineage_edges: usize,
+    pub dynamic_sql_sites: usize,
+    pub opaque_sites: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookLineageSummary {
+    pub notebook_item_id: String,
+    pub cells: usize,
+    /// SQL and PySpark cells; other languages are kept as activities but not scanned.
+    pub analyzed_cells: usize,
+    pub links: usize,
+    /// Referenced tables with no matching object in the canonical model.
+    pub unresolved_tables: Vec<String>,
+    /// Cells that build SQL or table names at runtime.
+    pub dynamic_cells: Vec<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicSqlSite {
+    pub procedure_data_object_id: String,
+    pub schema_name: String,
+    pub procedure_name: String,
+    pub line_number: i64,
+    pub exec_kind: String,
+    pub resolution: String,
+    pub statements: Vec<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetSegment {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectSearchHit {
+    pub data_object_id: String,
+    pub schema_name: String,
+    pub object_name: String,
+    pub object_type: String,
+    /// Excerpt of the body around the best match, split into plain and matched segments.
+    pub snippet: Vec<SnippetSegment>,
+    /// FTS5 bm25 score; lower is a better match.
+    pub rank: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphNodeKind {
+    Table,
+    View,
+    Procedure,
+    Function,
+    Unknown,
+    OrchestrationActivity,
+    PipelineActivity,
+}
+
+/// How the downstream end of an edge depends on the upstream end.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphEdgeRelation {
+    /// Downstream procedure or activity reads the upstream object.
+    Reads,
+    /// Upstream procedure or activity writes the downstream object.
+    Writes,
+    /// Upstream activity executes the downstream procedure.
+    Executes,
+    /// Upstream activity must finish before the downstream activity starts.
+    Precedes,
+    /// Downstream activity references the upstream object without a known access type.
+    References,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNode {
+    pub id: String,
+    pub kind: GraphNodeKind,
+    pub schema_name: Option<String>,
+    pub name: String,
+}
+
+/// Edges point in data-flow direction: `from` is upstream of `to`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub relation: GraphEdgeRelation,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNeighbor {
+    pub node: GraphNode,
+    /// Number of edges between the starting node and this one.
+    pub depth: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpactAnalysis {
+    pub root: GraphNode,
+    /// Everything the root is built from; these must move for the root to keep loading.
+    pub required: Vec<GraphNeighbor>,
+    /// Everything built from the root; these are affected when the root moves.
+    pub affected: Vec<GraphNeighbor>,
+    /// Dependency cycles that include the root or one of its required nodes.
+    pub cycles: Vec<Vec<GraphNode>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeWarning {
+    pub selected_table_id: String,
+    pub schema_name: String,
+    pub table_name: String,
+    /// `schema.table` names of upstream tables and views that are not selected.
+    pub missing_upstream: Vec<String>,
+}
+
+/// Machine-readable lineage written to `lineage/lineage.json` in the migration repo.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageGraph {
+    pub workspace_id: String,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Activity outcome that releases a dependent activity, as in pipeline `dependsOn` entries.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DependencyCondition {
+    Succeeded,
+    Failed,
+    Completed,
+    Skipped,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityDependency {
+    pub activity: String,
+    pub conditions: Vec<DependencyCondition>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineActivityEdge {
+    pub activity: String,
+    pub depends_on: String,
+    /// Empty for implicit container edges.
+    pub conditions: Vec<DependencyCondition>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DanglingDependency {
+    pub activity: String,
+    /// Name referenced in `dependsOn` that is not an activity of the same pipeline.
+    pub missing: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineExecutionPlan {
+    pub pipeline_item_id: String,
+    pub pipeline_name: String,
+    /// Activities in a valid execution order. Activities in or after a cycle are left out.
+    pub order: Vec<String>,
+    /// Groups of activities that can run in parallel once the previous stage has finished.
+    pub stages: Vec<Vec<String>>,
+    pub edges: Vec<PipelineActivityEdge>,
+    pub cycles: Vec<Vec<String>>,
+    pub dangling: Vec<DanglingDependency>,
+    /// Activities that cannot be scheduled because they are in or depend on a cycle.
+    pub unscheduled: Vec<String>,
+    /// Activities whose `dependsOn` could not be parsed, with the reason.
+    pub parse_errors: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineDefinitionImport {
+    pub pipeline_item_id: String,
+    /// Pipeline-level `parameters` object, verbatim.
+    pub parameters_json: Option<String>,
+    pub activities: Vec<PipelineActivity>,
+    /// Activities whose target procedure is a dynamic expression and could not be resolved.
+    pub unresolved: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FabricSyncSummary {
+    pub items: usize,
+    pub warehouses: usize,
+    pub schemas: usize,
+    pub tables: usize,
+    pub procedures: usize,
+    pub pipeline_activities: usize,
+    pub notebooks: usize,
+    pub notebook_links: usize,
+    /// Items that could not be read or parsed; the rest of the workspace is still synced.
+    pub warnings: Vec<String>,
+}
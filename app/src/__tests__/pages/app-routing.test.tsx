@@ -37,7 +37,7 @@ describe('App routing guards', () => {
         planFinalized: false,
       },
       github_get_user: null,
-      get_settings: { anthropicApiKey: null },
+      get_settings: { hasAnthropicApiKey: false, anthropicApiKeyHint: null },
     });
 
     render(<App />);
@@ -0,0 +1,41 @@
+import { render, screen, waitFor } from '@testing-library/react';
+import { describe, it, expect, beforeEach } from 'vitest';
+import ScopeStep from '../../routes/scope/scope-step';
+import { mockInvoke, mockInvokeCommands, resetTauriMocks } from '../../test/mocks/tauri';
+import { useWorkflowStore } from '../../stores/workflow-store';
+
+describe('ScopeStep', () => {
+  beforeEach(() => {
+    resetTauriMocks();
+    useWorkflowStore.setState((s) => ({ ...s, workspaceId: 'ws-1' }));
+  });
+
+  it('lists selected tables whose upstream tables are out of scope', async () => {
+    mockInvokeCommands({
+      graph_scope_warnings: [
+        {
+          selectedTableId: 't1',
+          schemaName: 'dbo',
+          tableName: 'fact_sales',
+          missingUpstream: ['dbo.dim_customer', 'stage.sales'],
+        },
+      ],
+    });
+    render(<ScopeStep />);
+
+    await waitFor(() => {
+      expect(screen.getByTestId('scope-warnings')).toHaveTextContent(
+        'dbo.fact_sales needs dbo.dim_customer, stage.sales',
+      );
+    });
+    expect(mockInvoke).toHaveBeenCalledWith('graph_scope_warnings', { workspaceId: 'ws-1' });
+  });
+
+  it('shows nothing when the scope is complete', async () => {
+    mockInvokeCommands({ graph_scope_warnings: [] });
+    render(<ScopeStep />);
+
+    await waitFor(() => expect(mockInvoke).toHaveBeenCalled());
+    expect(screen.queryByTestId('scope-warnings')).not.toBeInTheDocument();
+  });
+});
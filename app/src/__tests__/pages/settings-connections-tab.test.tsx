@@ -53,7 +53,7 @@ beforeEach(() => {
 
 describe('ConnectionsTab — GitHub card', () => {
   it('shows Sign in button when not connected', async () => {
-    mockInvokeCommands({ github_get_user: null, get_settings: { anthropicApiKey: null }, app_hydrate_phase: PHASE_STATE });
+    mockInvokeCommands({ github_get_user: null, get_settings: { hasAnthropicApiKey: false, anthropicApiKeyHint: null }, app_hydrate_phase: PHASE_STATE });
     renderTab();
     await waitFor(() => {
       expect(screen.getByTestId('btn-connect-github')).toBeVisible();
@@ -66,7 +66,7 @@ describe('ConnectionsTab — GitHub card', () => {
   });
 
   it('shows github URL and Disconnect when connected', async () => {
-    mockInvokeCommands({ github_get_user: MOCK_USER, get_settings: { anthropicApiKey: null }, app_hydrate_phase: PHASE_STATE });
+    mockInvokeCommands({ github_get_user: MOCK_USER, get_settings: { hasAnthropicApiKey: false, anthropicApiKeyHint: null }, app_hydrate_phase: PHASE_STATE });
     renderTab();
     await waitFor(() => {
       expect(screen.getByText('@octocat')).toBeVisible();
@@ -84,7 +84,7 @@ describe('ConnectionsTab — GitHub card', () => {
       lastCheckedAt: null,
       loadUser: async () => {},
     });
-    mockInvokeCommands({ github_get_user: MOCK_USER, get_settings: { anthropicApiKey: null }, app_hydrate_phase: PHASE_STATE });
+    mockInvokeCommands({ github_get_user: MOCK_USER, get_settings: { hasAnthropicApiKey: false, anthropicApiKeyHint: null }, app_hydrate_phase: PHASE_STATE });
     act(() => {
       useWorkflowStore.setState((s) => ({ ...s, appPhase: 'running_locked' }));
     });
@@ -97,7 +97,7 @@ describe('ConnectionsTab — GitHub card', () => {
   it('shows checking state while auth is loading', async () => {
     mockInvokeCommands({
       github_get_user: new Promise(() => {}),
-      get_settings: { anthropicApiKey: null },
+      get_settings: { hasAnthropicApiKey: false, anthropicApiKeyHint: null },
       app_hydrate_phase: PHASE_STATE,
     });
     renderTab();
@@ -109,7 +109,7 @@ describe('ConnectionsTab — GitHub card', () => {
   });
 
   it('Anthropic key input and Update button are present', async () => {
-    mockInvokeCommands({ github_get_user: null, get_settings: { anthropicApiKey: null }, app_hydrate_phase: PHASE_STATE });
+    mockInvokeCommands({ github_get_user: null, get_settings: { hasAnthropicApiKey: false, anthropicApiKeyHint: null }, app_hydrate_phase: PHASE_STATE });
     renderTab();
     await waitFor(() => {
       expect(screen.getByTestId('input-anthropic-key')).toBeInTheDocument();
@@ -32,6 +32,7 @@ describe('WorkspaceTab (Settings)', () => {
     resetTauriMocks();
     mockInvokeCommands({
       workspace_get: null,
+      workspace_list: [],
       github_list_repos: [],
       workspace_apply_start: 'job-1',
       workspace_apply_status: {
@@ -80,6 +81,7 @@ describe('WorkspaceTab (Settings)', () => {
     await waitFor(() => {
       expect(mockInvoke).toHaveBeenCalledWith('workspace_discover_source_databases', {
         args: {
+          workspaceId: null,
           sourceType: 'sql_server',
           sourceServer: 'sql.acme.local',
           sourcePort: 1433,
@@ -100,6 +102,7 @@ describe('WorkspaceTab (Settings)', () => {
     const user = userEvent.setup();
     mockInvokeCommands({
       workspace_get: null,
+      workspace_list: [],
       workspace_apply_start: 'job-1',
       workspace_apply_status: {
         jobId: 'job-1',
@@ -172,6 +175,7 @@ describe('WorkspaceTab (Settings)', () => {
     await waitFor(() => {
       expect(mockInvoke).toHaveBeenCalledWith('workspace_test_source_connection', {
         args: {
+          workspaceId: null,
           sourceType: 'sql_server',
           sourceServer: 'sql.acme.local',
           sourcePort: 1433,
@@ -210,6 +214,7 @@ describe('WorkspaceTab (Settings)', () => {
     const user = userEvent.setup();
     mockInvokeCommands({
       workspace_get: null,
+      workspace_list: [],
       workspace_apply_start: 'job-1',
       workspace_apply_status: {
         jobId: 'job-1',
@@ -256,6 +261,7 @@ describe('WorkspaceTab (Settings)', () => {
     const user = userEvent.setup();
     mockInvokeCommands({
       workspace_get: null,
+      workspace_list: [],
       workspace_apply_start: 'job-1',
       workspace_apply_status: {
         jobId: 'job-1',
@@ -279,6 +285,12 @@ describe('WorkspaceTab (Settings)', () => {
     await user.type(screen.getByTestId('input-source-port'), '1433');
     await user.type(screen.getByTestId('input-source-username'), 'sa');
     await user.type(screen.getByTestId('input-source-password'), 'secret');
+    await user.type(
+      screen.getByTestId('input-fabric-url'),
+      'https://app.fabric.microsoft.com/groups/ws-1?ctid=tenant-1',
+    );
+    await user.type(screen.getByTestId('input-fabric-client-id'), 'fabric-app');
+    await user.type(screen.getByTestId('input-fabric-secret'), 'fabric-secret');
     await user.click(screen.getByTestId('btn-pick-repo-path'));
     await user.click(screen.getByTestId('input-repo-name'));
     await user.selectOptions(screen.getByTestId('input-repo-name'), 'acme/data-platform');
@@ -290,12 +302,13 @@ describe('WorkspaceTab (Settings)', () => {
     await waitFor(() => {
       expect(mockInvoke).toHaveBeenCalledWith('workspace_apply_start', {
         args: {
+          workspaceId: null,
           name: 'Migration Workspace',
           migrationRepoName: 'acme/data-platform',
           migrationRepoPath: '/selected/path',
-          fabricUrl: null,
-          fabricServicePrincipalId: null,
-          fabricServicePrincipalSecret: null,
+          fabricUrl: 'https://app.fabric.microsoft.com/groups/ws-1?ctid=tenant-1',
+          fabricServicePrincipalId: 'fabric-app',
+          fabricServicePrincipalSecret: 'fabric-secret',
           sourceType: 'sql_server',
           sourceServer: 'sql.acme.local',
           sourceDatabase: 'AdventureWorks',
@@ -360,10 +373,103 @@ describe('WorkspaceTab (Settings)', () => {
     await user.click(screen.getByTestId('btn-confirm-reset-migration'));
 
     await waitFor(() => {
-      expect(mockInvoke).toHaveBeenCalledWith('workspace_reset_state');
+      expect(mockInvoke).toHaveBeenCalledWith('workspace_reset_state', { force: false });
       expect(screen.getByTestId('settings-panel-workspace')).toBeInTheDocument();
       expect(screen.getByTestId('input-source-server')).toHaveValue('');
       expect((screen.getByTestId('input-source-database') as HTMLSelectElement).value).toBe('');
     });
   });
+
+  it('offers a forced reset when the repo holds unpushed work', async () => {
+    const user = userEvent.setup();
+    const commands: Record<string, unknown> = {
+      workspace_get: null,
+      workspace_list: [],
+      github_list_repos: [],
+      workspace_test_source_connection: 'Connection successful',
+      workspace_discover_source_databases: ['AdventureWorks'],
+      app_hydrate_phase: phaseState,
+    };
+    let resetCalls = 0;
+    mockInvoke.mockImplementation((cmd: string) => {
+      if (cmd === 'workspace_reset_state') {
+        resetCalls += 1;
+        return resetCalls === 1
+          ? Promise.reject(new Error('Commits that are on no remote would be deleted: plan (1)'))
+          : Promise.resolve(undefined);
+      }
+      if (cmd in commands) return Promise.resolve(commands[cmd]);
+      return Promise.reject(new Error(`Unmocked command: ${cmd}`));
+    });
+    renderPage();
+
+    await user.type(screen.getByTestId('input-source-server'), 'sql.acme.local');
+    await user.type(screen.getByTestId('input-source-username'), 'sa');
+    await user.type(screen.getByTestId('input-source-password'), 'secret');
+    await user.click(screen.getByTestId('btn-test-connection'));
+    await waitFor(() => {
+      expect((screen.getByTestId('input-source-database') as HTMLSelectElement).value).toBe(
+        'AdventureWorks',
+      );
+    });
+
+    await user.click(screen.getByTestId('btn-open-reset-migration-dialog'));
+    await user.type(screen.getByTestId('input-reset-confirmation'), 'RESET AdventureWorks');
+    await user.click(screen.getByTestId('btn-confirm-reset-migration'));
+
+    await waitFor(() => {
+      expect(screen.getByTestId('workspace-reset-error')).toHaveTextContent('plan (1)');
+    });
+    await user.click(screen.getByTestId('btn-force-reset-migration'));
+
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith('workspace_reset_state', { force: true });
+      expect(screen.getByTestId('input-source-server')).toHaveValue('');
+    });
+  });
+
+  it('switches to another workspace and loads its settings', async () => {
+    const user = userEvent.setup();
+    const summary = (id: string, displayName: string, isActive: boolean) => ({
+      id,
+      displayName,
+      migrationRepoName: `acme/${id}`,
+      migrationRepoPath: `/repos/${id}`,
+      appPhase: 'scope_editable',
+      isActive,
+      createdAt: '2026-01-01T00:00:00Z',
+    });
+    mockInvokeCommands({
+      workspace_get: null,
+      workspace_list: [summary('ws-a', 'Finance', false), summary('ws-b', 'Sales', false)],
+      workspace_switch: {
+        id: 'ws-b',
+        displayName: 'Sales',
+        migrationRepoName: 'acme/ws-b',
+        migrationRepoPath: '/repos/ws-b',
+        sourceServer: 'sales.acme.local',
+        sourceDatabase: 'SalesDb',
+        sourcePort: 1433,
+        sourceAuthenticationMode: 'sql_password',
+        sourceUsername: 'sa',
+        hasSourceSecret: true,
+        createdAt: '2026-01-01T00:00:00Z',
+      },
+      github_list_repos: [],
+      app_hydrate_phase: phaseState,
+    });
+    renderPage();
+
+    await waitFor(() => {
+      expect(screen.getByRole('option', { name: 'Sales' })).toBeInTheDocument();
+    });
+    await user.selectOptions(screen.getByTestId('select-active-workspace'), 'ws-b');
+
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith('workspace_switch', { workspaceId: 'ws-b' });
+      expect(screen.getByTestId('input-workspace-name')).toHaveValue('Sales');
+      expect(screen.getByTestId('input-source-server')).toHaveValue('sales.acme.local');
+      expect(useWorkflowStore.getState().workspaceId).toBe('ws-b');
+    });
+  });
 });
@@ -6,14 +6,18 @@ import type {
   ApplyWorkspaceArgs,
   DeviceFlowResponse,
   GitHubAuthResult,
+  GitHubConfig,
   GitHubRepo,
   GitHubUser,
+  ScopeWarning,
+  SecretsStatus,
   UsageRun,
   UsageRunDetail,
   UsageSummary,
   WorkspaceApplyJobStatus,
   WorkspaceApplyProgressEvent,
   Workspace,
+  WorkspaceSummary,
 } from './types';
 
 export const githubStartDeviceFlow = () =>
@@ -28,20 +32,55 @@ export const githubGetUser = () =>
 export const githubLogout = () =>
   invoke<void>('github_logout');
 
-export const githubListRepos = (query: string, limit = 10) =>
-  invoke<GitHubRepo[]>('github_list_repos', { query, limit });
+export const githubListRepos = (query: string, limit = 10, org?: string) =>
+  invoke<GitHubRepo[]>('github_list_repos', { query, limit, org: org ?? null });
+
+export const githubGetConfig = () =>
+  invoke<GitHubConfig>('github_get_config');
+
+export const githubSaveConfig = (config: GitHubConfig) =>
+  invoke<void>('github_save_config', { config });
+
+export const githubListOrgs = () =>
+  invoke<string[]>('github_list_orgs');
+
+export const githubCreateRepo = (args: {
+  name: string;
+  owner?: string;
+  description?: string;
+  template?: string;
+}) =>
+  invoke<GitHubRepo>('github_create_repo', {
+    name: args.name,
+    owner: args.owner ?? null,
+    description: args.description ?? null,
+    template: args.template ?? null,
+  });
 
 export const workspaceGet = () =>
   invoke<Workspace | null>('workspace_get');
 
+export const workspaceList = () =>
+  invoke<WorkspaceSummary[]>('workspace_list');
+
+export const workspaceSwitch = (workspaceId: string) =>
+  invoke<Workspace>('workspace_switch', { workspaceId });
+
+export const workspaceRename = (workspaceId: string, name: string) =>
+  invoke<Workspace>('workspace_rename', { workspaceId, name });
+
+export const workspaceDelete = (workspaceId: string) =>
+  invoke<void>('workspace_delete', { workspaceId });
+
 export const workspaceApplyStart = (args: ApplyWorkspaceArgs) =>
   invoke<string>('workspace_apply_start', { args });
 
 export const workspaceApplyStatus = (jobId: string) =>
   invoke<WorkspaceApplyJobStatus>('workspace_apply_status', { jobId });
 
-export const workspaceResetState = () =>
-  invoke<void>('workspace_reset_state');
+/** `force` skips the repo check that refuses to delete uncommitted or unpushed work. */
+export const workspaceResetState = (force = false) =>
+  invoke<void>('workspace_reset_state', { force });
 
 export type { WorkspaceApplyProgressEvent };
 
@@ -54,6 +93,7 @@ export const workspaceTestSourceConnection = (args: {
   sourcePassword: string;
   sourceEncrypt: boolean;
   sourceTrustServerCertificate: boolean;
+  workspaceId?: string | null;
 }) =>
   invoke<string>('workspace_test_source_connection', { args });
 
@@ -66,6 +106,7 @@ export const workspaceDiscoverSourceDatabases = (args: {
   sourcePassword: string;
   sourceEncrypt: boolean;
   sourceTrustServerCertificate: boolean;
+  workspaceId?: string | null;
 }) =>
   invoke<string[]>('workspace_discover_source_databases', { args });
 
@@ -75,9 +116,16 @@ export const getSettings = () =>
 export const saveAnthropicApiKey = (apiKey: string | null) =>
   invoke<void>('save_anthropic_api_key', { apiKey });
 
-export const testApiKey = (apiKey: string) =>
+/** Pass null to test the saved key. */
+export const testApiKey = (apiKey: string | null) =>
   invoke<boolean>('test_api_key', { apiKey });
 
+export const secretsStatus = () =>
+  invoke<SecretsStatus>('secrets_status');
+
+export const secretsUnlock = (passphrase: string) =>
+  invoke<SecretsStatus>('secrets_unlock', { passphrase });
+
 export const appHydratePhase = () =>
   invoke<AppPhaseState>('app_hydrate_phase');
 
@@ -116,3 +164,6 @@ export const usageListRuns = (limit = 50) =>
 
 export const usageGetRunDetail = (runId: string) =>
   invoke<UsageRunDetail>('usage_get_run_detail', { runId });
+
+export const graphScopeWarnings = (workspaceId: string) =>
+  invoke<ScopeWarning[]>('graph_scope_warnings', { workspaceId });
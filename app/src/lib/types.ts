@@ -12,6 +12,12 @@ export interface GitHubUser {
   email: string | null;
 }
 
+export interface GitHubConfig {
+  host: string | null;
+  apiBaseUrl: string | null;
+  oauthClientId: string | null;
+}
+
 export interface GitHubRepo {
   id: number;
   fullName: string;
@@ -25,20 +31,35 @@ export interface Workspace {
   migrationRepoPath: string;
   fabricUrl?: string | null;
   fabricServicePrincipalId?: string | null;
-  fabricServicePrincipalSecret?: string | null;
   sourceType?: 'sql_server' | 'fabric_warehouse' | null;
   sourceServer?: string | null;
   sourceDatabase?: string | null;
   sourcePort?: number | null;
   sourceAuthenticationMode?: 'sql_password' | 'entra_service_principal' | null;
   sourceUsername?: string | null;
-  sourcePassword?: string | null;
   sourceEncrypt?: boolean | null;
   sourceTrustServerCertificate?: boolean | null;
   createdAt: string;
+  /** A source password or service principal secret is saved; leave the field blank to keep it. */
+  hasSourceSecret?: boolean;
+  /** A Fabric service principal secret is saved; leave the field blank to keep it. */
+  hasFabricSecret?: boolean;
+}
+
+export interface WorkspaceSummary {
+  id: string;
+  displayName: string;
+  migrationRepoName?: string | null;
+  migrationRepoPath: string;
+  /** Phase as last stored for the workspace; reconciled when it becomes active. */
+  appPhase: AppPhase;
+  isActive: boolean;
+  createdAt: string;
 }
 
 export interface ApplyWorkspaceArgs {
+  /** Workspace to update; a new workspace is created when absent. */
+  workspaceId?: string | null;
   name: string;
   migrationRepoName: string;
   migrationRepoPath: string;
@@ -60,6 +81,7 @@ export interface WorkspaceApplyProgressEvent {
   stage:
     | 'validating_source_access'
     | 'verifying_repo'
+    | 'cloning_repo'
     | 'importing_schemas'
     | 'importing_tables'
     | 'importing_procedures'
@@ -81,13 +103,21 @@ export interface WorkspaceApplyJobStatus {
 }
 
 export interface AppSettings {
-  anthropicApiKey: string | null;
-  githubOauthToken: string | null;
+  hasAnthropicApiKey: boolean;
+  /** Last four characters of the saved API key. */
+  anthropicApiKeyHint: string | null;
+  hasGithubOauthToken: boolean;
   githubUserLogin: string | null;
   githubUserAvatar: string | null;
   githubUserEmail: string | null;
 }
 
+export interface SecretsStatus {
+  keySource: 'keyring' | 'passphrase';
+  unlocked: boolean;
+  passphraseSet: boolean;
+}
+
 export type AppPhase =
   | 'setup_required'
   | 'scope_editable'
@@ -144,3 +174,11 @@ export interface UsageRunDetail {
   run: UsageRun;
   events: UsageEvent[];
 }
+
+export interface ScopeWarning {
+  selectedTableId: string;
+  schemaName: string;
+  tableName: string;
+  /** `schema.table` names of upstream tables and views that are not selected. */
+  missingUpstream: string[];
+}
@@ -1,3 +1,45 @@
+import { useEffect, useState } from 'react';
+import { graphScopeWarnings } from '@/lib/tauri';
+import { logger } from '@/lib/logger';
+import type { ScopeWarning } from '@/lib/types';
+import { useWorkflowStore } from '@/stores/workflow-store';
+
 export default function ScopeStep() {
-  return <p className="text-sm text-muted-foreground">Scope — coming soon.</p>;
+  const workspaceId = useWorkflowStore((s) => s.workspaceId);
+  const [warnings, setWarnings] = useState<ScopeWarning[]>([]);
+
+  useEffect(() => {
+    if (!workspaceId) {
+      setWarnings([]);
+      return;
+    }
+    graphScopeWarnings(workspaceId)
+      .then((found) => setWarnings(found ?? []))
+      .catch((e) => logger.error('graph_scope_warnings failed', e));
+  }, [workspaceId]);
+
+  return (
+    <div className="flex flex-col gap-3">
+      <p className="text-sm text-muted-foreground">Scope — coming soon.</p>
+      {warnings.length > 0 ? (
+        <div
+          className="text-sm text-amber-700 dark:text-amber-400 bg-amber-50 dark:bg-amber-950/30 border border-amber-200 dark:border-amber-800 rounded-md px-3 py-2"
+          role="alert"
+          data-testid="scope-warnings"
+        >
+          <p className="font-medium">Selected tables depend on tables that are not in scope</p>
+          <ul className="mt-1 list-disc pl-5">
+            {warnings.map((w) => (
+              <li key={w.selectedTableId}>
+                <span className="font-mono">
+                  {w.schemaName}.{w.tableName}
+                </span>{' '}
+                needs <span className="font-mono">{w.missingUpstream.join(', ')}</span>
+              </li>
+            ))}
+          </ul>
+        </div>
+      ) : null}
+    </div>
+  );
 }
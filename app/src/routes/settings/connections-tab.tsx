@@ -1,5 +1,5 @@
 import { useEffect, useState } from 'react';
-import { CheckCircle2, Github, Loader2, LogOut } from 'lucide-react';
+import { CheckCircle2, Github, KeyRound, Loader2, LogOut } from 'lucide-react';
 import { toast } from 'sonner';
 import { useWorkflowStore } from '@/stores/workflow-store';
 import { useAuthStore } from '@/stores/auth-store';
@@ -10,7 +10,15 @@ import { Input } from '@/components/ui/input';
 import { Card, CardContent, CardHeader, CardTitle, CardDescription } from '@/components/ui/card';
 import { GitHubLoginDialog } from '@/components/github-login-dialog';
 import SettingsPanelShell from '@/components/settings/settings-panel-shell';
-import { appHydratePhase, getSettings, saveAnthropicApiKey, testApiKey } from '@/lib/tauri';
+import {
+  appHydratePhase,
+  getSettings,
+  saveAnthropicApiKey,
+  secretsStatus,
+  secretsUnlock,
+  testApiKey,
+} from '@/lib/tauri';
+import type { SecretsStatus } from '@/lib/types';
 import { logger } from '@/lib/logger';
 
 export default function ConnectionsTab() {
@@ -20,7 +28,13 @@ export default function ConnectionsTab() {
 
   const { user, isLoggedIn, isLoading: isAuthLoading, lastCheckedAt, loadUser, logout } = useAuthStore();
   const [loginDialogOpen, setLoginDialogOpen] = useState(false);
+  // The saved key never comes back from the backend; the input only holds a replacement.
   const [apiKey, setApiKey] = useState('');
+  const [savedKeyHint, setSavedKeyHint] = useState<string | null>(null);
+  const [hasSavedKey, setHasSavedKey] = useState(false);
+  const [secrets, setSecrets] = useState<SecretsStatus | null>(null);
+  const [passphrase, setPassphrase] = useState('');
+  const [unlocking, setUnlocking] = useState(false);
   const [testingApiKey, setTestingApiKey] = useState(false);
   const [apiKeyValid, setApiKeyValid] = useState<boolean | null>(null);
 
@@ -31,16 +45,27 @@ export default function ConnectionsTab() {
   useEffect(() => {
     getSettings()
       .then((settings) => {
-        setApiKey(settings.anthropicApiKey ?? '');
+        setHasSavedKey(Boolean(settings.hasAnthropicApiKey));
+        setSavedKeyHint(settings.anthropicApiKeyHint ?? null);
       })
       .catch((err) => {
         logger.error('get_settings failed', err);
       });
+    secretsStatus()
+      .then(setSecrets)
+      .catch((err) => {
+        logger.error('secrets_status failed', err);
+      });
   }, []);
 
   async function handleSaveApiKey(nextValue: string) {
+    const key = nextValue.trim();
+    if (!key) return;
     try {
-      await saveAnthropicApiKey(nextValue.trim() ? nextValue.trim() : null);
+      await saveAnthropicApiKey(key);
+      setHasSavedKey(true);
+      setSavedKeyHint(key.slice(-4));
+      setApiKey('');
       const phase = await appHydratePhase();
       setAppPhaseState(phase);
       logger.info('settings: anthropic API key saved');
@@ -50,16 +75,34 @@ export default function ConnectionsTab() {
     }
   }
 
+  async function handleUnlockSecrets() {
+    if (!passphrase) return;
+    setUnlocking(true);
+    try {
+      const next = await secretsUnlock(passphrase);
+      setSecrets(next);
+      setPassphrase('');
+      setAppPhaseState(await appHydratePhase());
+      logger.info('settings: stored credentials unlocked');
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      logger.error('secrets_unlock failed', err);
+      toast.error(message);
+    } finally {
+      setUnlocking(false);
+    }
+  }
+
   async function handleTestApiKey() {
     const key = apiKey.trim();
-    if (!key) {
+    if (!key && !hasSavedKey) {
       toast.error('Enter an API key first');
       return;
     }
     setTestingApiKey(true);
     setApiKeyValid(null);
     try {
-      await testApiKey(key);
+      await testApiKey(key || null);
       setApiKeyValid(true);
       toast.success('API key is valid');
     } catch (err) {
@@ -167,7 +210,7 @@ export default function ConnectionsTab() {
               onBlur={() => {
                 void handleSaveApiKey(apiKey);
               }}
-              placeholder="sk-ant-api03-…"
+              placeholder={savedKeyHint ? `Saved key ending in ${savedKeyHint}` : 'sk-ant-api03-…'}
               className="font-mono text-sm flex-1"
               disabled={isLocked}
             />
@@ -178,7 +221,7 @@ export default function ConnectionsTab() {
               onClick={() => {
                 void handleTestApiKey();
               }}
-              disabled={isLocked || testingApiKey || !apiKey.trim()}
+              disabled={isLocked || testingApiKey || (!apiKey.trim() && !hasSavedKey)}
               className={apiKeyValid ? 'text-white' : undefined}
               style={apiKeyValid ? { background: 'var(--color-seafoam)', color: 'white' } : undefined}
             >
@@ -189,6 +232,44 @@ export default function ConnectionsTab() {
           </CardContent>
         </Card>
 
+        {/* Passphrase unlock, when the OS keyring is unavailable */}
+        {secrets?.keySource === 'passphrase' && !secrets.unlocked ? (
+          <Card className="gap-0 py-5" data-testid="settings-connections-secrets-card">
+            <CardHeader className="pb-3">
+              <CardTitle>Credential passphrase</CardTitle>
+              <CardDescription className="mt-0.5">
+                {secrets.passphraseSet
+                  ? 'Enter your passphrase to unlock saved credentials for this session.'
+                  : 'No OS keyring is available. Choose a passphrase to encrypt saved credentials.'}
+              </CardDescription>
+            </CardHeader>
+            <CardContent className="pt-0 flex gap-2 items-center">
+              <Input
+                data-testid="input-secrets-passphrase"
+                type="password"
+                value={passphrase}
+                onChange={(e) => setPassphrase(e.target.value)}
+                onKeyDown={(e) => {
+                  if (e.key === 'Enter') void handleUnlockSecrets();
+                }}
+                className="text-sm flex-1"
+              />
+              <Button
+                type="button"
+                variant="outline"
+                data-testid="btn-unlock-secrets"
+                onClick={() => {
+                  void handleUnlockSecrets();
+                }}
+                disabled={unlocking || !passphrase}
+              >
+                {unlocking ? <Loader2 className="size-3.5 animate-spin" /> : <KeyRound className="size-3.5" />}
+                {secrets.passphraseSet ? 'Unlock' : 'Set passphrase'}
+              </Button>
+            </CardContent>
+          </Card>
+        ) : null}
+
       <GitHubLoginDialog open={loginDialogOpen} onOpenChange={setLoginDialogOpen} />
     </SettingsPanelShell>
   );
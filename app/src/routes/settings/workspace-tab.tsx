@@ -14,11 +14,15 @@ import {
   githubListRepos,
   workspaceApplyStart,
   workspaceApplyStatus,
+  workspaceDelete,
   workspaceGet,
+  workspaceList,
+  workspaceRename,
   workspaceResetState,
+  workspaceSwitch,
   workspaceTestSourceConnection,
 } from '@/lib/tauri';
-import type { GitHubRepo } from '@/lib/types';
+import type { GitHubRepo, Workspace, WorkspaceSummary } from '@/lib/types';
 import { logger } from '@/lib/logger';
 
 const DEFAULT_WORKSPACE_NAME = 'Migration Workspace';
@@ -62,6 +66,10 @@ export default function WorkspaceTab() {
   const { setWorkspaceId, clearWorkspaceId, appPhase, setAppPhaseState, reset } = useWorkflowStore();
   const isLocked = appPhase === 'running_locked';
 
+  const [activeWorkspaceId, setActiveWorkspaceId] = useState<string | null>(null);
+  const [workspaces, setWorkspaces] = useState<WorkspaceSummary[]>([]);
+  const [workspaceError, setWorkspaceError] = useState<string | null>(null);
+  const [deleteDialogOpen, setDeleteDialogOpen] = useState(false);
   const [workspaceName, setWorkspaceName] = useState(DEFAULT_WORKSPACE_NAME);
   const [repoName, setRepoName] = useState('');
   const [repoPath, setRepoPath] = useState('');
@@ -77,6 +85,12 @@ export default function WorkspaceTab() {
     useState<SourceAuthenticationMode>(SOURCE_DEFAULTS.authenticationMode);
   const [sourceUsername, setSourceUsername] = useState('');
   const [sourcePassword, setSourcePassword] = useState('');
+  // The saved password never comes back from the backend; a blank field keeps it.
+  const [hasSavedSecret, setHasSavedSecret] = useState(false);
+  const [fabricUrl, setFabricUrl] = useState('');
+  const [fabricClientId, setFabricClientId] = useState('');
+  const [fabricSecret, setFabricSecret] = useState('');
+  const [hasSavedFabricSecret, setHasSavedFabricSecret] = useState(false);
   const [sourceEncrypt, setSourceEncrypt] = useState(SOURCE_DEFAULTS.encrypt);
   const [sourceTrustServerCertificate, setSourceTrustServerCertificate] = useState(
     SOURCE_DEFAULTS.trustServerCertificate,
@@ -90,6 +104,9 @@ export default function WorkspaceTab() {
     sourcePort?: string;
     sourceUsername?: string;
     sourcePassword?: string;
+    fabricUrl?: string;
+    fabricClientId?: string;
+    fabricSecret?: string;
   }>({});
   const [applying, setApplying] = useState(false);
   const [resetDialogOpen, setResetDialogOpen] = useState(false);
@@ -111,33 +128,49 @@ export default function WorkspaceTab() {
   const [suggestionsLoading, setSuggestionsLoading] = useState(false);
   const repoIndexLoadedRef = useRef(false);
 
+  function loadWorkspaceIntoForm(ws: Workspace) {
+    setWorkspaceId(ws.id);
+    setActiveWorkspaceId(ws.id);
+    setWorkspaceName(ws.displayName || DEFAULT_WORKSPACE_NAME);
+    setRepoName(ws.migrationRepoName ?? '');
+    setRepoPath(ws.migrationRepoPath ?? '');
+    setRepoSelected(Boolean(ws.migrationRepoName));
+    setIsConfigured(Boolean(ws.migrationRepoName && ws.migrationRepoPath));
+
+    setSourceServer(ws.sourceServer ?? '');
+    const initialDatabase = ws.sourceDatabase ?? '';
+    setSourceDatabase(initialDatabase);
+    setSourceDatabases(initialDatabase ? [initialDatabase] : []);
+    setSourcePort(String(ws.sourcePort ?? SOURCE_DEFAULTS.port));
+    setSourceAuthenticationMode(ws.sourceAuthenticationMode ?? SOURCE_DEFAULTS.authenticationMode);
+    setSourceUsername(ws.sourceUsername ?? '');
+    setHasSavedSecret(Boolean(ws.hasSourceSecret));
+    setFabricUrl(ws.fabricUrl ?? '');
+    setFabricClientId(ws.fabricServicePrincipalId ?? '');
+    setFabricSecret('');
+    setHasSavedFabricSecret(Boolean(ws.hasFabricSecret));
+    setSourceEncrypt(ws.sourceEncrypt ?? SOURCE_DEFAULTS.encrypt);
+    setSourceTrustServerCertificate(
+      ws.sourceTrustServerCertificate ?? SOURCE_DEFAULTS.trustServerCertificate,
+    );
+  }
+
+  async function refreshWorkspaceList() {
+    try {
+      setWorkspaces((await workspaceList()) ?? []);
+    } catch (err) {
+      logger.error('workspace_list failed', err);
+    }
+  }
+
   useEffect(() => {
     workspaceGet()
       .then((ws) => {
-        if (!ws) return;
-
-        setWorkspaceId(ws.id);
-        setWorkspaceName(ws.displayName || DEFAULT_WORKSPACE_NAME);
-        setRepoName(ws.migrationRepoName ?? '');
-        setRepoPath(ws.migrationRepoPath ?? '');
-        setRepoSelected(Boolean(ws.migrationRepoName));
-        setIsConfigured(Boolean(ws.migrationRepoName && ws.migrationRepoPath));
-
-        setSourceServer(ws.sourceServer ?? '');
-        const initialDatabase = ws.sourceDatabase ?? '';
-        setSourceDatabase(initialDatabase);
-        setSourceDatabases(initialDatabase ? [initialDatabase] : []);
-        setSourcePort(String(ws.sourcePort ?? SOURCE_DEFAULTS.port));
-        setSourceAuthenticationMode(ws.sourceAuthenticationMode ?? SOURCE_DEFAULTS.authenticationMode);
-        setSourceUsername(ws.sourceUsername ?? ws.fabricServicePrincipalId ?? '');
-        setSourcePassword(ws.sourcePassword ?? ws.fabricServicePrincipalSecret ?? '');
-        setSourceEncrypt(ws.sourceEncrypt ?? SOURCE_DEFAULTS.encrypt);
-        setSourceTrustServerCertificate(
-          ws.sourceTrustServerCertificate ?? SOURCE_DEFAULTS.trustServerCertificate,
-        );
+        if (ws) loadWorkspaceIntoForm(ws);
       })
       .catch((e) => logger.error('workspace_get failed', e));
-  }, [setWorkspaceId]);
+    void refreshWorkspaceList();
+  }, []);
 
   const pageLocked = isLocked || isConfigured;
 
@@ -191,6 +224,11 @@ export default function WorkspaceTab() {
     setSourceAuthenticationMode(SOURCE_DEFAULTS.authenticationMode);
     setSourceUsername('');
     setSourcePassword('');
+    setHasSavedSecret(false);
+    setFabricUrl('');
+    setFabricClientId('');
+    setFabricSecret('');
+    setHasSavedFabricSecret(false);
     setSourceEncrypt(SOURCE_DEFAULTS.encrypt);
     setSourceTrustServerCertificate(SOURCE_DEFAULTS.trustServerCertificate);
 
@@ -212,6 +250,9 @@ export default function WorkspaceTab() {
       sourcePort?: string;
       sourceUsername?: string;
       sourcePassword?: string;
+      fabricUrl?: string;
+      fabricClientId?: string;
+      fabricSecret?: string;
     } = {};
 
     if (!repoName.trim()) errs.repoName = 'Repo selection is required';
@@ -233,11 +274,21 @@ export default function WorkspaceTab() {
           ? 'SQL login is required'
           : 'Service principal ID is required';
     }
-    if (!sourcePassword.trim()) {
+    if (!sourcePassword.trim() && !hasSavedSecret) {
       errs.sourcePassword =
         sourceAuthenticationMode === 'sql_password' ? 'Password is required' : 'Secret is required';
     }
 
+    // Fabric sync is optional, but needs all three once any is given.
+    const fabricStarted = Boolean(
+      fabricUrl.trim() || fabricClientId.trim() || fabricSecret.trim() || hasSavedFabricSecret,
+    );
+    if (fabricStarted) {
+      if (!fabricUrl.trim()) errs.fabricUrl = 'Fabric workspace URL is required';
+      if (!fabricClientId.trim()) errs.fabricClientId = 'Service principal ID is required';
+      if (!fabricSecret.trim() && !hasSavedFabricSecret) errs.fabricSecret = 'Secret is required';
+    }
+
     setErrors(errs);
     return Object.keys(errs).length === 0;
   }
@@ -293,12 +344,13 @@ export default function WorkspaceTab() {
 
     try {
       const jobId = await workspaceApplyStart({
+        workspaceId: activeWorkspaceId,
         name: workspaceName.trim() || DEFAULT_WORKSPACE_NAME,
         migrationRepoName: repoName.trim(),
         migrationRepoPath: repoPath.trim(),
-        fabricUrl: null,
-        fabricServicePrincipalId: null,
-        fabricServicePrincipalSecret: null,
+        fabricUrl: fabricUrl.trim() || null,
+        fabricServicePrincipalId: fabricClientId.trim() || null,
+        fabricServicePrincipalSecret: fabricSecret.trim() || null,
         sourceType,
         sourceServer: sourceServerValue,
         sourceDatabase: sourceDatabaseValue,
@@ -328,8 +380,10 @@ export default function WorkspaceTab() {
           const ws = await workspaceGet();
           if (ws) {
             setWorkspaceId(ws.id);
+            setActiveWorkspaceId(ws.id);
             setWorkspaceName(ws.displayName);
           }
+          await refreshWorkspaceList();
           try {
             const next = await appHydratePhase();
             setAppPhaseState(next);
@@ -366,12 +420,13 @@ export default function WorkspaceTab() {
     }
   }
 
-  async function handleResetMigration() {
+  async function handleResetMigration(force = false) {
     setResetError(null);
     try {
-      await workspaceResetState();
+      await workspaceResetState(force);
       reset();
       clearWorkspaceId();
+      setActiveWorkspaceId(null);
       setIsConfigured(false);
       setResetConfirmationInput('');
       setResetDialogOpen(false);
@@ -382,6 +437,7 @@ export default function WorkspaceTab() {
       } catch (hydrateErr) {
         logger.error('workspace reset: failed to hydrate app phase', hydrateErr);
       }
+      await refreshWorkspaceList();
       logger.info('workspace: reset migration state');
     } catch (err) {
       const message = getErrorMessage(err);
@@ -390,6 +446,78 @@ export default function WorkspaceTab() {
     }
   }
 
+  async function hydratePhaseAfterWorkspaceChange() {
+    try {
+      const next = await appHydratePhase();
+      setAppPhaseState(next);
+    } catch (err) {
+      logger.error('workspace: failed to hydrate app phase after workspace change', err);
+    }
+  }
+
+  async function handleSwitchWorkspace(workspaceId: string) {
+    if (!workspaceId || workspaceId === activeWorkspaceId) return;
+    setWorkspaceError(null);
+    try {
+      const ws = await workspaceSwitch(workspaceId);
+      reset();
+      clearWorkspaceForm();
+      loadWorkspaceIntoForm(ws);
+      await hydratePhaseAfterWorkspaceChange();
+      await refreshWorkspaceList();
+      logger.info('workspace: switched active workspace');
+    } catch (err) {
+      setWorkspaceError(getErrorMessage(err));
+      logger.error('workspace switch failed', err);
+    }
+  }
+
+  function handleNewWorkspace() {
+    // The new workspace is only persisted on Apply; until then nothing is active in the form.
+    setWorkspaceError(null);
+    clearWorkspaceForm();
+    setActiveWorkspaceId(null);
+    setIsConfigured(false);
+  }
+
+  async function handleRenameWorkspace() {
+    if (!activeWorkspaceId) return;
+    const current = workspaces.find((w) => w.id === activeWorkspaceId);
+    const name = workspaceName.trim();
+    if (current && current.displayName === name) return;
+    setWorkspaceError(null);
+    try {
+      const ws = await workspaceRename(activeWorkspaceId, name);
+      setWorkspaceName(ws.displayName);
+      await refreshWorkspaceList();
+      logger.info('workspace: renamed workspace');
+    } catch (err) {
+      setWorkspaceError(getErrorMessage(err));
+      logger.error('workspace rename failed', err);
+    }
+  }
+
+  async function handleDeleteWorkspace() {
+    if (!activeWorkspaceId) return;
+    setWorkspaceError(null);
+    try {
+      await workspaceDelete(activeWorkspaceId);
+      reset();
+      clearWorkspaceId();
+      setActiveWorkspaceId(null);
+      setIsConfigured(false);
+      setDeleteDialogOpen(false);
+      clearWorkspaceForm();
+      await hydratePhaseAfterWorkspaceChange();
+      await refreshWorkspaceList();
+      logger.info('workspace: deleted workspace');
+    } catch (err) {
+      setDeleteDialogOpen(false);
+      setWorkspaceError(getErrorMessage(err));
+      logger.error('workspace delete failed', err);
+    }
+  }
+
   async function handleTestConnection() {
     const sourceServerValue = sourceServer.trim();
     const sourcePortValue = Number(sourcePort);
@@ -398,7 +526,7 @@ export default function WorkspaceTab() {
     if (
       !sourceServerValue ||
       !sourceUsernameValue ||
-      !sourcePasswordValue ||
+      (!sourcePasswordValue && !hasSavedSecret) ||
       !Number.isInteger(sourcePortValue) ||
       sourcePortValue <= 0
     ) {
@@ -414,6 +542,7 @@ export default function WorkspaceTab() {
     setTestConnectionError(null);
     try {
       const message = await workspaceTestSourceConnection({
+        workspaceId: activeWorkspaceId,
         sourceType,
         sourceServer: sourceServerValue,
         sourcePort: sourcePortValue,
@@ -424,6 +553,7 @@ export default function WorkspaceTab() {
         sourceTrustServerCertificate,
       });
       const databases = await workspaceDiscoverSourceDatabases({
+        workspaceId: activeWorkspaceId,
         sourceType,
         sourceServer: sourceServerValue,
         sourcePort: sourcePortValue,
@@ -477,6 +607,74 @@ export default function WorkspaceTab() {
           </p>
         ) : null}
 
+        <Card className="gap-0 py-5" data-testid="settings-workspace-list-card">
+          <CardHeader className="pb-3">
+            <CardTitle>Workspaces</CardTitle>
+            <CardDescription className="mt-0.5">
+              Each workspace keeps its own source, repository and migration progress.
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="pt-0 flex flex-col gap-3">
+            <div className="flex flex-col gap-1">
+              <Label htmlFor="active-workspace">Active workspace</Label>
+              <select
+                id="active-workspace"
+                data-testid="select-active-workspace"
+                value={activeWorkspaceId ?? ''}
+                onChange={(e) => void handleSwitchWorkspace(e.target.value)}
+                className="h-9 rounded-md border border-input bg-transparent px-3 py-1 text-sm shadow-xs outline-none focus-visible:ring-2 focus-visible:ring-ring/50"
+                disabled={isLocked || applying}
+              >
+                {activeWorkspaceId ? null : <option value="">New workspace</option>}
+                {workspaces.map((w) => (
+                  <option key={w.id} value={w.id}>
+                    {w.displayName}
+                  </option>
+                ))}
+              </select>
+            </div>
+            <div className="flex flex-col gap-1">
+              <Label htmlFor="workspace-name">Name</Label>
+              <Input
+                id="workspace-name"
+                data-testid="input-workspace-name"
+                type="text"
+                value={workspaceName}
+                onChange={(e) => setWorkspaceName(e.target.value)}
+                onBlur={() => void handleRenameWorkspace()}
+                disabled={applying}
+              />
+            </div>
+            {workspaceError ? (
+              <p className="text-xs text-destructive" role="alert" data-testid="workspace-list-error">
+                {workspaceError}
+              </p>
+            ) : null}
+            <div className="flex items-center justify-end gap-2">
+              <Button
+                type="button"
+                variant="outline"
+                size="sm"
+                data-testid="btn-new-workspace"
+                onClick={handleNewWorkspace}
+                disabled={isLocked || applying || !activeWorkspaceId}
+              >
+                New workspace
+              </Button>
+              <Button
+                type="button"
+                variant="outline"
+                size="sm"
+                data-testid="btn-open-delete-workspace-dialog"
+                onClick={() => setDeleteDialogOpen(true)}
+                disabled={isLocked || applying || !activeWorkspaceId}
+              >
+                Delete workspace
+              </Button>
+            </div>
+          </CardContent>
+        </Card>
+
         <Card className="gap-0 py-5" data-testid="settings-workspace-fabric-card">
           <CardHeader className="pb-3">
             <CardTitle>Source connection</CardTitle>
@@ -601,7 +799,7 @@ export default function WorkspaceTab() {
                     setSourcePassword(e.target.value);
                     invalidateConnectionTestState();
                   }}
-                  placeholder="••••••••••••"
+                  placeholder={hasSavedSecret ? 'Saved — leave blank to keep' : '••••••••••••'}
                   className="font-mono text-sm"
                   disabled={pageLocked}
                 />
@@ -716,6 +914,76 @@ export default function WorkspaceTab() {
           </CardContent>
         </Card>
 
+        <Card className="gap-0 py-5" data-testid="settings-workspace-fabric-sync-card">
+          <CardHeader className="pb-3">
+            <CardTitle>Fabric workspace</CardTitle>
+            <CardDescription className="mt-0.5">
+              Optional. The service principal that syncs warehouses and their catalogs from Fabric.
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="pt-0 flex flex-col gap-3">
+            <div className="flex flex-col gap-1">
+              <Label htmlFor="fabric-url">Workspace URL</Label>
+              <Input
+                id="fabric-url"
+                data-testid="input-fabric-url"
+                type="text"
+                value={fabricUrl}
+                onChange={(e) => setFabricUrl(e.target.value)}
+                placeholder="https://app.fabric.microsoft.com/groups/<workspace-id>?ctid=<tenant-id>"
+                className="font-mono text-sm"
+                disabled={pageLocked}
+              />
+              {errors.fabricUrl ? (
+                <p className="text-xs text-destructive" role="alert">
+                  {errors.fabricUrl}
+                </p>
+              ) : null}
+            </div>
+            <div className="flex flex-col gap-2">
+              <div className="flex flex-col gap-1">
+                <Label htmlFor="fabric-client-id" className="text-sm text-muted-foreground">
+                  Service principal ID
+                </Label>
+                <Input
+                  id="fabric-client-id"
+                  data-testid="input-fabric-client-id"
+                  type="text"
+                  value={fabricClientId}
+                  onChange={(e) => setFabricClientId(e.target.value)}
+                  className="font-mono text-sm"
+                  disabled={pageLocked}
+                />
+                {errors.fabricClientId ? (
+                  <p className="text-xs text-destructive" role="alert">
+                    {errors.fabricClientId}
+                  </p>
+                ) : null}
+              </div>
+              <div className="flex flex-col gap-1">
+                <Label htmlFor="fabric-secret" className="text-sm text-muted-foreground">
+                  Secret
+                </Label>
+                <Input
+                  id="fabric-secret"
+                  data-testid="input-fabric-secret"
+                  type="password"
+                  value={fabricSecret}
+                  onChange={(e) => setFabricSecret(e.target.value)}
+                  placeholder={hasSavedFabricSecret ? 'Saved — leave blank to keep' : '••••••••••••'}
+                  className="font-mono text-sm"
+                  disabled={pageLocked}
+                />
+                {errors.fabricSecret ? (
+                  <p className="text-xs text-destructive" role="alert">
+                    {errors.fabricSecret}
+                  </p>
+                ) : null}
+              </div>
+            </div>
+          </CardContent>
+        </Card>
+
         <Card className="gap-0 py-5" data-testid="settings-workspace-repo-card">
           <CardHeader className="pb-3">
             <CardTitle>Migration repo</CardTitle>
@@ -834,12 +1102,6 @@ export default function WorkspaceTab() {
             </div>
           </div>
         ) : null}
-        {resetError ? (
-          <p className="text-xs text-destructive" role="alert" data-testid="workspace-reset-error">
-            {resetError}
-          </p>
-        ) : null}
-
         <div className="flex items-center justify-end gap-2" data-testid="settings-workspace-actions">
           <Button
             type="button"
@@ -896,6 +1158,12 @@ export default function WorkspaceTab() {
             />
           </div>
 
+          {resetError ? (
+            <p className="text-xs text-destructive" role="alert" data-testid="workspace-reset-error">
+              {resetError}
+            </p>
+          ) : null}
+
           <div className="flex justify-end gap-2">
             <Button
               type="button"
@@ -903,10 +1171,22 @@ export default function WorkspaceTab() {
               onClick={() => {
                 setResetDialogOpen(false);
                 setResetConfirmationInput('');
+                setResetError(null);
               }}
             >
               Cancel
             </Button>
+            {resetError ? (
+              <Button
+                type="button"
+                variant="destructive"
+                data-testid="btn-force-reset-migration"
+                onClick={() => void handleResetMigration(true)}
+                disabled={resetConfirmationInput.trim() !== resetToken}
+              >
+                Reset anyway
+              </Button>
+            ) : null}
             <Button
               type="button"
               variant="destructive"
@@ -919,6 +1199,32 @@ export default function WorkspaceTab() {
           </div>
         </DialogContent>
       </Dialog>
+
+      <Dialog open={deleteDialogOpen} onOpenChange={setDeleteDialogOpen}>
+        <DialogContent>
+          <DialogHeader>
+            <DialogTitle>Delete workspace</DialogTitle>
+            <DialogDescription>
+              This removes <strong>{workspaceName.trim() || DEFAULT_WORKSPACE_NAME}</strong> and its local
+              migration state. The local clone and remote GitHub repository are not touched.
+            </DialogDescription>
+          </DialogHeader>
+
+          <div className="flex justify-end gap-2">
+            <Button type="button" variant="outline" onClick={() => setDeleteDialogOpen(false)}>
+              Cancel
+            </Button>
+            <Button
+              type="button"
+              variant="destructive"
+              data-testid="btn-confirm-delete-workspace"
+              onClick={() => void handleDeleteWorkspace()}
+            >
+              Delete workspace
+            </Button>
+          </div>
+        </DialogContent>
+      </Dialog>
     </SettingsPanelShell>
   );
 }
@@ -8,17 +8,17 @@ export function makeWorkspace(overrides: Record<string, unknown> = {}) {
     migrationRepoPath: "/tmp/repo",
     fabricUrl: null,
     fabricServicePrincipalId: null,
-    fabricServicePrincipalSecret: null,
     sourceType: "fabric_warehouse",
     sourceServer: null,
     sourceDatabase: null,
     sourcePort: 1433,
     sourceAuthenticationMode: "entra_service_principal",
     sourceUsername: null,
-    sourcePassword: null,
     sourceEncrypt: true,
     sourceTrustServerCertificate: false,
     createdAt: "2026-01-01T00:00:00Z",
+    hasSourceSecret: false,
+    hasFabricSecret: false,
     ...overrides,
   };
 }
@@ -22,6 +22,8 @@ const mockResponses: Record<string, unknown> = {
   workspace_discover_source_databases: ["master"],
   workspace_reset_state: undefined,
   workspace_create: undefined,
+  workspace_list: [],
+  workspace_delete: undefined,
   get_workspaces: [],
   create_workspace: undefined,
   delete_workspace: undefined,
@@ -33,6 +35,7 @@ const mockResponses: Record<string, unknown> = {
   save_table_config: undefined,
   // Selected tables
   get_selected_tables: [],
+  graph_scope_warnings: [],
   // Plan
   get_plan_status: { status: "pending", updatedAt: null },
   finalize_plan: undefined,
@@ -41,9 +44,20 @@ const mockResponses: Record<string, unknown> = {
   // GitHub auth
   github_get_user: null,
   github_list_repos: [],
+  github_list_orgs: [],
+  github_get_config: { host: null, apiBaseUrl: null, oauthClientId: null },
   github_logout: undefined,
   // Settings
-  get_settings: { anthropicApiKey: null },
+  get_settings: {
+    hasAnthropicApiKey: false,
+    anthropicApiKeyHint: null,
+    hasGithubOauthToken: false,
+    githubUserLogin: null,
+    githubUserAvatar: null,
+    githubUserEmail: null,
+  },
+  secrets_status: { keySource: "keyring", unlocked: true, passphraseSet: false },
+  secrets_unlock: { keySource: "passphrase", unlocked: true, passphraseSet: true },
   save_anthropic_api_key: undefined,
   test_api_key: true,
   app_hydrate_phase: {
@@ -100,9 +114,9 @@ function getSeededWorkspaceResponse(): Record<string, unknown> | null {
       sourcePort: 1433,
       sourceAuthenticationMode: "sql_password",
       sourceUsername: "sa",
-      sourcePassword: "password",
       sourceEncrypt: true,
       sourceTrustServerCertificate: false,
+      hasSourceSecret: true,
     };
   } catch {
     return null;
@@ -123,10 +137,14 @@ export async function invoke<T>(cmd: string, args?: Record<string, unknown>): Pr
     return val as T;
   }
 
-  if (cmd === "workspace_get") {
+  if (cmd === "workspace_get" || cmd === "workspace_switch") {
     return getSeededWorkspaceResponse() as T;
   }
 
+  if (cmd === "workspace_rename") {
+    return { ...getSeededWorkspaceResponse(), displayName: args?.name } as T;
+  }
+
   if (cmd in mockResponses) {
     return mockResponses[cmd] as T;
   }